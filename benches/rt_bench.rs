@@ -15,6 +15,7 @@ fn bench_process_block(c: &mut Criterion) {
             to_node: gain,
             to_port: PortId(0),
             rate: Rate::Audio,
+            muted: false,
         })
         .unwrap();
     graph
@@ -24,6 +25,7 @@ fn bench_process_block(c: &mut Criterion) {
             to_node: out_node,
             to_port: PortId(0),
             rate: Rate::Audio,
+            muted: false,
         })
         .unwrap();
     let plan = Plan::compile(&graph, 1024).unwrap();
@@ -51,6 +53,7 @@ fn bench_timing_stability(c: &mut Criterion) {
                 to_node: next,
                 to_port: PortId(0),
                 rate: Rate::Audio,
+                muted: false,
             })
             .unwrap();
         prev = next;
@@ -63,6 +66,7 @@ fn bench_timing_stability(c: &mut Criterion) {
             to_node: sink,
             to_port: PortId(0),
             rate: Rate::Audio,
+            muted: false,
         })
         .unwrap();
 
@@ -29,6 +29,7 @@ fn main() {
             to_node: mod_gain,
             to_port: PortId(0),
             rate: Rate::Audio,
+            muted: false,
         })
         .unwrap();
 
@@ -43,6 +44,7 @@ fn main() {
             to_node: sink,
             to_port: PortId(0),
             rate: Rate::Audio,
+            muted: false,
         })
         .unwrap();
 
@@ -14,6 +14,7 @@ fn main() {
             to_node: sink,
             to_port: PortId(0),
             rate: Rate::Audio,
+            muted: false,
         })
         .unwrap();
 
@@ -26,6 +26,7 @@ fn main() {
             to_node: gain1,
             to_port: PortId(0),
             rate: Rate::Audio,
+            muted: false,
         })
         .unwrap();
     graph
@@ -35,6 +36,7 @@ fn main() {
             to_node: gain2,
             to_port: PortId(0),
             rate: Rate::Audio,
+            muted: false,
         })
         .unwrap();
     graph
@@ -44,6 +46,7 @@ fn main() {
             to_node: gain3,
             to_port: PortId(0),
             rate: Rate::Audio,
+            muted: false,
         })
         .unwrap();
     graph
@@ -53,6 +56,7 @@ fn main() {
             to_node: output,
             to_port: PortId(0),
             rate: Rate::Audio,
+            muted: false,
         })
         .unwrap();
 
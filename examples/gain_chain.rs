@@ -1,42 +1,17 @@
-use auxide::graph::{Graph, NodeType, PortId, Rate};
+use auxide::dsl::GraphBuilder;
+use auxide::graph::NodeType;
 use auxide::plan::Plan;
 use auxide::rt::Runtime;
 
 fn main() {
     // Create a chain: Osc -> Gain -> Gain -> Output
-    let mut graph = Graph::new();
-    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
-    let gain1 = graph.add_node(NodeType::Gain { gain: 0.5 });
-    let gain2 = graph.add_node(NodeType::Gain { gain: 0.5 });
-    let sink = graph.add_node(NodeType::OutputSink);
-
-    graph
-        .add_edge(auxide::graph::Edge {
-            from_node: osc,
-            from_port: PortId(0),
-            to_node: gain1,
-            to_port: PortId(0),
-            rate: Rate::Audio,
-        })
-        .unwrap();
-    graph
-        .add_edge(auxide::graph::Edge {
-            from_node: gain1,
-            from_port: PortId(0),
-            to_node: gain2,
-            to_port: PortId(0),
-            rate: Rate::Audio,
-        })
-        .unwrap();
-    graph
-        .add_edge(auxide::graph::Edge {
-            from_node: gain2,
-            from_port: PortId(0),
-            to_node: sink,
-            to_port: PortId(0),
-            rate: Rate::Audio,
-        })
-        .unwrap();
+    let mut builder = GraphBuilder::new();
+    let osc = builder.node(NodeType::SineOsc { freq: 440.0 });
+    let gain1 = builder.node(NodeType::Gain { gain: 0.5 });
+    let gain2 = builder.node(NodeType::Gain { gain: 0.5 });
+    let sink = builder.node(NodeType::OutputSink);
+    builder.chain(&[osc, gain1, gain2, sink]).unwrap();
+    let graph = builder.build().unwrap();
 
     let plan = Plan::compile(&graph, 64).unwrap();
     let mut runtime = Runtime::new(plan, &graph, 44100.0);
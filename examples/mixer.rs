@@ -17,6 +17,7 @@ fn main() {
             to_node: mix,
             to_port: PortId(0),
             rate: Rate::Audio,
+            muted: false,
         })
         .unwrap();
     graph
@@ -26,6 +27,7 @@ fn main() {
             to_node: mix,
             to_port: PortId(1),
             rate: Rate::Audio,
+            muted: false,
         })
         .unwrap();
     graph
@@ -35,6 +37,7 @@ fn main() {
             to_node: sink,
             to_port: PortId(0),
             rate: Rate::Audio,
+            muted: false,
         })
         .unwrap();
 
@@ -13,6 +13,7 @@ fn main() {
             to_node: sink,
             to_port: PortId(0),
             rate: Rate::Audio,
+            muted: false,
         })
         .unwrap();
 
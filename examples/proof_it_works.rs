@@ -1,7 +1,8 @@
 // examples/proof_it_works.rs
+// Run with `cargo run --example proof_it_works --features wav`.
 use auxide::graph::{Graph, NodeType, PortId, Rate};
 use auxide::plan::Plan;
-use auxide::rt::{render_offline, Runtime};
+use auxide::rt::{render_offline_to_wav, Runtime, WavFormat};
 
 fn main() {
     // Build 440Hz sine
@@ -15,27 +16,15 @@ fn main() {
             to_node: sink,
             to_port: PortId(0),
             rate: Rate::Audio,
+            muted: false,
         })
         .unwrap();
 
     let plan = Plan::compile(&graph, 512).unwrap();
     let mut runtime = Runtime::new(plan, &graph, 44100.0);
 
-    // Generate 1 second of audio
-    let samples = render_offline(&mut runtime, 44100).unwrap();
-
-    // Save to WAV
-    let spec = hound::WavSpec {
-        channels: 1,
-        sample_rate: 44100,
-        bits_per_sample: 16,
-        sample_format: hound::SampleFormat::Int,
-    };
-    let mut writer = hound::WavWriter::create("proof.wav", spec).unwrap();
-    for &sample in &samples {
-        writer.write_sample((sample * 32767.0) as i16).unwrap();
-    }
-    writer.finalize().unwrap();
+    // Render 1 second of audio to WAV
+    render_offline_to_wav(&mut runtime, 44100, "proof.wav", WavFormat::Int16).unwrap();
 
     println!("Generated proof.wav - open it and you should hear a 440Hz tone");
 }
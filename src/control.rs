@@ -12,21 +12,260 @@
 //! - Self-contained (no references or pointers)
 //!
 //! The RT callback drains the control queue each buffer and applies updates.
+//! Anything the RT side reports back (invariant signals, meter readings,
+//! parameter acknowledgements) travels the other way as [`RtMessage`]s over
+//! a single queue; [`RuntimeControl`] bundles both ends the main thread holds.
+//! Graph edits ride a third queue of runtimes pre-built off the audio thread
+//! (see [`RuntimeControl::swap_plan`]).
 
-use crate::graph::NodeId;
+use crate::graph::{Graph, NodeId, NodeType};
+use crate::midi::{MidiEvent, MidiMap};
+use crate::plan::Plan;
+use crate::rt::{Runtime, SWAP_QUEUE_CAPACITY};
+use crate::transport::NoteDivision;
 use rtrb::{Consumer, Producer, RingBuffer};
+use std::collections::HashMap;
 
 /// Capacity for control message queue.
 /// Should handle bursts of MIDI events (e.g., chord presses).
 pub const CONTROL_QUEUE_CAPACITY: usize = 256;
 
+/// A queued control message with the sample offset into the next block it
+/// takes effect at, if it was sent with [`RuntimeControl::send_at`].
+pub type QueuedControl = (Option<u32>, ControlMsg);
+
 /// Creates a new control message queue pair.
 ///
 /// Returns (producer for main thread, consumer for RT).
-pub fn new_control_queue() -> (Producer<ControlMsg>, Consumer<ControlMsg>) {
+pub fn new_control_queue() -> (Producer<QueuedControl>, Consumer<QueuedControl>) {
     RingBuffer::new(CONTROL_QUEUE_CAPACITY)
 }
 
+/// Capacity for the RT → main message queue.
+/// Holds a few blocks' worth of invariant signals and meter readings.
+pub const RT_MESSAGE_QUEUE_CAPACITY: usize = 512;
+
+/// Messages sent from the RT callback back to the main thread.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RtMessage {
+    /// An invariant was signaled (see `invariant_rt` for the IDs).
+    Invariant(u8),
+    /// Peak absolute sample of the block just rendered.
+    Meter { peak: f32 },
+    /// A `ControlMsg::SetParam` was applied.
+    ParamResponse {
+        node: NodeId,
+        param_idx: u8,
+        value: f32,
+    },
+    /// Node timings of the block just rendered, posted while node profiling
+    /// is on (`profiling` feature, `Runtime::set_node_profiling`).
+    BlockProfile {
+        /// Time spent in all nodes.
+        total: std::time::Duration,
+        /// The node that took longest, and its time.
+        slowest: NodeId,
+        slowest_time: std::time::Duration,
+    },
+}
+
+/// The main thread's ends of the control and RT message queues.
+#[derive(Debug)]
+pub struct RuntimeControl {
+    control_tx: Producer<QueuedControl>,
+    message_rx: Consumer<RtMessage>,
+    /// Set up by [`RuntimeControl::enable_swaps`].
+    swaps: Option<PlanSwaps>,
+    /// Node names of the running graph (see
+    /// [`RuntimeControl::set_node_names`]).
+    names: HashMap<String, NodeId>,
+    /// Translates [`RuntimeControl::send_midi`] events.
+    midi: MidiMap,
+}
+
+/// Main thread's side of plan swapping.
+#[derive(Debug)]
+struct PlanSwaps {
+    tx: Producer<Box<Runtime>>,
+    retired_rx: Consumer<Box<Runtime>>,
+    sample_rate: f32,
+    /// Node types of the runtime most recently sent (or enabled).
+    nodes: Vec<Option<NodeType>>,
+    /// Node ids of that runtime, by slot.
+    ids: Vec<Option<NodeId>>,
+}
+
+impl RuntimeControl {
+    /// Queue a control message for the RT callback. Returns the message back
+    /// if the queue is full.
+    pub fn send(&mut self, msg: ControlMsg) -> Result<(), ControlMsg> {
+        self.control_tx
+            .push((None, msg))
+            .map_err(|rtrb::PushError::Full((_, msg))| msg)
+    }
+
+    /// Queue `msg` to take effect `offset` samples into the next block the
+    /// runtime renders, for sample-accurate sequencing. Offsets past that
+    /// block carry over into later blocks. Returns the message back if the
+    /// queue is full.
+    pub fn send_at(&mut self, offset: u32, msg: ControlMsg) -> Result<(), ControlMsg> {
+        self.control_tx
+            .push((Some(offset), msg))
+            .map_err(|rtrb::PushError::Full((_, msg))| msg)
+    }
+
+    /// Address nodes by the names they have in `graph` (see
+    /// `Graph::set_node_name`), the graph the runtime was built from.
+    /// [`RuntimeControl::swap_plan`] picks up the new graph's names itself.
+    pub fn set_node_names(&mut self, graph: &Graph) {
+        self.names = graph
+            .nodes
+            .iter()
+            .flatten()
+            .filter_map(|n| Some((n.name.clone()?, n.id)))
+            .collect();
+    }
+
+    /// The node called `name` in the running graph.
+    pub fn node_by_name(&self, name: &str) -> Option<NodeId> {
+        self.names.get(name).copied()
+    }
+
+    /// Queue the message `msg` builds for the node called `name`, resolved
+    /// here on the main thread, e.g.
+    /// `send_named("cutoff", |node| ControlMsg::SetFilterCutoff { node, hz: 800.0 })`.
+    pub fn send_named(
+        &mut self,
+        name: &str,
+        msg: impl FnOnce(NodeId) -> ControlMsg,
+    ) -> Result<(), &'static str> {
+        let node = self.node_by_name(name).ok_or("unknown node name")?;
+        self.send(msg(node)).map_err(|_| "control queue full")
+    }
+
+    /// Translate later [`RuntimeControl::send_midi`] events with `map`.
+    pub fn set_midi_map(&mut self, map: MidiMap) {
+        self.midi = map;
+    }
+
+    /// The table [`RuntimeControl::send_midi`] translates with, to change
+    /// mappings in place.
+    pub fn midi_map_mut(&mut self) -> &mut MidiMap {
+        &mut self.midi
+    }
+
+    /// Queue the control messages the MIDI map translates `event` into
+    /// (see [`MidiMap::translate`]). Nothing is queued, and the map's note
+    /// tracking is left alone, if the queue cannot take them all.
+    pub fn send_midi(&mut self, event: MidiEvent) -> Result<(), &'static str> {
+        // A translation is at most two messages.
+        if self.control_tx.slots() < 2 {
+            return Err("control queue full");
+        }
+        let messages = self.midi.translate(event).ok_or("unmapped MIDI event")?;
+        for msg in messages {
+            let _ = self.control_tx.push((None, msg));
+        }
+        Ok(())
+    }
+
+    /// Let [`RuntimeControl::swap_plan`] replace `runtime`'s graph while it
+    /// runs. Call before handing `runtime` to the audio thread; it picks up
+    /// swaps in `Runtime::process_block_with_channels`.
+    pub fn enable_swaps(&mut self, runtime: &mut Runtime) {
+        let (tx, rx) = RingBuffer::new(SWAP_QUEUE_CAPACITY);
+        let (retired_tx, retired_rx) = RingBuffer::new(SWAP_QUEUE_CAPACITY);
+        runtime.set_swap_queues(rx, retired_tx);
+        self.swaps = Some(PlanSwaps {
+            tx,
+            retired_rx,
+            sample_rate: runtime.sample_rate(),
+            nodes: runtime.node_types().to_vec(),
+            ids: runtime.node_ids().to_vec(),
+        });
+    }
+
+    /// Replace the running graph with `graph`, compiled as `plan`, without
+    /// a dropout.
+    ///
+    /// The new runtime is built here, off the audio thread, and takes over
+    /// at the start of the next block, crossfading from the old output over
+    /// that block. Nodes with the same `NodeId`, generation included, and
+    /// identical parameters in both graphs keep their state (phases, delay lines, envelopes) and any
+    /// parameters changed by control messages; the rest start fresh. The
+    /// plan must keep the running block size or the swap is ignored.
+    pub fn swap_plan(&mut self, plan: Plan, graph: &Graph) -> Result<(), &'static str> {
+        let swaps = self.swaps.as_mut().ok_or("plan swaps not enabled")?;
+        while swaps.retired_rx.pop().is_ok() {}
+        let mut runtime = Box::new(Runtime::new(plan, graph, swaps.sample_rate));
+        let nodes = runtime.node_types().to_vec();
+        let ids = runtime.node_ids().to_vec();
+        runtime.carry_over = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| match (swaps.nodes.get(i), node) {
+                (Some(Some(old)), Some(new)) => swaps.ids.get(i) == ids.get(i) && old == new,
+                _ => false,
+            })
+            .collect();
+        swaps.tx.push(runtime).map_err(|_| "plan swap queue full")?;
+        swaps.nodes = nodes;
+        swaps.ids = ids;
+        self.set_node_names(graph);
+        Ok(())
+    }
+
+    /// Drain every pending RT message, in the order the RT side sent them.
+    ///
+    /// Also frees runtimes retired by [`RuntimeControl::swap_plan`].
+    pub fn drain_messages(&mut self) -> Vec<RtMessage> {
+        if let Some(swaps) = self.swaps.as_mut() {
+            while swaps.retired_rx.pop().is_ok() {}
+        }
+        let mut messages = Vec::with_capacity(self.message_rx.slots());
+        while let Ok(msg) = self.message_rx.pop() {
+            messages.push(msg);
+        }
+        messages
+    }
+}
+
+/// Creates both queues for a runtime.
+///
+/// Returns the main thread's [`RuntimeControl`] and, for RT, the control
+/// consumer and the message producer (hand the latter to
+/// `Runtime::set_message_queue`).
+pub fn new_runtime_control() -> (RuntimeControl, Consumer<QueuedControl>, Producer<RtMessage>) {
+    let (control_tx, control_rx) = new_control_queue();
+    let (message_tx, message_rx) = RingBuffer::new(RT_MESSAGE_QUEUE_CAPACITY);
+    (
+        RuntimeControl {
+            control_tx,
+            message_rx,
+            swaps: None,
+            names: HashMap::new(),
+            midi: MidiMap::default(),
+        },
+        control_rx,
+        message_tx,
+    )
+}
+
+/// Transport state of a `NodeType::Looper`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LooperMode {
+    /// Output silence; the loop buffer is kept.
+    #[default]
+    Stop,
+    /// Record input into a fresh loop, discarding previous content.
+    Record,
+    /// Play the recorded loop.
+    Play,
+    /// Play the recorded loop while summing new input onto it.
+    Overdub,
+}
+
 /// Control messages sent from main thread to RT callback.
 #[derive(Debug, Clone, Copy)]
 pub enum ControlMsg {
@@ -96,18 +335,59 @@ pub enum ControlMsg {
     },
 
     /// Immediately silence a node (emergency mute).
-    Mute {
-        node: NodeId,
-    },
+    Mute { node: NodeId },
 
     /// Remove mute from a node.
-    Unmute {
-        node: NodeId,
-    },
+    Unmute { node: NodeId },
+
+    /// Solo a node: while any node is soloed, only signal flowing through a
+    /// soloed node reaches the outputs.
+    Solo { node: NodeId },
+
+    /// Remove a node from the solo set.
+    Unsolo { node: NodeId },
+
+    /// Freeze a node: while frozen it repeats its last output block and its
+    /// state does not advance (stutter/glitch effects).
+    FreezeNode { node: NodeId, frozen: bool },
+
+    /// Bypass a node: while bypassed its first input is passed straight to
+    /// its first output and the node is not processed (A/B-ing effects).
+    /// Other outputs are silent; sinks ignore it.
+    Bypass { node: NodeId, enabled: bool },
+
+    /// Switch a looper between stop/record/play/overdub.
+    SetLooperMode { node: NodeId, mode: LooperMode },
+
+    /// Play MIDI note `note` on one of the graph's instanced voices, picked
+    /// by the runtime's `VoiceAllocator`: the voice's oscillator is tuned to
+    /// the note and its amp envelope opened at `velocity` (`0.0..=1.0`).
+    /// Ignored by graphs without voices.
+    NoteOn { note: u8, velocity: f32 },
+
+    /// Release the voice playing MIDI note `note`.
+    NoteOff { note: u8 },
 
     /// All notes off (for all nodes that support it).
     AllNotesOff,
 
+    /// Set a `Delay` node's time to a note value at the transport's tempo.
+    /// The delay follows later `SetTempo` messages; it is capped at the
+    /// time the node was created with, which sizes its buffer.
+    SetSyncedTime {
+        node: NodeId,
+        division: NoteDivision,
+    },
+
+    /// Set the runtime transport's tempo in beats per minute.
+    SetTempo { bpm: f32 },
+
+    /// Start the runtime transport from its current position.
+    Start,
+
+    /// Stop the runtime transport, keeping its position.
+    Stop,
+
     /// Reset all parameters to defaults.
     Reset,
 }
@@ -127,7 +407,18 @@ impl ControlMsg {
             ControlMsg::SetPan { node, .. } => Some(*node),
             ControlMsg::Mute { node } => Some(*node),
             ControlMsg::Unmute { node } => Some(*node),
+            ControlMsg::Solo { node } => Some(*node),
+            ControlMsg::Unsolo { node } => Some(*node),
+            ControlMsg::FreezeNode { node, .. } => Some(*node),
+            ControlMsg::Bypass { node, .. } => Some(*node),
+            ControlMsg::SetLooperMode { node, .. } => Some(*node),
+            ControlMsg::NoteOn { .. } => None,
+            ControlMsg::NoteOff { .. } => None,
             ControlMsg::AllNotesOff => None,
+            ControlMsg::SetSyncedTime { node, .. } => Some(*node),
+            ControlMsg::SetTempo { .. } => None,
+            ControlMsg::Start => None,
+            ControlMsg::Stop => None,
             ControlMsg::Reset => None,
         }
     }
@@ -146,7 +437,18 @@ impl ControlMsg {
             ControlMsg::SetPan { .. } => "SetPan",
             ControlMsg::Mute { .. } => "Mute",
             ControlMsg::Unmute { .. } => "Unmute",
+            ControlMsg::Solo { .. } => "Solo",
+            ControlMsg::Unsolo { .. } => "Unsolo",
+            ControlMsg::FreezeNode { .. } => "FreezeNode",
+            ControlMsg::Bypass { .. } => "Bypass",
+            ControlMsg::SetLooperMode { .. } => "SetLooperMode",
+            ControlMsg::NoteOn { .. } => "NoteOn",
+            ControlMsg::NoteOff { .. } => "NoteOff",
             ControlMsg::AllNotesOff => "AllNotesOff",
+            ControlMsg::SetSyncedTime { .. } => "SetSyncedTime",
+            ControlMsg::SetTempo { .. } => "SetTempo",
+            ControlMsg::Start => "Start",
+            ControlMsg::Stop => "Stop",
             ControlMsg::Reset => "Reset",
         }
     }
@@ -159,7 +461,7 @@ mod tests {
     #[test]
     fn test_control_msg_is_copy() {
         let msg = ControlMsg::SetGain {
-            node: NodeId(0),
+            node: NodeId(0, 0),
             gain: 0.5,
         };
         let msg2 = msg; // Copy
@@ -170,31 +472,42 @@ mod tests {
     fn test_control_queue_roundtrip() {
         let (mut tx, mut rx) = new_control_queue();
 
-        tx.push(ControlMsg::SetGain {
-            node: NodeId(0),
-            gain: 0.5,
-        })
+        tx.push((
+            None,
+            ControlMsg::SetGain {
+                node: NodeId(0, 0),
+                gain: 0.5,
+            },
+        ))
         .unwrap();
-        tx.push(ControlMsg::TriggerGate {
-            node: NodeId(1),
-            on: true,
-        })
+        tx.push((
+            Some(10),
+            ControlMsg::TriggerGate {
+                node: NodeId(1, 0),
+                on: true,
+            },
+        ))
         .unwrap();
 
         let msg1 = rx.pop().unwrap();
         let msg2 = rx.pop().unwrap();
 
-        assert!(matches!(msg1, ControlMsg::SetGain { gain, .. } if (gain - 0.5).abs() < 0.001));
-        assert!(matches!(msg2, ControlMsg::TriggerGate { on: true, .. }));
+        assert!(
+            matches!(msg1, (None, ControlMsg::SetGain { gain, .. }) if (gain - 0.5).abs() < 0.001)
+        );
+        assert!(matches!(
+            msg2,
+            (Some(10), ControlMsg::TriggerGate { on: true, .. })
+        ));
     }
 
     #[test]
     fn test_target_node() {
         let msg = ControlMsg::SetGain {
-            node: NodeId(42),
+            node: NodeId(42, 0),
             gain: 1.0,
         };
-        assert_eq!(msg.target_node(), Some(NodeId(42)));
+        assert_eq!(msg.target_node(), Some(NodeId(42, 0)));
 
         let msg = ControlMsg::AllNotesOff;
         assert_eq!(msg.target_node(), None);
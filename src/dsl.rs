@@ -3,18 +3,70 @@
 #![forbid(unsafe_code)]
 // #![deny(missing_docs)]
 
+use crate::dsp::Interpolation;
 use crate::graph::{Graph, GraphError, NodeId, NodeType, PortId, Rate};
+use crate::patch::PatchError;
+use crate::voice::{LogicalNode, VoiceNodes};
 use std::collections::HashMap;
 
 /// Handle to a node in the builder.
 #[derive(Debug, Clone, Copy)]
 pub struct NodeHandle(pub NodeId);
 
+/// Nodes of a voice made by [`GraphBuilder::subtractive_voice`], or by a
+/// template passed to [`GraphBuilder::instance_voices`].
+#[derive(Debug, Clone, Copy)]
+pub struct VoiceHandles {
+    /// Sound source (a saw wavetable in a subtractive voice).
+    pub osc: NodeHandle,
+    /// Low-pass filter on the source.
+    pub filter: NodeHandle,
+    /// ADSR amp; the voice's output.
+    pub amp: NodeHandle,
+}
+
+impl VoiceHandles {
+    /// The node playing `logical`'s role in this voice.
+    pub fn node(&self, logical: LogicalNode) -> NodeId {
+        self.nodes()[logical as usize]
+    }
+
+    /// All of the voice's nodes, indexed by [`LogicalNode`].
+    pub fn nodes(&self) -> VoiceNodes {
+        [self.osc.0, self.filter.0, self.amp.0]
+    }
+}
+
+/// A subgraph defined once with [`GraphBuilder::define_template`].
+#[derive(Debug, Clone)]
+struct Template {
+    graph: Graph,
+    inputs: Vec<(NodeId, PortId)>,
+    outputs: Vec<(NodeId, PortId)>,
+}
+
+/// One copy of a template, made by [`GraphBuilder::instantiate`].
+#[derive(Debug, Clone)]
+pub struct TemplateInstance {
+    /// The copy of each template node, indexed by its id in the template
+    /// (`None` for ids removed there).
+    pub nodes: Vec<Option<NodeHandle>>,
+    /// Exposed inputs, in the order given to `define_template`.
+    pub inputs: Vec<(NodeHandle, PortId)>,
+    /// Exposed outputs, in the order given to `define_template`.
+    pub outputs: Vec<(NodeHandle, PortId)>,
+}
+
+/// Entries in the subtractive voice's saw table.
+const VOICE_TABLE_LEN: usize = 2048;
+/// Harmonics summed into the saw table (keeps it band-limited).
+const VOICE_HARMONICS: usize = 32;
+
 /// The graph builder.
 #[derive(Debug)]
 pub struct GraphBuilder {
     graph: Graph,
-    node_names: HashMap<String, NodeId>, // For named nodes, optional
+    templates: HashMap<String, Template>,
 }
 
 impl GraphBuilder {
@@ -22,20 +74,42 @@ impl GraphBuilder {
     pub fn new() -> Self {
         Self {
             graph: Graph::new(),
-            node_names: HashMap::new(),
+            templates: HashMap::new(),
         }
     }
 
+    /// Start from a patch in the text format of the [`patch`](crate::patch)
+    /// module, e.g. `osc: sine(440); g: gain(0.5); osc -> g -> out`.
+    /// Labels are node names, so `get_node_by_name("g")` finds the gain.
+    pub fn parse(text: &str) -> Result<Self, DslError> {
+        let graph = crate::patch::parse(text).map_err(|(offset, error)| {
+            let before = &text[..offset];
+            let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+            DslError::Parse {
+                line: before.matches('\n').count() + 1,
+                column: before[line_start..].chars().count() + 1,
+                error,
+            }
+        })?;
+        Ok(Self {
+            graph,
+            templates: HashMap::new(),
+        })
+    }
+
     /// Add a node with type.
     pub fn node(&mut self, node_type: NodeType) -> NodeHandle {
         let id = self.graph.add_node(node_type);
         NodeHandle(id)
     }
 
-    /// Add a named node.
+    /// Add a named node. The name is kept in the built graph (see
+    /// [`Graph::node_by_name`]).
     pub fn node_named(&mut self, name: &str, node_type: NodeType) -> NodeHandle {
         let handle = self.node(node_type);
-        self.node_names.insert(name.to_string(), handle.0);
+        self.graph
+            .set_node_name(handle.0, name)
+            .expect("node was just added");
         handle
     }
 
@@ -54,11 +128,222 @@ impl GraphBuilder {
             to_node: to.0,
             to_port,
             rate,
+            muted: false,
         };
         self.graph.add_edge(edge).map_err(DslError::Graph)?;
         Ok(())
     }
 
+    /// Connect two ports, inserting a rate adapter if their rates differ.
+    ///
+    /// Audio feeding a control input goes through `NodeType::AudioToControl`
+    /// (segment means); control feeding audio goes through
+    /// `NodeType::ControlToAudio`. Returns the inserted adapter, if any.
+    /// Event-rate mismatches have no adapter and fail with `RateMismatch`.
+    pub fn connect_adapting(
+        &mut self,
+        from: NodeHandle,
+        from_port: PortId,
+        to: NodeHandle,
+        to_port: PortId,
+    ) -> Result<Option<NodeHandle>, DslError> {
+        let from_rate = self
+            .graph
+            .get_port_rate(from.0, from_port, false)
+            .map_err(DslError::Graph)?;
+        let to_rate = self
+            .graph
+            .get_port_rate(to.0, to_port, true)
+            .map_err(DslError::Graph)?;
+        let adapter = match (&from_rate, &to_rate) {
+            _ if from_rate == to_rate => {
+                self.connect(from, from_port, to, to_port, from_rate)?;
+                return Ok(None);
+            }
+            // Fail before inserting anything so an error leaves no stray adapter.
+            _ if !self.graph.edges_into_port(to.0, to_port).is_empty() => {
+                return Err(DslError::Graph(GraphError::PortAlreadyConnected));
+            }
+            (Rate::Audio, Rate::Control) => NodeType::AudioToControl,
+            (Rate::Control, Rate::Audio) => NodeType::ControlToAudio,
+            _ => return Err(DslError::Graph(GraphError::RateMismatch)),
+        };
+        let adapter = self.node(adapter);
+        self.connect(from, from_port, adapter, PortId(0), from_rate)?;
+        self.connect(adapter, PortId(0), to, to_port, to_rate)?;
+        Ok(Some(adapter))
+    }
+
+    /// Connect `nodes` in series, output 0 of each into input 0 of the
+    /// next, at the rate of the output (see [`GraphBuilder::then`]).
+    pub fn chain(&mut self, nodes: &[NodeHandle]) -> Result<(), DslError> {
+        for pair in nodes.windows(2) {
+            self.connect_serial(pair[0], pair[1])?;
+        }
+        Ok(())
+    }
+
+    /// Start a serial chain at `node`:
+    /// `builder.from(osc).then(filter)?.then(amp)?` connects output 0 to
+    /// input 0 down the line.
+    pub fn from(&mut self, node: NodeHandle) -> Chain<'_> {
+        Chain {
+            builder: self,
+            last: node,
+        }
+    }
+
+    /// `connect` port 0 to port 0 at the rate of `from`'s output. A control
+    /// output into an audio input is upsampled as by `Graph::add_edge`.
+    fn connect_serial(&mut self, from: NodeHandle, to: NodeHandle) -> Result<(), DslError> {
+        let rate = self
+            .graph
+            .get_port_rate(from.0, PortId(0), false)
+            .map_err(DslError::Graph)?;
+        self.connect(from, PortId(0), to, PortId(0), rate)
+    }
+
+    /// Define a reusable subgraph called `name` from the graph `template`
+    /// builds, exposing the given input and output ports of its nodes.
+    /// Each [`GraphBuilder::instantiate`] adds a fresh copy. Redefining a
+    /// name replaces the template. Fails with `UnboundPort` if an exposed
+    /// port does not exist.
+    pub fn define_template(
+        &mut self,
+        name: &str,
+        template: GraphBuilder,
+        inputs: &[(NodeHandle, PortId)],
+        outputs: &[(NodeHandle, PortId)],
+    ) -> Result<(), DslError> {
+        let graph = template.graph;
+        let check = |ports: &[(NodeHandle, PortId)], input: bool| {
+            ports
+                .iter()
+                .map(|&(node, port)| {
+                    graph
+                        .get_port_rate(node.0, port, input)
+                        .map(|_| (node.0, port))
+                        .map_err(|_| DslError::UnboundPort)
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let inputs = check(inputs, true)?;
+        let outputs = check(outputs, false)?;
+        self.templates.insert(
+            name.to_string(),
+            Template {
+                graph,
+                inputs,
+                outputs,
+            },
+        );
+        Ok(())
+    }
+
+    /// Add a copy of the template `name`, unconnected, and return handles
+    /// to its nodes and exposed ports.
+    pub fn instantiate(&mut self, name: &str) -> Result<TemplateInstance, DslError> {
+        let template = self
+            .templates
+            .get(name)
+            .ok_or_else(|| DslError::UnknownTemplate(name.to_string()))?;
+        let ids = self.graph.append(&template.graph);
+        // Exposed ports were checked against existing nodes.
+        let map = |ports: &[(NodeId, PortId)]| {
+            ports
+                .iter()
+                .map(|&(node, port)| (NodeHandle(ids[node.0].expect("exposed node")), port))
+                .collect()
+        };
+        Ok(TemplateInstance {
+            inputs: map(&template.inputs),
+            outputs: map(&template.outputs),
+            nodes: ids.iter().map(|id| id.map(NodeHandle)).collect(),
+        })
+    }
+
+    /// Add a subtractive-synth voice: a band-limited saw wavetable at
+    /// `freq` Hz into a resonant low-pass at four times `freq`, into an
+    /// ADSR amp (5 ms attack, 100 ms decay, 0.7 sustain, 200 ms release).
+    ///
+    /// Nothing sounds until the amp's gate opens: send
+    /// `ControlMsg::TriggerGate` to `amp`, and connect `amp` onwards.
+    /// `SetFrequency` on `osc` and `SetFilterCutoff` on `filter` retune it.
+    pub fn subtractive_voice(&mut self, freq: f32) -> VoiceHandles {
+        let table = (0..VOICE_TABLE_LEN)
+            .map(|i| {
+                let x = 2.0 * std::f32::consts::PI * i as f32 / VOICE_TABLE_LEN as f32;
+                (1..=VOICE_HARMONICS)
+                    .map(|k| (k as f32 * x).sin() / k as f32)
+                    .sum::<f32>()
+                    * (2.0 / std::f32::consts::PI)
+            })
+            .collect();
+        let osc = self.node(NodeType::Wavetable {
+            table,
+            freq,
+            interpolation: Interpolation::Cubic,
+        });
+        let filter = self.node(NodeType::Filter {
+            cutoff: freq * 4.0,
+            q: std::f32::consts::FRAC_1_SQRT_2,
+        });
+        let amp = self.node(NodeType::Adsr {
+            attack: 5.0,
+            decay: 100.0,
+            sustain: 0.7,
+            release: 200.0,
+        });
+        // Fresh nodes with matching audio ports: these cannot fail.
+        self.connect(osc, PortId(0), filter, PortId(0), Rate::Audio)
+            .expect("voice osc -> filter");
+        self.connect(filter, PortId(0), amp, PortId(0), Rate::Audio)
+            .expect("voice filter -> amp");
+        VoiceHandles { osc, filter, amp }
+    }
+
+    /// Instance `count` subtractive voices (see
+    /// [`GraphBuilder::subtractive_voice`]) at `freq` Hz with
+    /// [`GraphBuilder::instance_voices`].
+    pub fn subtractive_voices(
+        &mut self,
+        count: usize,
+        freq: f32,
+    ) -> (Vec<VoiceHandles>, NodeHandle) {
+        self.instance_voices(count, |builder| builder.subtractive_voice(freq))
+    }
+
+    /// Instance `count` (at least one) voices built by `template`, summed
+    /// through `Mix` nodes. Returns the voices and the node carrying their
+    /// sum. `template` adds one voice's nodes and returns its oscillator,
+    /// filter and amp envelope; `amp` is the voice's output and must have
+    /// an audio output port 0.
+    ///
+    /// Voices are built one after another, so the same calls always give
+    /// the same node ids. Each is recorded in the graph's voice table, so
+    /// `Runtime::voice_node` maps a voice index and role back to its node
+    /// and `ControlMsg::NoteOn` / `NoteOff` play the voices.
+    pub fn instance_voices(
+        &mut self,
+        count: usize,
+        mut template: impl FnMut(&mut Self) -> VoiceHandles,
+    ) -> (Vec<VoiceHandles>, NodeHandle) {
+        let voices: Vec<VoiceHandles> = (0..count.max(1)).map(|_| template(self)).collect();
+        self.graph
+            .voices
+            .extend(voices.iter().map(VoiceHandles::nodes));
+        let mut out = voices[0].amp;
+        for voice in &voices[1..] {
+            let mix = self.node(NodeType::Mix);
+            self.connect(out, PortId(0), mix, PortId(0), Rate::Audio)
+                .expect("voice sum -> mix");
+            self.connect(voice.amp, PortId(0), mix, PortId(1), Rate::Audio)
+                .expect("voice amp -> mix");
+            out = mix;
+        }
+        (voices, out)
+    }
+
     /// Build the graph.
     pub fn build(self) -> Result<Graph, DslError> {
         Ok(self.graph)
@@ -66,7 +351,36 @@ impl GraphBuilder {
 
     /// Get a node by name.
     pub fn get_node_by_name(&self, name: &str) -> Option<NodeId> {
-        self.node_names.get(name).copied()
+        self.graph.node_by_name(name)
+    }
+}
+
+/// A serial chain under construction (see [`GraphBuilder::from`]).
+#[derive(Debug)]
+pub struct Chain<'a> {
+    builder: &'a mut GraphBuilder,
+    last: NodeHandle,
+}
+
+impl<'a> Chain<'a> {
+    /// Connect the end of the chain to `node`, which becomes the new end.
+    pub fn then(self, node: NodeHandle) -> Result<Self, DslError> {
+        self.builder.connect_serial(self.last, node)?;
+        Ok(Chain {
+            builder: self.builder,
+            last: node,
+        })
+    }
+
+    /// Add a node of `node_type` and connect the end of the chain to it.
+    pub fn then_new(self, node_type: NodeType) -> Result<Self, DslError> {
+        let node = self.builder.node(node_type);
+        self.then(node)
+    }
+
+    /// The node at the end of the chain.
+    pub fn end(self) -> NodeHandle {
+        self.last
     }
 }
 
@@ -82,6 +396,15 @@ pub enum DslError {
     Graph(GraphError),
     MissingNode(String),
     UnboundPort,
+    /// No template was defined under this name.
+    UnknownTemplate(String),
+    /// A patch passed to [`GraphBuilder::parse`] is invalid at `line` and
+    /// `column` (both from 1, the column counted in characters).
+    Parse {
+        line: usize,
+        column: usize,
+        error: PatchError,
+    },
 }
 
 #[cfg(test)]
@@ -109,6 +432,7 @@ mod tests {
                 to_node: m_node2,
                 to_port: PortId(0),
                 rate: Rate::Audio,
+                muted: false,
             })
             .unwrap();
 
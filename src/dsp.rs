@@ -0,0 +1,1163 @@
+//! DSP building blocks shared by the built-in nodes.
+//!
+//! Everything here is allocated up front and is RT-safe to run.
+
+#![forbid(unsafe_code)]
+
+/// Ring-buffer delay line with fractional (linearly interpolated) reads.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DelayLine {
+    buffer: Vec<f32>,
+    write: usize,
+}
+
+impl DelayLine {
+    /// Longest delay, in samples, a line can hold: about 87 s at 48 kHz,
+    /// 16 MB of buffer.
+    pub const MAX_DELAY: f32 = 4_194_304.0;
+
+    /// Create a delay line able to delay by up to `max_delay` samples,
+    /// clamped with [`DelayLine::clamp_delay`].
+    pub fn new(max_delay: f32) -> Self {
+        let len = Self::clamp_delay(max_delay).ceil() as usize + 2;
+        Self {
+            buffer: vec![0.0; len],
+            write: 0,
+        }
+    }
+
+    /// `delay` limited to `[0, MAX_DELAY]`. Delays must be causal, so
+    /// negative ones become 0, and so does NaN.
+    pub fn clamp_delay(delay: f32) -> f32 {
+        if delay.is_nan() {
+            return 0.0;
+        }
+        delay.clamp(0.0, Self::MAX_DELAY)
+    }
+
+    /// Largest delay, in samples, this line can produce.
+    pub fn max_delay(&self) -> f32 {
+        (self.buffer.len() - 2) as f32
+    }
+
+    /// Copy `saved` into this line if it has the same length and a write
+    /// position inside its buffer; returns whether it did.
+    pub(crate) fn restore(&mut self, saved: &DelayLine) -> bool {
+        if saved.buffer.len() != self.buffer.len() || saved.write >= saved.buffer.len() {
+            return false;
+        }
+        self.buffer.copy_from_slice(&saved.buffer);
+        self.write = saved.write;
+        true
+    }
+
+    /// Push one sample and read the signal `delay` samples in the past.
+    ///
+    /// `delay` is clamped to `[0, max_delay]`, NaN to 0; a delay of zero
+    /// passes the input straight through.
+    #[inline]
+    pub fn process(&mut self, x: f32, delay: f32) -> f32 {
+        self.write(x);
+        self.read(delay)
+    }
+
+    /// Append one sample to the line.
+    #[inline]
+    pub fn write(&mut self, x: f32) {
+        self.buffer[self.write] = x;
+        self.write = (self.write + 1) % self.buffer.len();
+    }
+
+    /// Read `delay` samples behind the most recently written sample.
+    ///
+    /// Several reads per write are allowed, which makes multi-tap effects
+    /// share one line.
+    #[inline]
+    pub fn read(&self, delay: f32) -> f32 {
+        let len = self.buffer.len();
+        let delay = Self::clamp_delay(delay).min(self.max_delay());
+        let whole = delay.floor() as usize;
+        let frac = delay - whole as f32;
+        let newest = self.write + len - 1;
+        let a = self.buffer[(newest - whole) % len];
+        let b = self.buffer[(newest - whole - 1) % len];
+        a + (b - a) * frac
+    }
+}
+
+/// Deterministic xorshift64* generator for noise sources.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Xorshift {
+    state: u64,
+}
+
+impl Xorshift {
+    /// Seeded generator; a zero seed is replaced by a fixed non-zero one.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            state: if seed == 0 {
+                0x9E37_79B9_7F4A_7C15
+            } else {
+                seed
+            },
+        }
+    }
+
+    /// Next raw 64-bit value.
+    #[inline]
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.state = x;
+        x.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Uniform sample in `[-1.0, 1.0)`.
+    #[inline]
+    pub fn next_bipolar(&mut self) -> f32 {
+        // Top 24 bits give an exactly representable f32 in [0, 1).
+        let unit = (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32;
+        unit * 2.0 - 1.0
+    }
+}
+
+/// Coloured noise: white, pink and brown spectra blended by `color`.
+///
+/// Pink uses Paul Kellet's three-pole approximation and brown a leaky
+/// integrator, both fed from one white source so every colour stays
+/// deterministic for a given seed.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ColoredNoise {
+    rng: Xorshift,
+    pink: [f32; 3],
+    brown: f32,
+}
+
+impl ColoredNoise {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: Xorshift::new(seed),
+            pink: [0.0; 3],
+            brown: 0.0,
+        }
+    }
+
+    /// Next sample. `color` is clamped to `[0, 2]`: 0 = white, 1 = pink
+    /// (-3 dB/oct), 2 = brown (-6 dB/oct); values in between crossfade.
+    #[inline]
+    pub fn next(&mut self, color: f32) -> f32 {
+        let white = self.rng.next_bipolar();
+        self.pink[0] = 0.99765 * self.pink[0] + white * 0.099_046;
+        self.pink[1] = 0.963 * self.pink[1] + white * 0.296_516_4;
+        self.pink[2] = 0.57 * self.pink[2] + white * 1.052_691_3;
+        let pink = (self.pink[0] + self.pink[1] + self.pink[2] + white * 0.1848) * 0.25;
+        self.brown = (self.brown + 0.02 * white) / 1.02;
+        let brown = self.brown * 3.5;
+
+        let color = color.clamp(0.0, 2.0);
+        if color <= 1.0 {
+            white + (pink - white) * color
+        } else {
+            pink + (brown - pink) * (color - 1.0)
+        }
+    }
+}
+
+/// Linear ramp towards a target over a fixed number of samples, used to
+/// de-zipper parameter changes.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LinearRamp {
+    value: f32,
+    target: f32,
+    step: f32,
+    remaining: usize,
+}
+
+impl LinearRamp {
+    /// Ramp from `from` to `target` over `samples` samples; zero samples
+    /// jumps straight to the target.
+    pub fn new(from: f32, target: f32, samples: usize) -> Self {
+        if samples == 0 {
+            return Self {
+                value: target,
+                target,
+                step: 0.0,
+                remaining: 0,
+            };
+        }
+        Self {
+            value: from,
+            target,
+            step: (target - from) / samples as f32,
+            remaining: samples,
+        }
+    }
+
+    /// Whether the ramp has samples left before reaching its target.
+    pub fn is_ramping(&self) -> bool {
+        self.remaining > 0
+    }
+
+    /// Current value.
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+
+    /// Value the ramp ends on.
+    pub fn target(&self) -> f32 {
+        self.target
+    }
+
+    /// Step `samples` samples at once.
+    pub fn advance_by(&mut self, samples: usize) {
+        if samples >= self.remaining {
+            self.value = self.target;
+            self.remaining = 0;
+        } else {
+            self.value += self.step * samples as f32;
+            self.remaining -= samples;
+        }
+    }
+
+    /// Step one sample and return the new value; lands exactly on the
+    /// target at the end.
+    #[inline]
+    pub fn advance(&mut self) -> f32 {
+        if self.remaining > 0 {
+            self.remaining -= 1;
+            self.value = if self.remaining == 0 {
+                self.target
+            } else {
+                self.value + self.step
+            };
+        }
+        self.value
+    }
+}
+
+/// Peak envelope follower with separate attack and release times.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EnvelopeFollower {
+    attack: f32,
+    release: f32,
+    env: f32,
+}
+
+impl EnvelopeFollower {
+    /// Create a follower with the given attack/release times.
+    pub fn new(attack_ms: f32, release_ms: f32, sample_rate: f32) -> Self {
+        Self {
+            attack: time_coef(attack_ms, sample_rate),
+            release: time_coef(release_ms, sample_rate),
+            env: 0.0,
+        }
+    }
+
+    /// Track `|x|` and return the current envelope.
+    #[inline]
+    pub fn process(&mut self, x: f32) -> f32 {
+        let level = x.abs();
+        let coef = if level > self.env {
+            self.attack
+        } else {
+            self.release
+        };
+        self.env = level + (self.env - level) * coef;
+        self.env
+    }
+
+    /// Current envelope value.
+    pub fn value(&self) -> f32 {
+        self.env
+    }
+}
+
+/// Detector release of [`NoiseGate`]: long enough to ride over the zero
+/// crossings of low notes, short next to typical hold times.
+const GATE_DETECTOR_RELEASE_MS: f32 = 5.0;
+
+/// Noise gate: closes when the input envelope stays below a threshold for
+/// longer than the hold time.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NoiseGate {
+    detector: EnvelopeFollower,
+    threshold: f32,
+    attack: f32,
+    release: f32,
+    hold_samples: usize,
+    hold: usize,
+    gain: f32,
+}
+
+impl NoiseGate {
+    /// Create a gate. Times are in milliseconds; the gate starts closed.
+    pub fn new(
+        threshold_db: f32,
+        attack_ms: f32,
+        hold_ms: f32,
+        release_ms: f32,
+        sample_rate: f32,
+    ) -> Self {
+        Self {
+            detector: EnvelopeFollower::new(0.0, GATE_DETECTOR_RELEASE_MS, sample_rate),
+            threshold: db_to_lin(threshold_db),
+            attack: time_coef(attack_ms, sample_rate),
+            release: time_coef(release_ms, sample_rate),
+            hold_samples: (hold_ms.max(0.0) * sample_rate / 1000.0) as usize,
+            hold: 0,
+            gain: 0.0,
+        }
+    }
+
+    /// Gate one sample.
+    #[inline]
+    pub fn process(&mut self, x: f32) -> f32 {
+        let target = if self.detector.process(x) >= self.threshold {
+            self.hold = self.hold_samples;
+            1.0
+        } else if self.hold > 0 {
+            self.hold -= 1;
+            1.0
+        } else {
+            0.0
+        };
+        let coef = if target > self.gain {
+            self.attack
+        } else {
+            self.release
+        };
+        self.gain = target + (self.gain - target) * coef;
+        x * self.gain
+    }
+}
+
+/// Stage of an [`Adsr`] envelope.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AdsrStage {
+    Idle,
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+}
+
+/// Linear-segment ADSR envelope generator.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Adsr {
+    /// Per-sample level increments of the attack, decay and release ramps.
+    attack_step: f32,
+    decay_step: f32,
+    release_step: f32,
+    sustain: f32,
+    stage: AdsrStage,
+    level: f32,
+    /// Scale of the whole envelope, from the last [`Adsr::gate_velocity`].
+    velocity: f32,
+}
+
+impl Adsr {
+    /// Times are in milliseconds, each the length of a full ramp (attack
+    /// 0 to 1, decay 1 to `sustain`, release 1 to 0; a release from a lower
+    /// level is proportionally shorter). `sustain` is a level in `[0, 1]`.
+    /// A zero time makes its segment instantaneous.
+    pub fn new(
+        attack_ms: f32,
+        decay_ms: f32,
+        sustain: f32,
+        release_ms: f32,
+        sample_rate: f32,
+    ) -> Self {
+        let step = |ms: f32| {
+            let samples = ms * sample_rate / 1000.0;
+            if samples <= 1.0 {
+                1.0
+            } else {
+                1.0 / samples
+            }
+        };
+        Self {
+            attack_step: step(attack_ms),
+            decay_step: step(decay_ms),
+            release_step: step(release_ms),
+            sustain: sustain.clamp(0.0, 1.0),
+            stage: AdsrStage::Idle,
+            level: 0.0,
+            velocity: 1.0,
+        }
+    }
+
+    /// Open (`true`, note on) or close the gate. Retriggering attacks from
+    /// the current level.
+    pub fn gate(&mut self, on: bool) {
+        if on {
+            self.stage = AdsrStage::Attack;
+        } else if self.stage != AdsrStage::Idle {
+            self.stage = AdsrStage::Release;
+        }
+    }
+
+    /// Open the gate for a note played at `velocity` in `[0, 1]`, which
+    /// scales the envelope until the next note. [`Adsr::gate`] keeps the
+    /// last velocity (1.0 until one is given).
+    pub fn gate_velocity(&mut self, velocity: f32) {
+        self.velocity = velocity.clamp(0.0, 1.0);
+        self.gate(true);
+    }
+
+    /// Current stage.
+    pub fn stage(&self) -> AdsrStage {
+        self.stage
+    }
+
+    /// Advance one sample and return the envelope level, scaled by the
+    /// note velocity.
+    #[inline]
+    pub fn advance(&mut self) -> f32 {
+        match self.stage {
+            AdsrStage::Idle | AdsrStage::Sustain => {}
+            AdsrStage::Attack => {
+                self.level += self.attack_step;
+                if self.level >= 1.0 {
+                    self.level = 1.0;
+                    self.stage = AdsrStage::Decay;
+                }
+            }
+            AdsrStage::Decay => {
+                self.level -= self.decay_step * (1.0 - self.sustain);
+                if self.level <= self.sustain {
+                    self.level = self.sustain;
+                    self.stage = AdsrStage::Sustain;
+                }
+            }
+            AdsrStage::Release => {
+                self.level -= self.release_step;
+                if self.level <= 0.0 {
+                    self.level = 0.0;
+                    self.stage = AdsrStage::Idle;
+                }
+            }
+        }
+        self.level * self.velocity
+    }
+}
+
+/// How far [`TapeSaturator`]'s output lags its input at full saturation.
+const TAPE_HYSTERESIS: f32 = 0.3;
+
+/// Tape-style saturation: `tanh` soft clipping around a bias point, with a
+/// lag that grows with level so loud signals trace a hysteresis loop while
+/// quiet ones pass through linearly at unity gain.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TapeSaturator {
+    drive: f32,
+    bias: f32,
+    /// Output of the curve at rest, removed to keep the bias from adding DC.
+    rest: f32,
+    /// Inverse of the small-signal slope at the bias point.
+    makeup: f32,
+    prev: f32,
+}
+
+impl TapeSaturator {
+    /// `drive` scales the input into the curve (floored at 0.001); `bias`
+    /// offsets the operating point, making the curve asymmetric so even
+    /// harmonics appear.
+    pub fn new(drive: f32, bias: f32) -> Self {
+        let drive = drive.max(1e-3);
+        let rest = bias.tanh();
+        Self {
+            drive,
+            bias,
+            rest,
+            makeup: 1.0 / (drive * (1.0 - rest * rest)),
+            prev: rest,
+        }
+    }
+
+    /// Saturate one sample.
+    #[inline]
+    pub fn process(&mut self, x: f32) -> f32 {
+        let shaped = (self.drive * x + self.bias).tanh();
+        let excursion = shaped - self.rest;
+        let lagged = shaped + TAPE_HYSTERESIS * excursion * excursion * (self.prev - shaped);
+        self.prev = shaped;
+        (lagged - self.rest) * self.makeup
+    }
+}
+
+/// Head radius, in metres, of [`BinauralPanner`]'s spherical-head model.
+pub const HEAD_RADIUS_M: f32 = 0.0875;
+
+/// Speed of sound in air, in metres per second.
+pub const SPEED_OF_SOUND: f32 = 343.0;
+
+/// Inter-aural level difference, in dB, for a source directly to one side.
+pub const BINAURAL_MAX_ILD_DB: f32 = 6.0;
+
+/// Simple binaural panner: places a mono source at an azimuth using only an
+/// inter-aural time difference (Woodworth's spherical-head formula) and a
+/// level difference growing with `sin(azimuth)`. The near ear hears the
+/// source directly; the far ear hears it later and quieter.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BinauralPanner {
+    left: DelayLine,
+    right: DelayLine,
+    /// (left, right) delay in samples.
+    delays: [f32; 2],
+    /// (left, right) gain.
+    gains: [f32; 2],
+}
+
+impl BinauralPanner {
+    /// Largest inter-aural time difference, in samples, at `sample_rate`.
+    pub fn max_itd(sample_rate: f32) -> f32 {
+        Self::itd(std::f32::consts::FRAC_PI_2, sample_rate)
+    }
+
+    /// Woodworth ITD for a lateral angle `theta` in `[0, pi/2]` radians.
+    fn itd(theta: f32, sample_rate: f32) -> f32 {
+        HEAD_RADIUS_M / SPEED_OF_SOUND * (theta + theta.sin()) * sample_rate
+    }
+
+    /// Place the source at `azimuth_deg` (0 = front, 90 = right, -90 = left).
+    pub fn new(azimuth_deg: f32, sample_rate: f32) -> Self {
+        let max = Self::max_itd(sample_rate);
+        let mut panner = Self {
+            left: DelayLine::new(max),
+            right: DelayLine::new(max),
+            delays: [0.0; 2],
+            gains: [1.0; 2],
+        };
+        panner.set_azimuth(azimuth_deg, sample_rate);
+        panner
+    }
+
+    /// Move the source. Sources behind the head mirror those in front.
+    pub fn set_azimuth(&mut self, azimuth_deg: f32, sample_rate: f32) {
+        let lateral = azimuth_deg.to_radians().sin();
+        let far_delay = Self::itd(lateral.abs().asin(), sample_rate);
+        let far_gain = db_to_lin(-BINAURAL_MAX_ILD_DB * lateral.abs());
+        // Positive azimuth puts the source on the right: the left ear is far.
+        let far = if lateral >= 0.0 { 0 } else { 1 };
+        self.delays = [0.0; 2];
+        self.gains = [1.0; 2];
+        self.delays[far] = far_delay;
+        self.gains[far] = far_gain;
+    }
+
+    /// Render one sample as a (left, right) pair.
+    #[inline]
+    pub fn process(&mut self, x: f32) -> (f32, f32) {
+        (
+            self.left.process(x, self.delays[0]) * self.gains[0],
+            self.right.process(x, self.delays[1]) * self.gains[1],
+        )
+    }
+}
+
+/// Oversampling factor of [`TruePeakLimiter`]'s peak detector.
+pub const TRUE_PEAK_OVERSAMPLE: usize = 4;
+/// Interpolation taps on each side of an inter-sample position.
+const TRUE_PEAK_HALF_TAPS: usize = 8;
+const TRUE_PEAK_TAPS: usize = 2 * TRUE_PEAK_HALF_TAPS;
+
+/// Look-ahead limiter that keeps the 4x-oversampled (true-peak) signal
+/// under a ceiling.
+///
+/// Inter-sample peaks are estimated with a windowed-sinc interpolator. The
+/// output is delayed by [`TruePeakLimiter::LATENCY`] samples so gain can drop
+/// before every sample that contributes to an over; it recovers with a
+/// one-pole release.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TruePeakLimiter {
+    /// Interpolation kernels for the fractional positions 1/4, 2/4, 3/4.
+    kernels: [[f32; TRUE_PEAK_TAPS]; TRUE_PEAK_OVERSAMPLE - 1],
+    /// Last `TRUE_PEAK_TAPS` inputs, oldest first once rotated by `pos`.
+    history: [f32; TRUE_PEAK_TAPS],
+    /// Gain each recent inter-sample interval can tolerate.
+    required: [f32; TRUE_PEAK_TAPS],
+    pos: usize,
+    ceiling: f32,
+    release: f32,
+    gain: f32,
+}
+
+impl TruePeakLimiter {
+    /// Output delay in samples.
+    pub const LATENCY: usize = TRUE_PEAK_TAPS - 1;
+
+    /// Limit to `ceiling_db` dBTP, recovering over `release_ms`.
+    pub fn new(ceiling_db: f32, release_ms: f32, sample_rate: f32) -> Self {
+        let kernels = std::array::from_fn(|k| {
+            let frac = (k + 1) as f32 / TRUE_PEAK_OVERSAMPLE as f32;
+            std::array::from_fn(|i| {
+                // Tap i holds x[m - HALF + 1 + i]; interpolate at m + frac.
+                let t = frac + TRUE_PEAK_HALF_TAPS as f32 - 1.0 - i as f32;
+                let x = std::f32::consts::PI * t;
+                let sinc = if x.abs() < 1e-6 { 1.0 } else { x.sin() / x };
+                let window = 0.5 + 0.5 * (x / TRUE_PEAK_HALF_TAPS as f32).cos();
+                sinc * window
+            })
+        });
+        Self {
+            kernels,
+            history: [0.0; TRUE_PEAK_TAPS],
+            required: [1.0; TRUE_PEAK_TAPS],
+            pos: 0,
+            ceiling: db_to_lin(ceiling_db),
+            release: time_coef(release_ms, sample_rate),
+            gain: 1.0,
+        }
+    }
+
+    /// Push one sample and return the limited sample from `LATENCY` ago.
+    #[inline]
+    pub fn process(&mut self, x: f32) -> f32 {
+        self.history[self.pos] = x;
+        self.pos = (self.pos + 1) % TRUE_PEAK_TAPS;
+        let tap = |i: usize| self.history[(self.pos + i) % TRUE_PEAK_TAPS];
+
+        // Peak of the interval starting at the centre tap.
+        let mut peak = tap(TRUE_PEAK_HALF_TAPS - 1).abs();
+        for kernel in &self.kernels {
+            let y: f32 = kernel.iter().enumerate().map(|(i, c)| c * tap(i)).sum();
+            peak = peak.max(y.abs());
+        }
+        let slot = self.pos;
+        self.required[slot] = if peak > self.ceiling {
+            self.ceiling / peak
+        } else {
+            1.0
+        };
+
+        // Every interval whose interpolation touches the outgoing sample.
+        let target = self.required.iter().copied().fold(1.0, f32::min);
+        self.gain = if target < self.gain {
+            target
+        } else {
+            target + (self.gain - target) * self.release
+        };
+        // The oldest tap is the sample leaving the look-ahead window.
+        tap(0) * self.gain
+    }
+}
+
+/// Kernel half-width of [`Resampler`], in zero crossings.
+const RESAMPLE_ZERO_CROSSINGS: f64 = 16.0;
+
+/// Streaming round-trip sample-rate converter.
+///
+/// Input is converted to a virtual stream at `ratio` times the graph rate
+/// and immediately back, both steps by Hann-windowed sinc interpolation
+/// band-limited to the lower of the two rates. The output is the input
+/// delayed by [`Resampler::latency`] samples and, for `ratio < 1`, low-passed
+/// at the virtual Nyquist frequency.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Resampler {
+    ratio: f64,
+    /// Band limit of each step as a fraction of that step's input Nyquist.
+    up_cutoff: f64,
+    down_cutoff: f64,
+    /// Kernel half-widths in input and virtual samples.
+    up_width: f64,
+    down_width: f64,
+    latency: usize,
+    input: Vec<f32>,
+    inputs_seen: u64,
+    virtual_buf: Vec<f32>,
+    next_virtual: u64,
+}
+
+impl Resampler {
+    /// Smallest supported `ratio`.
+    pub const MIN_RATIO: f32 = 0.125;
+    /// Largest supported `ratio`.
+    pub const MAX_RATIO: f32 = 8.0;
+
+    /// Round trip through a virtual rate of `ratio` times the input rate.
+    pub fn new(ratio: f32) -> Self {
+        let ratio = Self::supported(ratio);
+        let up_cutoff = ratio.min(1.0);
+        let down_cutoff = (1.0 / ratio).min(1.0);
+        let up_width = RESAMPLE_ZERO_CROSSINGS / up_cutoff;
+        let down_width = RESAMPLE_ZERO_CROSSINGS / down_cutoff;
+        Self {
+            ratio,
+            up_cutoff,
+            down_cutoff,
+            up_width,
+            down_width,
+            latency: Self::latency_for(ratio as f32),
+            input: vec![0.0; 2 * up_width.ceil() as usize + 4],
+            inputs_seen: 0,
+            virtual_buf: vec![0.0; (2.0 * down_width + ratio).ceil() as usize + 4],
+            next_virtual: 0,
+        }
+    }
+
+    /// Delay, in samples, introduced by a resampler at `ratio`.
+    pub fn latency_for(ratio: f32) -> usize {
+        let ratio = Self::supported(ratio);
+        let up_width = RESAMPLE_ZERO_CROSSINGS / ratio.min(1.0);
+        let down_width = RESAMPLE_ZERO_CROSSINGS / (1.0 / ratio).min(1.0);
+        up_width.ceil() as usize + (down_width / ratio).ceil() as usize
+    }
+
+    /// Delay, in samples, between input and output.
+    pub fn latency(&self) -> usize {
+        self.latency
+    }
+
+    /// `ratio` clamped to the supported range; 1.0 if it is not finite.
+    fn supported(ratio: f32) -> f64 {
+        if !ratio.is_finite() {
+            return 1.0;
+        }
+        f64::from(ratio.clamp(Self::MIN_RATIO, Self::MAX_RATIO))
+    }
+
+    /// Push one input sample and return one output sample.
+    pub fn process(&mut self, x: f32) -> f32 {
+        let newest = self.inputs_seen;
+        let len = self.input.len();
+        self.input[(newest % len as u64) as usize] = x;
+        self.inputs_seen += 1;
+        let newest = newest as f64;
+
+        // Up: every virtual sample whose kernel is now fully in the past.
+        loop {
+            let t = self.next_virtual as f64 / self.ratio;
+            if t + self.up_width > newest {
+                break;
+            }
+            let first = (t - self.up_width).ceil().max(0.0) as u64;
+            let last = (t + self.up_width).floor() as u64;
+            let v: f64 = (first..=last)
+                .map(|j| {
+                    let x = self.input[(j % len as u64) as usize];
+                    f64::from(x) * kernel(t - j as f64, self.up_cutoff, self.up_width)
+                })
+                .sum();
+            let vlen = self.virtual_buf.len() as u64;
+            self.virtual_buf[(self.next_virtual % vlen) as usize] = v as f32;
+            self.next_virtual += 1;
+        }
+
+        // Down: the output sample `latency` behind the newest input.
+        let m = newest - self.latency as f64;
+        if m < 0.0 {
+            return 0.0;
+        }
+        let p = m * self.ratio;
+        let first = (p - self.down_width).ceil().max(0.0) as u64;
+        let last = ((p + self.down_width).floor() as u64).min(self.next_virtual.saturating_sub(1));
+        let vlen = self.virtual_buf.len() as u64;
+        let y: f64 = (first..=last)
+            .map(|k| {
+                let v = self.virtual_buf[(k % vlen) as usize];
+                f64::from(v) * kernel(p - k as f64, self.down_cutoff, self.down_width)
+            })
+            .sum();
+        y as f32
+    }
+}
+
+/// Hann-windowed sinc low-pass at `cutoff` (fraction of Nyquist), `width`
+/// samples either side of the centre.
+fn kernel(t: f64, cutoff: f64, width: f64) -> f64 {
+    if t.abs() >= width {
+        return 0.0;
+    }
+    let x = std::f64::consts::PI * cutoff * t;
+    let sinc = if x.abs() < 1e-9 { 1.0 } else { x.sin() / x };
+    let window = 0.5 + 0.5 * (std::f64::consts::PI * t / width).cos();
+    cutoff * sinc * window
+}
+
+/// One-pole smoothing coefficient reaching ~63% of a step in `ms`.
+/// Zero or negative times give an instant response.
+pub fn time_coef(ms: f32, sample_rate: f32) -> f32 {
+    let samples = ms * sample_rate / 1000.0;
+    if samples <= 0.0 {
+        0.0
+    } else {
+        (-1.0 / samples).exp()
+    }
+}
+
+/// Second-order IIR section (transposed direct form II).
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Biquad {
+    b: [f32; 3],
+    a: [f32; 2],
+    z: [f32; 2],
+}
+
+impl Biquad {
+    /// Butterworth (Q = 1/sqrt 2) low-pass at `freq` Hz.
+    pub fn lowpass(freq: f32, sample_rate: f32) -> Self {
+        Self::resonant_lowpass(freq, std::f32::consts::FRAC_1_SQRT_2, sample_rate)
+    }
+
+    /// Low-pass at `freq` Hz with resonance `q` (floored at 0.1).
+    pub fn resonant_lowpass(freq: f32, q: f32, sample_rate: f32) -> Self {
+        let (cos, alpha) = Self::prewarp(freq, q, sample_rate);
+        let b1 = 1.0 - cos;
+        Self::normalized([b1 * 0.5, b1, b1 * 0.5], cos, alpha)
+    }
+
+    /// Butterworth (Q = 1/sqrt 2) high-pass at `freq` Hz.
+    pub fn highpass(freq: f32, sample_rate: f32) -> Self {
+        let (cos, alpha) = Self::prewarp(freq, std::f32::consts::FRAC_1_SQRT_2, sample_rate);
+        let b1 = 1.0 + cos;
+        Self::normalized([b1 * 0.5, -b1, b1 * 0.5], cos, alpha)
+    }
+
+    /// Replace the coefficients with those of
+    /// [`Biquad::resonant_lowpass`], keeping the filter memory so a sweep
+    /// does not click. RT-safe.
+    pub fn retune_lowpass(&mut self, freq: f32, q: f32, sample_rate: f32) {
+        let z = self.z;
+        *self = Self::resonant_lowpass(freq, q, sample_rate);
+        self.z = z;
+    }
+
+    fn prewarp(freq: f32, q: f32, sample_rate: f32) -> (f32, f32) {
+        let nyquist = sample_rate * 0.5;
+        let w0 = 2.0 * std::f32::consts::PI * freq.clamp(1.0, nyquist * 0.99) / sample_rate;
+        (w0.cos(), w0.sin() / (2.0 * q.max(0.1)))
+    }
+
+    fn normalized(b: [f32; 3], cos: f32, alpha: f32) -> Self {
+        let a0 = 1.0 + alpha;
+        Self {
+            b: [b[0] / a0, b[1] / a0, b[2] / a0],
+            a: [-2.0 * cos / a0, (1.0 - alpha) / a0],
+            z: [0.0; 2],
+        }
+    }
+
+    /// Filter one sample.
+    #[inline]
+    pub fn process(&mut self, x: f32) -> f32 {
+        let y = self.b[0] * x + self.z[0];
+        self.z[0] = self.b[1] * x - self.a[0] * y + self.z[1];
+        self.z[1] = self.b[2] * x - self.a[1] * y;
+        y
+    }
+}
+
+/// Fourth-order Linkwitz-Riley band split.
+///
+/// Each band is two cascaded Butterworth sections, so both are -6 dB at the
+/// crossover and their sum is an all-pass: flat magnitude, only phase moves.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Crossover {
+    low: [Biquad; 2],
+    high: [Biquad; 2],
+}
+
+impl Crossover {
+    /// Split at `freq` Hz.
+    pub fn new(freq: f32, sample_rate: f32) -> Self {
+        let lp = Biquad::lowpass(freq, sample_rate);
+        let hp = Biquad::highpass(freq, sample_rate);
+        Self {
+            low: [lp.clone(), lp],
+            high: [hp.clone(), hp],
+        }
+    }
+
+    /// Split one sample into `(low, high)`.
+    #[inline]
+    pub fn process(&mut self, x: f32) -> (f32, f32) {
+        let low = self.low[0].process(x);
+        let high = self.high[0].process(x);
+        (self.low[1].process(low), self.high[1].process(high))
+    }
+}
+
+/// Squared coefficients of the two allpass chains of a Hilbert transformer
+/// (Niemitalo's design); their outputs stay 90 degrees apart from about
+/// 20 Hz to 0.45 times the sample rate.
+const HILBERT_COEFS: [[f32; 4]; 2] = [
+    [0.479_400_9, 0.876_218_5, 0.976_597_6, 0.997_499_3],
+    [0.161_758_5, 0.733_028_9, 0.945_349_7, 0.990_599_2],
+];
+
+/// Single-sideband frequency shifter: moves every component of the input
+/// by a fixed number of Hz (so harmonics become inharmonic).
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FreqShifter {
+    /// `[x1, x2, y1, y2]` per allpass section, per chain.
+    sections: [[[f32; 4]; 4]; 2],
+    /// One-sample delay that aligns the in-phase chain.
+    delayed: f32,
+    /// Carrier phase in cycles, [0, 1).
+    phase: f32,
+    step: f32,
+}
+
+impl FreqShifter {
+    /// Shift by `shift_hz` (negative shifts down).
+    pub fn new(shift_hz: f32, sample_rate: f32) -> Self {
+        Self {
+            sections: [[[0.0; 4]; 4]; 2],
+            delayed: 0.0,
+            phase: 0.0,
+            step: shift_hz / sample_rate,
+        }
+    }
+
+    /// Shift one sample.
+    #[inline]
+    pub fn process(&mut self, x: f32) -> f32 {
+        let mut paths = [x, x];
+        for ((y, sections), coefs) in paths
+            .iter_mut()
+            .zip(self.sections.iter_mut())
+            .zip(HILBERT_COEFS)
+        {
+            for (z, a) in sections.iter_mut().zip(coefs) {
+                let out = a * (*y + z[3]) - z[1];
+                *z = [*y, z[0], out, z[2]];
+                *y = out;
+            }
+        }
+        let (re, im) = (self.delayed, paths[1]);
+        self.delayed = paths[0];
+        let (sin, cos) = (2.0 * std::f32::consts::PI * self.phase).sin_cos();
+        self.phase = (self.phase + self.step).rem_euclid(1.0);
+        re * cos + im * sin
+    }
+}
+
+/// Map `x` (clamped to `[-1, 1]`) through a transfer-function table spanning
+/// `[-1, 1]`, interpolating linearly between entries.
+#[inline]
+pub fn table_lookup(table: &[f32], x: f32) -> f32 {
+    match table.len() {
+        0 => 0.0,
+        1 => table[0],
+        len => {
+            let pos = (x.clamp(-1.0, 1.0) + 1.0) * 0.5 * (len - 1) as f32;
+            let i = (pos.floor() as usize).min(len - 2);
+            let frac = pos - i as f32;
+            table[i] + (table[i + 1] - table[i]) * frac
+        }
+    }
+}
+
+/// How a wavetable is read between its entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Interpolation {
+    /// Truncate to the entry below: cheapest, most aliasing.
+    None,
+    /// Straight line between neighbouring entries.
+    #[default]
+    Linear,
+    /// Four-point Catmull-Rom spline: smoothest, about twice the arithmetic
+    /// of `Linear`.
+    Cubic,
+}
+
+/// Read a single-cycle `table` at `phase` cycles (wrapped to `[0, 1)`).
+#[inline]
+pub fn wavetable_read(table: &[f32], phase: f32, interpolation: Interpolation) -> f32 {
+    let len = table.len();
+    if len == 0 {
+        return 0.0;
+    }
+    let pos = phase.rem_euclid(1.0) * len as f32;
+    let i = (pos as usize).min(len - 1);
+    let frac = pos - i as f32;
+    let at = |offset: usize| table[(i + offset) % len];
+    match interpolation {
+        Interpolation::None => at(0),
+        Interpolation::Linear => at(0) + (at(1) - at(0)) * frac,
+        Interpolation::Cubic => {
+            let (y0, y1, y2, y3) = (at(len - 1), at(0), at(1), at(2));
+            let c1 = 0.5 * (y2 - y0);
+            let c2 = y0 - 2.5 * y1 + 2.0 * y2 - 0.5 * y3;
+            let c3 = 0.5 * (y3 - y0) + 1.5 * (y1 - y2);
+            ((c3 * frac + c2) * frac + c1) * frac + y1
+        }
+    }
+}
+
+/// PolyBLEP residual of a rising unit step at phase 0, for a waveform at
+/// `phase` cycles advancing `dt` cycles per sample. Spans one sample either
+/// side of the step; zero elsewhere.
+#[inline]
+pub fn poly_blep(phase: f32, dt: f32) -> f32 {
+    if phase < dt {
+        let t = phase / dt;
+        t + t - t * t - 1.0
+    } else if phase > 1.0 - dt {
+        let t = (phase - 1.0) / dt;
+        t * t + t + t + 1.0
+    } else {
+        0.0
+    }
+}
+
+/// PolyBLAMP residual of a unit change of slope (per sample) at phase 0:
+/// the integral of [`poly_blep`], used to round off corners.
+#[inline]
+pub fn poly_blamp(phase: f32, dt: f32) -> f32 {
+    let distance = if phase < dt {
+        phase / dt
+    } else if phase > 1.0 - dt {
+        (1.0 - phase) / dt
+    } else {
+        return 0.0;
+    };
+    let x = 1.0 - distance;
+    x * x * x / 6.0
+}
+
+/// Band-limited sawtooth at `phase` cycles, rising from -1 to 1.
+#[inline]
+pub fn saw_blep(phase: f32, dt: f32) -> f32 {
+    2.0 * phase - 1.0 - poly_blep(phase, dt)
+}
+
+/// Band-limited pulse at `phase` cycles: 1 for the first `width` of the
+/// cycle, -1 for the rest. `width` is clamped to `[0.01, 0.99]`.
+#[inline]
+pub fn pulse_blep(phase: f32, dt: f32, width: f32) -> f32 {
+    let width = width.clamp(0.01, 0.99);
+    let naive = if phase < width { 1.0 } else { -1.0 };
+    naive + poly_blep(phase, dt) - poly_blep((phase + 1.0 - width) % 1.0, dt)
+}
+
+/// Band-limited triangle at `phase` cycles: -1 at phase 0, 1 at 0.5.
+#[inline]
+pub fn tri_blamp(phase: f32, dt: f32) -> f32 {
+    // The slope swings by 8 per cycle at each corner.
+    let corner = 8.0 * dt;
+    1.0 - 4.0 * (phase - 0.5).abs() + corner * poly_blamp(phase, dt)
+        - corner * poly_blamp((phase + 0.5) % 1.0, dt)
+}
+
+/// Linear amplitude to decibels, floored at -120 dB.
+#[inline]
+pub fn lin_to_db(x: f32) -> f32 {
+    20.0 * x.max(1e-6).log10()
+}
+
+/// Decibels to linear amplitude.
+#[inline]
+pub fn db_to_lin(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+/// Frequency in Hz of MIDI note `note` (69 = A4 = 440 Hz), equal temperament.
+#[inline]
+pub fn midi_to_hz(note: u8) -> f32 {
+    440.0 * 2f32.powf((note as f32 - 69.0) / 12.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delay_line_fractional_impulse() {
+        let mut line = DelayLine::new(10.5);
+        let out: Vec<f32> = (0..16)
+            .map(|n| line.process(if n == 0 { 1.0 } else { 0.0 }, 10.5))
+            .collect();
+        for (n, &y) in out.iter().enumerate() {
+            match n {
+                10 | 11 => assert!((y - 0.5).abs() < 1e-6, "sample {} = {}", n, y),
+                _ => assert_eq!(y, 0.0, "sample {} should be silent", n),
+            }
+        }
+    }
+
+    #[test]
+    fn linear_ramp_reaches_target_exactly() {
+        let mut ramp = LinearRamp::new(1.0, 0.0, 4);
+        let out: Vec<f32> = (0..6).map(|_| ramp.advance()).collect();
+        assert_eq!(out, [0.75, 0.5, 0.25, 0.0, 0.0, 0.0]);
+        assert!(!ramp.is_ramping());
+        assert_eq!(LinearRamp::new(1.0, 0.5, 0).value(), 0.5);
+    }
+
+    /// Energy at DFT bins that are not harmonics of `bin`, i.e. aliasing.
+    fn alias_energy(signal: &[f32], bin: usize) -> f32 {
+        let n = signal.len();
+        (1..n / 2)
+            .filter(|b| b % bin != 0)
+            .map(|b| {
+                let (mut re, mut im) = (0.0f32, 0.0f32);
+                for (i, &x) in signal.iter().enumerate() {
+                    let w = 2.0 * std::f32::consts::PI * ((b * i) % n) as f32 / n as f32;
+                    re += x * w.cos();
+                    im -= x * w.sin();
+                }
+                re * re + im * im
+            })
+            .sum()
+    }
+
+    #[test]
+    fn blep_oscillators_alias_less_than_naive() {
+        // 3140 Hz at 48 kHz: harmonics land on every 157th of 2400 bins,
+        // their aliases between them.
+        let dt = 3140.0 / 48000.0;
+        let render = |osc: &dyn Fn(f32) -> f32| -> Vec<f32> {
+            (0..2400).map(|n| osc((n as f32 * dt) % 1.0)).collect()
+        };
+        let naive_saw = render(&|p| 2.0 * p - 1.0);
+        let naive_tri = render(&|p| 1.0 - 4.0 * (p - 0.5).abs());
+        let naive_pulse = render(&|p| if p < 0.5 { 1.0 } else { -1.0 });
+        let saw = render(&|p| saw_blep(p, dt));
+        let tri = render(&|p| tri_blamp(p, dt));
+        let pulse = render(&|p| pulse_blep(p, dt, 0.5));
+        assert!(alias_energy(&saw, 157) < 0.1 * alias_energy(&naive_saw, 157));
+        assert!(alias_energy(&pulse, 157) < 0.1 * alias_energy(&naive_pulse, 157));
+        assert!(alias_energy(&tri, 157) < 0.1 * alias_energy(&naive_tri, 157));
+        assert!(saw.iter().chain(&tri).chain(&pulse).all(|x| x.abs() <= 1.0));
+    }
+
+    #[test]
+    fn delay_line_clamps_negative_delay() {
+        let mut line = DelayLine::new(4.0);
+        assert_eq!(line.process(0.75, -3.0), 0.75);
+    }
+
+    #[test]
+    fn delay_line_limits_its_length() {
+        for (max_delay, expected) in [
+            (f32::NAN, 0.0),
+            (-5.0, 0.0),
+            (f32::NEG_INFINITY, 0.0),
+            (f32::INFINITY, DelayLine::MAX_DELAY),
+            (1e12, DelayLine::MAX_DELAY),
+        ] {
+            let mut line = DelayLine::new(max_delay);
+            assert_eq!(line.max_delay(), expected, "max_delay {}", max_delay);
+            assert_eq!(line.process(0.75, 0.0), 0.75);
+        }
+    }
+}
@@ -3,24 +3,46 @@
 #![forbid(unsafe_code)]
 // #![deny(missing_docs)]
 
+/// Audio samples per control-rate value: a control-rate buffer holds one
+/// value per `CONTROL_RATE_DIVISOR` samples of the block, rounded up.
+pub const CONTROL_RATE_DIVISOR: usize = 16;
+
 #[non_exhaustive]
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Rate {
     Audio,
+    /// One value per [`CONTROL_RATE_DIVISOR`] audio samples.
     Control,
     Event,
 }
 
-/// Unique identifier for a node.
+impl Rate {
+    /// Length of a buffer at this rate covering `frames` audio samples.
+    pub fn buffer_len(&self, frames: usize) -> usize {
+        match self {
+            Rate::Control => frames.div_ceil(CONTROL_RATE_DIVISOR),
+            Rate::Audio | Rate::Event => frames,
+        }
+    }
+}
+
+/// Unique identifier for a node: its index in `Graph::nodes` and the
+/// generation of that slot. Removing a node frees its slot for a later node
+/// under the next generation, so the removed node's id goes stale instead
+/// of referring to the newcomer.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
-pub struct NodeId(pub usize);
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeId(pub usize, pub u32);
 
 /// Unique identifier for a port.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PortId(pub usize);
 
 /// A port with its rate.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Port {
     pub id: PortId,
     pub rate: Rate,
@@ -28,89 +50,376 @@ pub struct Port {
 
 /// An edge connecting two ports.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Edge {
     pub from_node: NodeId,
     pub from_port: PortId,
     pub to_node: NodeId,
     pub to_port: PortId,
     pub rate: Rate,
+    /// A muted edge carries silence instead of its source's output.
+    pub muted: bool,
 }
 
 /// A node in the graph.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodeData {
     pub id: NodeId,
     pub inputs: Vec<Port>,
     pub outputs: Vec<Port>,
     pub node_type: NodeType,
+    /// Generic named parameters, applied when a runtime is built
+    /// (see [`Graph::set_node_param`]).
+    pub params: Vec<(String, f32)>,
+    /// Unique name for lookups (see [`Graph::set_node_name`]).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub name: Option<String>,
 }
 
+use crate::dsp::Interpolation;
 use crate::invariant_ppt::{assert_invariant, GRAPH_REJECTS_INVALID};
+#[cfg(feature = "serde")]
+use crate::node::NodeRegistry;
+use crate::node::{NodeDef, NodeDefDyn};
+use crate::voice::VoiceNodes;
+use std::borrow::Cow;
+use std::sync::Arc;
 
 #[non_exhaustive]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Types of DSP nodes available in the graph.
 pub enum NodeType {
     /// Sine wave oscillator.
     SineOsc { freq: f32 },
+    /// Sine sweep from `start_hz` to `end_hz` over `duration_samples`,
+    /// linear in frequency or, with `log`, exponential (equal time per
+    /// octave). Silent once the sweep is over.
+    Chirp {
+        start_hz: f32,
+        end_hz: f32,
+        duration_samples: usize,
+        log: bool,
+    },
+    /// Two summed sine oscillators (e.g. a DTMF digit), each at half
+    /// amplitude so the sum stays within [-1, 1]. `ControlMsg::SetParam`
+    /// index 0 sets `freq_a`, index 1 `freq_b`.
+    DualTone { freq_a: f32, freq_b: f32 },
+    /// Wavetable oscillator playing the single-cycle `table` at `freq` Hz.
+    /// `interpolation` trades CPU for fidelity between table entries.
+    Wavetable {
+        table: Vec<f32>,
+        freq: f32,
+        interpolation: Interpolation,
+    },
+    /// Band-limited (polyBLEP) sawtooth oscillator, rising from -1 to 1.
+    SawOsc { freq: f32 },
+    /// Band-limited (polyBLEP) pulse oscillator, high for the first `width`
+    /// of each cycle; 0.5 is a square. `width` is clamped to `[0.01, 0.99]`.
+    PulseOsc { freq: f32, width: f32 },
+    /// Band-limited (polyBLAMP) triangle oscillator.
+    TriOsc { freq: f32 },
     /// Gain/multiplication node.
     Gain { gain: f32 },
     /// Mixer node (sums two inputs).
     Mix,
     /// Output sink (terminates the graph).
     OutputSink,
+    /// Multi-channel output sink: input port `c` is output channel `c`, all
+    /// required. Rendered with `Runtime::process_block_multi`.
+    MultiOutputSink { channels: usize },
+    /// Host audio input. Plays the buffer last supplied with
+    /// `Runtime::set_input` / `Runtime::set_input_named`, or the host's
+    /// input `channel` when driven by `Runtime::process_block_io`; `label`
+    /// names the input's role (e.g. "mic", "line").
+    AudioInput { label: String, channel: usize },
+    /// Live looper: records its input into a pre-allocated buffer of
+    /// `max_samples` and plays it back, driven by `ControlMsg::SetLooperMode`.
+    Looper { max_samples: usize },
+    /// Delay line. `delay_samples` may be fractional (linear interpolation)
+    /// and is clamped with `dsp::DelayLine::clamp_delay`: negative values
+    /// and NaN become zero, since delays must be causal, and longer delays
+    /// are capped at `DelayLine::MAX_DELAY`. [`Graph::validate`] reports
+    /// values that get clamped.
+    ///
+    /// Cycles are allowed as long as they pass through a delay. Inside such a
+    /// loop the delay hears its input one block late, at the block boundary,
+    /// and shortens itself to match, so the loop delay is `delay_samples`
+    /// whenever that is at least the block size (and one block otherwise).
+    Delay { delay_samples: f32 },
+    /// Chorus: `voices` LFO-modulated delay taps mixed with the dry signal.
+    /// `mix` is the wet proportion (0.0 = dry only, 1.0 = wet only).
+    Chorus {
+        rate_hz: f32,
+        depth_ms: f32,
+        mix: f32,
+        voices: usize,
+    },
+    /// Feed-forward compressor. Port 0 is the main input; port 1 is an
+    /// optional sidechain that, when connected, drives gain reduction instead
+    /// of the main signal (e.g. ducking a pad from a kick drum).
+    Compressor {
+        threshold_db: f32,
+        ratio: f32,
+        attack_ms: f32,
+        release_ms: f32,
+    },
+    /// Noise gate: silences the input once its envelope has stayed below
+    /// `threshold_db` for `hold_ms`, fading out over `release_ms` and back
+    /// in over `attack_ms` when the signal returns.
+    NoiseGate {
+        threshold_db: f32,
+        attack_ms: f32,
+        hold_ms: f32,
+        release_ms: f32,
+    },
+    /// True-peak limiter for export: keeps the 4x-oversampled signal under
+    /// `ceiling_db` (dBTP), recovering over `release_ms`. Delays the signal
+    /// by `dsp::TruePeakLimiter::LATENCY` samples of look-ahead.
+    TruePeakLimiter { ceiling_db: f32, release_ms: f32 },
+    /// Sample-rate round trip: converts the input to a virtual stream at
+    /// `ratio` times the graph rate and back with windowed-sinc
+    /// interpolation, buffering across blocks. `ratio` is clamped to
+    /// `[0.125, 8]`, and a NaN or infinite one counts as 1; the output lags
+    /// by `dsp::Resampler::latency_for(ratio)`.
+    Resample { ratio: f32 },
+    /// First difference: `y[n] = x[n] - x[n-1]`, continuous across blocks.
+    Diff,
+    /// Seeded noise source. `color` sets the spectral slope:
+    /// 0.0 = white, 1.0 = pink, 2.0 = brown, fractional values blend.
+    Noise { seed: u64, color: f32 },
+    /// Static waveshaper. Each input sample, clamped to `[-1, 1]`, is mapped
+    /// through `table` (evenly spanning `[-1, 1]`) with linear interpolation.
+    Waveshaper { table: Vec<f32> },
+    /// Panner bank: input `i` is equal-power panned to `pans[i]`
+    /// (-1.0 = left, 1.0 = right) and all inputs are summed into a stereo
+    /// pair on output ports 0 (left) and 1 (right). Unconnected inputs are
+    /// silent. `ControlMsg::SetParam` with `param_idx = i` re-pans input `i`.
+    PanBank { pans: Vec<f32> },
+    /// Auto-panner: the mono input is equal-power panned to a stereo pair
+    /// (port 0 left, port 1 right) by a sine LFO at `rate_hz` swinging
+    /// `depth` of the way to each side (0.0 = centered, 1.0 = hard L/R).
+    /// `ControlMsg::SetParam` index 0 sets the rate, index 1 the depth.
+    AutoPan { rate_hz: f32, depth: f32 },
+    /// Rotary speaker: the mono input is sent through a rotating horn at
+    /// `speed_hz` revolutions per second onto a stereo pair (port 0 left,
+    /// port 1 right), with Doppler pitch wobble from a modulated delay,
+    /// amplitude modulation and panning, all scaled by `depth` in [0, 1].
+    /// `ControlMsg::SetParam` index 0 sets the speed (e.g. to switch between
+    /// chorale and tremolo), index 1 the depth.
+    Rotary { speed_hz: f32, depth: f32 },
+    /// Binaural panner (`dsp::BinauralPanner`): the mono input is placed at
+    /// `azimuth` degrees (0 = front, 90 = right, -90 = left) on a stereo
+    /// pair (port 0 left, port 1 right) using inter-aural time and level
+    /// differences only, not HRTF filtering. `ControlMsg::SetParam` index 0
+    /// sets the azimuth.
+    Binaural { azimuth: f32 },
+    /// Resonant low-pass biquad at `cutoff` Hz with resonance `q`
+    /// (0.707 = no peak). Retuned by `ControlMsg::SetFilterCutoff` and
+    /// `ControlMsg::SetFilterResonance`.
+    Filter { cutoff: f32, q: f32 },
+    /// ADSR envelope (`dsp::Adsr`; times in ms, `sustain` a level in
+    /// [0, 1]) opened and closed by `ControlMsg::TriggerGate`, and released
+    /// along with every other envelope by `ControlMsg::AllNotesOff`. Multiplies
+    /// its input, or outputs the envelope itself if the input is
+    /// unconnected.
+    Adsr {
+        attack: f32,
+        decay: f32,
+        sustain: f32,
+        release: f32,
+    },
+    /// Tape-style saturation (`dsp::TapeSaturator`): linear at low levels,
+    /// soft-clipping with level-dependent hysteresis when driven. `bias`
+    /// makes the curve asymmetric, adding even harmonics.
+    TapeSat { drive: f32, bias: f32 },
+    /// Single-sideband frequency shifter: every input component moves by
+    /// `shift_hz` (negative shifts down), using an allpass Hilbert
+    /// transformer and quadrature modulation.
+    FreqShift { shift_hz: f32 },
+    /// Gain driven by a signal: the audio on port 0 is multiplied by the
+    /// control-rate input on port 1 clamped to [0, 1], each control value
+    /// covering its `CONTROL_RATE_DIVISOR` samples (e.g. a vocoder band
+    /// envelope or a rhythmic gate pattern).
+    GatedGain,
+    /// Mid-side width: the stereo pair on ports 0 (left) and 1 (right) has
+    /// its side component scaled by `width` (0.0 = mono, 1.0 = unchanged,
+    /// above 1.0 = wider). `ControlMsg::SetParam` index 0 sets the width.
+    StereoWidth { width: f32 },
+    /// Rate adapter: audio in, control out carrying the mean of every
+    /// `CONTROL_RATE_DIVISOR` samples.
+    AudioToControl,
+    /// Rate adapter: control in, audio out ramping linearly from each
+    /// control value to the next. Inserted automatically by
+    /// `Graph::add_edge` when a control edge feeds an audio input.
+    ControlToAudio,
+    /// Fourth-order Linkwitz-Riley crossover at `freq` Hz. Output port 0
+    /// carries the low band and port 1 the high band; their sum has the
+    /// input's magnitude response.
+    Crossover { freq: f32 },
+    /// User-defined node implementing [`NodeDef`]. At most
+    /// `rt::MAX_EXTERNAL_PORTS` inputs and outputs are processed.
+    /// `ControlMsg::SetParam` reaches [`NodeDef::set_param_index`]. Two
+    /// external nodes are equal only when they share one `def`.
+    External {
+        #[cfg_attr(feature = "serde", serde(with = "crate::node::registered"))]
+        def: Arc<dyn NodeDefDyn>,
+    },
+    /// A whole graph used as one node (see [`Graph::add_subgraph`]). Input
+    /// port `i` feeds `inputs[i]` inside and output port `j` carries
+    /// `outputs[j]`. Flattened into the enclosing graph when a plan or
+    /// runtime is built.
+    Subgraph {
+        graph: Box<Graph>,
+        inputs: Vec<(NodeId, PortId)>,
+        outputs: Vec<(NodeId, PortId)>,
+    },
     /// Dummy node for testing.
     Dummy, // For testing
 }
 
 impl NodeType {
+    /// Wrap a [`NodeDef`] implementation as an external node.
+    pub fn external<T: NodeDef>(def: T) -> Self {
+        NodeType::External { def: Arc::new(def) }
+    }
+
+    /// The variant's name, e.g. `"SineOsc"`, for logs and bug reports.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            NodeType::SineOsc { .. } => "SineOsc",
+            NodeType::Chirp { .. } => "Chirp",
+            NodeType::DualTone { .. } => "DualTone",
+            NodeType::Wavetable { .. } => "Wavetable",
+            NodeType::SawOsc { .. } => "SawOsc",
+            NodeType::PulseOsc { .. } => "PulseOsc",
+            NodeType::TriOsc { .. } => "TriOsc",
+            NodeType::Gain { .. } => "Gain",
+            NodeType::Mix => "Mix",
+            NodeType::OutputSink => "OutputSink",
+            NodeType::MultiOutputSink { .. } => "MultiOutputSink",
+            NodeType::AudioInput { .. } => "AudioInput",
+            NodeType::Looper { .. } => "Looper",
+            NodeType::Delay { .. } => "Delay",
+            NodeType::Chorus { .. } => "Chorus",
+            NodeType::Compressor { .. } => "Compressor",
+            NodeType::NoiseGate { .. } => "NoiseGate",
+            NodeType::TruePeakLimiter { .. } => "TruePeakLimiter",
+            NodeType::Resample { .. } => "Resample",
+            NodeType::Diff => "Diff",
+            NodeType::Noise { .. } => "Noise",
+            NodeType::Waveshaper { .. } => "Waveshaper",
+            NodeType::PanBank { .. } => "PanBank",
+            NodeType::AutoPan { .. } => "AutoPan",
+            NodeType::Binaural { .. } => "Binaural",
+            NodeType::Rotary { .. } => "Rotary",
+            NodeType::GatedGain => "GatedGain",
+            NodeType::FreqShift { .. } => "FreqShift",
+            NodeType::TapeSat { .. } => "TapeSat",
+            NodeType::Filter { .. } => "Filter",
+            NodeType::Adsr { .. } => "Adsr",
+            NodeType::StereoWidth { .. } => "StereoWidth",
+            NodeType::AudioToControl => "AudioToControl",
+            NodeType::ControlToAudio => "ControlToAudio",
+            NodeType::Crossover { .. } => "Crossover",
+            NodeType::External { .. } => "External",
+            NodeType::Subgraph { .. } => "Subgraph",
+            NodeType::Dummy => "Dummy",
+        }
+    }
+
     pub fn input_ports(&self) -> Vec<Port> {
         match self {
-            NodeType::Dummy => vec![Port {
-                id: PortId(0),
-                rate: Rate::Audio,
-            }],
-            NodeType::SineOsc { .. } => vec![],
-            NodeType::Gain { .. } => vec![Port {
-                id: PortId(0),
-                rate: Rate::Audio,
-            }],
-            NodeType::Mix => vec![
+            NodeType::External { def } => def.input_ports().to_vec(),
+            NodeType::Subgraph { graph, inputs, .. } => graph.exposed_ports(inputs, true),
+            NodeType::SineOsc { .. }
+            | NodeType::Chirp { .. }
+            | NodeType::Wavetable { .. }
+            | NodeType::SawOsc { .. }
+            | NodeType::PulseOsc { .. }
+            | NodeType::TriOsc { .. }
+            | NodeType::DualTone { .. }
+            | NodeType::Noise { .. }
+            | NodeType::AudioInput { .. } => vec![],
+            NodeType::Mix | NodeType::Compressor { .. } | NodeType::StereoWidth { .. } => {
+                audio_ports(2)
+            }
+            NodeType::ControlToAudio => control_ports(1),
+            NodeType::GatedGain => vec![
                 Port {
                     id: PortId(0),
                     rate: Rate::Audio,
                 },
                 Port {
                     id: PortId(1),
-                    rate: Rate::Audio,
+                    rate: Rate::Control,
                 },
             ],
-            NodeType::OutputSink => vec![Port {
-                id: PortId(0),
-                rate: Rate::Audio,
-            }],
+            NodeType::PanBank { pans } => audio_ports(pans.len()),
+            NodeType::MultiOutputSink { channels } => audio_ports(*channels),
+            NodeType::Dummy
+            | NodeType::Gain { .. }
+            | NodeType::OutputSink
+            | NodeType::Looper { .. }
+            | NodeType::Delay { .. }
+            | NodeType::Chorus { .. }
+            | NodeType::Diff
+            | NodeType::Waveshaper { .. }
+            | NodeType::Crossover { .. }
+            | NodeType::AutoPan { .. }
+            | NodeType::Binaural { .. }
+            | NodeType::Rotary { .. }
+            | NodeType::FreqShift { .. }
+            | NodeType::TapeSat { .. }
+            | NodeType::Filter { .. }
+            | NodeType::Adsr { .. }
+            | NodeType::NoiseGate { .. }
+            | NodeType::TruePeakLimiter { .. }
+            | NodeType::Resample { .. }
+            | NodeType::AudioToControl => audio_ports(1),
         }
     }
 
     pub fn output_ports(&self) -> Vec<Port> {
         match self {
-            NodeType::Dummy => vec![Port {
-                id: PortId(0),
-                rate: Rate::Audio,
-            }],
-            NodeType::SineOsc { .. } => vec![Port {
-                id: PortId(0),
-                rate: Rate::Audio,
-            }],
-            NodeType::Gain { .. } => vec![Port {
-                id: PortId(0),
-                rate: Rate::Audio,
-            }],
-            NodeType::Mix => vec![Port {
-                id: PortId(0),
-                rate: Rate::Audio,
-            }],
-            NodeType::OutputSink => vec![],
+            NodeType::External { def } => def.output_ports().to_vec(),
+            NodeType::Subgraph { graph, outputs, .. } => graph.exposed_ports(outputs, false),
+            NodeType::OutputSink | NodeType::MultiOutputSink { .. } => vec![],
+            NodeType::Crossover { .. }
+            | NodeType::PanBank { .. }
+            | NodeType::AutoPan { .. }
+            | NodeType::Binaural { .. }
+            | NodeType::Rotary { .. }
+            | NodeType::StereoWidth { .. } => audio_ports(2),
+            NodeType::AudioToControl => control_ports(1),
+            NodeType::Dummy
+            | NodeType::SineOsc { .. }
+            | NodeType::Chirp { .. }
+            | NodeType::Wavetable { .. }
+            | NodeType::SawOsc { .. }
+            | NodeType::PulseOsc { .. }
+            | NodeType::TriOsc { .. }
+            | NodeType::DualTone { .. }
+            | NodeType::AudioInput { .. }
+            | NodeType::Gain { .. }
+            | NodeType::Mix
+            | NodeType::Looper { .. }
+            | NodeType::Delay { .. }
+            | NodeType::Chorus { .. }
+            | NodeType::Compressor { .. }
+            | NodeType::NoiseGate { .. }
+            | NodeType::TruePeakLimiter { .. }
+            | NodeType::Resample { .. }
+            | NodeType::FreqShift { .. }
+            | NodeType::TapeSat { .. }
+            | NodeType::Filter { .. }
+            | NodeType::Adsr { .. }
+            | NodeType::GatedGain
+            | NodeType::Diff
+            | NodeType::Noise { .. }
+            | NodeType::Waveshaper { .. }
+            | NodeType::ControlToAudio => audio_ports(1),
         }
     }
 
@@ -118,16 +427,148 @@ impl NodeType {
         match self {
             NodeType::Gain { .. } => 1,
             NodeType::OutputSink => 1,
+            NodeType::MultiOutputSink { channels } => *channels,
+            NodeType::Looper { .. } => 1,
+            NodeType::Delay { .. } => 1,
+            NodeType::Chorus { .. } => 1,
+            NodeType::Compressor { .. } => 1,
+            NodeType::NoiseGate { .. } => 1,
+            NodeType::TruePeakLimiter { .. } => 1,
+            NodeType::Resample { .. } => 1,
+            NodeType::Diff => 1,
+            NodeType::Waveshaper { .. } => 1,
+            NodeType::Crossover { .. } => 1,
+            NodeType::AutoPan { .. } => 1,
+            NodeType::Binaural { .. } => 1,
+            NodeType::Rotary { .. } => 1,
+            NodeType::GatedGain => 2,
+            NodeType::StereoWidth { .. } => 2,
+            NodeType::FreqShift { .. } => 1,
+            NodeType::TapeSat { .. } => 1,
+            NodeType::Filter { .. } => 1,
+            NodeType::AudioToControl | NodeType::ControlToAudio => 1,
+            NodeType::External { def } => def.required_inputs(),
+            _ => 0,
+        }
+    }
+
+    /// Input port carrying a sidechain (detector) signal, if any.
+    ///
+    /// Sidechain inputs are always optional: they never count towards
+    /// `required_inputs`.
+    pub fn sidechain_port(&self) -> Option<PortId> {
+        match self {
+            NodeType::Compressor { .. } => Some(PortId(1)),
+            NodeType::External { def } => def.sidechain_port(),
+            _ => None,
+        }
+    }
+
+    /// Samples by which this node delays the signal passing through it.
+    pub fn latency(&self) -> usize {
+        match self {
+            NodeType::Delay { delay_samples } => {
+                crate::dsp::DelayLine::clamp_delay(*delay_samples).round() as usize
+            }
+            NodeType::TruePeakLimiter { .. } => crate::dsp::TruePeakLimiter::LATENCY,
+            NodeType::Resample { ratio } => crate::dsp::Resampler::latency_for(*ratio),
+            NodeType::External { def } => def.latency(),
             _ => 0,
         }
     }
+
+    /// Set the built-in parameter called `name` (named after the variant's
+    /// field, e.g. "freq" or "gain"). Returns false if this node type has no
+    /// such parameter.
+    pub fn set_param(&mut self, name: &str, value: f32) -> bool {
+        let field = match (self, name) {
+            (NodeType::SineOsc { freq: field }, "freq")
+            | (NodeType::Wavetable { freq: field, .. }, "freq")
+            | (NodeType::SawOsc { freq: field }, "freq")
+            | (NodeType::PulseOsc { freq: field, .. }, "freq")
+            | (NodeType::PulseOsc { width: field, .. }, "width")
+            | (NodeType::TriOsc { freq: field }, "freq")
+            | (NodeType::Crossover { freq: field }, "freq")
+            | (NodeType::DualTone { freq_a: field, .. }, "freq_a")
+            | (NodeType::DualTone { freq_b: field, .. }, "freq_b")
+            | (NodeType::Gain { gain: field }, "gain")
+            | (
+                NodeType::Delay {
+                    delay_samples: field,
+                },
+                "delay_samples",
+            )
+            | (NodeType::AutoPan { rate_hz: field, .. }, "rate_hz")
+            | (NodeType::AutoPan { depth: field, .. }, "depth")
+            | (NodeType::Binaural { azimuth: field }, "azimuth")
+            | (
+                NodeType::Rotary {
+                    speed_hz: field, ..
+                },
+                "speed_hz",
+            )
+            | (NodeType::Rotary { depth: field, .. }, "depth")
+            | (NodeType::StereoWidth { width: field }, "width")
+            | (NodeType::FreqShift { shift_hz: field }, "shift_hz")
+            | (NodeType::TapeSat { drive: field, .. }, "drive")
+            | (NodeType::TapeSat { bias: field, .. }, "bias")
+            | (NodeType::Filter { cutoff: field, .. }, "cutoff")
+            | (NodeType::Filter { q: field, .. }, "q")
+            | (NodeType::Adsr { attack: field, .. }, "attack")
+            | (NodeType::Adsr { decay: field, .. }, "decay")
+            | (NodeType::Adsr { sustain: field, .. }, "sustain")
+            | (NodeType::Adsr { release: field, .. }, "release")
+            | (NodeType::Resample { ratio: field }, "ratio") => field,
+            _ => return false,
+        };
+        *field = value;
+        true
+    }
+
+    /// Smallest block size this node can process. Built-in nodes accept any.
+    pub fn min_block_size(&self) -> usize {
+        match self {
+            NodeType::External { def } => def.min_block_size(),
+            NodeType::Subgraph { graph, .. } => graph.min_block_size(),
+            _ => 1,
+        }
+    }
+}
+
+/// `count` audio-rate ports numbered from zero.
+fn audio_ports(count: usize) -> Vec<Port> {
+    (0..count)
+        .map(|i| Port {
+            id: PortId(i),
+            rate: Rate::Audio,
+        })
+        .collect()
+}
+
+/// `count` control-rate ports numbered from zero.
+fn control_ports(count: usize) -> Vec<Port> {
+    (0..count)
+        .map(|i| Port {
+            id: PortId(i),
+            rate: Rate::Control,
+        })
+        .collect()
 }
 
 /// The signal graph: a DAG of nodes and edges.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Graph {
     pub nodes: Vec<Option<NodeData>>,
     pub edges: Vec<Edge>,
+    /// Self-edges read one block late (see [`Graph::add_feedback_edge`]).
+    /// Kept apart from `edges` so the rest of the graph stays acyclic.
+    pub feedback_edges: Vec<Edge>,
+    /// Instanced voices, in voice order (see `voice::LogicalNode`).
+    pub voices: Vec<VoiceNodes>,
+    /// Ids of removed nodes, whose slots `add_node` reuses.
+    #[cfg_attr(feature = "serde", serde(default))]
+    free: Vec<NodeId>,
 }
 
 /// Errors that can occur when building the graph.
@@ -140,64 +581,262 @@ pub enum GraphError {
     PortAlreadyConnected,
 }
 
+/// One problem found by [`Graph::validate`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationError {
+    /// An edge's rate differs from the rate of `port` on `node`, one of
+    /// its ends.
+    RateMismatch { node: NodeId, port: PortId },
+    /// `node` has fewer inputs connected than it requires.
+    RequiredInputMissing { node: NodeId },
+    /// More than one edge writes input `port` of `node`.
+    MultipleWritersToInput { node: NodeId, port: PortId },
+    /// `nodes` form a cycle that no `Delay` breaks.
+    Cycle { nodes: Vec<NodeId> },
+    /// `node` is a `Delay` whose `delay_samples` is negative, NaN or longer
+    /// than `DelayLine::MAX_DELAY`, so it gets clamped.
+    DelayOutOfRange { node: NodeId },
+}
+
+/// Everything [`Graph::validate`] found wrong with a graph.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValidationReport {
+    /// Rate mismatches first, then missing inputs, multiple writers,
+    /// cycles and out-of-range delays; each kind ordered by node id.
+    pub errors: Vec<ValidationError>,
+}
+
+impl ValidationReport {
+    /// True if nothing was found: `Plan::compile` will not fail on any of
+    /// these checks.
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
 impl Graph {
+    /// Write the graph as JSON. `External` nodes are stored by
+    /// `NodeDef::type_name`.
+    #[cfg(feature = "serde")]
+    pub fn to_writer<W: std::io::Write>(&self, writer: W) -> serde_json::Result<()> {
+        serde_json::to_writer(writer, self)
+    }
+
+    /// Read a graph written by [`Graph::to_writer`], restoring `External`
+    /// nodes from `registry`. Fails on an external node that is not
+    /// registered.
+    #[cfg(feature = "serde")]
+    pub fn from_reader<R: std::io::Read>(
+        reader: R,
+        registry: &NodeRegistry,
+    ) -> serde_json::Result<Self> {
+        crate::node::registered::with_registry(registry, || serde_json::from_reader(reader))
+    }
+
     /// Create a new empty graph.
     pub fn new() -> Self {
         Self {
             nodes: Vec::new(),
             edges: Vec::new(),
+            feedback_edges: Vec::new(),
+            voices: Vec::new(),
+            free: Vec::new(),
         }
     }
 
-    /// Add a node.
+    /// Add a node, in the slot of a removed one if there is any.
     pub fn add_node(&mut self, node_type: NodeType) -> NodeId {
         let inputs = node_type.input_ports();
         let outputs = node_type.output_ports();
-        let id = NodeId(self.nodes.len());
-        self.nodes.push(Some(NodeData {
+        let id = match self.free.pop() {
+            Some(NodeId(index, generation)) => NodeId(index, generation.wrapping_add(1)),
+            None => {
+                self.nodes.push(None);
+                NodeId(self.nodes.len() - 1, 0)
+            }
+        };
+        self.nodes[id.0] = Some(NodeData {
             id,
             inputs,
             outputs,
             node_type,
-        }));
+            params: Vec::new(),
+            name: None,
+        });
         id
     }
 
-    /// Add an edge, validating rates match and no cycles.
-    pub fn add_edge(&mut self, edge: Edge) -> Result<(), GraphError> {
-        // Validate node existence and get node data
-        let from_node_data = self
-            .nodes
-            .get(edge.from_node.0)
-            .and_then(|n| n.as_ref())
-            .ok_or(GraphError::InvalidNode)?;
-        let to_node_data = self
-            .nodes
-            .get(edge.to_node.0)
-            .and_then(|n| n.as_ref())
-            .ok_or(GraphError::InvalidNode)?;
+    /// Whether `node` is a node of this graph: not removed, and not the id
+    /// of an earlier node in the same slot.
+    pub fn contains(&self, node: NodeId) -> bool {
+        self.node(node).is_some()
+    }
 
-        // Check that from_port is an output port
-        if !from_node_data
-            .outputs
+    /// The data of `node`, unless its id is stale or unknown.
+    pub(crate) fn node(&self, node: NodeId) -> Option<&NodeData> {
+        self.nodes
+            .get(node.0)?
+            .as_ref()
+            .filter(|data| data.id == node)
+    }
+
+    /// Id of the node in slot `index` of `Graph::nodes`, if it holds one.
+    pub(crate) fn id_at(&self, index: usize) -> Option<NodeId> {
+        Some(self.nodes.get(index)?.as_ref()?.id)
+    }
+
+    fn node_mut(&mut self, node: NodeId) -> Option<&mut NodeData> {
+        self.nodes
+            .get_mut(node.0)?
+            .as_mut()
+            .filter(|data| data.id == node)
+    }
+
+    /// Add `sub` as one composite node: its input port `i` feeds port
+    /// `inputs[i]` of `sub` and its output port `j` carries `outputs[j]`.
+    /// Connect it like any node; plans and runtimes see the flattened graph
+    /// (see [`Graph::flatten`]). Fails with `InvalidNode` or `InvalidPort`
+    /// if an exposed port does not exist in `sub`.
+    pub fn add_subgraph(
+        &mut self,
+        sub: Graph,
+        inputs: &[(NodeId, PortId)],
+        outputs: &[(NodeId, PortId)],
+    ) -> Result<NodeId, GraphError> {
+        for &(node, port) in inputs {
+            sub.get_port_rate(node, port, true)?;
+        }
+        for &(node, port) in outputs {
+            sub.get_port_rate(node, port, false)?;
+        }
+        Ok(self.add_node(NodeType::Subgraph {
+            graph: Box::new(sub),
+            inputs: inputs.to_vec(),
+            outputs: outputs.to_vec(),
+        }))
+    }
+
+    /// The graph with every `NodeType::Subgraph` node, nested ones
+    /// included, replaced by its contents. Other node ids are unchanged;
+    /// the contents get new ids, in the slots of removed nodes first, and
+    /// the composite node's id is left empty. `Plan::compile` and `Runtime::new` flatten
+    /// the graph they are given, so `NodeId`s in a runtime refer to this
+    /// graph.
+    pub fn flatten(&self) -> Cow<'_, Graph> {
+        if !self
+            .nodes
             .iter()
-            .any(|p| p.id == edge.from_port)
+            .flatten()
+            .any(|n| matches!(n.node_type, NodeType::Subgraph { .. }))
         {
-            return Err(GraphError::InvalidPort);
+            return Cow::Borrowed(self);
         }
-
-        // Check that to_port is an input port
-        if !to_node_data.inputs.iter().any(|p| p.id == edge.to_port) {
-            return Err(GraphError::InvalidPort);
+        let mut flat = self.clone();
+        // Contents may land in earlier free slots, so search from the start
+        // for nested subgraphs each time.
+        while let Some(idx) = flat.nodes.iter().position(|n| {
+            matches!(
+                n,
+                Some(NodeData {
+                    node_type: NodeType::Subgraph { .. },
+                    ..
+                })
+            )
+        }) {
+            let Some(NodeData {
+                id: composite,
+                node_type:
+                    NodeType::Subgraph {
+                        graph: sub,
+                        inputs,
+                        outputs,
+                    },
+                ..
+            }) = flat.nodes[idx].take()
+            else {
+                unreachable!("position found a subgraph");
+            };
+            let ids = flat.append(&sub);
+            let inner = |exposed: &[(NodeId, PortId)], port: PortId| {
+                let &(node, port) = exposed.get(port.0)?;
+                Some((ids[node.0]?, port))
+            };
+            for edge in flat.edges.iter_mut().chain(&mut flat.feedback_edges) {
+                if edge.to_node == composite {
+                    if let Some((node, port)) = inner(&inputs, edge.to_port) {
+                        edge.to_node = node;
+                        edge.to_port = port;
+                    }
+                }
+                if edge.from_node == composite {
+                    if let Some((node, port)) = inner(&outputs, edge.from_port) {
+                        edge.from_node = node;
+                        edge.from_port = port;
+                    }
+                }
+            }
+            flat.edges
+                .retain(|e| e.from_node != composite && e.to_node != composite);
+            flat.feedback_edges
+                .retain(|e| e.from_node != composite && e.to_node != composite);
         }
+        Cow::Owned(flat)
+    }
 
-        // Check rate mismatch
-        if edge.rate != self.get_port_rate(edge.from_node, edge.from_port)? {
-            return Err(GraphError::RateMismatch);
-        }
-        if edge.rate != self.get_port_rate(edge.to_node, edge.to_port)? {
-            return Err(GraphError::RateMismatch);
+    /// Ports numbered from zero with the rates of the `exposed` ports.
+    fn exposed_ports(&self, exposed: &[(NodeId, PortId)], input: bool) -> Vec<Port> {
+        exposed
+            .iter()
+            .enumerate()
+            .map(|(i, &(node, port))| Port {
+                id: PortId(i),
+                rate: self.get_port_rate(node, port, input).unwrap_or(Rate::Audio),
+            })
+            .collect()
+    }
+
+    /// Copy every node and edge of `other` into this graph, unconnected to
+    /// what is already here. Node names and voices are not copied. Returns
+    /// the new id of each of `other`'s nodes, indexed by its old id.
+    pub(crate) fn append(&mut self, other: &Graph) -> Vec<Option<NodeId>> {
+        let ids: Vec<Option<NodeId>> = other
+            .nodes
+            .iter()
+            .map(|n| {
+                n.as_ref().map(|data| {
+                    let id = self.add_node(data.node_type.clone());
+                    if let Some(copy) = self.node_mut(id) {
+                        copy.params = data.params.clone();
+                    }
+                    id
+                })
+            })
+            .collect();
+        let moved = |e: &Edge| {
+            Some(Edge {
+                from_node: ids.get(e.from_node.0).copied().flatten()?,
+                to_node: ids.get(e.to_node.0).copied().flatten()?,
+                ..e.clone()
+            })
+        };
+        let edges: Vec<Edge> = other.edges.iter().filter_map(moved).collect();
+        let feedback_edges: Vec<Edge> = other.feedback_edges.iter().filter_map(moved).collect();
+        self.edges.extend(edges);
+        self.feedback_edges.extend(feedback_edges);
+        ids
+    }
+
+    /// Add an edge, validating rates match and no cycles.
+    ///
+    /// A control-rate edge into an audio input is routed through a new
+    /// `NodeType::ControlToAudio` node, which upsamples it.
+    pub fn add_edge(&mut self, edge: Edge) -> Result<(), GraphError> {
+        if edge.rate == Rate::Control
+            && self.get_port_rate(edge.to_node, edge.to_port, true)? == Rate::Audio
+        {
+            return self.add_upsampled_edge(edge);
         }
+        self.check_endpoints(&edge)?;
 
         // Check for cycles (simple check: if adding would create cycle)
         if self.would_create_cycle(&edge) {
@@ -210,44 +849,526 @@ impl Graph {
             return Err(GraphError::CycleDetected);
         }
 
-        // Check if port is already connected
-        if self
+        if self.input_connected(edge.to_node, edge.to_port) {
+            return Err(GraphError::PortAlreadyConnected);
+        }
+
+        self.edges.push(edge);
+        Ok(())
+    }
+
+    /// Like [`Graph::add_edge`], but an audio edge into an input that
+    /// already has a writer is summed with it: the existing edge is
+    /// rerouted through a new `NodeType::Mix` together with `edge`, and the
+    /// mix feeds the input. Returns the inserted mix, if any. Further
+    /// writers nest further mixes.
+    ///
+    /// Inputs fed by a feedback edge or at control rate still fail with
+    /// `GraphError::PortAlreadyConnected`.
+    pub fn add_edge_auto_mix(&mut self, edge: Edge) -> Result<Option<NodeId>, GraphError> {
+        let Some(existing) = self
             .edges
             .iter()
-            .any(|e| e.to_node == edge.to_node && e.to_port == edge.to_port)
+            .position(|e| e.to_node == edge.to_node && e.to_port == edge.to_port)
+        else {
+            return self.add_edge(edge).map(|()| None);
+        };
+        self.check_endpoints(&edge)?;
+        if edge.rate != Rate::Audio
+            || self
+                .feedback_edges
+                .iter()
+                .any(|e| e.to_node == edge.to_node && e.to_port == edge.to_port)
         {
             return Err(GraphError::PortAlreadyConnected);
         }
+        // The mix sits on the same path, so cycles are the same.
+        if self.would_create_cycle(&edge) {
+            return Err(GraphError::CycleDetected);
+        }
 
-        self.edges.push(edge);
+        let old = self.edges.remove(existing);
+        let mix = self.add_node(NodeType::Mix);
+        self.edges.push(Edge {
+            from_node: mix,
+            from_port: PortId(0),
+            rate: Rate::Audio,
+            muted: false,
+            ..old.clone()
+        });
+        self.edges.push(Edge {
+            to_node: mix,
+            to_port: PortId(0),
+            ..old
+        });
+        self.edges.push(Edge {
+            to_node: mix,
+            to_port: PortId(1),
+            ..edge
+        });
+        Ok(Some(mix))
+    }
+
+    /// `add_edge` for a control edge into an audio input: validate it as if
+    /// the input were control rate, then insert the upsampler.
+    fn add_upsampled_edge(&mut self, edge: Edge) -> Result<(), GraphError> {
+        if edge.rate != self.get_port_rate(edge.from_node, edge.from_port, false)? {
+            return Err(GraphError::RateMismatch);
+        }
+        // The adapter sits on the same path, so cycles are the same.
+        if self.would_create_cycle(&edge) {
+            return Err(GraphError::CycleDetected);
+        }
+        if self.input_connected(edge.to_node, edge.to_port) {
+            return Err(GraphError::PortAlreadyConnected);
+        }
+        let adapter = self.add_node(NodeType::ControlToAudio);
+        self.edges.push(Edge {
+            to_node: adapter,
+            to_port: PortId(0),
+            ..edge.clone()
+        });
+        self.edges.push(Edge {
+            from_node: adapter,
+            from_port: PortId(0),
+            rate: Rate::Audio,
+            ..edge
+        });
+        Ok(())
+    }
+
+    /// Connect a node's output back to one of its own inputs through a
+    /// one-block delay: the input reads what the output produced in the
+    /// previous block (silence in the first), e.g. for a leaky integrator.
+    ///
+    /// Fails with `GraphError::InvalidNode` unless `from_node` and
+    /// `to_node` are the same node.
+    pub fn add_feedback_edge(&mut self, edge: Edge) -> Result<(), GraphError> {
+        self.check_endpoints(&edge)?;
+        if edge.from_node != edge.to_node {
+            return Err(GraphError::InvalidNode);
+        }
+        if self.input_connected(edge.to_node, edge.to_port) {
+            return Err(GraphError::PortAlreadyConnected);
+        }
+        self.feedback_edges.push(edge);
+        Ok(())
+    }
+
+    /// Whether any edge, feedback included, already writes `node`:`port`.
+    fn input_connected(&self, node: NodeId, port: PortId) -> bool {
+        self.edges
+            .iter()
+            .chain(&self.feedback_edges)
+            .any(|e| e.to_node == node && e.to_port == port)
+    }
+
+    /// Check that both ends of `edge` exist with matching rates.
+    fn check_endpoints(&self, edge: &Edge) -> Result<(), GraphError> {
+        // Validate node existence and get node data
+        let from_node_data = self.node(edge.from_node).ok_or(GraphError::InvalidNode)?;
+        let to_node_data = self.node(edge.to_node).ok_or(GraphError::InvalidNode)?;
+
+        // Check that from_port is an output port
+        if !from_node_data
+            .outputs
+            .iter()
+            .any(|p| p.id == edge.from_port)
+        {
+            return Err(GraphError::InvalidPort);
+        }
+
+        // Check that to_port is an input port
+        if !to_node_data.inputs.iter().any(|p| p.id == edge.to_port) {
+            return Err(GraphError::InvalidPort);
+        }
+
+        // Check rate mismatch
+        if edge.rate != self.get_port_rate(edge.from_node, edge.from_port, false)? {
+            return Err(GraphError::RateMismatch);
+        }
+        if edge.rate != self.get_port_rate(edge.to_node, edge.to_port, true)? {
+            return Err(GraphError::RateMismatch);
+        }
         Ok(())
     }
 
     /// Remove a node and all edges connected to it.
+    ///
+    /// The slot goes to the next node added, under a new generation, so
+    /// `node_id` stays invalid afterwards: adding an edge to it, removing it
+    /// again or compiling a plan with an edge pushed onto it fails with
+    /// `InvalidNode`.
     pub fn remove_node(&mut self, node_id: NodeId) -> Result<(), GraphError> {
-        if node_id.0 >= self.nodes.len() {
+        if !self.contains(node_id) {
             return Err(GraphError::InvalidNode);
         }
         // Remove the node
         self.nodes[node_id.0] = None;
+        self.free.push(node_id);
         // Remove edges connected to the node
         self.edges
             .retain(|e| e.from_node != node_id && e.to_node != node_id);
+        self.feedback_edges.retain(|e| e.from_node != node_id);
+        Ok(())
+    }
+
+    /// Mute or unmute the edge from `from`:`from_port` to `to`:`to_port`.
+    /// A muted edge stays in the graph but feeds silence to its destination.
+    pub fn set_edge_muted(
+        &mut self,
+        from: NodeId,
+        from_port: PortId,
+        to: NodeId,
+        to_port: PortId,
+        muted: bool,
+    ) -> Result<(), GraphError> {
+        let edge = self
+            .edges
+            .iter_mut()
+            .find(|e| {
+                e.from_node == from
+                    && e.from_port == from_port
+                    && e.to_node == to
+                    && e.to_port == to_port
+            })
+            .ok_or(GraphError::InvalidPort)?;
+        edge.muted = muted;
+        Ok(())
+    }
+
+    /// Edges writing into input `port` of `node` (at most one under the
+    /// single-writer rule).
+    pub fn edges_into_port(&self, node: NodeId, port: PortId) -> Vec<&Edge> {
+        self.edges
+            .iter()
+            .filter(|e| e.to_node == node && e.to_port == port)
+            .collect()
+    }
+
+    /// Edges reading from output `port` of `node` (any number, for fan-out).
+    pub fn edges_from_port(&self, node: NodeId, port: PortId) -> Vec<&Edge> {
+        self.edges
+            .iter()
+            .filter(|e| e.from_node == node && e.from_port == port)
+            .collect()
+    }
+
+    /// Edges into any input of `node`, feedback edges included.
+    pub fn inputs_of(&self, node: NodeId) -> impl Iterator<Item = &Edge> + '_ {
+        self.edges
+            .iter()
+            .chain(&self.feedback_edges)
+            .filter(move |e| e.to_node == node)
+    }
+
+    /// Edges out of any output of `node`, feedback edges included.
+    pub fn outputs_of(&self, node: NodeId) -> impl Iterator<Item = &Edge> + '_ {
+        self.edges
+            .iter()
+            .chain(&self.feedback_edges)
+            .filter(move |e| e.from_node == node)
+    }
+
+    /// Nodes with an edge into `node`, each once, in id order.
+    pub fn predecessors(&self, node: NodeId) -> impl Iterator<Item = NodeId> {
+        let mut nodes: Vec<NodeId> = self.inputs_of(node).map(|e| e.from_node).collect();
+        nodes.sort_unstable();
+        nodes.dedup();
+        nodes.into_iter()
+    }
+
+    /// Nodes `node` has an edge into, each once, in id order.
+    pub fn successors(&self, node: NodeId) -> impl Iterator<Item = NodeId> {
+        let mut nodes: Vec<NodeId> = self.outputs_of(node).map(|e| e.to_node).collect();
+        nodes.sort_unstable();
+        nodes.dedup();
+        nodes.into_iter()
+    }
+
+    /// Edges from `from` to `to`, on any ports.
+    pub fn edges_between(&self, from: NodeId, to: NodeId) -> impl Iterator<Item = &Edge> + '_ {
+        self.outputs_of(from).filter(move |e| e.to_node == to)
+    }
+
+    /// Check that no input port has more than one incoming edge.
+    ///
+    /// Sorts the edge destinations and scans for neighbours, so it stays
+    /// cheap on very large graphs. Returns the lowest offending port.
+    pub fn check_single_writer(&self) -> Result<(), (NodeId, PortId)> {
+        let mut targets: Vec<(NodeId, PortId)> = self
+            .edges
+            .iter()
+            .chain(&self.feedback_edges)
+            .map(|e| (e.to_node, e.to_port))
+            .collect();
+        targets.sort_unstable();
+        match targets.windows(2).find(|w| w[0] == w[1]) {
+            Some(w) => Err(w[0]),
+            None => Ok(()),
+        }
+    }
+
+    /// Check the whole graph and report every problem at once, where
+    /// `add_edge` and `Plan::compile` stop at the first. Meant for editors
+    /// that want to flag all broken connections together.
+    pub fn validate(&self) -> ValidationReport {
+        let mut mismatches: Vec<(NodeId, PortId)> = crate::plan::rate_mismatches(self).collect();
+        mismatches.sort_unstable();
+        mismatches.dedup();
+        let mut errors: Vec<ValidationError> = mismatches
+            .into_iter()
+            .map(|(node, port)| ValidationError::RateMismatch { node, port })
+            .collect();
+        errors.extend(
+            crate::plan::missing_inputs(self)
+                .map(|node| ValidationError::RequiredInputMissing { node }),
+        );
+
+        let mut targets: Vec<(NodeId, PortId)> = self
+            .edges
+            .iter()
+            .chain(&self.feedback_edges)
+            .map(|e| (e.to_node, e.to_port))
+            .collect();
+        targets.sort_unstable();
+        let mut conflicts: Vec<(NodeId, PortId)> = targets
+            .windows(2)
+            .filter(|w| w[0] == w[1])
+            .map(|w| w[0])
+            .collect();
+        conflicts.dedup();
+        errors.extend(
+            conflicts
+                .into_iter()
+                .map(|(node, port)| ValidationError::MultipleWritersToInput { node, port }),
+        );
+
+        errors.extend(
+            crate::plan::cycles(self)
+                .into_iter()
+                .map(|nodes| ValidationError::Cycle { nodes }),
+        );
+        errors.extend(
+            self.nodes
+                .iter()
+                .flatten()
+                .filter_map(|n| match n.node_type {
+                    NodeType::Delay { delay_samples }
+                        if crate::dsp::DelayLine::clamp_delay(delay_samples) != delay_samples =>
+                    {
+                        Some(ValidationError::DelayOutOfRange { node: n.id })
+                    }
+                    _ => None,
+                }),
+        );
+        ValidationReport { errors }
+    }
+
+    /// Worst-case linear gain from the sources to any `OutputSink`, for
+    /// headroom checks before rendering.
+    ///
+    /// Sources count as full scale (a wavetable or waveshaper as its largest
+    /// table entry), `Gain` multiplies, and summing nodes (`Mix`, `PanBank`,
+    /// `StereoWidth`) add their inputs' worst cases. Every other node is
+    /// assumed to pass its loudest input unchanged, so resonant filters and
+    /// external nodes may exceed the estimate. Muted edges carry nothing,
+    /// and loops through a `Delay` are counted once around.
+    /// Returns 0.0 for a graph without sinks, and
+    /// `GraphError::CycleDetected` if a cycle bypasses every `Delay` (only
+    /// possible by editing `edges` directly).
+    pub fn max_path_gain(&self) -> Result<f32, GraphError> {
+        let order = crate::plan::topo_sort(self).map_err(|_| GraphError::CycleDetected)?;
+        let mut peak = vec![0.0f32; self.nodes.len()];
+        let mut worst: f32 = 0.0;
+        for node in order {
+            let Some(data) = &self.nodes[node.0] else {
+                continue;
+            };
+            let inputs = self
+                .edges
+                .iter()
+                .filter(|e| e.to_node == node && !e.muted)
+                .map(|e| peak[e.from_node.0]);
+            let table_peak = |table: &[f32]| table.iter().fold(0.0f32, |m, x| m.max(x.abs()));
+            peak[node.0] = match &data.node_type {
+                NodeType::SineOsc { .. }
+                | NodeType::Chirp { .. }
+                | NodeType::DualTone { .. }
+                | NodeType::SawOsc { .. }
+                | NodeType::PulseOsc { .. }
+                | NodeType::TriOsc { .. }
+                | NodeType::Noise { .. }
+                | NodeType::AudioInput { .. } => 1.0,
+                NodeType::Wavetable { table, .. } | NodeType::Waveshaper { table } => {
+                    table_peak(table)
+                }
+                NodeType::Gain { gain } => gain.abs() * inputs.fold(0.0, f32::max),
+                NodeType::Mix | NodeType::PanBank { .. } => inputs.sum(),
+                NodeType::StereoWidth { width } => (1.0 + width) * 0.5 * inputs.sum::<f32>(),
+                NodeType::OutputSink | NodeType::MultiOutputSink { .. } => {
+                    let level = inputs.fold(0.0, f32::max);
+                    worst = worst.max(level);
+                    level
+                }
+                _ => inputs.fold(0.0, f32::max),
+            };
+        }
+        Ok(worst)
+    }
+
+    /// Time-align every merge point by inserting `Delay` nodes.
+    ///
+    /// Each audio edge arriving earlier than the latest input of its target
+    /// (by `NodeType::latency` summed along the longest path) is routed
+    /// through a new delay making up the difference. Returns the inserted
+    /// delay nodes; control and event edges are left alone.
+    pub fn compensate_latency(&mut self) -> Vec<NodeId> {
+        let Ok(order) = crate::plan::topo_sort(self) else {
+            return Vec::new();
+        };
+        // Loops through a delay have no consistent arrival time; leave
+        // them alone.
+        let loop_edges = crate::plan::delay_loop_edges(self);
+        let mut arrival = vec![0; self.nodes.len()];
+        let mut output = vec![0; self.nodes.len()];
+        for node in order {
+            arrival[node.0] = self
+                .edges
+                .iter()
+                .zip(&loop_edges)
+                .filter(|(e, &l)| e.to_node == node && !l)
+                .map(|(e, _)| e)
+                .map(|e| output[e.from_node.0])
+                .max()
+                .unwrap_or(0);
+            let latency = self.nodes[node.0]
+                .as_ref()
+                .map_or(0, |n| n.node_type.latency());
+            output[node.0] = arrival[node.0] + latency;
+        }
+
+        let mut inserted = Vec::new();
+        for (idx, in_loop) in loop_edges.into_iter().enumerate() {
+            let edge = &self.edges[idx];
+            if in_loop {
+                continue;
+            }
+            let shortfall = arrival[edge.to_node.0] - output[edge.from_node.0];
+            if shortfall == 0 || edge.rate != Rate::Audio {
+                continue;
+            }
+            let (to_node, to_port) = (edge.to_node, edge.to_port);
+            let delay = self.add_node(NodeType::Delay {
+                delay_samples: shortfall as f32,
+            });
+            self.edges[idx].to_node = delay;
+            self.edges[idx].to_port = PortId(0);
+            self.edges.push(Edge {
+                from_node: delay,
+                from_port: PortId(0),
+                to_node,
+                to_port,
+                rate: Rate::Audio,
+                muted: false,
+            });
+            inserted.push(delay);
+        }
+        inserted
+    }
+
+    /// Attach a named parameter to `node`, replacing any earlier value.
+    ///
+    /// Runtimes built from the graph apply it: built-in nodes take names
+    /// matching their fields (see [`NodeType::set_param`]) and every name
+    /// is offered to external nodes through `NodeDef::set_param`.
+    pub fn set_node_param(
+        &mut self,
+        node: NodeId,
+        name: &str,
+        value: f32,
+    ) -> Result<(), GraphError> {
+        let data = self.node_mut(node).ok_or(GraphError::InvalidNode)?;
+        match data.params.iter_mut().find(|(n, _)| n == name) {
+            Some((_, v)) => *v = value,
+            None => data.params.push((name.to_string(), value)),
+        }
         Ok(())
     }
 
-    fn get_port_rate(&self, node_id: NodeId, port_id: PortId) -> Result<Rate, GraphError> {
-        if node_id.0 >= self.nodes.len() {
+    /// Value of a parameter set with [`Graph::set_node_param`].
+    pub fn get_node_param(&self, node: NodeId, name: &str) -> Option<f32> {
+        self.node(node)?
+            .params
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|&(_, v)| v)
+    }
+
+    /// Name `node` so it can be found with [`Graph::node_by_name`]. Names
+    /// are unique: a name already used by another node moves to `node`.
+    pub fn set_node_name(&mut self, node: NodeId, name: &str) -> Result<(), GraphError> {
+        if !self.contains(node) {
             return Err(GraphError::InvalidNode);
         }
-        let node = &self.nodes[node_id.0];
-        let node = node.as_ref().ok_or(GraphError::InvalidNode)?;
-        for port in &node.inputs {
-            if port.id == port_id {
-                return Ok(port.rate.clone());
+        for data in self.nodes.iter_mut().flatten() {
+            if data.name.as_deref() == Some(name) {
+                data.name = None;
             }
         }
-        for port in &node.outputs {
+        if let Some(data) = self.node_mut(node) {
+            data.name = Some(name.to_string());
+        }
+        Ok(())
+    }
+
+    /// Name of `node`, if it has one.
+    pub fn node_name(&self, node: NodeId) -> Option<&str> {
+        self.node(node)?.name.as_deref()
+    }
+
+    /// The node named `name`.
+    pub fn node_by_name(&self, name: &str) -> Option<NodeId> {
+        self.nodes
+            .iter()
+            .flatten()
+            .find(|n| n.name.as_deref() == Some(name))
+            .map(|n| n.id)
+    }
+
+    /// Smallest block size every node in the graph can process.
+    pub fn min_block_size(&self) -> usize {
+        self.nodes
+            .iter()
+            .flatten()
+            .map(|n| n.node_type.min_block_size())
+            .max()
+            .unwrap_or(1)
+    }
+
+    /// Try compiling the graph at each of `sizes`, pairing every size with
+    /// the outcome, to check a patch works across hosts' buffer sizes.
+    pub fn validate_block_sizes(
+        &self,
+        sizes: &[usize],
+    ) -> Vec<(usize, Result<(), crate::plan::PlanError>)> {
+        sizes
+            .iter()
+            .map(|&size| (size, crate::plan::Plan::compile(self, size).map(|_| ())))
+            .collect()
+    }
+
+    /// Rate of an input (`input == true`) or output port. Input and output
+    /// ids overlap, so the side must be given.
+    pub(crate) fn get_port_rate(
+        &self,
+        node_id: NodeId,
+        port_id: PortId,
+        input: bool,
+    ) -> Result<Rate, GraphError> {
+        let node = self.node(node_id).ok_or(GraphError::InvalidNode)?;
+        let ports = if input { &node.inputs } else { &node.outputs };
+        for port in ports {
             if port.id == port_id {
                 return Ok(port.rate.clone());
             }
@@ -256,27 +1377,119 @@ impl Graph {
     }
 
     fn would_create_cycle(&self, edge: &Edge) -> bool {
-        // Simple cycle detection: check if to_node can reach from_node
-        // For now, basic implementation; can be improved with proper topo sort
-        let mut visited = vec![false; self.nodes.len()];
-        self.dfs(edge.to_node, edge.from_node, &mut visited)
-    }
-
-    fn dfs(&self, current: NodeId, target: NodeId, visited: &mut [bool]) -> bool {
-        if current == target {
-            return true;
+        // The edge closes a cycle if its destination already reaches its
+        // source. Loops through a `Delay` node are allowed, so the search
+        // does not pass through delays. Iterative so long chains cannot
+        // overflow the stack.
+        let mut successors = vec![Vec::new(); self.nodes.len()];
+        for e in &self.edges {
+            successors[e.from_node.0].push(e.to_node);
         }
-        if visited[current.0] {
-            return false;
-        }
-        visited[current.0] = true;
-        for edge in &self.edges {
-            if edge.from_node == current && self.dfs(edge.to_node, target, visited) {
+        let is_delay = |node: NodeId| {
+            matches!(
+                self.nodes.get(node.0),
+                Some(Some(NodeData {
+                    node_type: NodeType::Delay { .. },
+                    ..
+                }))
+            )
+        };
+        let mut visited = vec![false; self.nodes.len()];
+        let mut stack = vec![edge.to_node];
+        while let Some(current) = stack.pop() {
+            if is_delay(current) {
+                continue;
+            }
+            if current == edge.from_node {
                 return true;
             }
+            if !std::mem::replace(&mut visited[current.0], true) {
+                stack.extend_from_slice(&successors[current.0]);
+            }
         }
         false
     }
+
+    /// Render the graph in Graphviz DOT format: one box per node labelled
+    /// with its id and type name, one arrow per edge labelled with its rate
+    /// and port ids at either end. Muted edges are dashed and feedback
+    /// edges dotted. See `Plan::to_dot` for the execution order as well.
+    pub fn to_dot(&self) -> String {
+        let edges = self
+            .edges
+            .iter()
+            .map(|e| (e, false))
+            .chain(self.feedback_edges.iter().map(|e| (e, true)))
+            .map(|(e, feedback)| DotEdge {
+                from: e.from_node,
+                from_port: e.from_port,
+                to: e.to_node,
+                to_port: e.to_port,
+                rate: &e.rate,
+                muted: e.muted,
+                feedback,
+            });
+        write_dot(self, None, edges)
+    }
+}
+
+/// One edge as drawn by [`write_dot`].
+pub(crate) struct DotEdge<'a> {
+    pub from: NodeId,
+    pub from_port: PortId,
+    pub to: NodeId,
+    pub to_port: PortId,
+    pub rate: &'a Rate,
+    pub muted: bool,
+    pub feedback: bool,
+}
+
+/// DOT text for `graph`'s nodes and `edges`. With an execution `order`,
+/// scheduled nodes are labelled with their step and unscheduled ones are
+/// dashed.
+pub(crate) fn write_dot<'a>(
+    graph: &Graph,
+    order: Option<&[NodeId]>,
+    edges: impl IntoIterator<Item = DotEdge<'a>>,
+) -> String {
+    use std::fmt::Write;
+
+    let escape = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+    let mut dot = String::from("digraph auxide {\n    rankdir=LR;\n    node [shape=box];\n");
+    for data in graph.nodes.iter().flatten() {
+        let id = data.id;
+        let mut label = format!("#{} {}", id.0, escape(data.node_type.type_name()));
+        let mut style = "";
+        if let Some(order) = order {
+            match order.iter().position(|&n| n == id) {
+                Some(step) => {
+                    let _ = write!(label, "\\nstep {}", step);
+                }
+                None => style = ", style=dashed",
+            }
+        }
+        // Writing to a String cannot fail.
+        let _ = writeln!(dot, "    n{} [label=\"{}\"{}];", id.0, label, style);
+    }
+    for edge in edges {
+        let rate = match edge.rate {
+            Rate::Audio => "audio",
+            Rate::Control => "control",
+            Rate::Event => "event",
+        };
+        let style = match (edge.muted, edge.feedback) {
+            (true, _) => ", style=dashed",
+            (false, true) => ", style=dotted, constraint=false",
+            (false, false) => "",
+        };
+        let _ = writeln!(
+            dot,
+            "    n{} -> n{} [label=\"{}\", taillabel=\"{}\", headlabel=\"{}\"{}];",
+            edge.from.0, edge.to.0, rate, edge.from_port.0, edge.to_port.0, style
+        );
+    }
+    dot.push_str("}\n");
+    dot
 }
 
 impl Default for Graph {
@@ -301,6 +1514,7 @@ mod tests {
             to_node: node2,
             to_port: PortId(0),
             rate: Rate::Control, // Mismatch
+            muted: false,
         };
         assert_eq!(graph.add_edge(edge), Err(GraphError::RateMismatch));
     }
@@ -317,6 +1531,7 @@ mod tests {
             to_node: node2,
             to_port: PortId(0),
             rate: Rate::Audio,
+            muted: false,
         };
         graph.add_edge(edge1).unwrap();
         // Try to add 2 -> 1, creating cycle
@@ -326,6 +1541,7 @@ mod tests {
             to_node: node1,
             to_port: PortId(0),
             rate: Rate::Audio,
+            muted: false,
         };
         assert_eq!(graph.add_edge(edge2), Err(GraphError::CycleDetected));
     }
@@ -340,6 +1556,63 @@ mod tests {
         assert!(node1 < node2); // Since NodeId is Ord
     }
 
+    #[test]
+    fn graph_port_edge_queries() {
+        let mut graph = Graph::new();
+        let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+        let g1 = graph.add_node(NodeType::Gain { gain: 1.0 });
+        let g2 = graph.add_node(NodeType::Gain { gain: 0.5 });
+        for to in [g1, g2] {
+            graph
+                .add_edge(Edge {
+                    from_node: osc,
+                    from_port: PortId(0),
+                    to_node: to,
+                    to_port: PortId(0),
+                    rate: Rate::Audio,
+                    muted: false,
+                })
+                .unwrap();
+        }
+
+        let fan_out = graph.edges_from_port(osc, PortId(0));
+        assert_eq!(fan_out.len(), 2);
+        assert_eq!(fan_out[0].to_node, g1);
+        assert_eq!(fan_out[1].to_node, g2);
+
+        let into = graph.edges_into_port(g2, PortId(0));
+        assert_eq!(into.len(), 1);
+        assert_eq!(into[0].from_node, osc);
+
+        assert!(graph.edges_into_port(osc, PortId(0)).is_empty());
+        assert!(graph.edges_from_port(g1, PortId(0)).is_empty());
+    }
+
+    #[test]
+    fn graph_check_single_writer() {
+        let mut graph = Graph::new();
+        let a = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+        let b = graph.add_node(NodeType::SineOsc { freq: 660.0 });
+        let g1 = graph.add_node(NodeType::Gain { gain: 1.0 });
+        let g2 = graph.add_node(NodeType::Gain { gain: 1.0 });
+        let edge = |from, to| Edge {
+            from_node: from,
+            from_port: PortId(0),
+            to_node: to,
+            to_port: PortId(0),
+            rate: Rate::Audio,
+            muted: false,
+        };
+        // Fan-out from one output is fine.
+        graph.add_edge(edge(a, g1)).unwrap();
+        graph.add_edge(edge(a, g2)).unwrap();
+        assert_eq!(graph.check_single_writer(), Ok(()));
+
+        // add_edge refuses a second writer, so push it directly.
+        graph.edges.push(edge(b, g2));
+        assert_eq!(graph.check_single_writer(), Err((g2, PortId(0))));
+    }
+
     proptest! {
         #[test]
         fn graph_rate_mismatch_prop(_rate1 in 0..3usize, _rate2 in 0..3usize) {
@@ -353,6 +1626,7 @@ mod tests {
                 to_node: node2,
                 to_port: PortId(0),
                 rate: Rate::Control, // Mismatch
+                muted: false,
             };
             prop_assert_eq!(graph.add_edge(edge), Err(GraphError::RateMismatch));
         }
@@ -50,6 +50,13 @@ pub const INV_CONTROL_MSG_PROCESSED: u8 = 5;
 /// RT callback executed without panic.
 pub const INV_RT_CALLBACK_CLEAN: u8 = 6;
 
+/// A block took longer than the configured processing budget.
+pub const INV_RT_OVERLOAD: u8 = 7;
+
+/// A node produced NaN or infinite samples, which were replaced by silence
+/// (see `Runtime::set_sanitize`).
+pub const INV_NONFINITE_DETECTED: u8 = 8;
+
 // ============================================================================
 // Invariant Signal Queue
 // ============================================================================
@@ -154,6 +161,8 @@ pub const fn invariant_name(id: u8) -> &'static str {
         INV_GATE_TRIGGER_HONORED => "GATE_TRIGGER_HONORED",
         INV_CONTROL_MSG_PROCESSED => "CONTROL_MSG_PROCESSED",
         INV_RT_CALLBACK_CLEAN => "RT_CALLBACK_CLEAN",
+        INV_RT_OVERLOAD => "RT_OVERLOAD",
+        INV_NONFINITE_DETECTED => "NONFINITE_DETECTED",
         _ => "UNKNOWN",
     }
 }
@@ -237,8 +246,14 @@ mod tests {
 
     #[test]
     fn test_invariant_names() {
-        assert_eq!(invariant_name(INV_PARAM_UPDATE_DELIVERED), "PARAM_UPDATE_DELIVERED");
-        assert_eq!(invariant_name(INV_SAMPLE_BUFFER_FILLED), "SAMPLE_BUFFER_FILLED");
+        assert_eq!(
+            invariant_name(INV_PARAM_UPDATE_DELIVERED),
+            "PARAM_UPDATE_DELIVERED"
+        );
+        assert_eq!(
+            invariant_name(INV_SAMPLE_BUFFER_FILLED),
+            "SAMPLE_BUFFER_FILLED"
+        );
         assert_eq!(invariant_name(255), "UNKNOWN");
     }
 }
@@ -40,6 +40,7 @@
 //!     to_node: sink,
 //!     to_port: PortId(0),
 //!     rate: Rate::Audio,
+//!     muted: false,
 //! }).unwrap();
 //!
 //! let plan = Plan::compile(&graph, 64).unwrap();
@@ -52,12 +53,20 @@
     html_logo_url = "https://raw.githubusercontent.com/Michael-A-Kuykendall/auxide/main/assets/auxide-logo.png"
 )]
 
+pub mod control;
 pub mod dsl;
+pub mod dsp;
 pub mod graph;
 pub mod invariant_ppt;
 pub mod invariant_rt;
-pub mod control;
+pub mod midi;
 pub mod node;
+pub mod patch;
 pub mod plan;
 pub mod rt;
+pub mod snapshot;
 pub mod states;
+#[cfg(feature = "testutil")]
+pub mod testutil;
+pub mod transport;
+pub mod voice;
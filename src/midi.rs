@@ -0,0 +1,274 @@
+//! MIDI input for the control layer.
+//!
+//! [`MidiEvent`] is a decoded channel message: note on/off, control change
+//! or pitch bend. A [`MidiMap`] turns events into [`ControlMsg`]s on the
+//! main thread; `RuntimeControl::send_midi` queues the result.
+//!
+//! Notes go to the graph's instanced voices (`ControlMsg::NoteOn` /
+//! `NoteOff`) unless [`MidiMap::map_notes`] routes them to one oscillator
+//! and envelope instead. Controllers drive whatever the CC table maps them
+//! to, scaled into the mapping's range. Pitch bend detunes the mapped
+//! oscillator. Channels are not distinguished; filter them before sending.
+
+#![forbid(unsafe_code)]
+
+use crate::control::ControlMsg;
+use crate::dsp::midi_to_hz;
+use crate::graph::NodeId;
+use std::collections::HashMap;
+
+/// Controller number of the "All Notes Off" channel mode message.
+const ALL_NOTES_OFF_CC: u8 = 123;
+
+/// A decoded MIDI channel message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MidiEvent {
+    /// Note `note` pressed with `velocity` (`0..=127`); velocity 0 releases
+    /// it, as MIDI allows.
+    NoteOn {
+        note: u8,
+        velocity: u8,
+    },
+    NoteOff {
+        note: u8,
+    },
+    /// Controller `controller` moved to `value` (`0..=127`).
+    ControlChange {
+        controller: u8,
+        value: u8,
+    },
+    /// Pitch wheel position, `-8192..=8191` with 0 centred.
+    PitchBend {
+        value: i16,
+    },
+}
+
+impl MidiEvent {
+    /// Decode a raw channel message (status byte first, no running status).
+    /// `None` for other or truncated messages.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let (&status, data) = bytes.split_first()?;
+        let byte = |i: usize| data.get(i).map(|b| b & 0x7f);
+        match status & 0xf0 {
+            0x80 => Some(Self::NoteOff { note: byte(0)? }),
+            0x90 => Some(Self::NoteOn {
+                note: byte(0)?,
+                velocity: byte(1)?,
+            }),
+            0xb0 => Some(Self::ControlChange {
+                controller: byte(0)?,
+                value: byte(1)?,
+            }),
+            0xe0 => {
+                let value = (byte(1)? as i16) << 7 | byte(0)? as i16;
+                Some(Self::PitchBend {
+                    value: value - 8192,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// What a MIDI controller drives; see [`MidiMap::map_cc`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CcTarget {
+    /// `ControlMsg::SetGain`.
+    Gain { node: NodeId },
+    /// `ControlMsg::SetFrequency`.
+    Frequency { node: NodeId },
+    /// `ControlMsg::SetFilterCutoff`.
+    FilterCutoff { node: NodeId },
+    /// `ControlMsg::SetFilterResonance`.
+    FilterResonance { node: NodeId },
+    /// `ControlMsg::SetParam` on parameter `param_idx`.
+    Param { node: NodeId, param_idx: u8 },
+    /// `ControlMsg::TriggerGate`, open from value 64 up (a sustain pedal).
+    /// The range is ignored.
+    Gate { node: NodeId },
+}
+
+/// Translation table from MIDI events to control messages.
+#[derive(Debug, Clone)]
+pub struct MidiMap {
+    /// Controller number to target and the (min, max) its 0..=127 spans.
+    cc: HashMap<u8, (CcTarget, f32, f32)>,
+    /// Oscillator and envelope notes play, from `map_notes`.
+    notes: Option<(NodeId, NodeId)>,
+    /// Pitch bend range each way, in semitones.
+    bend_range: f32,
+    /// Current pitch bend, in semitones.
+    bend: f32,
+    /// The note the mapped oscillator is playing.
+    held: Option<u8>,
+}
+
+impl Default for MidiMap {
+    /// Notes to voices, no controllers, a bend range of 2 semitones.
+    fn default() -> Self {
+        Self {
+            cc: HashMap::new(),
+            notes: None,
+            bend_range: 2.0,
+            bend: 0.0,
+            held: None,
+        }
+    }
+}
+
+impl MidiMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drive `target` with controller `controller`, scaling 0..=127 linearly
+    /// onto `min..=max`. Replaces any earlier mapping of the controller.
+    pub fn map_cc(&mut self, controller: u8, target: CcTarget, min: f32, max: f32) {
+        self.cc.insert(controller, (target, min, max));
+    }
+
+    /// Stop translating controller `controller`.
+    pub fn unmap_cc(&mut self, controller: u8) {
+        self.cc.remove(&controller);
+    }
+
+    /// Play notes monophonically on `osc` (tuned with `SetFrequency`) and
+    /// `env` (gated with `TriggerGate`) instead of the graph's voices. The
+    /// last note pressed sounds; releasing an older one does nothing.
+    pub fn map_notes(&mut self, osc: NodeId, env: NodeId) {
+        self.notes = Some((osc, env));
+    }
+
+    /// Pitch bend range each way, in semitones (2 by default).
+    pub fn set_bend_range(&mut self, semitones: f32) {
+        self.bend_range = semitones;
+    }
+
+    /// The control messages for `event`, in order; `None` if nothing maps
+    /// it. Tracks the held note and bend, so feed it every event.
+    pub fn translate(&mut self, event: MidiEvent) -> Option<Vec<ControlMsg>> {
+        match event {
+            MidiEvent::NoteOn { note, velocity: 0 } | MidiEvent::NoteOff { note } => {
+                let Some((_, env)) = self.notes else {
+                    return Some(vec![ControlMsg::NoteOff { note }]);
+                };
+                if self.held != Some(note) {
+                    return Some(Vec::new());
+                }
+                self.held = None;
+                Some(vec![ControlMsg::TriggerGate {
+                    node: env,
+                    on: false,
+                }])
+            }
+            MidiEvent::NoteOn { note, velocity } => {
+                let Some((osc, env)) = self.notes else {
+                    let velocity = velocity as f32 / 127.0;
+                    return Some(vec![ControlMsg::NoteOn { note, velocity }]);
+                };
+                self.held = Some(note);
+                Some(vec![
+                    self.tune(osc, note),
+                    ControlMsg::TriggerGate {
+                        node: env,
+                        on: true,
+                    },
+                ])
+            }
+            MidiEvent::ControlChange { controller, value } => {
+                let Some(&(target, min, max)) = self.cc.get(&controller) else {
+                    return (controller == ALL_NOTES_OFF_CC).then(|| self.all_notes_off());
+                };
+                let scaled = min + (max - min) * value as f32 / 127.0;
+                Some(vec![match target {
+                    CcTarget::Gain { node } => ControlMsg::SetGain { node, gain: scaled },
+                    CcTarget::Frequency { node } => ControlMsg::SetFrequency { node, hz: scaled },
+                    CcTarget::FilterCutoff { node } => {
+                        ControlMsg::SetFilterCutoff { node, hz: scaled }
+                    }
+                    CcTarget::FilterResonance { node } => {
+                        ControlMsg::SetFilterResonance { node, q: scaled }
+                    }
+                    CcTarget::Param { node, param_idx } => ControlMsg::SetParam {
+                        node,
+                        param_idx,
+                        value: scaled,
+                    },
+                    CcTarget::Gate { node } => ControlMsg::TriggerGate {
+                        node,
+                        on: value >= 64,
+                    },
+                }])
+            }
+            MidiEvent::PitchBend { value } => {
+                let (osc, _) = self.notes?;
+                self.bend = self.bend_range * value as f32 / 8192.0;
+                Some(
+                    self.held
+                        .map(|note| self.tune(osc, note))
+                        .into_iter()
+                        .collect(),
+                )
+            }
+        }
+    }
+
+    fn all_notes_off(&mut self) -> Vec<ControlMsg> {
+        match self.notes {
+            Some((_, env)) => {
+                self.held = None;
+                vec![ControlMsg::TriggerGate {
+                    node: env,
+                    on: false,
+                }]
+            }
+            None => vec![ControlMsg::AllNotesOff],
+        }
+    }
+
+    /// Tune `osc` to `note` under the current bend.
+    fn tune(&self, osc: NodeId, note: u8) -> ControlMsg {
+        ControlMsg::SetFrequency {
+            node: osc,
+            hz: midi_to_hz(note) * 2f32.powf(self.bend / 12.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_channel_messages() {
+        assert_eq!(
+            MidiEvent::from_bytes(&[0x93, 60, 100]),
+            Some(MidiEvent::NoteOn {
+                note: 60,
+                velocity: 100
+            })
+        );
+        assert_eq!(
+            MidiEvent::from_bytes(&[0x80, 60, 0]),
+            Some(MidiEvent::NoteOff { note: 60 })
+        );
+        assert_eq!(
+            MidiEvent::from_bytes(&[0xb0, 74, 127]),
+            Some(MidiEvent::ControlChange {
+                controller: 74,
+                value: 127
+            })
+        );
+        assert_eq!(
+            MidiEvent::from_bytes(&[0xe0, 0x00, 0x40]),
+            Some(MidiEvent::PitchBend { value: 0 })
+        );
+        assert_eq!(
+            MidiEvent::from_bytes(&[0xe0, 0x7f, 0x7f]),
+            Some(MidiEvent::PitchBend { value: 8191 })
+        );
+        assert_eq!(MidiEvent::from_bytes(&[0x90, 60]), None);
+        assert_eq!(MidiEvent::from_bytes(&[0xf8]), None);
+        assert_eq!(MidiEvent::from_bytes(&[]), None);
+    }
+}
@@ -2,41 +2,156 @@
 
 #![forbid(unsafe_code)]
 
-use crate::graph::Port;
+use crate::graph::{Port, PortId};
+use crate::transport::Transport;
 use std::any::Any;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// What a node knows about the block it is rendering, besides its buffers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProcessCtx {
+    pub sample_rate: f32,
+    /// The plan's block size. A block split at timed control messages is
+    /// rendered in shorter pieces; the input length gives the frames in
+    /// this one.
+    pub block_size: usize,
+    /// Blocks started before this one; the pieces of a split block share
+    /// it.
+    pub block_index: u64,
+    /// The runtime transport at the first frame of this call.
+    pub transport: Transport,
+}
 
 /// Object-safe node definition for external nodes.
 pub trait NodeDefDyn: Send + Sync {
+    fn type_name(&self) -> &'static str;
     fn input_ports(&self) -> &'static [Port];
     fn output_ports(&self) -> &'static [Port];
     fn required_inputs(&self) -> usize;
+    fn sidechain_port(&self) -> Option<PortId>;
+    fn min_block_size(&self) -> usize;
+    fn latency(&self) -> usize;
     fn init_state(&self, sample_rate: f32, block_size: usize) -> Box<dyn Any + Send>;
-    fn process_block(
+    fn serialize_state(&self, state: &dyn Any) -> Option<Vec<u8>>;
+    fn deserialize_state(&self, state: &mut dyn Any, bytes: &[u8]) -> Result<(), &'static str>;
+    fn set_param(&self, state: &mut dyn Any, name: &str, value: f32);
+    fn set_param_index(&self, state: &mut dyn Any, idx: u8, value: f32);
+    /// Render through [`NodeDef::process`], or through the deprecated
+    /// [`NodeDef::process_block`] via `legacy_outputs` (one buffer per
+    /// output port, reused every block) and copied into `outputs`.
+    fn process(
         &self,
         state: &mut dyn Any,
         inputs: &[&[f32]],
-        outputs: &mut [Vec<f32>],
-        sample_rate: f32,
+        outputs: &mut [&mut [f32]],
+        legacy_outputs: &mut [Vec<f32>],
+        ctx: &ProcessCtx,
     ) -> Result<(), &'static str>;
 }
 
+impl std::fmt::Debug for dyn NodeDefDyn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NodeDef")
+            .field("inputs", &self.input_ports().len())
+            .field("outputs", &self.output_ports().len())
+            .finish()
+    }
+}
+
+/// Definitions compare by identity: two `Arc`s are equal when they share
+/// one definition, as with `Arc::ptr_eq`.
+impl PartialEq for dyn NodeDefDyn {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::addr_eq(self, other)
+    }
+}
+
 /// Generic node definition; implement this for your DSP nodes.
 pub trait NodeDef: Send + Sync + 'static {
     type State: Send + 'static;
+    /// Name this node is saved under and looked up by in a
+    /// [`NodeRegistry`]. Defaults to the Rust type name, which is only
+    /// stable within one build; override it for files that must outlive
+    /// one.
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
     fn input_ports(&self) -> &'static [Port];
     fn output_ports(&self) -> &'static [Port];
     fn required_inputs(&self) -> usize;
+    /// Input port tagged as a sidechain; it never counts as a required input.
+    fn sidechain_port(&self) -> Option<PortId> {
+        None
+    }
+    /// Smallest block size this node can process (e.g. an FFT frame or a
+    /// lookahead window). `Plan::compile` rejects smaller blocks.
+    fn min_block_size(&self) -> usize {
+        1
+    }
+    /// Samples by which this node delays its input (e.g. look-ahead).
+    fn latency(&self) -> usize {
+        0
+    }
     fn init_state(&self, sample_rate: f32, block_size: usize) -> Self::State;
+    /// Encode `state` for a `RuntimeSnapshot`. `None` (the default) leaves
+    /// the state out; it is re-initialised on restore.
+    fn serialize_state(&self, _state: &Self::State) -> Option<Vec<u8>> {
+        None
+    }
+    /// Decode bytes produced by [`NodeDef::serialize_state`] into `state`.
+    fn deserialize_state(
+        &self,
+        _state: &mut Self::State,
+        _bytes: &[u8],
+    ) -> Result<(), &'static str> {
+        Err("node does not support state restore")
+    }
+    /// Apply a named parameter from `Graph::set_node_param` to a freshly
+    /// initialised `state`. Unknown names should be ignored (the default).
+    fn set_param(&self, _state: &mut Self::State, _name: &str, _value: f32) {}
+    /// Apply `ControlMsg::SetParam` parameter `idx` to the live `state`.
+    /// Called on the audio thread between blocks, so it must not allocate
+    /// or block. Unknown indices should be ignored (the default).
+    fn set_param_index(&self, _state: &mut Self::State, _idx: u8, _value: f32) {}
+    /// Render one block. Audio ports carry one sample per frame; control
+    /// ports one value per `CONTROL_RATE_DIVISOR` frames (see
+    /// `Rate::buffer_len`). `ctx` describes the block being rendered.
+    ///
+    /// Every node should implement this; the default falls back to
+    /// [`NodeDef::process_block`] for definitions written before it.
+    fn process(
+        &self,
+        _state: &mut Self::State,
+        _inputs: &[&[f32]],
+        _outputs: &mut [&mut [f32]],
+        _ctx: &ProcessCtx,
+    ) -> Result<(), &'static str> {
+        Err(LEGACY_PROCESS)
+    }
+    /// Render one block into `Vec` outputs. Only called when
+    /// [`NodeDef::process`] is not implemented.
+    #[deprecated(note = "implement `NodeDef::process`, which takes output slices")]
     fn process_block(
         &self,
-        state: &mut Self::State,
-        inputs: &[&[f32]],
-        outputs: &mut [Vec<f32>],
-        sample_rate: f32,
-    ) -> Result<(), &'static str>;
+        _state: &mut Self::State,
+        _inputs: &[&[f32]],
+        _outputs: &mut [Vec<f32>],
+        _sample_rate: f32,
+    ) -> Result<(), &'static str> {
+        Err("node implements neither process nor process_block")
+    }
 }
 
+/// Returned by the default [`NodeDef::process`] to select the deprecated
+/// [`NodeDef::process_block`].
+const LEGACY_PROCESS: &str = "node renders through the deprecated process_block";
+
 impl<T: NodeDef> NodeDefDyn for T {
+    fn type_name(&self) -> &'static str {
+        <T as NodeDef>::type_name(self)
+    }
+
     fn input_ports(&self) -> &'static [Port] {
         <T as NodeDef>::input_ports(self)
     }
@@ -49,23 +164,160 @@ impl<T: NodeDef> NodeDefDyn for T {
         <T as NodeDef>::required_inputs(self)
     }
 
+    fn sidechain_port(&self) -> Option<PortId> {
+        <T as NodeDef>::sidechain_port(self)
+    }
+
+    fn min_block_size(&self) -> usize {
+        <T as NodeDef>::min_block_size(self)
+    }
+
+    fn latency(&self) -> usize {
+        <T as NodeDef>::latency(self)
+    }
+
     fn init_state(&self, sample_rate: f32, block_size: usize) -> Box<dyn Any + Send> {
         Box::new(<T as NodeDef>::init_state(self, sample_rate, block_size))
     }
 
-    fn process_block(
+    fn serialize_state(&self, state: &dyn Any) -> Option<Vec<u8>> {
+        state
+            .downcast_ref::<<T as NodeDef>::State>()
+            .and_then(|typed| <T as NodeDef>::serialize_state(self, typed))
+    }
+
+    fn deserialize_state(&self, state: &mut dyn Any, bytes: &[u8]) -> Result<(), &'static str> {
+        match state.downcast_mut::<<T as NodeDef>::State>() {
+            Some(typed) => <T as NodeDef>::deserialize_state(self, typed, bytes),
+            None => Err("State type mismatch in External node deserialize_state"),
+        }
+    }
+
+    fn set_param(&self, state: &mut dyn Any, name: &str, value: f32) {
+        if let Some(typed) = state.downcast_mut::<<T as NodeDef>::State>() {
+            <T as NodeDef>::set_param(self, typed, name, value);
+        }
+    }
+
+    fn set_param_index(&self, state: &mut dyn Any, idx: u8, value: f32) {
+        if let Some(typed) = state.downcast_mut::<<T as NodeDef>::State>() {
+            <T as NodeDef>::set_param_index(self, typed, idx, value);
+        }
+    }
+
+    fn process(
         &self,
         state: &mut dyn Any,
         inputs: &[&[f32]],
-        outputs: &mut [Vec<f32>],
-        sample_rate: f32,
+        outputs: &mut [&mut [f32]],
+        legacy_outputs: &mut [Vec<f32>],
+        ctx: &ProcessCtx,
     ) -> Result<(), &'static str> {
         // Downcast to concrete state; if type mismatch, return error.
-        if let Some(typed) = state.downcast_mut::<<T as NodeDef>::State>() {
-            <T as NodeDef>::process_block(self, typed, inputs, outputs, sample_rate)
-        } else {
+        let Some(typed) = state.downcast_mut::<<T as NodeDef>::State>() else {
             // Type mismatch: this indicates a wiring bug in runtime state initialization.
-            Err("State type mismatch in External node process_block - this indicates a wiring bug")
+            return Err(
+                "State type mismatch in External node process_block - this indicates a wiring bug",
+            );
+        };
+        match <T as NodeDef>::process(self, typed, inputs, outputs, ctx) {
+            Err(LEGACY_PROCESS) => {
+                let ports = outputs.len().min(legacy_outputs.len());
+                for (legacy, output) in legacy_outputs.iter_mut().zip(outputs.iter()) {
+                    // Within capacity: never reallocates.
+                    legacy.clear();
+                    legacy.resize(output.len(), 0.0);
+                }
+                #[allow(deprecated)]
+                let result = <T as NodeDef>::process_block(
+                    self,
+                    typed,
+                    inputs,
+                    &mut legacy_outputs[..ports],
+                    ctx.sample_rate,
+                );
+                for (output, legacy) in outputs.iter_mut().zip(legacy_outputs.iter()) {
+                    let len = output.len().min(legacy.len());
+                    output[..len].copy_from_slice(&legacy[..len]);
+                }
+                result
+            }
+            result => result,
         }
     }
 }
+
+/// External node definitions by [`NodeDef::type_name`], used to restore
+/// `External` nodes when loading a saved graph (`Graph::from_reader`).
+///
+/// A saved graph records only the name, so the registered instance supplies
+/// any configuration the definition carries.
+#[derive(Clone, Default)]
+pub struct NodeRegistry {
+    defs: HashMap<String, Arc<dyn NodeDefDyn>>,
+}
+
+impl NodeRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `def` under its [`NodeDef::type_name`], replacing any
+    /// definition already registered under that name.
+    pub fn register<T: NodeDef>(&mut self, def: T) -> &mut Self {
+        let def: Arc<dyn NodeDefDyn> = Arc::new(def);
+        self.defs.insert(def.type_name().to_string(), def);
+        self
+    }
+
+    /// The definition registered as `type_name`.
+    pub fn get(&self, type_name: &str) -> Option<Arc<dyn NodeDefDyn>> {
+        self.defs.get(type_name).cloned()
+    }
+}
+
+impl std::fmt::Debug for NodeRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.defs.keys()).finish()
+    }
+}
+
+/// Serde support for `NodeType::External`: the definition is written as its
+/// type name and read back through the registry installed by
+/// [`registered::with_registry`].
+#[cfg(feature = "serde")]
+pub(crate) mod registered {
+    use super::{NodeDefDyn, NodeRegistry};
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::cell::RefCell;
+    use std::sync::Arc;
+
+    thread_local! {
+        static ACTIVE: RefCell<Option<NodeRegistry>> = const { RefCell::new(None) };
+    }
+
+    /// Run `f` with `registry` resolving external nodes on this thread.
+    pub(crate) fn with_registry<R>(registry: &NodeRegistry, f: impl FnOnce() -> R) -> R {
+        let previous = ACTIVE.with(|active| active.replace(Some(registry.clone())));
+        let result = f();
+        ACTIVE.with(|active| *active.borrow_mut() = previous);
+        result
+    }
+
+    pub(crate) fn serialize<S: Serializer>(
+        def: &Arc<dyn NodeDefDyn>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(def.type_name())
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Arc<dyn NodeDefDyn>, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        ACTIVE
+            .with(|active| active.borrow().as_ref().and_then(|r| r.get(&name)))
+            .ok_or_else(|| D::Error::custom(format!("unregistered external node `{}`", name)))
+    }
+}
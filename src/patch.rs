@@ -0,0 +1,301 @@
+//! Patch-cable text format for sharing simple graphs.
+//!
+//! A patch is one or more chains separated by `;` or newlines. Each chain
+//! lists nodes joined by `->`, every arrow connecting output port 0 of the
+//! left node to the lowest free input port of the right node:
+//!
+//! ```text
+//! osc(440) -> gain(0.5) -> sink
+//! ```
+//!
+//! A node written `name: kind(args)` can be referred to later by `name`,
+//! which is how fan-in and fan-out are spelled:
+//!
+//! ```text
+//! m: mix -> sink; osc(440) -> m; osc(660) -> m
+//! ```
+//!
+//! Labels become node names (see `Graph::node_by_name`), and a label may
+//! stand alone to declare a node before its chains:
+//!
+//! ```text
+//! osc: sine(440); g: gain(0.5); osc -> g -> out
+//! ```
+//!
+//! Only built-in nodes with scalar parameters have a spelling: `osc(freq)`
+//! (or `sine`), `noise(seed, color)`, `gain(gain)`, `mix`, `sink` (or
+//! `out`), `delay(samples)`, `diff`, `crossover(freq)`,
+//! `autopan(rate_hz, depth)`, `width(width)` and `dummy`.
+
+#![forbid(unsafe_code)]
+
+use crate::graph::{Edge, Graph, GraphError, NodeId, NodeType, PortId};
+use std::collections::HashMap;
+
+/// Errors from parsing or printing a patch string.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatchError {
+    /// Malformed text, with the offending fragment.
+    Syntax(String),
+    /// Unknown node kind or label.
+    UnknownNode(String),
+    /// Wrong number or form of arguments for a node kind.
+    BadArgs(String),
+    /// An arrow points at a node whose inputs are all connected.
+    NoFreeInput(String),
+    /// The graph rejected an edge.
+    Graph(GraphError),
+    /// The graph uses nodes or connections the format cannot express.
+    Unsupported,
+}
+
+impl Graph {
+    /// Parse a patch string (see the [`patch`](crate::patch) module).
+    pub fn from_patch_str(text: &str) -> Result<Graph, PatchError> {
+        parse(text).map_err(|(_, err)| err)
+    }
+
+    /// Print the graph as a patch string that parses back to an equivalent
+    /// graph. Fails with `Unsupported` for nodes without a spelling or with
+    /// generic parameters, muted or feedback edges, edges leaving a port
+    /// other than 0, or inputs not filled from port 0 up.
+    pub fn to_patch_str(&self) -> Result<String, PatchError> {
+        if !self.feedback_edges.is_empty() {
+            return Err(PatchError::Unsupported);
+        }
+        let spellings: Vec<Option<String>> = self
+            .nodes
+            .iter()
+            .map(|n| {
+                n.as_ref()
+                    .map(|data| {
+                        if data.params.is_empty() {
+                            spell(&data.node_type)
+                        } else {
+                            Err(PatchError::Unsupported)
+                        }
+                    })
+                    .transpose()
+            })
+            .collect::<Result<_, _>>()?;
+
+        // Emit edges in port order so reparsing assigns the same ports.
+        let mut edges: Vec<&Edge> = self.edges.iter().collect();
+        edges.sort_by_key(|e| e.to_port.0);
+        let mut filled = vec![0; self.nodes.len()];
+        for edge in &edges {
+            if edge.muted || edge.from_port != PortId(0) || edge.to_port.0 != filled[edge.to_node.0]
+            {
+                return Err(PatchError::Unsupported);
+            }
+            filled[edge.to_node.0] += 1;
+        }
+
+        // Greedily string edges into chains.
+        filled.fill(0);
+        let mut emitted = vec![false; edges.len()];
+        let mut chains: Vec<Vec<NodeId>> = Vec::new();
+        let ready = |i: usize, emitted: &[bool], filled: &[usize]| {
+            !emitted[i] && edges[i].to_port.0 == filled[edges[i].to_node.0]
+        };
+        while let Some(first) = (0..edges.len()).find(|&i| ready(i, &emitted, &filled)) {
+            let mut chain = vec![edges[first].from_node];
+            let mut next = Some(first);
+            while let Some(i) = next {
+                emitted[i] = true;
+                filled[edges[i].to_node.0] += 1;
+                let tail = edges[i].to_node;
+                chain.push(tail);
+                next = (0..edges.len())
+                    .find(|&j| edges[j].from_node == tail && ready(j, &emitted, &filled));
+            }
+            chains.push(chain);
+        }
+        for data in self.nodes.iter().flatten() {
+            if !chains.iter().flatten().any(|&n| n == data.id) {
+                chains.push(vec![data.id]);
+            }
+        }
+
+        // Nodes mentioned more than once need a label.
+        let mut mentions = vec![0; self.nodes.len()];
+        for node in chains.iter().flatten() {
+            mentions[node.0] += 1;
+        }
+        let mut defined = vec![false; self.nodes.len()];
+        let chains: Vec<String> = chains
+            .iter()
+            .map(|chain| {
+                let nodes: Vec<String> = chain
+                    .iter()
+                    .map(|node| {
+                        let spelling = spellings[node.0].as_deref().unwrap_or_default();
+                        match (mentions[node.0] > 1, defined[node.0]) {
+                            (false, _) => spelling.to_string(),
+                            (true, true) => format!("n{}", node.0),
+                            (true, false) => {
+                                defined[node.0] = true;
+                                format!("n{}: {}", node.0, spelling)
+                            }
+                        }
+                    })
+                    .collect();
+                nodes.join(" -> ")
+            })
+            .collect();
+        Ok(chains.join("; "))
+    }
+}
+
+/// Parse `text`; an error comes with the byte offset of the chain element
+/// it is about.
+pub(crate) fn parse(text: &str) -> Result<Graph, (usize, PatchError)> {
+    let mut graph = Graph::new();
+    let mut labels: HashMap<&str, NodeId> = HashMap::new();
+    let mut chain_start = 0;
+    for raw_chain in text.split([';', '\n']) {
+        let mut token_start = chain_start;
+        chain_start += raw_chain.len() + 1;
+        if raw_chain.trim().is_empty() {
+            continue;
+        }
+        let mut prev = None;
+        for raw_token in raw_chain.split("->") {
+            let offset = token_start + raw_token.len() - raw_token.trim_start().len();
+            token_start += raw_token.len() + "->".len();
+            let token = raw_token.trim();
+            let node = parse_node(&mut graph, &mut labels, token).map_err(|e| (offset, e))?;
+            if let Some(from) = prev {
+                connect(&mut graph, from, node, token).map_err(|e| (offset, e))?;
+            }
+            prev = Some(node);
+        }
+    }
+    Ok(graph)
+}
+
+/// Resolve a chain element to a label reference or a newly added node.
+fn parse_node<'a>(
+    graph: &mut Graph,
+    labels: &mut HashMap<&'a str, NodeId>,
+    token: &'a str,
+) -> Result<NodeId, PatchError> {
+    let (label, spec) = match token.split_once(':') {
+        Some((label, spec)) => (Some(label.trim()), spec.trim()),
+        None => (None, token),
+    };
+    if let Some(label) = label {
+        if !is_ident(label) || labels.contains_key(label) {
+            return Err(PatchError::Syntax(token.to_string()));
+        }
+    } else if let Some(&node) = labels.get(spec) {
+        return Ok(node);
+    }
+
+    let (kind, args) = match spec.split_once('(') {
+        Some((kind, rest)) => {
+            let args = rest
+                .strip_suffix(')')
+                .ok_or_else(|| PatchError::Syntax(token.to_string()))?;
+            let args: Vec<&str> = args
+                .split(',')
+                .map(str::trim)
+                .filter(|a| !a.is_empty())
+                .collect();
+            (kind.trim(), args)
+        }
+        None => (spec, Vec::new()),
+    };
+    if !is_ident(kind) {
+        return Err(PatchError::Syntax(token.to_string()));
+    }
+    let bad_args = || PatchError::BadArgs(spec.to_string());
+    let num = |arg: &str| arg.parse::<f32>().map_err(|_| bad_args());
+    let node_type = match (kind, args.as_slice()) {
+        ("osc" | "sine", &[freq]) => NodeType::SineOsc { freq: num(freq)? },
+        ("noise", &[seed, color]) => NodeType::Noise {
+            seed: seed.parse().map_err(|_| bad_args())?,
+            color: num(color)?,
+        },
+        ("gain", &[gain]) => NodeType::Gain { gain: num(gain)? },
+        ("mix", &[]) => NodeType::Mix,
+        ("sink" | "out", &[]) => NodeType::OutputSink,
+        ("delay", &[delay]) => NodeType::Delay {
+            delay_samples: num(delay)?,
+        },
+        ("diff", &[]) => NodeType::Diff,
+        ("crossover", &[freq]) => NodeType::Crossover { freq: num(freq)? },
+        ("autopan", &[rate_hz, depth]) => NodeType::AutoPan {
+            rate_hz: num(rate_hz)?,
+            depth: num(depth)?,
+        },
+        ("width", &[width]) => NodeType::StereoWidth { width: num(width)? },
+        ("dummy", &[]) => NodeType::Dummy,
+        ("osc" | "sine" | "noise" | "gain" | "mix" | "sink" | "out" | "delay", _)
+        | ("diff" | "crossover" | "autopan" | "width" | "dummy", _) => return Err(bad_args()),
+        _ => return Err(PatchError::UnknownNode(kind.to_string())),
+    };
+    let node = graph.add_node(node_type);
+    if let Some(label) = label {
+        labels.insert(label, node);
+        graph
+            .set_node_name(node, label)
+            .map_err(PatchError::Graph)?;
+    }
+    Ok(node)
+}
+
+/// Connect output port 0 of `from` to the lowest free input of `to`.
+fn connect(graph: &mut Graph, from: NodeId, to: NodeId, token: &str) -> Result<(), PatchError> {
+    let (Some(from_data), Some(to_data)) = (&graph.nodes[from.0], &graph.nodes[to.0]) else {
+        return Err(PatchError::Graph(GraphError::InvalidNode));
+    };
+    let rate = from_data
+        .outputs
+        .first()
+        .ok_or(PatchError::Graph(GraphError::InvalidPort))?
+        .rate
+        .clone();
+    let to_port = to_data
+        .inputs
+        .iter()
+        .map(|p| p.id)
+        .find(|&port| graph.edges_into_port(to, port).is_empty())
+        .ok_or_else(|| PatchError::NoFreeInput(token.to_string()))?;
+    graph
+        .add_edge(Edge {
+            from_node: from,
+            from_port: PortId(0),
+            to_node: to,
+            to_port,
+            rate,
+            muted: false,
+        })
+        .map_err(PatchError::Graph)
+}
+
+/// The patch spelling of a node, if it has one.
+fn spell(node_type: &NodeType) -> Result<String, PatchError> {
+    Ok(match node_type {
+        NodeType::SineOsc { freq } => format!("osc({})", freq),
+        NodeType::Noise { seed, color } => format!("noise({}, {})", seed, color),
+        NodeType::Gain { gain } => format!("gain({})", gain),
+        NodeType::Mix => "mix".to_string(),
+        NodeType::OutputSink => "sink".to_string(),
+        NodeType::Delay { delay_samples } => format!("delay({})", delay_samples),
+        NodeType::Diff => "diff".to_string(),
+        NodeType::Crossover { freq } => format!("crossover({})", freq),
+        NodeType::AutoPan { rate_hz, depth } => format!("autopan({}, {})", rate_hz, depth),
+        NodeType::StereoWidth { width } => format!("width({})", width),
+        NodeType::Dummy => "dummy".to_string(),
+        _ => return Err(PatchError::Unsupported),
+    })
+}
+
+fn is_ident(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
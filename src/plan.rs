@@ -3,7 +3,8 @@
 #![forbid(unsafe_code)]
 // #![deny(missing_docs)]
 
-use crate::graph::{Graph, NodeId, PortId, Rate};
+use crate::graph::{write_dot, DotEdge, Graph, NodeId, NodeType, PortId, Rate};
+use std::borrow::Cow;
 
 /// Edge spec for the plan.
 #[derive(Debug, Clone, PartialEq)]
@@ -13,6 +14,10 @@ pub struct EdgeSpec {
     pub to_node: NodeId,
     pub to_port: PortId,
     pub rate: Rate,
+    /// Carries silence (see `Edge::muted`).
+    pub muted: bool,
+    /// Delivers the previous block (see `Graph::add_feedback_edge`).
+    pub feedback: bool,
 }
 
 /// The compiled plan: execution order and edge specs.
@@ -25,40 +30,207 @@ pub struct Plan {
     pub block_size: usize,
     pub max_inputs: usize,
     pub max_outputs: usize,
+    /// Runtime buffer index backing each edge.
+    edge_buffers: Vec<usize>,
+    buffer_count: usize,
+    /// Edges whose lifetimes do not overlap share buffers (see
+    /// [`Plan::compile_shared`]).
+    shares_buffers: bool,
+    /// Per node: every node with a path into it.
+    ancestors: Vec<Vec<NodeId>>,
+    /// Per node: every node reachable from it.
+    descendants: Vec<Vec<NodeId>>,
+    /// Per node: `NodeType::latency`.
+    latencies: Vec<usize>,
+    /// Per node: true if it can only ever output silence.
+    static_silence: Vec<bool>,
+    /// Nodes whose output port 0 is summed into the runtime output (see
+    /// [`Plan::compile_subgraph`]).
+    preview_outputs: Vec<NodeId>,
+    /// `OutputSink` and `MultiOutputSink` nodes.
+    sinks: Vec<NodeId>,
+    /// Nodes left out by [`Plan::compile_pruned`].
+    pruned: Vec<NodeId>,
+    /// Gain chains collapsed by [`Plan::compile_fused`].
+    fused: Vec<(NodeId, Vec<NodeId>)>,
+    /// Delays inserted by [`Plan::compile_compensated`], with their times
+    /// in samples.
+    compensation: Vec<(NodeId, f32)>,
+    /// Nodes grouped by dependency depth (see [`Plan::levels`]).
+    levels: Vec<Vec<NodeId>>,
 }
 
 impl Plan {
     /// Create a plan from a graph.
     pub fn compile(graph: &Graph, block_size: usize) -> Result<Self, PlanError> {
+        Self::compile_with(graph, block_size, false)
+    }
+
+    /// Like [`Plan::compile`], but nodes with missing required inputs are
+    /// accepted and read silence on the open ports.
+    pub fn compile_lenient(graph: &Graph, block_size: usize) -> Result<Self, PlanError> {
+        Self::compile_with(graph, block_size, true)
+    }
+
+    /// Like [`Plan::compile`], but edges share runtime buffers: a buffer is
+    /// handed to the next edge produced once every reader of its current
+    /// edge has run, so the runtime allocates only as many buffers as are
+    /// live at once rather than one per edge.
+    ///
+    /// Feedback edges keep a buffer of their own, since they carry data
+    /// across blocks. For the same reason nodes cannot be frozen under a
+    /// shared plan; `ControlMsg::FreezeNode` has no effect.
+    pub fn compile_shared(graph: &Graph, block_size: usize) -> Result<Self, PlanError> {
+        let mut plan = Self::compile(graph, block_size)?;
+        let (edge_buffers, buffer_count) = share_buffers(&plan);
+        plan.edge_buffers = edge_buffers;
+        plan.buffer_count = buffer_count;
+        plan.shares_buffers = true;
+        Ok(plan)
+    }
+
+    /// Compile only `outputs` and the nodes feeding them, to audition part
+    /// of a graph while editing. Output port 0 of every node in `outputs`
+    /// is summed into the runtime's output as if it fed an `OutputSink`;
+    /// nodes outside the subgraph are left out of the schedule. Node ids are
+    /// unchanged, so run the plan with the full `graph`.
+    pub fn compile_subgraph(
+        graph: &Graph,
+        block_size: usize,
+        outputs: &[NodeId],
+    ) -> Result<Self, PlanError> {
+        let subgraph = feeding(&graph.flatten(), outputs)?;
+        let mut plan = Self::compile_with(&subgraph, block_size, false)?;
+        plan.preview_outputs = outputs.to_vec();
+        Ok(plan)
+    }
+
+    /// Like [`Plan::compile`], but nodes that feed no `OutputSink` or
+    /// `MultiOutputSink` are left out of the schedule, so they cost nothing
+    /// at runtime; [`Plan::pruned_nodes`] lists them. Nodes only observed
+    /// through RT messages, such as a meter on a dead branch, are dropped
+    /// too. Node ids are unchanged, so run the plan with the full `graph`.
+    pub fn compile_pruned(graph: &Graph, block_size: usize) -> Result<Self, PlanError> {
+        let graph = graph.flatten();
+        let sinks: Vec<NodeId> = graph
+            .nodes
+            .iter()
+            .flatten()
+            .filter(|n| {
+                matches!(
+                    n.node_type,
+                    NodeType::OutputSink | NodeType::MultiOutputSink { .. }
+                )
+            })
+            .map(|n| n.id)
+            .collect();
+        let live = feeding(&graph, &sinks)?;
+        let mut plan = Self::compile_with(&live, block_size, false)?;
+        plan.pruned = graph
+            .nodes
+            .iter()
+            .zip(&live.nodes)
+            .filter_map(|(node, kept)| match (node, kept) {
+                (Some(node), None) => Some(node.id),
+                _ => None,
+            })
+            .collect();
+        Ok(plan)
+    }
+
+    /// Like [`Plan::compile`], but chains of `Gain` nodes, each feeding
+    /// only the next, run as their first node applying the product of the
+    /// chain's gains: one buffer pass instead of one per node.
+    /// [`Plan::fused_gains`] reports what was fused.
+    ///
+    /// `ControlMsg::SetGain` still reaches every node of a chain. Mute,
+    /// solo and freeze act on the first node only; the rest are not
+    /// scheduled.
+    pub fn compile_fused(graph: &Graph, block_size: usize) -> Result<Self, PlanError> {
+        let mut graph = graph.flatten().into_owned();
+        let chains = gain_chains(&graph);
+        for (head, rest) in &chains {
+            let tail = *rest.last().expect("chains have two or more nodes");
+            // Drop the links inside the chain; the tail's outputs leave
+            // from the head.
+            graph.edges.retain(|e| !rest.contains(&e.to_node));
+            for edge in &mut graph.edges {
+                if edge.from_node == tail {
+                    edge.from_node = *head;
+                }
+            }
+            for node in rest {
+                graph.nodes[node.0] = None;
+            }
+        }
+        let mut plan = Self::compile_with(&graph, block_size, false)?;
+        plan.fused = chains;
+        Ok(plan)
+    }
+
+    /// Like [`Plan::compile`], but merge points are time-aligned first:
+    /// audio inputs that arrive early are delayed to match the latest one,
+    /// as [`Graph::compensate_latency`] does, without changing `graph`.
+    /// [`Plan::compensation_delays`] lists the inserted delays; runtimes
+    /// built from the plan and `graph` create them.
+    pub fn compile_compensated(graph: &Graph, block_size: usize) -> Result<Self, PlanError> {
+        let mut graph = graph.flatten().into_owned();
+        let inserted = graph.compensate_latency();
+        let mut plan = Self::compile_with(&graph, block_size, false)?;
+        plan.compensation = inserted
+            .into_iter()
+            .map(
+                |node| match graph.nodes[node.0].as_ref().map(|n| &n.node_type) {
+                    Some(NodeType::Delay { delay_samples }) => (node, *delay_samples),
+                    _ => unreachable!("compensate_latency inserts delays"),
+                },
+            )
+            .collect();
+        Ok(plan)
+    }
+
+    fn compile_with(graph: &Graph, block_size: usize, lenient: bool) -> Result<Self, PlanError> {
+        let graph = &*graph.flatten();
         if block_size == 0 {
             return Err(PlanError::InvalidBlockSize);
         }
+        let required = graph.min_block_size();
+        if block_size < required {
+            return Err(PlanError::BlockSizeTooSmall { required });
+        }
+        check_endpoints(graph)?;
+        check_rates(graph)?;
         // Topological sort
         let order = topo_sort(graph)?;
 
-        // Build edges
+        // Build edges, feedback edges last. A feedback edge's buffer is read
+        // before its node runs, so it still holds the previous block. Edges
+        // closing a loop through a delay behave the same way.
+        let mut position = vec![0; graph.nodes.len()];
+        for (step, node) in order.iter().enumerate() {
+            position[node.0] = step;
+        }
         let edges: Vec<EdgeSpec> = graph
             .edges
             .iter()
-            .map(|e| EdgeSpec {
+            .chain(&graph.feedback_edges)
+            .enumerate()
+            .map(|(idx, e)| EdgeSpec {
                 from_node: e.from_node,
                 from_port: e.from_port,
                 to_node: e.to_node,
                 to_port: e.to_port,
                 rate: e.rate.clone(),
+                muted: e.muted,
+                feedback: idx >= graph.edges.len()
+                    || position[e.to_node.0] <= position[e.from_node.0],
             })
             .collect();
 
         // Validate single-writer: each input port has at most one edge
-        let mut input_ports = std::collections::HashSet::new();
-        for edge in &edges {
-            if !input_ports.insert((edge.to_node, edge.to_port)) {
-                return Err(PlanError::MultipleWritersToInput {
-                    node: edge.to_node,
-                    port: edge.to_port,
-                });
-            }
-        }
+        graph
+            .check_single_writer()
+            .map_err(|(node, port)| PlanError::MultipleWritersToInput { node, port })?;
 
         // Build node_inputs and node_outputs
         let mut node_inputs = vec![vec![]; graph.nodes.len()];
@@ -72,18 +244,35 @@ impl Plan {
         let max_outputs = node_outputs.iter().map(|v| v.len()).max().unwrap_or(0);
 
         // Validate required inputs
-        for node_data in graph.nodes.iter().flatten() {
-            let required = node_data.node_type.required_inputs();
-            let connected = graph
-                .edges
-                .iter()
-                .filter(|e| e.to_node == node_data.id)
-                .count();
-            if connected < required {
-                return Err(PlanError::RequiredInputMissing { node: node_data.id });
-            }
+        if let Some(node) = missing_inputs(graph).next().filter(|_| !lenient) {
+            return Err(PlanError::RequiredInputMissing { node });
         }
 
+        // One buffer per edge.
+        let edge_buffers: Vec<usize> = (0..edges.len()).collect();
+        let buffer_count = edge_buffers.len();
+
+        let (ancestors, descendants) = reachability(graph);
+        let static_silence = static_silence(graph, &order);
+        let levels = levels(&order, &edges, &node_inputs, &node_outputs);
+        let latencies = graph
+            .nodes
+            .iter()
+            .map(|n| n.as_ref().map_or(0, |n| n.node_type.latency()))
+            .collect();
+        let sinks = graph
+            .nodes
+            .iter()
+            .flatten()
+            .filter(|n| {
+                matches!(
+                    n.node_type,
+                    NodeType::OutputSink | NodeType::MultiOutputSink { .. }
+                )
+            })
+            .map(|n| n.id)
+            .collect();
+
         let plan = Self {
             order,
             node_inputs,
@@ -92,34 +281,674 @@ impl Plan {
             block_size,
             max_inputs,
             max_outputs,
+            edge_buffers,
+            buffer_count,
+            shares_buffers: false,
+            ancestors,
+            descendants,
+            latencies,
+            static_silence,
+            preview_outputs: Vec::new(),
+            sinks,
+            pruned: Vec::new(),
+            fused: Vec::new(),
+            compensation: Vec::new(),
+            levels,
         };
         Ok(plan)
     }
+
+    /// Index of the runtime buffer that carries edge `edge_idx`.
+    ///
+    /// Under [`Plan::compile`] every edge has a buffer of its own and this is
+    /// the identity. Under [`Plan::compile_shared`] edges reuse buffers, but
+    /// edges that are live at the same time never share one. Panics if
+    /// `edge_idx` is out of range.
+    pub fn edge_buffer_index(&self, edge_idx: usize) -> usize {
+        self.edge_buffers[edge_idx]
+    }
+
+    /// Number of buffers the runtime must allocate for this plan.
+    pub fn buffer_count(&self) -> usize {
+        self.buffer_count
+    }
+
+    /// Whether edges share buffers (the plan came from
+    /// [`Plan::compile_shared`]).
+    pub fn shares_buffers(&self) -> bool {
+        self.shares_buffers
+    }
+
+    /// Export the compiled schedule for external tools.
+    ///
+    /// Schema:
+    ///
+    /// ```text
+    /// {
+    ///   "block_size": usize,
+    ///   "buffer_count": usize,
+    ///   "order": [node_id, ...],          // execution order
+    ///   "edges": [{
+    ///     "from_node": usize, "from_port": usize,
+    ///     "to_node": usize, "to_port": usize,
+    ///     "rate": "audio" | "control" | "event",
+    ///     "muted": bool,
+    ///     "feedback": bool,               // delayed by one block
+    ///     "buffer": usize                 // runtime buffer index
+    ///   }, ...]                           // indexed by edge id
+    /// }
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        let json = PlanJson {
+            block_size: self.block_size,
+            buffer_count: self.buffer_count,
+            order: self.order.iter().map(|n| n.0).collect(),
+            edges: self
+                .edges
+                .iter()
+                .enumerate()
+                .map(|(idx, e)| EdgeJson {
+                    from_node: e.from_node.0,
+                    from_port: e.from_port.0,
+                    to_node: e.to_node.0,
+                    to_port: e.to_port.0,
+                    rate: match e.rate {
+                        Rate::Audio => "audio",
+                        Rate::Control => "control",
+                        Rate::Event => "event",
+                    },
+                    muted: e.muted,
+                    feedback: e.feedback,
+                    buffer: self.edge_buffer_index(idx),
+                })
+                .collect(),
+        };
+        serde_json::to_string(&json).expect("plan JSON serialization cannot fail")
+    }
+
+    /// Render the plan in Graphviz DOT format, like `Graph::to_dot` but
+    /// with each node labelled by its step in the execution order. Edges
+    /// are the plan's, so loops through a `Delay` show as feedback (dotted)
+    /// and nodes outside the schedule are dashed.
+    pub fn to_dot(&self, graph: &Graph) -> String {
+        let graph = &*self.scheduled_graph(graph);
+        let edges = self.edges.iter().map(|e| DotEdge {
+            from: e.from_node,
+            from_port: e.from_port,
+            to: e.to_node,
+            to_port: e.to_port,
+            rate: &e.rate,
+            muted: e.muted,
+            feedback: e.feedback,
+        });
+        write_dot(graph, Some(&self.order), edges)
+    }
+
+    /// Execution order as node type names (e.g. `["SineOsc", "Gain",
+    /// "OutputSink"]`), for debugging. Nodes missing from `graph` show as
+    /// `"?"`.
+    pub fn order_names(&self, graph: &Graph) -> Vec<&'static str> {
+        let graph = &*self.scheduled_graph(graph);
+        self.order
+            .iter()
+            .map(|n| match graph.nodes.get(n.0) {
+                Some(Some(data)) => data.node_type.type_name(),
+                _ => "?",
+            })
+            .collect()
+    }
+
+    /// Gain chains [`Plan::compile_fused`] collapsed: each entry is the
+    /// node that runs and the nodes folded into it, in signal order. Empty
+    /// for other plans.
+    pub fn fused_gains(&self) -> &[(NodeId, Vec<NodeId>)] {
+        &self.fused
+    }
+
+    /// Delays [`Plan::compile_compensated`] inserted, with their times in
+    /// samples. Empty for other plans.
+    pub fn compensation_delays(&self) -> &[(NodeId, f32)] {
+        &self.compensation
+    }
+
+    /// `graph` as this plan schedules it: flattened, plus the delays of
+    /// [`Plan::compile_compensated`] under the ids the plan gave them.
+    pub(crate) fn scheduled_graph<'a>(&self, graph: &'a Graph) -> Cow<'a, Graph> {
+        let mut graph = graph.flatten();
+        for &(node, delay_samples) in &self.compensation {
+            let id = graph.to_mut().add_node(NodeType::Delay { delay_samples });
+            debug_assert_eq!(id, node);
+        }
+        graph
+    }
+
+    /// Nodes [`Plan::compile_pruned`] left out because they feed no sink,
+    /// in id order. Empty for other plans.
+    pub fn pruned_nodes(&self) -> &[NodeId] {
+        &self.pruned
+    }
+
+    /// Latency, in samples, of the longest path from any source to the
+    /// output of each node, indexed by node id.
+    pub fn output_latencies(&self) -> Vec<usize> {
+        let mut out = vec![0; self.latencies.len()];
+        for &node in &self.order {
+            let arrival = self.input_latencies(node, &out).max().unwrap_or(0);
+            out[node.0] = arrival + self.latencies[node.0];
+        }
+        out
+    }
+
+    /// Latency, in samples, of the longest path to the runtime output: the
+    /// largest arrival time at any sink or preview output. Zero if nothing
+    /// reaches the output.
+    pub fn total_latency(&self) -> usize {
+        let latencies = self.output_latencies();
+        self.sinks
+            .iter()
+            .chain(&self.preview_outputs)
+            .map(|node| latencies[node.0])
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn input_latencies<'a>(
+        &'a self,
+        node: NodeId,
+        output_latencies: &'a [usize],
+    ) -> impl Iterator<Item = usize> + 'a {
+        self.node_inputs[node.0]
+            .iter()
+            .filter(move |&&(edge_idx, _)| !self.edges[edge_idx].feedback)
+            .map(move |&(edge_idx, _)| output_latencies[self.edges[edge_idx].from_node.0])
+    }
+
+    /// Nodes where signals arriving on different inputs are misaligned.
+    ///
+    /// Each entry is a merge node and the spread, in samples, between its
+    /// earliest and latest arriving input. Delaying the early inputs by
+    /// that much time-aligns them.
+    pub fn latency_imbalances(&self) -> Vec<(NodeId, usize)> {
+        let latencies = self.output_latencies();
+        self.order
+            .iter()
+            .filter_map(|&node| {
+                let max = self.input_latencies(node, &latencies).max()?;
+                let min = self.input_latencies(node, &latencies).min()?;
+                (max > min).then_some((node, max - min))
+            })
+            .collect()
+    }
+
+    /// Nodes that can only ever output silence because no signal source
+    /// reaches their (non-sidechain) inputs. The runtime skips them.
+    pub fn static_silence_nodes(&self) -> Vec<NodeId> {
+        self.order
+            .iter()
+            .copied()
+            .filter(|n| self.static_silence[n.0])
+            .collect()
+    }
+
+    /// Whether `node` is in [`Plan::static_silence_nodes`].
+    pub fn is_static_silence(&self, node: NodeId) -> bool {
+        self.static_silence.get(node.0).copied().unwrap_or(false)
+    }
+
+    /// Whether any input of `node` is a feedback edge, so it hears the
+    /// previous block rather than the current one.
+    pub fn reads_previous_block(&self, node: NodeId) -> bool {
+        self.node_inputs
+            .get(node.0)
+            .is_some_and(|inputs| inputs.iter().any(|&(e, _)| self.edges[e].feedback))
+    }
+
+    /// The schedule grouped into levels of mutually independent nodes.
+    ///
+    /// Every node reads its inputs from earlier levels, so the nodes of a
+    /// level may run in any order or at the same time once the levels
+    /// before it are done (see `ParallelRuntime`). A node reading a
+    /// feedback edge is never in a later level than the node writing it.
+    /// Within a level nodes keep their order in [`Plan::order`].
+    pub fn levels(&self) -> &[Vec<NodeId>] {
+        &self.levels
+    }
+
+    /// Nodes routed straight to the output by [`Plan::compile_subgraph`];
+    /// empty for a full plan.
+    pub fn preview_outputs(&self) -> &[NodeId] {
+        &self.preview_outputs
+    }
+
+    /// Nodes with a path into `node` (its upstream sources), in id order.
+    pub fn ancestors(&self, node: NodeId) -> &[NodeId] {
+        self.ancestors.get(node.0).map_or(&[], |v| v.as_slice())
+    }
+
+    /// Nodes reachable from `node` (everything downstream), in id order.
+    pub fn descendants(&self, node: NodeId) -> &[NodeId] {
+        self.descendants.get(node.0).map_or(&[], |v| v.as_slice())
+    }
+}
+
+/// JSON view of a compiled plan; see [`Plan::to_json`].
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize)]
+struct PlanJson {
+    block_size: usize,
+    buffer_count: usize,
+    order: Vec<usize>,
+    edges: Vec<EdgeJson>,
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize)]
+struct EdgeJson {
+    from_node: usize,
+    from_port: usize,
+    to_node: usize,
+    to_port: usize,
+    rate: &'static str,
+    muted: bool,
+    feedback: bool,
+    buffer: usize,
 }
 
 /// Errors during plan compilation.
 #[derive(Debug, Clone, PartialEq)]
 pub enum PlanError {
     CycleDetected,
-    RequiredInputMissing { node: NodeId },
-    MultipleWritersToInput { node: NodeId, port: PortId },
+    RequiredInputMissing {
+        node: NodeId,
+    },
+    MultipleWritersToInput {
+        node: NodeId,
+        port: PortId,
+    },
     InvalidBlockSize,
+    /// A node needs blocks of at least `required` samples.
+    BlockSizeTooSmall {
+        required: usize,
+    },
+    /// `node` is not in the graph.
+    InvalidNode {
+        node: NodeId,
+    },
+    /// An edge's rate differs from the rate of `port` on `node`, one of
+    /// its ends. Control and audio buffers have different lengths, so the
+    /// runtime cannot run it.
+    RateMismatch {
+        node: NodeId,
+        port: PortId,
+    },
+}
+
+/// `graph` with only `outputs` and the nodes feeding them; other slots are
+/// emptied, so node ids are unchanged.
+fn feeding(graph: &Graph, outputs: &[NodeId]) -> Result<Graph, PlanError> {
+    let mut keep = vec![false; graph.nodes.len()];
+    let mut stack = Vec::with_capacity(outputs.len());
+    for &node in outputs {
+        if !graph.contains(node) {
+            return Err(PlanError::InvalidNode { node });
+        }
+        stack.push(node);
+    }
+    while let Some(node) = stack.pop() {
+        if !std::mem::replace(&mut keep[node.0], true) {
+            stack.extend(
+                graph
+                    .edges
+                    .iter()
+                    .filter(|e| e.to_node == node)
+                    .map(|e| e.from_node),
+            );
+        }
+    }
+
+    let mut subgraph = graph.clone();
+    for (node, kept) in subgraph.nodes.iter_mut().zip(&keep) {
+        if !kept {
+            *node = None;
+        }
+    }
+    subgraph
+        .edges
+        .retain(|e| keep[e.from_node.0] && keep[e.to_node.0]);
+    subgraph.feedback_edges.retain(|e| keep[e.from_node.0]);
+    Ok(subgraph)
+}
+
+/// Chains of two or more `Gain` nodes where each feeds only the next, over
+/// a plain audio edge that is the next one's only input. Returns the first
+/// node of each chain and the rest in order.
+fn gain_chains(graph: &Graph) -> Vec<(NodeId, Vec<NodeId>)> {
+    let is_gain = |node: NodeId| {
+        matches!(
+            graph.nodes.get(node.0),
+            Some(Some(data)) if matches!(data.node_type, NodeType::Gain { .. })
+        )
+    };
+    // The gain `node` folds into, if any.
+    let next = |node: NodeId| {
+        if !is_gain(node) {
+            return None;
+        }
+        let mut outputs = graph.outputs_of(node);
+        let edge = outputs.next()?;
+        let plain = outputs.next().is_none()
+            && edge.rate == Rate::Audio
+            && !edge.muted
+            && edge.to_node != node
+            && is_gain(edge.to_node)
+            && graph.inputs_of(edge.to_node).count() == 1
+            && !graph.feedback_edges.contains(edge);
+        plain.then_some(edge.to_node)
+    };
+    let mut folded = vec![false; graph.nodes.len()];
+    for node in (0..graph.nodes.len()).filter_map(|i| graph.id_at(i)) {
+        if let Some(to) = next(node) {
+            folded[to.0] = true;
+        }
+    }
+    (0..graph.nodes.len())
+        .filter_map(|i| graph.id_at(i))
+        .filter(|&node| !folded[node.0])
+        .filter_map(|head| {
+            let rest: Vec<NodeId> = std::iter::successors(next(head), |&n| next(n)).collect();
+            (!rest.is_empty()).then_some((head, rest))
+        })
+        .collect()
+}
+
+/// Reject edges to or from removed or unknown nodes, e.g. through a stale
+/// `NodeId`. `add_edge` checks this too, but `Graph::edges` is public.
+fn check_endpoints(graph: &Graph) -> Result<(), PlanError> {
+    for edge in graph.edges.iter().chain(&graph.feedback_edges) {
+        for node in [edge.from_node, edge.to_node] {
+            if !graph.contains(node) {
+                return Err(PlanError::InvalidNode { node });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Reject edges whose rate disagrees with a port they connect. `add_edge`
+/// checks this too, but `Graph::edges` is public.
+fn check_rates(graph: &Graph) -> Result<(), PlanError> {
+    match rate_mismatches(graph).next() {
+        Some((node, port)) => Err(PlanError::RateMismatch { node, port }),
+        None => Ok(()),
+    }
+}
+
+/// Every edge end whose port rate differs from the edge's rate. Dangling
+/// ends are left to the rest of compilation.
+pub(crate) fn rate_mismatches(graph: &Graph) -> impl Iterator<Item = (NodeId, PortId)> + '_ {
+    graph
+        .edges
+        .iter()
+        .chain(&graph.feedback_edges)
+        .flat_map(move |edge| {
+            [
+                (edge.from_node, edge.from_port, false),
+                (edge.to_node, edge.to_port, true),
+            ]
+            .into_iter()
+            .filter(move |&(node, port, input)| {
+                graph
+                    .get_port_rate(node, port, input)
+                    .is_ok_and(|rate| rate != edge.rate)
+            })
+            .map(|(node, port, _)| (node, port))
+        })
+}
+
+/// Nodes with fewer connected (non-sidechain) inputs than they require.
+pub(crate) fn missing_inputs(graph: &Graph) -> impl Iterator<Item = NodeId> + '_ {
+    graph.nodes.iter().flatten().filter_map(move |node_data| {
+        let required = node_data.node_type.required_inputs();
+        let sidechain = node_data.node_type.sidechain_port();
+        let connected = graph
+            .edges
+            .iter()
+            .chain(&graph.feedback_edges)
+            .filter(|e| e.to_node == node_data.id && Some(e.to_port) != sidechain)
+            .count();
+        (connected < required).then_some(node_data.id)
+    })
+}
+
+/// Buffer assignment for [`Plan::compile_shared`]: walk the order, give each
+/// edge a free buffer when its producer runs and free it once its consumer
+/// has run. Feedback edges are never freed. Returns the buffer per edge and
+/// the number of buffers.
+fn share_buffers(plan: &Plan) -> (Vec<usize>, usize) {
+    let mut assigned = vec![0; plan.edges.len()];
+    let mut free: Vec<usize> = Vec::new();
+    let mut count = 0;
+    // Feedback edges are read before their producer runs, so their buffers
+    // are reserved up front and never enter the free list.
+    for (idx, edge) in plan.edges.iter().enumerate() {
+        if edge.feedback {
+            assigned[idx] = count;
+            count += 1;
+        }
+    }
+    for node in &plan.order {
+        for &(idx, _) in &plan.node_outputs[node.0] {
+            if plan.edges[idx].feedback {
+                continue;
+            }
+            assigned[idx] = free.pop().unwrap_or_else(|| {
+                count += 1;
+                count - 1
+            });
+        }
+        // Freed after the node's own outputs are placed, so a node never
+        // writes into a buffer it is still reading.
+        for &(idx, _) in &plan.node_inputs[node.0] {
+            if !plan.edges[idx].feedback {
+                free.push(assigned[idx]);
+            }
+        }
+    }
+    (assigned, count)
+}
+
+/// Mark nodes whose output is silent whatever happens at runtime: every
+/// node that is not itself a source and whose main inputs are all either
+/// open or fed by such nodes.
+fn static_silence(graph: &Graph, order: &[NodeId]) -> Vec<bool> {
+    let mut silent = vec![false; graph.nodes.len()];
+    for &node in order {
+        let Some(data) = graph.nodes[node.0].as_ref() else {
+            continue;
+        };
+        let sidechain = data.node_type.sidechain_port();
+        let mut main_inputs = graph
+            .edges
+            .iter()
+            .filter(|e| e.to_node == node && Some(e.to_port) != sidechain);
+        silent[node.0] = match data.node_type {
+            // Sources, and nodes that can map silence to a non-zero value.
+            NodeType::SineOsc { .. }
+            | NodeType::Chirp { .. }
+            | NodeType::Wavetable { .. }
+            | NodeType::SawOsc { .. }
+            | NodeType::PulseOsc { .. }
+            | NodeType::TriOsc { .. }
+            | NodeType::DualTone { .. }
+            | NodeType::Noise { .. }
+            | NodeType::AudioInput { .. }
+            | NodeType::External { .. } => false,
+            NodeType::Waveshaper { .. } => main_inputs.next().is_none(),
+            // Emits its envelope when nothing is connected.
+            NodeType::Adsr { .. } => {
+                let mut inputs = main_inputs.peekable();
+                inputs.peek().is_some() && inputs.all(|e| e.muted || silent[e.from_node.0])
+            }
+            _ => main_inputs.all(|e| e.muted || silent[e.from_node.0]),
+        };
+    }
+    silent
+}
+
+/// Upstream and downstream node sets for every node, by graph search.
+fn reachability(graph: &Graph) -> (Vec<Vec<NodeId>>, Vec<Vec<NodeId>>) {
+    let n = graph.nodes.len();
+    let mut forward: Vec<Vec<usize>> = vec![vec![]; n];
+    let mut backward: Vec<Vec<usize>> = vec![vec![]; n];
+    for edge in &graph.edges {
+        forward[edge.from_node.0].push(edge.to_node.0);
+        backward[edge.to_node.0].push(edge.from_node.0);
+    }
+    let search = |adj: &[Vec<usize>], start: usize| -> Vec<NodeId> {
+        let mut seen = vec![false; n];
+        let mut stack = adj[start].clone();
+        while let Some(next) = stack.pop() {
+            if next != start && !seen[next] {
+                seen[next] = true;
+                stack.extend_from_slice(&adj[next]);
+            }
+        }
+        (0..n)
+            .filter(|&i| seen[i])
+            .filter_map(|i| graph.id_at(i))
+            .collect()
+    };
+    let ancestors = (0..n).map(|i| search(&backward, i)).collect();
+    let descendants = (0..n).map(|i| search(&forward, i)).collect();
+    (ancestors, descendants)
+}
+
+/// Group `order` by dependency depth: one past the deepest node feeding a
+/// non-feedback input, and at least as deep as the reader of every
+/// feedback edge the node writes, so that reader still hears the previous
+/// block.
+fn levels(
+    order: &[NodeId],
+    edges: &[EdgeSpec],
+    node_inputs: &[Vec<(usize, PortId)>],
+    node_outputs: &[Vec<(usize, PortId)>],
+) -> Vec<Vec<NodeId>> {
+    let mut depth = vec![0; node_inputs.len()];
+    let mut levels: Vec<Vec<NodeId>> = Vec::new();
+    for &node in order {
+        let fed = node_inputs[node.0]
+            .iter()
+            .map(|&(e, _)| &edges[e])
+            .filter(|e| !e.feedback)
+            .map(|e| depth[e.from_node.0] + 1);
+        // Feedback readers come earlier in `order`, so their depth is known.
+        let fed_back = node_outputs[node.0]
+            .iter()
+            .map(|&(e, _)| &edges[e])
+            .filter(|e| e.feedback && e.to_node != node)
+            .map(|e| depth[e.to_node.0]);
+        let d = fed.chain(fed_back).max().unwrap_or(0);
+        depth[node.0] = d;
+        if levels.len() <= d {
+            levels.resize_with(d + 1, Vec::new);
+        }
+        levels[d].push(node);
+    }
+    levels
+}
+
+/// For each edge, whether it feeds a `Delay` node that reaches the edge's
+/// source: the edge closes a loop through that delay. Every cycle the graph
+/// accepts contains at least one such edge.
+pub(crate) fn delay_loop_edges(graph: &Graph) -> Vec<bool> {
+    let mut successors = vec![Vec::new(); graph.nodes.len()];
+    for e in &graph.edges {
+        successors[e.from_node.0].push(e.to_node.0);
+    }
+    let reaches = |start: usize, target: usize| {
+        let mut seen = vec![false; successors.len()];
+        let mut stack = vec![start];
+        while let Some(current) = stack.pop() {
+            if current == target {
+                return true;
+            }
+            if !std::mem::replace(&mut seen[current], true) {
+                stack.extend_from_slice(&successors[current]);
+            }
+        }
+        false
+    };
+    graph
+        .edges
+        .iter()
+        .map(|e| {
+            matches!(
+                graph.nodes[e.to_node.0].as_ref().map(|n| &n.node_type),
+                Some(NodeType::Delay { .. })
+            ) && reaches(e.to_node.0, e.from_node.0)
+        })
+        .collect()
+}
+
+/// Groups of nodes that form cycles not broken by a `Delay`, each sorted by
+/// id. A group is one strongly connected component, so several loops
+/// sharing nodes are reported together.
+pub(crate) fn cycles(graph: &Graph) -> Vec<Vec<NodeId>> {
+    let n = graph.nodes.len();
+    let mut successors = vec![Vec::new(); n];
+    let loop_edges = delay_loop_edges(graph);
+    for (edge, _) in graph.edges.iter().zip(loop_edges).filter(|(_, l)| !l) {
+        successors[edge.from_node.0].push(edge.to_node.0);
+    }
+    let reach: Vec<Vec<bool>> = (0..n)
+        .map(|start| {
+            let mut seen = vec![false; n];
+            let mut stack = successors[start].clone();
+            while let Some(next) = stack.pop() {
+                if !std::mem::replace(&mut seen[next], true) {
+                    stack.extend_from_slice(&successors[next]);
+                }
+            }
+            seen
+        })
+        .collect();
+    let mut grouped = vec![false; n];
+    let mut groups = Vec::new();
+    for i in 0..n {
+        if grouped[i] || !reach[i][i] {
+            continue;
+        }
+        let group: Vec<NodeId> = (i..n)
+            .filter(|&j| reach[i][j] && reach[j][i])
+            .filter_map(|j| graph.id_at(j))
+            .collect();
+        for node in &group {
+            grouped[node.0] = true;
+        }
+        groups.push(group);
+    }
+    groups
 }
 
 /// Topological sort of nodes.
-fn topo_sort(graph: &Graph) -> Result<Vec<NodeId>, PlanError> {
+///
+/// Edges that close a loop through a `Delay` node are left out of the
+/// ordering, so such loops sort like any other graph.
+pub(crate) fn topo_sort(graph: &Graph) -> Result<Vec<NodeId>, PlanError> {
     let mut in_degree = vec![0; graph.nodes.len()];
     let mut adj: Vec<Vec<NodeId>> = vec![vec![]; graph.nodes.len()];
 
-    for edge in &graph.edges {
+    let loop_edges = delay_loop_edges(graph);
+    for (edge, _) in graph.edges.iter().zip(loop_edges).filter(|(_, l)| !l) {
         adj[edge.from_node.0].push(edge.to_node);
         in_degree[edge.to_node.0] += 1;
     }
 
     let mut queue = std::collections::VecDeque::new();
     for (i, &deg) in in_degree.iter().enumerate().take(graph.nodes.len()) {
-        if graph.nodes[i].is_some() && deg == 0 {
-            queue.push_back(NodeId(i));
+        if let (Some(node), 0) = (graph.id_at(i), deg) {
+            queue.push_back(node);
         }
     }
 
@@ -159,6 +988,7 @@ mod tests {
                 to_node: node2,
                 to_port: PortId(0),
                 rate: Rate::Audio,
+                muted: false,
             })
             .unwrap();
 
@@ -181,6 +1011,7 @@ mod tests {
                 to_node: node2,
                 to_port: PortId(0),
                 rate: Rate::Audio,
+                muted: false,
             })
             .unwrap();
 
@@ -190,6 +1021,36 @@ mod tests {
         assert_eq!(plan.edges[0].to_node, node2);
     }
 
+    #[test]
+    fn shared_plan_edge_buffer_index_distinct_for_live_edges() {
+        let mut graph = Graph::new();
+        let a = graph.add_node(NodeType::Dummy);
+        let b = graph.add_node(NodeType::Dummy);
+        let mix = graph.add_node(NodeType::Mix);
+        for (from, port) in [(a, 0), (b, 1)] {
+            graph
+                .add_edge(Edge {
+                    from_node: from,
+                    from_port: PortId(0),
+                    to_node: mix,
+                    to_port: PortId(port),
+                    rate: Rate::Audio,
+                    muted: false,
+                })
+                .unwrap();
+        }
+
+        let plan = Plan::compile(&graph, 64).unwrap();
+        assert_eq!(plan.edge_buffer_index(0), 0);
+        assert_eq!(plan.edge_buffer_index(1), 1);
+
+        let plan = Plan::compile_shared(&graph, 64).unwrap();
+        // Both mix inputs are read in the same step, so they must not alias.
+        assert_ne!(plan.edge_buffer_index(0), plan.edge_buffer_index(1));
+        assert!(plan.edge_buffer_index(0) < plan.buffer_count());
+        assert!(plan.edge_buffer_index(1) < plan.buffer_count());
+    }
+
     #[test]
     fn plan_debug_smoke_test() {
         let mut graph = Graph::new();
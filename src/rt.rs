@@ -5,163 +5,2855 @@
 #![forbid(unsafe_code)]
 // #![deny(missing_docs)]
 
-use crate::graph::{Graph, NodeType};
+pub mod format;
+pub mod parallel;
+pub mod router;
+
+use crate::control::{ControlMsg, LooperMode, QueuedControl, RtMessage};
+use crate::dsp::{
+    db_to_lin, lin_to_db, midi_to_hz, pulse_blep, saw_blep, table_lookup, tri_blamp,
+    wavetable_read, Adsr, BinauralPanner, Biquad, ColoredNoise, Crossover, DelayLine,
+    EnvelopeFollower, FreqShifter, LinearRamp, NoiseGate, Resampler, TapeSaturator,
+    TruePeakLimiter,
+};
+use crate::graph::{Graph, NodeId, NodeType, Port, Rate, CONTROL_RATE_DIVISOR};
+#[cfg(feature = "invariant-queue")]
+use crate::invariant_rt::signal_invariant;
+#[cfg(feature = "profiling")]
+use crate::invariant_rt::INV_RT_OVERLOAD;
+use crate::invariant_rt::{INV_CONTROL_MSG_PROCESSED, INV_NONFINITE_DETECTED};
+use crate::node::ProcessCtx;
 use crate::plan::Plan;
+use crate::snapshot::{NodeSnapshot, RuntimeSnapshot};
+use crate::transport::{NoteDivision, Transport};
+use crate::voice::{LogicalNode, VoiceAllocator, VoiceNodes, VoiceStealPolicy};
+use parallel::WorkerPool;
+use router::ChannelRouter;
+use rtrb::{Consumer, Producer};
+
+pub use crate::states::NodeState;
+
+/// Centre delay of the chorus taps, in milliseconds.
+const CHORUS_BASE_DELAY_MS: f32 = 15.0;
+
+/// Rest delay of the rotary horn, in milliseconds.
+const ROTARY_BASE_DELAY_MS: f32 = 1.0;
+
+/// Doppler delay swing of the rotary horn at full depth, in milliseconds
+/// (a 15 cm horn radius at the speed of sound).
+const ROTARY_EXCURSION_MS: f32 = 0.45;
+
+/// `temp_inputs` marker for a port fed by a muted edge: it reads silence.
+const MUTED_INPUT: usize = usize::MAX;
+
+/// Maximum number of input or output ports on an external node.
+pub const MAX_EXTERNAL_PORTS: usize = 16;
+
+/// Runtimes in flight each way between threads for plan swaps.
+pub const SWAP_QUEUE_CAPACITY: usize = 4;
+
+/// Timed control messages a runtime holds at once; further timed messages
+/// are applied at the start of the block instead.
+pub const MAX_PENDING_EVENTS: usize = 256;
+
+/// The runtime engine.
+#[derive(Debug)]
+pub struct Runtime {
+    pub plan: Plan,
+    sample_rate: f32,
+    nodes: Vec<Option<NodeType>>,
+    /// Per slot: the id of the node in it, to tell stale ids apart.
+    ids: Vec<Option<NodeId>>,
+    states: Vec<Option<NodeState>>,
+    muted: Vec<bool>,
+    soloed: Vec<bool>,
+    /// False for nodes silenced by the solo set.
+    audible: Vec<bool>,
+    /// Frozen nodes skip processing and keep their last output block.
+    frozen: Vec<bool>,
+    /// Bypassed nodes pass their first input to their first output.
+    bypassed: Vec<bool>,
+    /// (input ports, output ports) per node, cached so RT never builds port lists.
+    port_counts: Vec<(usize, usize)>,
+    /// (input rates, output rates) per node, by port.
+    port_rates: Vec<(Vec<Rate>, Vec<Rate>)>,
+    /// Sized for the longest rate among the edges sharing each buffer.
+    edge_buffers: Vec<Vec<f32>>,
+    /// Per node and output port: the edge buffer the port renders into.
+    /// Other edges on the port get a copy; `None` renders into
+    /// `temp_output_vecs` (no edges, or only edges the node also reads).
+    direct_outputs: Vec<Vec<Option<usize>>>,
+    silence: Vec<f32>,
+    temp_inputs: Vec<Option<(usize, usize)>>, // (buffer index, length) per input port
+    temp_output_vecs: Vec<Vec<f32>>,          // one per output port, block_size long
+    bypasses: Vec<SubgraphBypass>,
+    block_size_policy: BlockSizePolicy,
+    control_timing: ControlTiming,
+    partial_out: Vec<f32>,
+    /// The plan has an edge delivering the previous block, which a block
+    /// split at a timed message would shorten to the previous piece.
+    reads_previous_block: bool,
+    /// Timed control messages by sample offset into the next block, latest
+    /// first so the next one due pops off the end; equal offsets pop in
+    /// arrival order. Capacity `MAX_PENDING_EVENTS`.
+    pending_events: Vec<(usize, ControlMsg)>,
+    /// Ramp time for `SetGain` / `SetFrequency`, in ms; 0 applies instantly.
+    smoothing_ms: f32,
+    /// Per node: the ramp its smoothed parameter is following.
+    ramps: Vec<LinearRamp>,
+    /// Fade time for mute and solo changes, in samples; 0 switches at once.
+    mute_fade_samples: usize,
+    /// Per node: output gain, 1.0 audible and 0.0 silenced.
+    mute_fades: Vec<LinearRamp>,
+    /// Replacement runtimes from `RuntimeControl::swap_plan`.
+    swap_rx: Option<Consumer<Box<Runtime>>>,
+    /// Runtimes replaced by a swap, returned to the main thread to drop.
+    retired_tx: Option<Producer<Box<Runtime>>>,
+    /// Per node: take over the state of the runtime this one replaces.
+    pub(crate) carry_over: Vec<bool>,
+    /// Per node: the head of the gain chain it was fused into by
+    /// `Plan::compile_fused` (the head maps to itself).
+    fused_into: Vec<Option<NodeId>>,
+    #[cfg(feature = "invariant-queue")]
+    invariant_tx: Option<Producer<u8>>,
+    message_tx: Option<Producer<RtMessage>>,
+    /// Post a `RtMessage::Meter` after every block with channels.
+    metering: bool,
+    /// Replace non-finite node output with silence.
+    sanitize: bool,
+    /// `sanitize` replaced something during the current render.
+    nonfinite: bool,
+    /// Instanced voices, copied from the graph.
+    voices: Vec<VoiceNodes>,
+    /// Assigns `NoteOn` messages to `voices`.
+    voice_alloc: VoiceAllocator,
+    transport: Transport,
+    /// Blocks started so far; see `ProcessCtx::block_index`.
+    blocks: u64,
+    /// Per node: the note value a `Delay` follows, from `SetSyncedTime`.
+    synced_times: Vec<Option<NoteDivision>>,
+    /// Logical to device channels for `process_block_interleaved`.
+    router: ChannelRouter,
+    /// Threads rendering plan levels; set by `ParallelRuntime`.
+    workers: Option<WorkerPool>,
+    #[cfg(feature = "profiling")]
+    block_budget: Option<std::time::Duration>,
+    /// Per-node processing time in the current block, by `NodeId`, while
+    /// profiling with `set_node_profiling`.
+    #[cfg(feature = "profiling")]
+    node_times: Vec<std::time::Duration>,
+    /// Start of the node being timed; `Some` only while profiling.
+    #[cfg(feature = "profiling")]
+    profile_lap: Option<std::time::Instant>,
+    #[cfg(feature = "profiling")]
+    node_profiling: bool,
+}
+
+/// When `Runtime::process_block_with_channels` applies queued control messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ControlTiming {
+    /// Drain the queue before rendering: a message affects the block being
+    /// rendered.
+    #[default]
+    ThisBlock,
+    /// Drain the queue after rendering: every message takes effect exactly
+    /// one block after it was queued, independent of when in the callback
+    /// period it arrived.
+    NextBlock,
+}
+
+/// How `Runtime::process_block` handles an output buffer whose length
+/// differs from the plan's `block_size`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlockSizePolicy {
+    /// Reject any other length with an error.
+    #[default]
+    Strict,
+    /// Accept shorter buffers: a full block is rendered internally and only
+    /// the requested frames are copied out. The rest of that block is
+    /// discarded, so use this to ride out transient host buffer-size
+    /// changes rather than as a steady-state mode.
+    PadShort,
+}
+
+impl Runtime {
+    /// Create a new runtime from a plan and graph.
+    pub fn new(plan: Plan, graph: &Graph, sample_rate: f32) -> Self {
+        let graph = &*plan.scheduled_graph(graph);
+        let ids: Vec<Option<NodeId>> = graph
+            .nodes
+            .iter()
+            .map(|n| n.as_ref().map(|nd| nd.id))
+            .collect();
+        let nodes: Vec<Option<NodeType>> = graph
+            .nodes
+            .iter()
+            .map(|n| {
+                n.as_ref().map(|nd| {
+                    let mut node_type = nd.node_type.clone();
+                    for (name, value) in &nd.params {
+                        node_type.set_param(name, *value);
+                    }
+                    node_type
+                })
+            })
+            .collect();
+        let mut states: Vec<Option<NodeState>> = nodes
+            .iter()
+            .map(|nt| {
+                nt.as_ref().map(|nt| match nt {
+                    NodeType::SineOsc { .. } => NodeState::SineOsc { phase: 0.0 },
+                    NodeType::Chirp { .. } => NodeState::Chirp {
+                        phase: 0.0,
+                        elapsed: 0,
+                    },
+                    NodeType::DualTone { .. } => NodeState::DualTone { phases: [0.0; 2] },
+                    NodeType::Wavetable { .. } => NodeState::Wavetable { phase: 0.0 },
+                    NodeType::SawOsc { .. }
+                    | NodeType::PulseOsc { .. }
+                    | NodeType::TriOsc { .. } => NodeState::BlepOsc { phase: 0.0 },
+                    NodeType::AutoPan { .. } => NodeState::AutoPan { phase: 0.0 },
+                    NodeType::Rotary { .. } => NodeState::Rotary {
+                        line: DelayLine::new(
+                            (ROTARY_BASE_DELAY_MS + ROTARY_EXCURSION_MS) * sample_rate / 1000.0
+                                + 1.0,
+                        ),
+                        phase: 0.0,
+                    },
+                    NodeType::Binaural { azimuth } => NodeState::Binaural {
+                        panner: BinauralPanner::new(*azimuth, sample_rate),
+                    },
+                    NodeType::StereoWidth { .. } => NodeState::StereoWidth,
+                    NodeType::GatedGain => NodeState::GatedGain,
+                    NodeType::Gain { .. } => NodeState::Gain {
+                        fused: LinearRamp::new(1.0, 1.0, 0),
+                    },
+                    NodeType::Mix => NodeState::Mix,
+                    NodeType::OutputSink => NodeState::OutputSink { peak: 0.0 },
+                    NodeType::MultiOutputSink { channels } => NodeState::MultiOutputSink {
+                        channels: vec![vec![0.0; plan.block_size]; *channels],
+                    },
+                    NodeType::Dummy | NodeType::Subgraph { .. } => NodeState::Dummy,
+                    NodeType::AudioInput { .. } => NodeState::AudioInput {
+                        buffer: vec![0.0; plan.block_size],
+                    },
+                    NodeType::Looper { max_samples } => NodeState::Looper {
+                        buffer: vec![0.0; *max_samples],
+                        len: 0,
+                        pos: 0,
+                        mode: LooperMode::Stop,
+                    },
+                    NodeType::Delay { delay_samples } => NodeState::Delay {
+                        line: DelayLine::new(*delay_samples),
+                    },
+                    NodeType::Chorus { depth_ms, .. } => NodeState::Chorus {
+                        line: DelayLine::new(
+                            (CHORUS_BASE_DELAY_MS + depth_ms.abs()) * sample_rate / 1000.0 + 1.0,
+                        ),
+                        phase: 0.0,
+                    },
+                    NodeType::Compressor {
+                        attack_ms,
+                        release_ms,
+                        ..
+                    } => NodeState::Compressor {
+                        follower: EnvelopeFollower::new(*attack_ms, *release_ms, sample_rate),
+                    },
+                    NodeType::NoiseGate {
+                        threshold_db,
+                        attack_ms,
+                        hold_ms,
+                        release_ms,
+                    } => NodeState::NoiseGate {
+                        gate: NoiseGate::new(
+                            *threshold_db,
+                            *attack_ms,
+                            *hold_ms,
+                            *release_ms,
+                            sample_rate,
+                        ),
+                    },
+                    NodeType::TruePeakLimiter {
+                        ceiling_db,
+                        release_ms,
+                    } => NodeState::TruePeakLimiter {
+                        limiter: Box::new(TruePeakLimiter::new(
+                            *ceiling_db,
+                            *release_ms,
+                            sample_rate,
+                        )),
+                    },
+                    NodeType::Resample { ratio } => NodeState::Resample {
+                        resampler: Box::new(Resampler::new(*ratio)),
+                    },
+                    NodeType::Diff => NodeState::Diff { prev: 0.0 },
+                    NodeType::Noise { seed, .. } => NodeState::Noise {
+                        noise: ColoredNoise::new(*seed),
+                    },
+                    NodeType::Waveshaper { .. } => NodeState::Waveshaper,
+                    NodeType::PanBank { .. } => NodeState::PanBank,
+                    NodeType::AudioToControl => NodeState::AudioToControl,
+                    NodeType::ControlToAudio => NodeState::ControlToAudio { last: 0.0 },
+                    NodeType::Crossover { freq } => NodeState::Crossover {
+                        split: Crossover::new(*freq, sample_rate),
+                    },
+                    NodeType::FreqShift { shift_hz } => NodeState::FreqShift {
+                        shifter: FreqShifter::new(*shift_hz, sample_rate),
+                    },
+                    NodeType::TapeSat { drive, bias } => NodeState::TapeSat {
+                        saturator: TapeSaturator::new(*drive, *bias),
+                    },
+                    NodeType::Filter { cutoff, q } => NodeState::Filter {
+                        biquad: Biquad::resonant_lowpass(*cutoff, *q, sample_rate),
+                    },
+                    NodeType::Adsr {
+                        attack,
+                        decay,
+                        sustain,
+                        release,
+                    } => NodeState::Adsr {
+                        env: Adsr::new(*attack, *decay, *sustain, *release, sample_rate),
+                    },
+                    NodeType::External { def } => NodeState::External {
+                        state: def.init_state(sample_rate, plan.block_size),
+                        legacy_outputs: (0..def.output_ports().len().min(MAX_EXTERNAL_PORTS))
+                            .map(|_| Vec::with_capacity(plan.block_size))
+                            .collect(),
+                    },
+                })
+            })
+            .collect();
+        // Parameters a built-in node does not know go to external nodes.
+        for ((node_type, state), data) in nodes.iter().zip(&mut states).zip(&graph.nodes) {
+            if let (
+                Some(NodeType::External { def }),
+                Some(NodeState::External { state, .. }),
+                Some(data),
+            ) = (node_type, state, data)
+            {
+                for (name, value) in &data.params {
+                    def.set_param(state.as_mut(), name, *value);
+                }
+            }
+        }
+        let port_counts: Vec<(usize, usize)> = graph
+            .nodes
+            .iter()
+            .map(|n| {
+                n.as_ref()
+                    .map_or((0, 0), |nd| (nd.inputs.len(), nd.outputs.len()))
+            })
+            .collect();
+        let port_rates = graph
+            .nodes
+            .iter()
+            .map(|n| {
+                n.as_ref().map_or_else(Default::default, |nd| {
+                    let rates = |ports: &[Port]| ports.iter().map(|p| p.rate.clone()).collect();
+                    (rates(&nd.inputs), rates(&nd.outputs))
+                })
+            })
+            .collect();
+        let max_in_ports = port_counts.iter().map(|c| c.0).max().unwrap_or(0);
+        let max_out_ports = port_counts.iter().map(|c| c.1).max().unwrap_or(0);
+        let muted = vec![false; nodes.len()];
+        let soloed = vec![false; nodes.len()];
+        let audible = vec![true; nodes.len()];
+        let frozen = vec![false; nodes.len()];
+        let bypassed = vec![false; nodes.len()];
+        let ramps = vec![LinearRamp::default(); nodes.len()];
+        let mute_fades = vec![LinearRamp::new(1.0, 1.0, 0); nodes.len()];
+        let synced_times = vec![None; nodes.len()];
+        #[cfg(feature = "profiling")]
+        let node_times = vec![std::time::Duration::ZERO; nodes.len()];
+        let mut buffer_lens = vec![0; plan.buffer_count()];
+        for (idx, edge) in plan.edges.iter().enumerate() {
+            let len = &mut buffer_lens[plan.edge_buffer_index(idx)];
+            *len = (*len).max(edge.rate.buffer_len(plan.block_size));
+        }
+        let edge_buffers = buffer_lens.into_iter().map(|len| vec![0.0; len]).collect();
+        let direct_outputs = (0..nodes.len())
+            .map(|node| {
+                let mut direct = vec![None; port_counts[node].1];
+                for &(edge_idx, port) in &plan.node_outputs[node] {
+                    let buffer = plan.edge_buffer_index(edge_idx);
+                    // A self-feedback edge is read while the node renders.
+                    let read = plan.node_inputs[node]
+                        .iter()
+                        .any(|&(input, _)| plan.edge_buffer_index(input) == buffer);
+                    if let Some(slot @ None) = direct.get_mut(port.0) {
+                        if !read {
+                            *slot = Some(buffer);
+                        }
+                    }
+                }
+                direct
+            })
+            .collect();
+        let silence = vec![0.0; plan.block_size];
+        let partial_out = vec![0.0; plan.block_size];
+        let reads_previous_block = plan.edges.iter().any(|e| e.feedback);
+        let temp_inputs = vec![None; max_in_ports];
+        let temp_output_vecs = (0..max_out_ports)
+            .map(|_| vec![0.0; plan.block_size])
+            .collect();
+        let mut fused_into = vec![None; nodes.len()];
+        for (head, rest) in plan.fused_gains() {
+            for &node in std::iter::once(head).chain(rest) {
+                fused_into[node.0] = Some(*head);
+            }
+        }
+        let mut runtime = Self {
+            plan,
+            sample_rate,
+            nodes,
+            ids,
+            states,
+            muted,
+            soloed,
+            audible,
+            frozen,
+            bypassed,
+            port_counts,
+            port_rates,
+            edge_buffers,
+            direct_outputs,
+            silence,
+            temp_inputs,
+            temp_output_vecs,
+            bypasses: Vec::new(),
+            block_size_policy: BlockSizePolicy::Strict,
+            control_timing: ControlTiming::ThisBlock,
+            partial_out,
+            reads_previous_block,
+            pending_events: Vec::with_capacity(MAX_PENDING_EVENTS),
+            smoothing_ms: 0.0,
+            ramps,
+            mute_fade_samples: 0,
+            mute_fades,
+            swap_rx: None,
+            retired_tx: None,
+            carry_over: Vec::new(),
+            #[cfg(feature = "invariant-queue")]
+            invariant_tx: None,
+            message_tx: None,
+            metering: false,
+            sanitize: false,
+            nonfinite: false,
+            voices: graph.voices.clone(),
+            voice_alloc: VoiceAllocator::new(graph.voices.len()),
+            transport: Transport::default(),
+            blocks: 0,
+            synced_times,
+            router: ChannelRouter::identity(1),
+            workers: None,
+            #[cfg(feature = "profiling")]
+            block_budget: None,
+            #[cfg(feature = "profiling")]
+            node_times,
+            #[cfg(feature = "profiling")]
+            profile_lap: None,
+            #[cfg(feature = "profiling")]
+            node_profiling: false,
+            fused_into,
+        };
+        runtime.sync_fused();
+        runtime
+    }
+
+    /// Apply a control message immediately.
+    ///
+    /// Messages targeting missing nodes or nodes that do not understand them
+    /// are ignored; a stale id of a removed node cannot, say, solo nothing
+    /// or retune the node that took its slot. RT-safe: no allocation, no
+    /// locking.
+    pub fn apply_control(&mut self, msg: ControlMsg) {
+        if let Some(node) = msg.target_node() {
+            if !self.contains(node) {
+                return;
+            }
+        }
+        match msg {
+            ControlMsg::SetGain { node, gain: value } => {
+                if let Some(Some(NodeType::Gain { gain })) = self.nodes.get_mut(node.0) {
+                    let from = std::mem::replace(gain, value);
+                    match self.fused_into[node.0] {
+                        Some(head) if head != node => self.ramp_fused(head),
+                        _ => self.start_ramp(node, from, value),
+                    }
+                }
+            }
+            ControlMsg::SetFrequency { node, hz } => {
+                if let Some(Some(
+                    NodeType::SineOsc { freq }
+                    | NodeType::Wavetable { freq, .. }
+                    | NodeType::SawOsc { freq }
+                    | NodeType::PulseOsc { freq, .. }
+                    | NodeType::TriOsc { freq },
+                )) = self.nodes.get_mut(node.0)
+                {
+                    let from = std::mem::replace(freq, hz);
+                    self.start_ramp(node, from, hz);
+                }
+            }
+            ControlMsg::TriggerGate { node, on } => {
+                if let Some(Some(NodeState::Adsr { env })) = self.states.get_mut(node.0) {
+                    env.gate(on);
+                }
+            }
+            ControlMsg::SetFilterCutoff { node, hz } => {
+                if let (
+                    Some(Some(NodeType::Filter { cutoff, q })),
+                    Some(Some(NodeState::Filter { biquad })),
+                ) = (self.nodes.get_mut(node.0), self.states.get_mut(node.0))
+                {
+                    *cutoff = hz;
+                    biquad.retune_lowpass(*cutoff, *q, self.sample_rate);
+                }
+            }
+            ControlMsg::SetFilterResonance { node, q: value } => {
+                if let (
+                    Some(Some(NodeType::Filter { cutoff, q })),
+                    Some(Some(NodeState::Filter { biquad })),
+                ) = (self.nodes.get_mut(node.0), self.states.get_mut(node.0))
+                {
+                    *q = value;
+                    biquad.retune_lowpass(*cutoff, *q, self.sample_rate);
+                }
+            }
+            ControlMsg::SetParam {
+                node,
+                param_idx,
+                value,
+            } => match self.nodes.get_mut(node.0) {
+                Some(Some(NodeType::PanBank { pans })) => {
+                    if let Some(pan) = pans.get_mut(param_idx as usize) {
+                        *pan = value.clamp(-1.0, 1.0);
+                    }
+                }
+                Some(Some(NodeType::AutoPan { rate_hz, depth })) => match param_idx {
+                    0 => *rate_hz = value.max(0.0),
+                    1 => *depth = value.clamp(0.0, 1.0),
+                    _ => {}
+                },
+                Some(Some(NodeType::DualTone { freq_a, freq_b })) => match param_idx {
+                    0 => *freq_a = value,
+                    1 => *freq_b = value,
+                    _ => {}
+                },
+                Some(Some(NodeType::StereoWidth { width })) if param_idx == 0 => {
+                    *width = value.max(0.0);
+                }
+                Some(Some(NodeType::Rotary { speed_hz, depth })) => match param_idx {
+                    0 => *speed_hz = value.max(0.0),
+                    1 => *depth = value.clamp(0.0, 1.0),
+                    _ => {}
+                },
+                Some(Some(NodeType::Binaural { azimuth })) if param_idx == 0 => {
+                    *azimuth = value;
+                    if let Some(Some(NodeState::Binaural { panner })) = self.states.get_mut(node.0)
+                    {
+                        panner.set_azimuth(value, self.sample_rate);
+                    }
+                }
+                Some(Some(NodeType::External { def })) => {
+                    if let Some(Some(NodeState::External { state, .. })) =
+                        self.states.get_mut(node.0)
+                    {
+                        def.set_param_index(state.as_mut(), param_idx, value);
+                    }
+                }
+                _ => {}
+            },
+            ControlMsg::Mute { node } => {
+                if let Some(m) = self.muted.get_mut(node.0) {
+                    *m = true;
+                    self.fade_mutes(self.mute_fade_samples);
+                }
+            }
+            ControlMsg::Unmute { node } => {
+                if let Some(m) = self.muted.get_mut(node.0) {
+                    *m = false;
+                    self.fade_mutes(self.mute_fade_samples);
+                }
+            }
+            ControlMsg::Solo { node } => {
+                if let Some(s) = self.soloed.get_mut(node.0) {
+                    *s = true;
+                    self.update_solo();
+                    self.fade_mutes(self.mute_fade_samples);
+                }
+            }
+            ControlMsg::Unsolo { node } => {
+                if let Some(s) = self.soloed.get_mut(node.0) {
+                    *s = false;
+                    self.update_solo();
+                    self.fade_mutes(self.mute_fade_samples);
+                }
+            }
+            ControlMsg::FreezeNode { node, frozen } => {
+                if let Some(f) = self.frozen.get_mut(node.0) {
+                    *f = frozen;
+                }
+            }
+            ControlMsg::Bypass { node, enabled } => {
+                if let Some(b) = self.bypassed.get_mut(node.0) {
+                    *b = enabled;
+                }
+            }
+            ControlMsg::SetLooperMode {
+                node,
+                mode: new_mode,
+            } => {
+                if let Some(Some(NodeState::Looper { len, pos, mode, .. })) =
+                    self.states.get_mut(node.0)
+                {
+                    match new_mode {
+                        LooperMode::Record if *mode != LooperMode::Record => {
+                            *len = 0;
+                            *pos = 0;
+                        }
+                        LooperMode::Play | LooperMode::Overdub if *mode == LooperMode::Record => {
+                            *pos = 0;
+                        }
+                        _ => {}
+                    }
+                    *mode = new_mode;
+                }
+            }
+            ControlMsg::NoteOn { note, velocity } => {
+                if self.voices.is_empty() {
+                    return;
+                }
+                let voice = self.voice_alloc.note_on(note);
+                let nodes = self.voices[voice];
+                self.tune_voice(nodes, midi_to_hz(note));
+                if let Some(Some(NodeState::Adsr { env })) =
+                    self.states.get_mut(nodes[LogicalNode::AmpEnv as usize].0)
+                {
+                    env.gate_velocity(velocity);
+                }
+            }
+            ControlMsg::NoteOff { note } => {
+                if self.voices.is_empty() {
+                    return;
+                }
+                if let Some(voice) = self.voice_alloc.note_off(note) {
+                    let amp = self.voices[voice][LogicalNode::AmpEnv as usize];
+                    if let Some(Some(NodeState::Adsr { env })) = self.states.get_mut(amp.0) {
+                        env.gate(false);
+                    }
+                }
+            }
+            ControlMsg::AllNotesOff => {
+                for state in self.states.iter_mut().flatten() {
+                    if let NodeState::Adsr { env } = state {
+                        env.gate(false);
+                    }
+                }
+                self.voice_alloc.release_all();
+            }
+            ControlMsg::SetSyncedTime { node, division } => {
+                if let Some(synced) = self.synced_times.get_mut(node.0) {
+                    *synced = Some(division);
+                    self.sync_time(node.0);
+                }
+            }
+            ControlMsg::SetTempo { bpm } => {
+                self.transport.set_tempo(bpm);
+                for idx in 0..self.synced_times.len() {
+                    self.sync_time(idx);
+                }
+            }
+            ControlMsg::Start => self.transport.start(),
+            ControlMsg::Stop => self.transport.stop(),
+            _ => {}
+        }
+    }
+
+    /// Jump a voice's oscillator to `hz`, without smoothing. A `Filter` in
+    /// the voice follows, keeping its cutoff-to-pitch ratio.
+    fn tune_voice(&mut self, nodes: VoiceNodes, hz: f32) {
+        let osc = nodes[LogicalNode::Oscillator as usize];
+        let Some(Some(
+            NodeType::SineOsc { freq }
+            | NodeType::Wavetable { freq, .. }
+            | NodeType::SawOsc { freq }
+            | NodeType::PulseOsc { freq, .. }
+            | NodeType::TriOsc { freq },
+        )) = self.nodes.get_mut(osc.0)
+        else {
+            return;
+        };
+        let ratio = hz / *freq;
+        *freq = hz;
+        self.ramps[osc.0] = LinearRamp::default();
+        let filter = nodes[LogicalNode::Filter as usize];
+        if let (
+            Some(Some(NodeType::Filter { cutoff, q })),
+            Some(Some(NodeState::Filter { biquad })),
+        ) = (self.nodes.get_mut(filter.0), self.states.get_mut(filter.0))
+        {
+            if ratio.is_finite() && ratio > 0.0 {
+                *cutoff = (*cutoff * ratio).min(self.sample_rate * 0.45);
+                biquad.retune_lowpass(*cutoff, *q, self.sample_rate);
+            }
+        }
+    }
+
+    /// Glide `node`'s smoothed parameter from `from` (or wherever a running
+    /// ramp has got to) to `to` over the smoothing time.
+    fn start_ramp(&mut self, node: NodeId, from: f32, to: f32) {
+        let samples = (self.smoothing_ms * self.sample_rate / 1000.0).round() as usize;
+        let ramp = &mut self.ramps[node.0];
+        let from = if ramp.is_ramping() {
+            ramp.value()
+        } else {
+            from
+        };
+        *ramp = LinearRamp::new(from, to, samples);
+    }
+
+    /// Product of the gains fused into `head`, not counting its own.
+    fn fused_gain(&self, head: NodeId) -> f32 {
+        let Some((_, rest)) = self.plan.fused_gains().iter().find(|(h, _)| *h == head) else {
+            return 1.0;
+        };
+        chain_gain(&self.nodes, rest)
+    }
+
+    /// Ramp `head` to the current product of the gains fused into it.
+    fn ramp_fused(&mut self, head: NodeId) {
+        let target = self.fused_gain(head);
+        let samples = (self.smoothing_ms * self.sample_rate / 1000.0).round() as usize;
+        if let Some(Some(NodeState::Gain { fused })) = self.states.get_mut(head.0) {
+            *fused = LinearRamp::new(fused.value(), target, samples);
+        }
+    }
+
+    /// Set every gain's fused factor from the current gains at once, e.g.
+    /// after states were carried over from a runtime fused differently.
+    fn sync_fused(&mut self) {
+        for state in self.states.iter_mut().flatten() {
+            if let NodeState::Gain { fused } = state {
+                *fused = LinearRamp::new(1.0, 1.0, 0);
+            }
+        }
+        for (head, rest) in self.plan.fused_gains() {
+            let target = chain_gain(&self.nodes, rest);
+            if let Some(Some(NodeState::Gain { fused })) = self.states.get_mut(head.0) {
+                *fused = LinearRamp::new(target, target, 0);
+            }
+        }
+    }
+
+    /// Recompute which nodes stay audible under the current solo set: the
+    /// soloed nodes plus everything upstream or downstream of them.
+    fn update_solo(&mut self) {
+        let solo_active = self.soloed.iter().any(|&s| s);
+        self.audible.fill(!solo_active);
+        for (idx, &soloed) in self.soloed.iter().enumerate() {
+            if let (true, Some(node)) = (soloed, self.ids[idx]) {
+                self.audible[idx] = true;
+                for &n in self.plan.ancestors(node) {
+                    self.audible[n.0] = true;
+                }
+                for &n in self.plan.descendants(node) {
+                    self.audible[n.0] = true;
+                }
+            }
+        }
+    }
+
+    /// Set a tempo-synced `Delay` to its note value at the current tempo.
+    fn sync_time(&mut self, idx: usize) {
+        if let (
+            Some(division),
+            Some(Some(NodeType::Delay { delay_samples })),
+            Some(Some(NodeState::Delay { line })),
+        ) = (
+            self.synced_times[idx],
+            self.nodes.get_mut(idx),
+            self.states.get(idx),
+        ) {
+            let samples = division.samples(&self.transport, self.sample_rate);
+            *delay_samples = samples.min(line.max_delay());
+        }
+    }
+
+    /// Fade every node whose mute or solo state changed towards it over
+    /// `samples` samples, starting from wherever its fade has got to.
+    fn fade_mutes(&mut self, samples: usize) {
+        for (node, fade) in self.mute_fades.iter_mut().enumerate() {
+            let target = if self.muted[node] || !self.audible[node] {
+                0.0
+            } else {
+                1.0
+            };
+            if fade.target() != target {
+                *fade = LinearRamp::new(fade.value(), target, samples);
+            }
+        }
+    }
+
+    /// Capture parameters, flags and node state for later restore.
+    ///
+    /// Allocates; call from a non-RT thread or between renders.
+    pub fn snapshot(&self) -> RuntimeSnapshot {
+        let nodes = self
+            .nodes
+            .iter()
+            .zip(&self.states)
+            .map(|(node_type, state)| {
+                Some(match (node_type.as_ref()?, state.as_ref()?) {
+                    (NodeType::SineOsc { freq }, NodeState::SineOsc { phase }) => {
+                        NodeSnapshot::SineOsc {
+                            freq: *freq,
+                            phase: *phase,
+                        }
+                    }
+                    (_, NodeState::Chirp { phase, elapsed }) => NodeSnapshot::Chirp {
+                        phase: *phase,
+                        elapsed: *elapsed,
+                    },
+                    (NodeType::DualTone { freq_a, freq_b }, NodeState::DualTone { phases }) => {
+                        NodeSnapshot::DualTone {
+                            freq_a: *freq_a,
+                            freq_b: *freq_b,
+                            phases: *phases,
+                        }
+                    }
+                    (NodeType::Wavetable { freq, .. }, NodeState::Wavetable { phase }) => {
+                        NodeSnapshot::Wavetable {
+                            freq: *freq,
+                            phase: *phase,
+                        }
+                    }
+                    (
+                        NodeType::SawOsc { freq } | NodeType::TriOsc { freq },
+                        NodeState::BlepOsc { phase },
+                    ) => NodeSnapshot::BlepOsc {
+                        freq: *freq,
+                        width: None,
+                        phase: *phase,
+                    },
+                    (NodeType::PulseOsc { freq, width }, NodeState::BlepOsc { phase }) => {
+                        NodeSnapshot::BlepOsc {
+                            freq: *freq,
+                            width: Some(*width),
+                            phase: *phase,
+                        }
+                    }
+                    (_, NodeState::ControlToAudio { last }) => {
+                        NodeSnapshot::ControlToAudio { last: *last }
+                    }
+                    (NodeType::Gain { gain }, _) => NodeSnapshot::Gain { gain: *gain },
+                    (NodeType::PanBank { pans }, _) => NodeSnapshot::PanBank { pans: pans.clone() },
+                    (NodeType::StereoWidth { width }, _) => {
+                        NodeSnapshot::StereoWidth { width: *width }
+                    }
+                    (NodeType::Rotary { speed_hz, depth }, NodeState::Rotary { line, phase }) => {
+                        NodeSnapshot::Rotary {
+                            speed_hz: *speed_hz,
+                            depth: *depth,
+                            line: line.clone(),
+                            phase: *phase,
+                        }
+                    }
+                    (NodeType::Binaural { azimuth }, NodeState::Binaural { panner }) => {
+                        NodeSnapshot::Binaural {
+                            azimuth: *azimuth,
+                            panner: panner.clone(),
+                        }
+                    }
+                    (NodeType::AutoPan { rate_hz, depth }, NodeState::AutoPan { phase }) => {
+                        NodeSnapshot::AutoPan {
+                            rate_hz: *rate_hz,
+                            depth: *depth,
+                            phase: *phase,
+                        }
+                    }
+                    (
+                        _,
+                        NodeState::Looper {
+                            buffer,
+                            len,
+                            pos,
+                            mode,
+                        },
+                    ) => NodeSnapshot::Looper {
+                        buffer: buffer.clone(),
+                        len: *len,
+                        pos: *pos,
+                        mode: *mode,
+                    },
+                    (_, NodeState::Delay { line }) => NodeSnapshot::Delay { line: line.clone() },
+                    (_, NodeState::Chorus { line, phase }) => NodeSnapshot::Chorus {
+                        line: line.clone(),
+                        phase: *phase,
+                    },
+                    (_, NodeState::Compressor { follower }) => NodeSnapshot::Compressor {
+                        follower: follower.clone(),
+                    },
+                    (_, NodeState::NoiseGate { gate }) => {
+                        NodeSnapshot::NoiseGate { gate: gate.clone() }
+                    }
+                    (_, NodeState::TruePeakLimiter { limiter }) => NodeSnapshot::TruePeakLimiter {
+                        limiter: limiter.clone(),
+                    },
+                    (_, NodeState::Resample { resampler }) => NodeSnapshot::Resample {
+                        resampler: resampler.clone(),
+                    },
+                    (_, NodeState::Diff { prev }) => NodeSnapshot::Diff { prev: *prev },
+                    (_, NodeState::Noise { noise }) => NodeSnapshot::Noise {
+                        noise: noise.clone(),
+                    },
+                    (_, NodeState::Crossover { split }) => NodeSnapshot::Crossover {
+                        split: split.clone(),
+                    },
+                    (_, NodeState::FreqShift { shifter }) => NodeSnapshot::FreqShift {
+                        shifter: shifter.clone(),
+                    },
+                    (_, NodeState::TapeSat { saturator }) => NodeSnapshot::TapeSat {
+                        saturator: saturator.clone(),
+                    },
+                    (NodeType::Filter { cutoff, q }, NodeState::Filter { biquad }) => {
+                        NodeSnapshot::Filter {
+                            cutoff: *cutoff,
+                            q: *q,
+                            biquad: biquad.clone(),
+                        }
+                    }
+                    (_, NodeState::Adsr { env }) => NodeSnapshot::Adsr { env: env.clone() },
+                    (NodeType::External { def }, NodeState::External { state, .. }) => {
+                        NodeSnapshot::External {
+                            state: def.serialize_state(state.as_ref()),
+                        }
+                    }
+                    _ => NodeSnapshot::Stateless,
+                })
+            })
+            .collect();
+        RuntimeSnapshot {
+            sample_rate: self.sample_rate,
+            block_size: self.plan.block_size,
+            muted: self.muted.clone(),
+            soloed: self.soloed.clone(),
+            frozen: self.frozen.clone(),
+            bypassed: self.bypassed.clone(),
+            tempo: Some(self.transport.tempo()),
+            synced_times: self.synced_times.clone(),
+            edge_buffers: self.edge_buffers.clone(),
+            nodes,
+        }
+    }
+
+    /// Build a runtime for `graph` and restore `snapshot` into it.
+    ///
+    /// `graph` and `plan` must be the ones the snapshot was taken from;
+    /// a snapshot whose shape does not match is rejected. External nodes
+    /// without serialized state start fresh.
+    pub fn from_snapshot(
+        plan: Plan,
+        graph: &Graph,
+        snapshot: &RuntimeSnapshot,
+    ) -> Result<Self, &'static str> {
+        const MISMATCH: &str = "snapshot does not match graph";
+        if plan.block_size != snapshot.block_size {
+            return Err("snapshot block size does not match plan");
+        }
+        let mut runtime = Self::new(plan, graph, snapshot.sample_rate);
+        let n = runtime.nodes.len();
+        if snapshot.nodes.len() != n
+            || snapshot.muted.len() != n
+            || snapshot.soloed.len() != n
+            || snapshot.frozen.len() != n
+            // Missing from snapshots taken before bypass existed.
+            || (!snapshot.bypassed.is_empty() && snapshot.bypassed.len() != n)
+            || (!snapshot.synced_times.is_empty() && snapshot.synced_times.len() != n)
+            || snapshot.edge_buffers.len() != runtime.edge_buffers.len()
+            || snapshot
+                .edge_buffers
+                .iter()
+                .zip(&runtime.edge_buffers)
+                .any(|(saved, buffer)| saved.len() != buffer.len())
+        {
+            return Err(MISMATCH);
+        }
+        runtime.muted.copy_from_slice(&snapshot.muted);
+        runtime.soloed.copy_from_slice(&snapshot.soloed);
+        runtime.frozen.copy_from_slice(&snapshot.frozen);
+        if !snapshot.bypassed.is_empty() {
+            runtime.bypassed.copy_from_slice(&snapshot.bypassed);
+        }
+        runtime.update_solo();
+        runtime.fade_mutes(0);
+        runtime.sync_fused();
+        runtime.edge_buffers.clone_from(&snapshot.edge_buffers);
+
+        for ((node_type, state), saved) in runtime
+            .nodes
+            .iter_mut()
+            .zip(runtime.states.iter_mut())
+            .zip(&snapshot.nodes)
+        {
+            let (Some(node_type), Some(state), Some(saved)) =
+                (node_type.as_mut(), state.as_mut(), saved.as_ref())
+            else {
+                if node_type.is_some() != saved.is_some() {
+                    return Err(MISMATCH);
+                }
+                continue;
+            };
+            match (node_type, state, saved) {
+                (
+                    NodeType::SineOsc { freq },
+                    NodeState::SineOsc { phase },
+                    NodeSnapshot::SineOsc {
+                        freq: saved_freq,
+                        phase: saved_phase,
+                    },
+                ) => {
+                    *freq = *saved_freq;
+                    *phase = *saved_phase;
+                }
+                (
+                    _,
+                    NodeState::Chirp { phase, elapsed },
+                    NodeSnapshot::Chirp {
+                        phase: saved_phase,
+                        elapsed: saved_elapsed,
+                    },
+                ) => {
+                    *phase = *saved_phase;
+                    *elapsed = *saved_elapsed;
+                }
+                (
+                    NodeType::DualTone { freq_a, freq_b },
+                    NodeState::DualTone { phases },
+                    NodeSnapshot::DualTone {
+                        freq_a: saved_a,
+                        freq_b: saved_b,
+                        phases: saved_phases,
+                    },
+                ) => {
+                    *freq_a = *saved_a;
+                    *freq_b = *saved_b;
+                    *phases = *saved_phases;
+                }
+                (
+                    NodeType::Wavetable { freq, .. },
+                    NodeState::Wavetable { phase },
+                    NodeSnapshot::Wavetable {
+                        freq: saved_freq,
+                        phase: saved_phase,
+                    },
+                ) => {
+                    *freq = *saved_freq;
+                    *phase = *saved_phase;
+                }
+                (
+                    NodeType::SawOsc { freq } | NodeType::TriOsc { freq },
+                    NodeState::BlepOsc { phase },
+                    NodeSnapshot::BlepOsc {
+                        freq: saved_freq,
+                        phase: saved_phase,
+                        ..
+                    },
+                ) => {
+                    *freq = *saved_freq;
+                    *phase = *saved_phase;
+                }
+                (
+                    NodeType::PulseOsc { freq, width },
+                    NodeState::BlepOsc { phase },
+                    NodeSnapshot::BlepOsc {
+                        freq: saved_freq,
+                        width: saved_width,
+                        phase: saved_phase,
+                    },
+                ) => {
+                    *freq = *saved_freq;
+                    if let Some(saved_width) = saved_width {
+                        *width = *saved_width;
+                    }
+                    *phase = *saved_phase;
+                }
+                (
+                    _,
+                    NodeState::ControlToAudio { last },
+                    NodeSnapshot::ControlToAudio { last: saved },
+                ) => {
+                    *last = *saved;
+                }
+                (NodeType::Gain { gain }, _, NodeSnapshot::Gain { gain: saved }) => {
+                    *gain = *saved;
+                }
+                (
+                    NodeType::Rotary { speed_hz, depth },
+                    NodeState::Rotary { line, phase },
+                    NodeSnapshot::Rotary {
+                        speed_hz: saved_speed,
+                        depth: saved_depth,
+                        line: saved_line,
+                        phase: saved_phase,
+                    },
+                ) => {
+                    if !line.restore(saved_line) {
+                        return Err(MISMATCH);
+                    }
+                    *speed_hz = *saved_speed;
+                    *depth = *saved_depth;
+                    *phase = *saved_phase;
+                }
+                (
+                    NodeType::Binaural { azimuth },
+                    NodeState::Binaural { panner },
+                    NodeSnapshot::Binaural {
+                        azimuth: saved_azimuth,
+                        panner: saved,
+                    },
+                ) => {
+                    *azimuth = *saved_azimuth;
+                    *panner = saved.clone();
+                }
+                (
+                    NodeType::AutoPan { rate_hz, depth },
+                    NodeState::AutoPan { phase },
+                    NodeSnapshot::AutoPan {
+                        rate_hz: saved_rate,
+                        depth: saved_depth,
+                        phase: saved_phase,
+                    },
+                ) => {
+                    *rate_hz = *saved_rate;
+                    *depth = *saved_depth;
+                    *phase = *saved_phase;
+                }
+                (
+                    NodeType::StereoWidth { width },
+                    _,
+                    NodeSnapshot::StereoWidth { width: saved },
+                ) => {
+                    *width = *saved;
+                }
+                (NodeType::PanBank { pans }, _, NodeSnapshot::PanBank { pans: saved }) => {
+                    if saved.len() != pans.len() {
+                        return Err(MISMATCH);
+                    }
+                    pans.copy_from_slice(saved);
+                }
+                (
+                    _,
+                    NodeState::Looper {
+                        buffer,
+                        len,
+                        pos,
+                        mode,
+                    },
+                    NodeSnapshot::Looper {
+                        buffer: saved_buffer,
+                        len: saved_len,
+                        pos: saved_pos,
+                        mode: saved_mode,
+                    },
+                ) => {
+                    // A loop never outgrows the buffer, and playback stays
+                    // inside the loop.
+                    if saved_buffer.len() != buffer.len()
+                        || *saved_len > buffer.len()
+                        || (*saved_pos > 0 && *saved_pos >= *saved_len)
+                    {
+                        return Err(MISMATCH);
+                    }
+                    buffer.copy_from_slice(saved_buffer);
+                    *len = *saved_len;
+                    *pos = *saved_pos;
+                    *mode = *saved_mode;
+                }
+                (_, NodeState::Delay { line }, NodeSnapshot::Delay { line: saved }) => {
+                    if !line.restore(saved) {
+                        return Err(MISMATCH);
+                    }
+                }
+                (
+                    _,
+                    NodeState::Chorus { line, phase },
+                    NodeSnapshot::Chorus {
+                        line: saved_line,
+                        phase: saved_phase,
+                    },
+                ) => {
+                    if !line.restore(saved_line) {
+                        return Err(MISMATCH);
+                    }
+                    *phase = *saved_phase;
+                }
+                (
+                    _,
+                    NodeState::Compressor { follower },
+                    NodeSnapshot::Compressor { follower: saved },
+                ) => {
+                    *follower = saved.clone();
+                }
+                (_, NodeState::NoiseGate { gate }, NodeSnapshot::NoiseGate { gate: saved }) => {
+                    *gate = saved.clone();
+                }
+                (
+                    _,
+                    NodeState::TruePeakLimiter { limiter },
+                    NodeSnapshot::TruePeakLimiter { limiter: saved },
+                ) => {
+                    *limiter = saved.clone();
+                }
+                (
+                    _,
+                    NodeState::Resample { resampler },
+                    NodeSnapshot::Resample { resampler: saved },
+                ) => {
+                    *resampler = saved.clone();
+                }
+                (_, NodeState::Diff { prev }, NodeSnapshot::Diff { prev: saved }) => {
+                    *prev = *saved;
+                }
+                (_, NodeState::Noise { noise }, NodeSnapshot::Noise { noise: saved }) => {
+                    *noise = saved.clone();
+                }
+                (_, NodeState::Crossover { split }, NodeSnapshot::Crossover { split: saved }) => {
+                    *split = saved.clone();
+                }
+                (
+                    _,
+                    NodeState::FreqShift { shifter },
+                    NodeSnapshot::FreqShift { shifter: saved },
+                ) => {
+                    *shifter = saved.clone();
+                }
+                (
+                    _,
+                    NodeState::TapeSat { saturator },
+                    NodeSnapshot::TapeSat { saturator: saved },
+                ) => {
+                    *saturator = saved.clone();
+                }
+                (
+                    NodeType::Filter { cutoff, q },
+                    NodeState::Filter { biquad },
+                    NodeSnapshot::Filter {
+                        cutoff: saved_cutoff,
+                        q: saved_q,
+                        biquad: saved,
+                    },
+                ) => {
+                    *cutoff = *saved_cutoff;
+                    *q = *saved_q;
+                    *biquad = saved.clone();
+                }
+                (_, NodeState::Adsr { env }, NodeSnapshot::Adsr { env: saved }) => {
+                    *env = saved.clone();
+                }
+                (
+                    NodeType::External { def },
+                    NodeState::External { state, .. },
+                    NodeSnapshot::External { state: saved },
+                ) => {
+                    if let Some(bytes) = saved {
+                        def.deserialize_state(state.as_mut(), bytes)?;
+                    }
+                }
+                (_, _, NodeSnapshot::Stateless) => {}
+                _ => return Err(MISMATCH),
+            }
+        }
+        // Synced delays follow the restored tempo, as after `SetTempo`.
+        if let Some(tempo) = snapshot.tempo {
+            runtime.transport.set_tempo(tempo);
+        }
+        if !snapshot.synced_times.is_empty() {
+            runtime.synced_times.copy_from_slice(&snapshot.synced_times);
+        }
+        for idx in 0..n {
+            runtime.sync_time(idx);
+        }
+        Ok(runtime)
+    }
+
+    /// The musical clock, advanced by every frame rendered while playing.
+    pub fn transport(&self) -> &Transport {
+        &self.transport
+    }
+
+    /// The allocator assigning `ControlMsg::NoteOn` to instanced voices.
+    pub fn voice_allocator(&self) -> &VoiceAllocator {
+        &self.voice_alloc
+    }
+
+    /// Choose which voice a `NoteOn` steals when every voice is busy. The
+    /// default steals the oldest.
+    pub fn set_voice_steal_policy(&mut self, policy: VoiceStealPolicy) {
+        self.voice_alloc.set_policy(policy);
+    }
+
+    /// The node playing `logical`'s role in instanced voice `voice`, e.g.
+    /// to aim `ControlMsg::SetFrequency` at a voice picked by the voice
+    /// allocator. `None` if the graph has no such voice.
+    pub fn voice_node(&self, voice: usize, logical: LogicalNode) -> Option<NodeId> {
+        self.voices.get(voice).map(|nodes| nodes[logical as usize])
+    }
+
+    /// Sample rate the runtime was built for, in Hz.
+    pub fn sample_rate(&self) -> f32 {
+        self.sample_rate
+    }
+
+    /// Supply the samples an `AudioInput` node plays from the next block on.
+    ///
+    /// `data` may be shorter than the block size; the rest is zero. RT-safe.
+    pub fn set_input(&mut self, node: NodeId, data: &[f32]) -> Result<(), &'static str> {
+        if !self.contains(node) {
+            return Err("node is not an audio input");
+        }
+        match self.states.get_mut(node.0) {
+            Some(Some(NodeState::AudioInput { buffer })) => {
+                if data.len() > buffer.len() {
+                    return Err("input longer than block size");
+                }
+                buffer[..data.len()].copy_from_slice(data);
+                buffer[data.len()..].fill(0.0);
+                Ok(())
+            }
+            _ => Err("node is not an audio input"),
+        }
+    }
+
+    /// [`Runtime::set_input`] for the `AudioInput` labelled `name`.
+    pub fn set_input_named(&mut self, name: &str, data: &[f32]) -> Result<(), &'static str> {
+        let node = self
+            .nodes
+            .iter()
+            .position(|n| matches!(n, Some(NodeType::AudioInput { label, .. }) if label == name))
+            .and_then(|idx| self.ids[idx])
+            .ok_or("no audio input with that label")?;
+        self.set_input(node, data)
+    }
+
+    /// Send RT invariant signals (see `invariant_rt`) to `tx`.
+    ///
+    /// Superseded by [`Runtime::set_message_queue`], which carries the same
+    /// IDs interleaved with the runtime's other messages; both may be set.
+    #[cfg(feature = "invariant-queue")]
+    pub fn set_invariant_queue(&mut self, tx: Producer<u8>) {
+        self.invariant_tx = Some(tx);
+    }
+
+    /// Send every RT → main message (invariant signals, meter readings,
+    /// parameter acknowledgements) to `tx`, in the order they occur.
+    pub fn set_message_queue(&mut self, tx: Producer<RtMessage>) {
+        self.message_tx = Some(tx);
+    }
+
+    /// Post a [`RtMessage::Meter`] with the block's peak after every
+    /// [`Runtime::process_block_with_channels`] call.
+    pub fn set_metering(&mut self, enabled: bool) {
+        self.metering = enabled;
+    }
+
+    /// Replace NaN and infinite samples in every node's output, and in the
+    /// rendered output, with 0.0, so one misbehaving node cannot poison the
+    /// rest of the graph. Each render that replaced anything signals
+    /// `INV_NONFINITE_DETECTED` once. Off by default: it scans every buffer
+    /// a node writes.
+    pub fn set_sanitize(&mut self, enabled: bool) {
+        self.sanitize = enabled;
+    }
+
+    /// Signal `INV_RT_OVERLOAD` whenever a block takes longer than `budget`.
+    /// `None` disables the check.
+    #[cfg(feature = "profiling")]
+    pub fn set_block_budget(&mut self, budget: Option<std::time::Duration>) {
+        self.block_budget = budget;
+    }
+
+    /// Time every node while rendering. After each block the runtime posts
+    /// an [`RtMessage::BlockProfile`] naming the slowest node, and
+    /// [`Runtime::node_times`] holds the full breakdown. Off by default:
+    /// it reads the clock once per node.
+    #[cfg(feature = "profiling")]
+    pub fn set_node_profiling(&mut self, enabled: bool) {
+        self.node_profiling = enabled;
+        self.node_times.fill(std::time::Duration::ZERO);
+    }
+
+    /// Processing time of each node, by `NodeId`, in the last block
+    /// rendered with node profiling on.
+    #[cfg(feature = "profiling")]
+    pub fn node_times(&self) -> &[std::time::Duration] {
+        &self.node_times
+    }
+
+    /// Charge the time since the last lap to `node` and start a new lap.
+    #[cfg(feature = "profiling")]
+    fn profile_lap(&mut self, node: Option<NodeId>) {
+        if let Some(lap) = self.profile_lap.as_mut() {
+            let now = std::time::Instant::now();
+            if let Some(node) = node {
+                self.node_times[node.0] += now - *lap;
+            }
+            *lap = now;
+        }
+    }
+
+    /// Post the profile of a finished block.
+    #[cfg(feature = "profiling")]
+    fn post_block_profile(&mut self) {
+        let Some((&slowest, &slowest_time)) = self
+            .ids
+            .iter()
+            .zip(&self.node_times)
+            .filter_map(|(id, time)| Some((id.as_ref()?, time)))
+            .max_by_key(|&(_, time)| *time)
+        else {
+            return;
+        };
+        let total = self.node_times.iter().sum();
+        self.post(RtMessage::BlockProfile {
+            total,
+            slowest,
+            slowest_time,
+        });
+    }
+
+    fn signal(&mut self, id: u8) {
+        #[cfg(feature = "invariant-queue")]
+        if let Some(tx) = self.invariant_tx.as_mut() {
+            signal_invariant(tx, id);
+        }
+        self.post(RtMessage::Invariant(id));
+    }
+
+    /// Push to the message queue, dropping the message if it is full.
+    fn post(&mut self, msg: RtMessage) {
+        if let Some(tx) = self.message_tx.as_mut() {
+            let _ = tx.push(msg);
+        }
+    }
+
+    /// Ramp `SetGain` and `SetFrequency` changes linearly over `ms`
+    /// milliseconds instead of applying them at once. 0 (the default)
+    /// disables smoothing. Takes effect from the next message.
+    pub fn set_param_smoothing(&mut self, ms: f32) {
+        self.smoothing_ms = ms.max(0.0);
+    }
+
+    /// Fade a node's output over `samples` samples when `Mute`, `Unmute`,
+    /// `Solo` or `Unsolo` changes whether it is heard, instead of switching
+    /// mid-block. 0 (the default) switches at once. Takes effect from the
+    /// next message.
+    pub fn set_mute_fade(&mut self, samples: usize) {
+        self.mute_fade_samples = samples;
+    }
+
+    /// Choose how `process_block` treats buffers that are not `block_size` long.
+    pub fn set_block_size_policy(&mut self, policy: BlockSizePolicy) {
+        self.block_size_policy = policy;
+    }
+
+    /// Choose whether queued control messages affect this block or the next.
+    pub fn set_control_timing(&mut self, timing: ControlTiming) {
+        self.control_timing = timing;
+    }
+
+    /// Process a block, applying every message waiting in `control_rx`
+    /// according to the runtime's [`ControlTiming`].
+    ///
+    /// A message sent with `RuntimeControl::send_at` lands on its exact
+    /// sample: the block is rendered in pieces split at each timed message.
+    /// Plans with a loop (a feedback edge or a cycle through a `Delay`) are
+    /// not split, since the loop must carry a whole block; their timed
+    /// messages apply at the start of the block they fall in.
+    pub fn process_block_with_channels(
+        &mut self,
+        out: &mut [f32],
+        control_rx: &mut Consumer<QueuedControl>,
+    ) -> Result<(), &'static str> {
+        if self.control_timing == ControlTiming::ThisBlock {
+            self.drain_controls(control_rx);
+        }
+        let result = match self.swap_rx.as_mut().and_then(|rx| rx.pop().ok()) {
+            Some(next) => self.swap_in(next, out),
+            None => self.process_block_timed(out),
+        };
+        if self.metering && result.is_ok() {
+            let peak = out.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+            self.post(RtMessage::Meter { peak });
+        }
+        if self.control_timing == ControlTiming::NextBlock {
+            self.drain_controls(control_rx);
+        }
+        result
+    }
+
+    fn drain_controls(&mut self, control_rx: &mut Consumer<QueuedControl>) {
+        while let Ok((offset, msg)) = control_rx.pop() {
+            match offset {
+                Some(offset) if self.pending_events.len() < MAX_PENDING_EVENTS => {
+                    let offset = offset as usize;
+                    let at = self.pending_events.partition_point(|&(o, _)| o > offset);
+                    self.pending_events.insert(at, (offset, msg));
+                }
+                _ => self.apply_queued(msg),
+            }
+        }
+    }
+
+    /// Receive plan swaps on `rx` and hand replaced runtimes back on
+    /// `retired_tx`; see `RuntimeControl::enable_swaps`.
+    pub(crate) fn set_swap_queues(
+        &mut self,
+        rx: Consumer<Box<Runtime>>,
+        retired_tx: Producer<Box<Runtime>>,
+    ) {
+        self.swap_rx = Some(rx);
+        self.retired_tx = Some(retired_tx);
+    }
+
+    /// The node types this runtime was built with, indexed by `NodeId`.
+    pub(crate) fn node_types(&self) -> &[Option<NodeType>] {
+        &self.nodes
+    }
+
+    /// The ids of this runtime's nodes, indexed by slot.
+    pub(crate) fn node_ids(&self) -> &[Option<NodeId>] {
+        &self.ids
+    }
+
+    /// Whether `node` names a node of this runtime, generation included.
+    fn contains(&self, node: NodeId) -> bool {
+        self.ids.get(node.0) == Some(&Some(node))
+    }
+
+    /// Replace this runtime with `next` during one block: render the block
+    /// with both, fading from the old output to the new.
+    ///
+    /// The old runtime renders first, then its queues, settings and the
+    /// state of every carried-over node move to `next`, so carried-over
+    /// nodes continue from where the old block left off. A runtime with a
+    /// different block size is refused.
+    fn swap_in(&mut self, mut next: Box<Runtime>, out: &mut [f32]) -> Result<(), &'static str> {
+        if next.plan.block_size != self.plan.block_size || out.len() > self.plan.block_size {
+            self.retire(next);
+            return self.process_block_timed(out);
+        }
+        let result = self.process_block_timed(out);
+        self.hand_over(&mut next);
+        let len = out.len();
+        let mut incoming = std::mem::take(&mut self.partial_out);
+        let next_result = next.process_block_timed(&mut incoming[..len]);
+        for (n, (o, &x)) in out.iter_mut().zip(&incoming).enumerate() {
+            let t = (n + 1) as f32 / len as f32;
+            *o += (x - *o) * t;
+        }
+        self.partial_out = incoming;
+        std::mem::swap(self, &mut *next);
+        self.retire(next);
+        result.and(next_result)
+    }
+
+    /// Move queues, settings and carried-over node state into `next`.
+    fn hand_over(&mut self, next: &mut Runtime) {
+        #[cfg(feature = "invariant-queue")]
+        {
+            next.invariant_tx = self.invariant_tx.take();
+        }
+        next.message_tx = self.message_tx.take();
+        next.swap_rx = self.swap_rx.take();
+        next.retired_tx = self.retired_tx.take();
+        next.block_size_policy = self.block_size_policy;
+        next.control_timing = self.control_timing;
+        next.metering = self.metering;
+        next.sanitize = self.sanitize;
+        next.smoothing_ms = self.smoothing_ms;
+        next.mute_fade_samples = self.mute_fade_samples;
+        #[cfg(feature = "profiling")]
+        {
+            next.block_budget = self.block_budget;
+            next.node_profiling = self.node_profiling;
+        }
+        std::mem::swap(&mut next.router, &mut self.router);
+        std::mem::swap(&mut next.workers, &mut self.workers);
+        if next.voices.len() == self.voices.len() {
+            // Same voice count: held notes keep their voices.
+            std::mem::swap(&mut next.voice_alloc, &mut self.voice_alloc);
+        }
+        std::mem::swap(&mut next.pending_events, &mut self.pending_events);
+        next.transport = self.transport;
+
+        let shared = self.nodes.len().min(next.nodes.len());
+        for i in 0..shared {
+            if !next.carry_over.get(i).copied().unwrap_or(false) {
+                continue;
+            }
+            std::mem::swap(&mut next.nodes[i], &mut self.nodes[i]);
+            std::mem::swap(&mut next.states[i], &mut self.states[i]);
+            std::mem::swap(&mut next.ramps[i], &mut self.ramps[i]);
+            next.muted[i] = self.muted[i];
+            next.soloed[i] = self.soloed[i];
+            next.frozen[i] = self.frozen[i];
+            next.bypassed[i] = self.bypassed[i];
+            next.synced_times[i] = self.synced_times[i];
+        }
+        next.update_solo();
+        next.fade_mutes(0);
+        next.sync_fused();
+    }
+
+    /// Send a replaced runtime to the main thread. Without a retired queue
+    /// (or with it full) it is dropped here, which frees memory on the RT
+    /// thread.
+    fn retire(&mut self, runtime: Box<Runtime>) {
+        if let Some(tx) = self.retired_tx.as_mut() {
+            let _ = tx.push(runtime);
+        }
+    }
+
+    /// [`Runtime::process_block`] with pending timed messages applied at
+    /// their offsets. `out` is checked against the block size policy the
+    /// same way.
+    fn process_block_timed(&mut self, out: &mut [f32]) -> Result<(), &'static str> {
+        let block_size = self.plan.block_size;
+        if self.pending_events.is_empty() {
+            return self.process_block(out);
+        }
+        if out.len() == block_size {
+            return self.process_frames_timed(out);
+        }
+        if self.block_size_policy == BlockSizePolicy::PadShort && out.len() < block_size {
+            let mut scratch = std::mem::take(&mut self.partial_out);
+            let result = self.process_frames_timed(&mut scratch);
+            out.copy_from_slice(&scratch[..out.len()]);
+            self.partial_out = scratch;
+            return result;
+        }
+        Err("output buffer must be exactly block_size long")
+    }
+
+    /// Render the block `out` in pieces, applying each pending timed message
+    /// at its offset, or all of them at the start for plans with loops.
+    /// Messages due after the block stay pending for the next one.
+    fn process_frames_timed(&mut self, out: &mut [f32]) -> Result<(), &'static str> {
+        let len = out.len();
+        let split = !self.reads_previous_block;
+        let mut result = Ok(());
+        let mut start = 0;
+        while start < len {
+            let due = if split { start } else { len - 1 };
+            while let Some(&(at, msg)) = self.pending_events.last() {
+                if at > due {
+                    break;
+                }
+                self.pending_events.pop();
+                self.apply_queued(msg);
+            }
+            let end = match self.pending_events.last() {
+                Some(&(at, _)) if split => at.min(len),
+                _ => len,
+            };
+            if let Err(e) = self.process_frames(&mut out[start..end], start) {
+                result = Err(e);
+            }
+            start = end;
+        }
+        for (at, _) in &mut self.pending_events {
+            *at -= len;
+        }
+        result
+    }
+
+    /// Apply a message taken from the control queue and report it.
+    fn apply_queued(&mut self, msg: ControlMsg) {
+        self.apply_control(msg);
+        if let ControlMsg::SetParam {
+            node,
+            param_idx,
+            value,
+        } = msg
+        {
+            self.post(RtMessage::ParamResponse {
+                node,
+                param_idx,
+                value,
+            });
+        }
+        self.signal(INV_CONTROL_MSG_PROCESSED);
+    }
+
+    /// Process a block of frames, writing to out (mono).
+    ///
+    /// `out` must be exactly `block_size` long unless the policy is
+    /// [`BlockSizePolicy::PadShort`].
+    pub fn process_block(&mut self, out: &mut [f32]) -> Result<(), &'static str> {
+        let block_size = self.plan.block_size;
+        if out.len() == block_size {
+            return self.process_block_exact(out);
+        }
+        if self.block_size_policy == BlockSizePolicy::PadShort && out.len() < block_size {
+            // Render a full block into the scratch buffer, hand out the head.
+            let mut scratch = std::mem::take(&mut self.partial_out);
+            let result = self.process_block_exact(&mut scratch);
+            out.copy_from_slice(&scratch[..out.len()]);
+            self.partial_out = scratch;
+            return result;
+        }
+        Err("output buffer must be exactly block_size long")
+    }
+
+    /// Choose which device channels [`Runtime::process_block_interleaved`]
+    /// writes each logical output channel to. The default routes the mono
+    /// output to device channel 0 only.
+    pub fn set_channel_router(&mut self, router: ChannelRouter) {
+        self.router = router;
+    }
+
+    /// Process a block into an interleaved device buffer of
+    /// `device_channels` channels, placed by the runtime's
+    /// [`ChannelRouter`]. `out` must hold exactly `block_size` frames.
+    /// RT-safe.
+    pub fn process_block_interleaved(
+        &mut self,
+        out: &mut [f32],
+        device_channels: usize,
+    ) -> Result<(), &'static str> {
+        if device_channels == 0 || out.len() != self.plan.block_size * device_channels {
+            return Err("output buffer must hold exactly block_size frames");
+        }
+        let mut mono = std::mem::take(&mut self.partial_out);
+        let result = self.process_block_exact(&mut mono);
+        self.router
+            .write_interleaved(&[&mono], out, device_channels);
+        self.partial_out = mono;
+        result
+    }
+
+    /// Process a block as an effect: each `AudioInput` node plays
+    /// `inputs[channel]` (silence if the host has no such channel), then the
+    /// graph renders into `out` as in [`Runtime::process_block`]. Input
+    /// slices may be shorter than the block; the rest is zero. If any is
+    /// longer, nothing is copied or rendered. RT-safe.
+    pub fn process_block_io(
+        &mut self,
+        inputs: &[&[f32]],
+        out: &mut [f32],
+    ) -> Result<(), &'static str> {
+        if inputs.iter().any(|data| data.len() > self.plan.block_size) {
+            return Err("input longer than block size");
+        }
+        for (node, state) in self.nodes.iter().zip(&mut self.states) {
+            if let (
+                Some(NodeType::AudioInput { channel, .. }),
+                Some(NodeState::AudioInput { buffer }),
+            ) = (node, state)
+            {
+                let data = inputs.get(*channel).copied().unwrap_or(&[]);
+                buffer[..data.len()].copy_from_slice(data);
+                buffer[data.len()..].fill(0.0);
+            }
+        }
+        self.process_block(out)
+    }
+
+    /// Process a block into one buffer per output channel, each exactly
+    /// `block_size` long.
+    ///
+    /// With a `MultiOutputSink` in the graph, `out` must have one buffer per
+    /// sink channel. Otherwise the mono output is copied to every buffer.
+    /// RT-safe.
+    pub fn process_block_multi(&mut self, out: &mut [&mut [f32]]) -> Result<(), &'static str> {
+        let block_size = self.plan.block_size;
+        if out.iter().any(|channel| channel.len() != block_size) {
+            return Err("output buffer must be exactly block_size long");
+        }
+        let sink_channels = self.states.iter().find_map(|state| match state {
+            Some(NodeState::MultiOutputSink { channels }) => Some(channels.len()),
+            _ => None,
+        });
+        if sink_channels.is_some_and(|n| n != out.len()) {
+            return Err("output channel count does not match the sink");
+        }
+        let mut mono = std::mem::take(&mut self.partial_out);
+        let result = self.process_block_exact(&mut mono);
+        let sink = self.states.iter().find_map(|state| match state {
+            Some(NodeState::MultiOutputSink { channels }) => Some(channels),
+            _ => None,
+        });
+        for (c, dst) in out.iter_mut().enumerate() {
+            dst.copy_from_slice(sink.map_or(&mono, |channels| &channels[c]));
+        }
+        self.partial_out = mono;
+        result
+    }
+
+    /// [`Runtime::process_block`], returning the peak absolute sample of
+    /// `out` for simple level metering. RT-safe.
+    ///
+    /// The `OutputSink` measures the peak while copying to `out`; only
+    /// when something else shapes `out` (preview outputs, a padded short
+    /// block, no sink, sanitized infinities) is `out` scanned again.
+    pub fn process_block_metered(&mut self, out: &mut [f32]) -> Result<f32, &'static str> {
+        self.process_block(out)?;
+        let sink_peak =
+            self.plan
+                .order
+                .iter()
+                .rev()
+                .find_map(|node| match self.states.get(node.0) {
+                    Some(Some(NodeState::OutputSink { peak })) => Some(*peak),
+                    _ => None,
+                });
+        match sink_peak {
+            Some(peak)
+                if out.len() == self.plan.block_size
+                    && self.plan.preview_outputs().is_empty()
+                    && (peak.is_finite() || !self.sanitize) =>
+            {
+                Ok(peak)
+            }
+            _ => Ok(out.iter().fold(0.0f32, |peak, s| peak.max(s.abs()))),
+        }
+    }
+
+    fn process_block_exact(&mut self, out: &mut [f32]) -> Result<(), &'static str> {
+        self.process_frames(out, 0)
+    }
+
+    /// Render `out.len()` frames (at most `block_size`) starting `start`
+    /// frames into the block: the whole block, or one piece of a block
+    /// split at timed control messages.
+    fn process_frames(&mut self, out: &mut [f32], start: usize) -> Result<(), &'static str> {
+        let frames = out.len();
+        #[cfg(feature = "profiling")]
+        let started = self.block_budget.map(|_| std::time::Instant::now());
+        #[cfg(feature = "profiling")]
+        if self.node_profiling {
+            if start == 0 {
+                self.node_times.fill(std::time::Duration::ZERO);
+            }
+            self.profile_lap = Some(std::time::Instant::now());
+        }
+        if start == 0 {
+            self.blocks += 1;
+        }
+        if !self.plan.preview_outputs().is_empty() {
+            // Preview outputs are summed in below.
+            out.fill(0.0);
+        }
+        let parallel = self.runs_in_parallel();
+        let mut result = Ok(());
+        if parallel {
+            result = self.process_levels(out, start);
+        } else {
+            // For each node in order
+            for step in 0..self.plan.order.len() {
+                let node_id = self.plan.order[step];
+                // Timed from here to the top of the next step, so nodes that
+                // do not run are charged too.
+                #[cfg(feature = "profiling")]
+                self.profile_lap(step.checked_sub(1).map(|prev| self.plan.order[prev]));
+                self.capture_bypass_dry(node_id);
+                if self.renders(node_id) {
+                    self.render_step(node_id, out, start);
+                } else {
+                    self.settle_node(node_id, out, start);
+                }
+                self.apply_bypass_fades(node_id, frames);
+            }
+        }
+        self.transport.advance(frames, self.sample_rate);
+        if self.sanitize {
+            let found = scrub_nonfinite(out) | std::mem::take(&mut self.nonfinite);
+            if found {
+                self.signal(INV_NONFINITE_DETECTED);
+            }
+        }
+        #[cfg(feature = "profiling")]
+        if self.node_profiling && !parallel {
+            self.profile_lap(self.plan.order.last().copied());
+            self.profile_lap = None;
+            if start + frames == self.plan.block_size {
+                self.post_block_profile();
+            }
+        }
+        #[cfg(feature = "profiling")]
+        if let (Some(started), Some(budget)) = (started, self.block_budget) {
+            if started.elapsed() > budget {
+                self.signal(INV_RT_OVERLOAD);
+            }
+        }
+        result
+    }
+
+    /// Whether `node_id` runs this step. Otherwise [`Runtime::settle_node`]
+    /// writes what it emits.
+    fn renders(&self, node_id: NodeId) -> bool {
+        !self.plan.is_static_silence(node_id)
+            && !self.replays_last_block(node_id)
+            && self.nodes[node_id.0].is_some()
+            && self.states[node_id.0].is_some()
+    }
+
+    /// Frozen: state stays put and the edge buffers still hold the last
+    /// block the node produced.
+    fn replays_last_block(&self, node_id: NodeId) -> bool {
+        // A shared plan hands a frozen node's buffers to other edges, so
+        // there is no last block to replay.
+        self.frozen[node_id.0] && !self.plan.shares_buffers() && self.port_counts[node_id.0].1 > 0
+    }
+
+    /// Silenced nodes keep running so their state stays continuous; only
+    /// what they emit is dropped. A node fading in or out is not silenced.
+    fn is_silenced(&self, node_id: NodeId) -> bool {
+        let fade = &self.mute_fades[node_id.0];
+        fade.value() == 0.0 && !fade.is_ramping()
+    }
+
+    /// Write the outputs of a node that does not run this step.
+    fn settle_node(&mut self, node_id: NodeId, out: &mut [f32], start: usize) {
+        let frames = out.len();
+        if self.plan.is_static_silence(node_id) {
+            // Nothing can reach this node: emit silence without running it.
+            for &(edge_idx, _) in &self.plan.node_outputs[node_id.0] {
+                self.edge_buffers[self.plan.edge_buffer_index(edge_idx)].fill(0.0);
+            }
+            match &mut self.states[node_id.0] {
+                Some(NodeState::OutputSink { peak }) => {
+                    out.fill(0.0);
+                    *peak = 0.0;
+                }
+                Some(NodeState::MultiOutputSink { channels }) => {
+                    for channel in channels {
+                        channel[start..start + frames].fill(0.0);
+                    }
+                }
+                _ => {}
+            }
+        } else if self.replays_last_block(node_id) {
+            if self.is_silenced(node_id) {
+                for &(edge_idx, _) in &self.plan.node_outputs[node_id.0] {
+                    self.edge_buffers[self.plan.edge_buffer_index(edge_idx)].fill(0.0);
+                }
+            }
+        } else {
+            // Fail-closed: silence outputs
+            for &(edge_idx, _) in &self.plan.node_outputs[node_id.0] {
+                self.edge_buffers[self.plan.edge_buffer_index(edge_idx)].fill(0.0);
+            }
+        }
+        self.mute_fades[node_id.0].advance_by(frames);
+    }
 
-/// Node states for mutable data.
-#[derive(Debug, Clone)]
-pub enum NodeState {
-    SineOsc { phase: f32 },
-    Gain,
-    Mix,
-    OutputSink,
-    Dummy,
-}
+    /// Run one node on the calling thread, reading its inputs straight from
+    /// the edge buffers and writing each output port into its direct edge
+    /// buffer.
+    fn render_step(&mut self, node_id: NodeId, out: &mut [f32], start: usize) {
+        let frames = out.len();
+        let silenced = self.is_silenced(node_id);
+        let (num_inputs, num_outputs) = self.port_counts[node_id.0];
+        // No built-in node has more outputs; external ones are capped.
+        let num_outputs = num_outputs.min(MAX_EXTERNAL_PORTS);
+        // Move the direct buffers into the output slots; swapped back below.
+        let direct = &self.direct_outputs[node_id.0];
+        for (port, buffer) in direct.iter().take(num_outputs).enumerate() {
+            if let Some(buffer) = *buffer {
+                std::mem::swap(
+                    &mut self.temp_output_vecs[port],
+                    &mut self.edge_buffers[buffer],
+                );
+            }
+        }
+        if let (Some(node_type), Some(node_state)) =
+            (&self.nodes[node_id.0], &mut self.states[node_id.0])
+        {
+            // Gather inputs by port
+            self.temp_inputs[..num_inputs].fill(None);
+            for &(edge_idx, port) in &self.plan.node_inputs[node_id.0] {
+                if let Some(slot) = self.temp_inputs.get_mut(port.0) {
+                    let edge = &self.plan.edges[edge_idx];
+                    let buf_idx = if edge.muted {
+                        MUTED_INPUT
+                    } else {
+                        self.plan.edge_buffer_index(edge_idx)
+                    };
+                    *slot = Some((buf_idx, edge.rate.buffer_len(frames)));
+                }
+            }
+            let edge_buffers = &self.edge_buffers;
+            let silence = &self.silence;
+            let temp_inputs = &self.temp_inputs;
+            let input = |port: usize| {
+                temp_inputs
+                    .get(port)
+                    .copied()
+                    .flatten()
+                    .map(|(buf_idx, len)| {
+                        if buf_idx == MUTED_INPUT {
+                            &silence[..len]
+                        } else {
+                            &edge_buffers[buf_idx][..len]
+                        }
+                    })
+            };
+            // One zeroed slice per output port, as long as its rate needs.
+            let (input_rates, output_rates) = &self.port_rates[node_id.0];
+            let mut outputs: [&mut [f32]; MAX_EXTERNAL_PORTS] = Default::default();
+            for ((slot, buffer), rate) in outputs
+                .iter_mut()
+                .zip(self.temp_output_vecs.iter_mut())
+                .zip(output_rates)
+            {
+                let buffer = &mut buffer[..rate.buffer_len(frames)];
+                buffer.fill(0.0);
+                *slot = buffer;
+            }
+            // Process
+            let env = NodeEnv {
+                sample_rate: self.sample_rate,
+                block_size: self.plan.block_size,
+                frames,
+                start,
+                input_rates,
+                silence,
+                reads_previous_block: self.plan.reads_previous_block(node_id),
+                silenced,
+                fade: Some(&self.mute_fades[node_id.0]).filter(|fade| fade.is_ramping()),
+                block_index: self.blocks - 1,
+                transport: &self.transport,
+            };
+            if self.bypassed[node_id.0] && num_outputs > 0 {
+                pass_through(input(0), &mut outputs[..num_outputs]);
+            } else {
+                render_node(
+                    node_type,
+                    node_state,
+                    &mut self.ramps[node_id.0],
+                    &env,
+                    &input,
+                    &mut outputs[..num_outputs],
+                    out,
+                );
+            }
+        }
+        self.fan_out(node_id, num_outputs, out);
+        let direct = &self.direct_outputs[node_id.0];
+        for (port, buffer) in direct.iter().take(num_outputs).enumerate() {
+            if let Some(buffer) = *buffer {
+                std::mem::swap(
+                    &mut self.temp_output_vecs[port],
+                    &mut self.edge_buffers[buffer],
+                );
+            }
+        }
+    }
 
-/// The runtime engine.
-#[derive(Debug)]
-pub struct Runtime {
-    pub plan: Plan,
-    sample_rate: f32,
-    nodes: Vec<Option<NodeType>>,
-    states: Vec<Option<NodeState>>,
-    edge_buffers: Vec<Vec<f32>>,
-    temp_inputs: Vec<usize>,
-    temp_output_vecs: Vec<Vec<f32>>,
-}
+    /// Copy the outputs [`Runtime::render_step`] left in `temp_output_vecs`
+    /// to the port's other edge buffers (silence if the node is silenced)
+    /// and sum a preview output into `out`.
+    fn fan_out(&mut self, node_id: NodeId, num_outputs: usize, out: &mut [f32]) {
+        let frames = out.len();
+        let silenced = self.is_silenced(node_id);
+        let output_rates = &self.port_rates[node_id.0].1;
+        let direct = &self.direct_outputs[node_id.0];
+        let fade = Some(&self.mute_fades[node_id.0]).filter(|fade| fade.is_ramping());
+        for (port, output) in self.temp_output_vecs[..num_outputs].iter_mut().enumerate() {
+            let output = &mut output[..output_rates[port].buffer_len(frames)];
+            if silenced {
+                output.fill(0.0);
+                continue;
+            }
+            if let Some(fade) = fade {
+                apply_fade(fade, output, frames);
+            }
+            if self.sanitize && scrub_nonfinite(output) {
+                self.nonfinite = true;
+            }
+        }
+        for &(edge_idx, port) in &self.plan.node_outputs[node_id.0] {
+            let buffer = self.plan.edge_buffer_index(edge_idx);
+            if direct.get(port.0) == Some(&Some(buffer)) {
+                continue;
+            }
+            let buffer = &mut self.edge_buffers[buffer];
+            match self.temp_output_vecs[..num_outputs].get(port.0) {
+                Some(output) if !silenced => {
+                    let len = output_rates[port.0].buffer_len(frames);
+                    buffer[..len].copy_from_slice(&output[..len]);
+                }
+                _ => buffer.fill(0.0),
+            }
+        }
+        if let (Some(output), false) = (self.temp_output_vecs[..num_outputs].first(), silenced) {
+            if self.plan.preview_outputs().contains(&node_id) {
+                for (o, &x) in out.iter_mut().zip(output.iter()) {
+                    *o += x;
+                }
+            }
+        }
+        self.mute_fades[node_id.0].advance_by(frames);
+    }
 
-impl Runtime {
-    /// Create a new runtime from a plan and graph.
-    pub fn new(plan: Plan, graph: &Graph, sample_rate: f32) -> Self {
-        let nodes: Vec<Option<NodeType>> = graph
-            .nodes
-            .iter()
-            .map(|n| n.as_ref().map(|nd| nd.node_type.clone()))
-            .collect();
-        let states: Vec<Option<NodeState>> = nodes
+    /// Copy a parallel job's rendered `outputs` into the node's edge buffers
+    /// (silence if the node is silenced) and sum a preview output into
+    /// `out`.
+    fn store_outputs(&mut self, node_id: NodeId, outputs: &mut [Vec<f32>], out: &mut [f32]) {
+        let frames = out.len();
+        let silenced = self.is_silenced(node_id);
+        if let Some(fade) = Some(&self.mute_fades[node_id.0]).filter(|fade| fade.is_ramping()) {
+            for output in outputs.iter_mut() {
+                apply_fade(fade, output, frames);
+            }
+        }
+        for &(edge_idx, port) in &self.plan.node_outputs[node_id.0] {
+            let buffer = &mut self.edge_buffers[self.plan.edge_buffer_index(edge_idx)];
+            match outputs.get(port.0) {
+                Some(output) if !silenced => buffer[..output.len()].copy_from_slice(output),
+                _ => buffer.fill(0.0),
+            }
+            if self.sanitize && scrub_nonfinite(buffer) {
+                self.nonfinite = true;
+            }
+        }
+        if let (Some(output), false) = (outputs.first(), silenced) {
+            if self.plan.preview_outputs().contains(&node_id) {
+                for (o, &x) in out.iter_mut().zip(output.iter()) {
+                    *o += x;
+                }
+            }
+        }
+        self.mute_fades[node_id.0].advance_by(frames);
+    }
+
+    /// Bypass a whole chain of nodes with a crossfade to its dry input.
+    ///
+    /// `nodes` must form a chain with exactly one edge entering it and one
+    /// edge leaving it. The leaving edge is crossfaded between the processed
+    /// signal and the entering (dry) signal over `fade_blocks` blocks; the
+    /// chain keeps running underneath so un-bypassing is also glitch-free.
+    ///
+    /// Not RT-safe on the first call for a given chain (allocates the dry
+    /// buffer); later calls for the same chain only retarget the fade.
+    pub fn set_subgraph_bypass(
+        &mut self,
+        nodes: &[NodeId],
+        bypass: bool,
+        fade_blocks: usize,
+    ) -> Result<(), &'static str> {
+        let mut input_edge = None;
+        let mut output_edge = None;
+        for (edge_idx, edge) in self.plan.edges.iter().enumerate() {
+            let from_inside = nodes.contains(&edge.from_node);
+            let to_inside = nodes.contains(&edge.to_node);
+            if to_inside && !from_inside {
+                if input_edge.replace(edge_idx).is_some() {
+                    return Err("subgraph must have exactly one input edge");
+                }
+            } else if from_inside && !to_inside && output_edge.replace(edge_idx).is_some() {
+                return Err("subgraph must have exactly one output edge");
+            }
+        }
+        let (Some(input_edge), Some(output_edge)) = (input_edge, output_edge) else {
+            return Err("subgraph must have exactly one input and one output edge");
+        };
+        if [input_edge, output_edge]
             .iter()
-            .map(|nt| {
-                nt.as_ref().map(|nt| match nt {
-                    NodeType::SineOsc { .. } => NodeState::SineOsc { phase: 0.0 },
-                    NodeType::Gain { .. } => NodeState::Gain,
-                    NodeType::Mix => NodeState::Mix,
-                    NodeType::OutputSink => NodeState::OutputSink,
-                    NodeType::Dummy => NodeState::Dummy,
-                })
-            })
-            .collect();
-        let edge_buffers = vec![vec![0.0; plan.block_size]; plan.edges.len()];
-        let temp_inputs = Vec::with_capacity(plan.max_inputs);
-        let temp_output_vecs = (0..plan.max_outputs)
-            .map(|_| vec![0.0; plan.block_size])
-            .collect();
-        Self {
-            plan,
-            sample_rate,
-            nodes,
-            states,
-            edge_buffers,
-            temp_inputs,
-            temp_output_vecs,
+            .any(|&idx| self.plan.edges[idx].rate != Rate::Audio)
+        {
+            return Err("subgraph bypass needs audio-rate input and output edges");
+        }
+
+        let target = if bypass { 1.0 } else { 0.0 };
+        let fade_samples = (fade_blocks * self.plan.block_size).max(1);
+        let step = 1.0 / fade_samples as f32;
+        if let Some(existing) = self
+            .bypasses
+            .iter_mut()
+            .find(|b| b.output_edge == output_edge)
+        {
+            existing.input_edge = input_edge;
+            existing.target = target;
+            existing.step = step;
+        } else {
+            self.bypasses.push(SubgraphBypass {
+                input_edge,
+                output_edge,
+                dry: vec![0.0; self.plan.block_size],
+                mix: 0.0,
+                target,
+                step,
+            });
         }
+        Ok(())
     }
 
-    /// Process a block of frames, writing to out (mono).
-    pub fn process_block(&mut self, out: &mut [f32]) -> Result<(), &'static str> {
-        let block_size = self.plan.block_size;
-        if out.len() != block_size {
-            return Err("output buffer must be exactly block_size long");
+    fn capture_bypass_dry(&mut self, node_id: NodeId) {
+        for bypass in &mut self.bypasses {
+            let edge = &self.plan.edges[bypass.input_edge];
+            if edge.to_node == node_id {
+                if edge.muted {
+                    bypass.dry.fill(0.0);
+                } else {
+                    let buf = self.plan.edge_buffer_index(bypass.input_edge);
+                    bypass.dry.copy_from_slice(&self.edge_buffers[buf]);
+                }
+            }
         }
-        // For each node in order
-        for &node_id in &self.plan.order {
-            if let (Some(node_type), Some(node_state)) =
-                (&self.nodes[node_id.0], &mut self.states[node_id.0])
-            {
-                // Gather inputs
-                self.temp_inputs.clear();
-                for &(edge_idx, _port) in &self.plan.node_inputs[node_id.0] {
-                    self.temp_inputs.push(edge_idx);
-                }
-                // Prepare outputs
-                let num_outputs = self.plan.node_outputs[node_id.0].len();
-                for i in 0..num_outputs {
-                    self.temp_output_vecs[i].fill(0.0);
-                }
-                let outputs = &mut self.temp_output_vecs[0..num_outputs];
-                // Process
-                match node_type {
-                    NodeType::Dummy => {
-                        for (i, &edge_idx) in self.temp_inputs.iter().enumerate() {
-                            let input = &self.edge_buffers[edge_idx][..];
-                            if let Some(output) = outputs.get_mut(i) {
-                                output.copy_from_slice(input);
+    }
+
+    fn apply_bypass_fades(&mut self, node_id: NodeId, frames: usize) {
+        for bypass in &mut self.bypasses {
+            if self.plan.edges[bypass.output_edge].from_node != node_id {
+                continue;
+            }
+            let buf = self.plan.edge_buffer_index(bypass.output_edge);
+            for (wet, &dry) in self.edge_buffers[buf][..frames].iter_mut().zip(&bypass.dry) {
+                if bypass.mix < bypass.target {
+                    bypass.mix = (bypass.mix + bypass.step).min(bypass.target);
+                } else if bypass.mix > bypass.target {
+                    bypass.mix = (bypass.mix - bypass.step).max(bypass.target);
+                }
+                *wet = *wet * (1.0 - bypass.mix) + dry * bypass.mix;
+            }
+        }
+    }
+}
+
+/// What a node reads while rendering besides its type, state, ramp and
+/// buffers.
+pub(crate) struct NodeEnv<'a> {
+    pub(crate) sample_rate: f32,
+    pub(crate) block_size: usize,
+    /// Frames to render, starting `start` frames into the block.
+    pub(crate) frames: usize,
+    pub(crate) start: usize,
+    /// One rate per input port.
+    pub(crate) input_rates: &'a [Rate],
+    /// At least `block_size` zeros.
+    pub(crate) silence: &'a [f32],
+    /// See [`Plan::reads_previous_block`].
+    pub(crate) reads_previous_block: bool,
+    /// Muted or not audible: sinks write silence.
+    pub(crate) silenced: bool,
+    /// Mute fade in progress, applied by sinks.
+    pub(crate) fade: Option<&'a LinearRamp>,
+    /// See [`ProcessCtx::block_index`].
+    pub(crate) block_index: u64,
+    pub(crate) transport: &'a Transport,
+}
+
+/// Render one node. `input(port)` is the buffer on an input port (`None`
+/// if unconnected) and `outputs` holds one zeroed buffer per output port,
+/// each sized for its rate. An `OutputSink` writes `out`. RT-safe.
+pub(crate) fn render_node<'a>(
+    node_type: &NodeType,
+    node_state: &mut NodeState,
+    ramp: &mut LinearRamp,
+    env: &NodeEnv<'a>,
+    input: &dyn Fn(usize) -> Option<&'a [f32]>,
+    outputs: &mut [&mut [f32]],
+    out: &mut [f32],
+) {
+    let NodeEnv {
+        sample_rate,
+        frames,
+        start,
+        input_rates,
+        silence,
+        silenced,
+        fade,
+        ..
+    } = *env;
+    let num_inputs = input_rates.len();
+    match node_type {
+        // Flattened away by `Runtime::new`.
+        NodeType::Subgraph { .. } => {}
+        NodeType::Dummy => {
+            if let (Some(input), Some(output)) = (input(0), outputs.first_mut()) {
+                output.copy_from_slice(input);
+            }
+        }
+        NodeType::SineOsc { freq } => {
+            if let NodeState::SineOsc { phase } = node_state {
+                let to_step = |hz: f32| 2.0 * std::f32::consts::PI * hz / sample_rate;
+                for sample in outputs[0].iter_mut() {
+                    let freq = if ramp.is_ramping() {
+                        ramp.advance()
+                    } else {
+                        *freq
+                    };
+                    *sample = phase.sin();
+                    *phase += to_step(freq);
+                    // Wrap phase to prevent precision loss over long sessions
+                    *phase %= 2.0 * std::f32::consts::PI;
+                }
+            }
+        }
+        NodeType::Chirp {
+            start_hz,
+            end_hz,
+            duration_samples,
+            log,
+        } => {
+            if let NodeState::Chirp { phase, elapsed } = node_state {
+                let duration = (*duration_samples).max(1);
+                let to_step = 2.0 * std::f32::consts::PI / sample_rate;
+                for sample in outputs[0].iter_mut() {
+                    if *elapsed >= duration {
+                        break;
+                    }
+                    let t = *elapsed as f32 / duration as f32;
+                    let freq = if *log {
+                        start_hz * (end_hz / start_hz).powf(t)
+                    } else {
+                        start_hz + (end_hz - start_hz) * t
+                    };
+                    *sample = phase.sin();
+                    *phase = (*phase + freq * to_step) % (2.0 * std::f32::consts::PI);
+                    *elapsed += 1;
+                }
+            }
+        }
+        NodeType::DualTone { freq_a, freq_b } => {
+            if let NodeState::DualTone { phases } = node_state {
+                let to_step = 2.0 * std::f32::consts::PI / sample_rate;
+                let steps = [freq_a * to_step, freq_b * to_step];
+                for sample in outputs[0].iter_mut() {
+                    *sample = 0.5 * (phases[0].sin() + phases[1].sin());
+                    for (phase, step) in phases.iter_mut().zip(steps) {
+                        *phase = (*phase + step) % (2.0 * std::f32::consts::PI);
+                    }
+                }
+            }
+        }
+        NodeType::Wavetable {
+            table,
+            freq,
+            interpolation,
+        } => {
+            if let NodeState::Wavetable { phase } = node_state {
+                for sample in outputs[0].iter_mut() {
+                    let freq = if ramp.is_ramping() {
+                        ramp.advance()
+                    } else {
+                        *freq
+                    };
+                    *sample = wavetable_read(table, *phase, *interpolation);
+                    *phase = (*phase + freq / sample_rate).rem_euclid(1.0);
+                }
+            }
+        }
+        NodeType::SawOsc { freq } | NodeType::PulseOsc { freq, .. } | NodeType::TriOsc { freq } => {
+            if let NodeState::BlepOsc { phase } = node_state {
+                for sample in outputs[0].iter_mut() {
+                    let freq = if ramp.is_ramping() {
+                        ramp.advance()
+                    } else {
+                        *freq
+                    };
+                    let dt = (freq / sample_rate).abs().min(0.5);
+                    *sample = match node_type {
+                        NodeType::PulseOsc { width, .. } => pulse_blep(*phase, dt, *width),
+                        NodeType::TriOsc { .. } => tri_blamp(*phase, dt),
+                        _ => saw_blep(*phase, dt),
+                    };
+                    *phase = (*phase + freq / sample_rate).rem_euclid(1.0);
+                }
+            }
+        }
+        NodeType::Gain { gain } => {
+            if let (Some(input), NodeState::Gain { fused }) = (input(0), node_state) {
+                for (o, &i_val) in outputs[0].iter_mut().zip(input) {
+                    let gain = if ramp.is_ramping() {
+                        ramp.advance()
+                    } else {
+                        *gain
+                    };
+                    let fused = if fused.is_ramping() {
+                        fused.advance()
+                    } else {
+                        fused.value()
+                    };
+                    *o = i_val * gain * fused;
+                }
+            }
+        }
+        NodeType::GatedGain => {
+            if let (Some(input), Some(control)) = (input(0), input(1)) {
+                let segments = outputs[0]
+                    .chunks_mut(CONTROL_RATE_DIVISOR)
+                    .zip(input.chunks(CONTROL_RATE_DIVISOR));
+                for ((out, x), &c) in segments.zip(control) {
+                    let gain = c.clamp(0.0, 1.0);
+                    for (o, &x) in out.iter_mut().zip(x) {
+                        *o = x * gain;
+                    }
+                }
+            }
+        }
+        NodeType::Mix => {
+            for port in 0..num_inputs {
+                if let Some(input) = input(port) {
+                    for (o, &i_val) in outputs[0].iter_mut().zip(input) {
+                        *o += i_val;
+                    }
+                }
+            }
+        }
+        NodeType::OutputSink => {
+            if let NodeState::OutputSink { peak } = node_state {
+                *peak = 0.0;
+                if silenced {
+                    out.fill(0.0);
+                } else if let Some(input) = input(0) {
+                    // Copy, fade and meter in one pass.
+                    let mut fade = fade.cloned();
+                    for (y, &x) in out.iter_mut().zip(input) {
+                        *y = fade.as_mut().map_or(x, |fade| x * fade.advance());
+                        *peak = peak.max(y.abs());
+                    }
+                }
+            }
+        }
+        NodeType::MultiOutputSink { .. } => {
+            if let NodeState::MultiOutputSink { channels } = node_state {
+                for (port, channel) in channels.iter_mut().enumerate() {
+                    let channel = &mut channel[start..start + frames];
+                    match input(port) {
+                        Some(input) if !silenced => {
+                            channel.copy_from_slice(input);
+                            if let Some(fade) = fade {
+                                apply_fade(fade, channel, frames);
                             }
                         }
+                        _ => channel.fill(0.0),
                     }
-                    NodeType::SineOsc { freq } => {
-                        if let NodeState::SineOsc { phase } = node_state {
-                            let step = 2.0 * std::f32::consts::PI * freq / self.sample_rate;
-                            for output in outputs.iter_mut() {
-                                for sample in output.iter_mut() {
-                                    *sample = phase.sin();
-                                    *phase += step;
-                                    // Wrap phase to prevent precision loss over long sessions
-                                    *phase %= 2.0 * std::f32::consts::PI;
-                                }
+                }
+            }
+        }
+        NodeType::AudioInput { .. } => {
+            if let NodeState::AudioInput { buffer } = node_state {
+                outputs[0].copy_from_slice(&buffer[start..start + frames]);
+            }
+        }
+        NodeType::Looper { .. } => {
+            if let NodeState::Looper {
+                buffer,
+                len,
+                pos,
+                mode,
+            } = node_state
+            {
+                let input = input(0);
+                for (n, y) in outputs[0].iter_mut().enumerate() {
+                    let x = input.map_or(0.0, |i| i[n]);
+                    *y = match mode {
+                        LooperMode::Stop => 0.0,
+                        LooperMode::Record => {
+                            if *len < buffer.len() {
+                                buffer[*len] = x;
+                                *len += 1;
                             }
+                            0.0
                         }
-                    }
-                    NodeType::Gain { gain } => {
-                        for (i, &edge_idx) in self.temp_inputs.iter().enumerate() {
-                            let input = &self.edge_buffers[edge_idx][..];
-                            if let Some(output) = outputs.get_mut(i) {
-                                for (o, &i_val) in output.iter_mut().zip(input) {
-                                    *o = i_val * gain;
+                        LooperMode::Play | LooperMode::Overdub => {
+                            if *len == 0 {
+                                0.0
+                            } else {
+                                let y = buffer[*pos];
+                                if *mode == LooperMode::Overdub {
+                                    buffer[*pos] += x;
                                 }
+                                *pos = (*pos + 1) % *len;
+                                y
                             }
                         }
+                    };
+                }
+            }
+        }
+        NodeType::Delay { delay_samples } => {
+            if let NodeState::Delay { line } = node_state {
+                // Inside a loop the input is already a block
+                // late; take that out of the delay.
+                let delay = if env.reads_previous_block {
+                    (delay_samples - env.block_size as f32).max(0.0)
+                } else {
+                    *delay_samples
+                };
+                let input = input(0);
+                for (n, y) in outputs[0].iter_mut().enumerate() {
+                    *y = line.process(input.map_or(0.0, |i| i[n]), delay);
+                }
+            }
+        }
+        NodeType::Chorus {
+            rate_hz,
+            depth_ms,
+            mix,
+            voices,
+        } => {
+            if let NodeState::Chorus { line, phase } = node_state {
+                let input = input(0);
+                let ms_to_samples = sample_rate / 1000.0;
+                let base = CHORUS_BASE_DELAY_MS * ms_to_samples;
+                let depth = depth_ms * ms_to_samples;
+                let voices = (*voices).max(1);
+                let phase_step = rate_hz / sample_rate;
+                for (n, y) in outputs[0].iter_mut().enumerate() {
+                    let x = input.map_or(0.0, |i| i[n]);
+                    line.write(x);
+                    let mut wet = 0.0;
+                    for v in 0..voices {
+                        let voice_phase = *phase + v as f32 / voices as f32;
+                        let lfo = (2.0 * std::f32::consts::PI * voice_phase).sin();
+                        wet += line.read(base + depth * lfo);
                     }
-                    NodeType::Mix => {
-                        for output in outputs.iter_mut() {
-                            for &edge_idx in &self.temp_inputs {
-                                let input = &self.edge_buffers[edge_idx][..];
-                                for (o, &i_val) in output.iter_mut().zip(input) {
-                                    *o += i_val;
-                                }
-                            }
+                    wet /= voices as f32;
+                    *y = x * (1.0 - mix) + wet * mix;
+                    *phase = (*phase + phase_step).rem_euclid(1.0);
+                }
+            }
+        }
+        NodeType::Compressor {
+            threshold_db,
+            ratio,
+            ..
+        } => {
+            if let NodeState::Compressor { follower } = node_state {
+                let main = input(0);
+                let detector = input(1).or(main);
+                let slope = 1.0 - 1.0 / ratio.max(1.0);
+                for (n, y) in outputs[0].iter_mut().enumerate() {
+                    let env = follower.process(detector.map_or(0.0, |d| d[n]));
+                    let over = lin_to_db(env) - threshold_db;
+                    let gain = if over > 0.0 {
+                        db_to_lin(-over * slope)
+                    } else {
+                        1.0
+                    };
+                    *y = main.map_or(0.0, |m| m[n]) * gain;
+                }
+            }
+        }
+        NodeType::NoiseGate { .. } => {
+            if let (NodeState::NoiseGate { gate }, Some(input)) = (node_state, input(0)) {
+                for (y, &x) in outputs[0].iter_mut().zip(input) {
+                    *y = gate.process(x);
+                }
+            }
+        }
+        NodeType::TruePeakLimiter { .. } => {
+            if let (NodeState::TruePeakLimiter { limiter }, Some(input)) = (node_state, input(0)) {
+                for (y, &x) in outputs[0].iter_mut().zip(input) {
+                    *y = limiter.process(x);
+                }
+            }
+        }
+        NodeType::Resample { .. } => {
+            if let (NodeState::Resample { resampler }, Some(input)) = (node_state, input(0)) {
+                for (y, &x) in outputs[0].iter_mut().zip(input) {
+                    *y = resampler.process(x);
+                }
+            }
+        }
+        NodeType::Diff => {
+            if let (NodeState::Diff { prev }, Some(input)) = (node_state, input(0)) {
+                for (y, &x) in outputs[0].iter_mut().zip(input) {
+                    *y = x - *prev;
+                    *prev = x;
+                }
+            }
+        }
+        NodeType::Noise { color, .. } => {
+            if let NodeState::Noise { noise } = node_state {
+                for y in outputs[0].iter_mut() {
+                    *y = noise.next(*color);
+                }
+            }
+        }
+        NodeType::Waveshaper { table } => {
+            if let Some(input) = input(0) {
+                for (y, &x) in outputs[0].iter_mut().zip(input) {
+                    *y = table_lookup(table, x);
+                }
+            }
+        }
+        NodeType::PanBank { pans } => {
+            let (left, right) = outputs.split_at_mut(1);
+            for (port, &pan) in pans.iter().enumerate() {
+                if let Some(input) = input(port) {
+                    let angle = (pan.clamp(-1.0, 1.0) + 1.0) * std::f32::consts::FRAC_PI_4;
+                    let (gain_r, gain_l) = angle.sin_cos();
+                    for ((l, r), &x) in left[0].iter_mut().zip(right[0].iter_mut()).zip(input) {
+                        *l += x * gain_l;
+                        *r += x * gain_r;
+                    }
+                }
+            }
+        }
+        NodeType::AutoPan { rate_hz, depth } => {
+            if let (NodeState::AutoPan { phase }, Some(input)) = (node_state, input(0)) {
+                let (left, right) = outputs.split_at_mut(1);
+                let step = rate_hz / sample_rate;
+                for ((l, r), &x) in left[0].iter_mut().zip(right[0].iter_mut()).zip(input) {
+                    let pan = depth * (2.0 * std::f32::consts::PI * *phase).sin();
+                    let angle = (pan.clamp(-1.0, 1.0) + 1.0) * std::f32::consts::FRAC_PI_4;
+                    let (gain_r, gain_l) = angle.sin_cos();
+                    *l = x * gain_l;
+                    *r = x * gain_r;
+                    *phase = (*phase + step) % 1.0;
+                }
+            }
+        }
+        NodeType::Rotary { speed_hz, depth } => {
+            if let (NodeState::Rotary { line, phase }, Some(input)) = (node_state, input(0)) {
+                let ms_to_samples = sample_rate / 1000.0;
+                let base = ROTARY_BASE_DELAY_MS * ms_to_samples;
+                let excursion = depth * ROTARY_EXCURSION_MS * ms_to_samples;
+                let step = speed_hz / sample_rate;
+                let (left, right) = outputs.split_at_mut(1);
+                for ((l, r), &x) in left[0].iter_mut().zip(right[0].iter_mut()).zip(input) {
+                    // Angle 0 points the horn at the listener.
+                    let (sin, cos) = (2.0 * std::f32::consts::PI * *phase).sin_cos();
+                    line.write(x);
+                    let wet = line.read(base + excursion * sin);
+                    let amp = 1.0 - 0.5 * depth * (1.0 - cos);
+                    let angle = (depth * sin + 1.0) * std::f32::consts::FRAC_PI_4;
+                    let (gain_r, gain_l) = angle.sin_cos();
+                    *l = wet * amp * gain_l;
+                    *r = wet * amp * gain_r;
+                    *phase = (*phase + step) % 1.0;
+                }
+            }
+        }
+        NodeType::Binaural { .. } => {
+            if let (NodeState::Binaural { panner }, Some(input)) = (node_state, input(0)) {
+                let (left, right) = outputs.split_at_mut(1);
+                for ((l, r), &x) in left[0].iter_mut().zip(right[0].iter_mut()).zip(input) {
+                    (*l, *r) = panner.process(x);
+                }
+            }
+        }
+        NodeType::StereoWidth { width } => {
+            if let (Some(left_in), Some(right_in)) = (input(0), input(1)) {
+                let (left, right) = outputs.split_at_mut(1);
+                for (((l, r), &x_l), &x_r) in left[0]
+                    .iter_mut()
+                    .zip(right[0].iter_mut())
+                    .zip(left_in)
+                    .zip(right_in)
+                {
+                    let mid = 0.5 * (x_l + x_r);
+                    let side = 0.5 * (x_l - x_r) * width;
+                    *l = mid + side;
+                    *r = mid - side;
+                }
+            }
+        }
+        NodeType::AudioToControl => {
+            if let Some(input) = input(0) {
+                for (y, segment) in outputs[0]
+                    .iter_mut()
+                    .zip(input.chunks(CONTROL_RATE_DIVISOR))
+                {
+                    *y = segment.iter().sum::<f32>() / segment.len() as f32;
+                }
+            }
+        }
+        NodeType::ControlToAudio => {
+            if let (NodeState::ControlToAudio { last }, Some(input)) = (node_state, input(0)) {
+                // Ramp from the previous value so each control
+                // value is reached at the end of its segment.
+                let step = 1.0 / CONTROL_RATE_DIVISOR as f32;
+                for (out, &target) in outputs[0].chunks_mut(CONTROL_RATE_DIVISOR).zip(input) {
+                    let from = *last;
+                    for (n, y) in out.iter_mut().enumerate() {
+                        *y = from + (target - from) * (n + 1) as f32 * step;
+                    }
+                    *last = target;
+                }
+            }
+        }
+        NodeType::Crossover { .. } => {
+            if let (NodeState::Crossover { split }, Some(input)) = (node_state, input(0)) {
+                let (low, high) = outputs.split_at_mut(1);
+                for ((lo, hi), &x) in low[0].iter_mut().zip(high[0].iter_mut()).zip(input) {
+                    (*lo, *hi) = split.process(x);
+                }
+            }
+        }
+        NodeType::FreqShift { .. } => {
+            if let (NodeState::FreqShift { shifter }, Some(input)) = (node_state, input(0)) {
+                for (y, &x) in outputs[0].iter_mut().zip(input) {
+                    *y = shifter.process(x);
+                }
+            }
+        }
+        NodeType::TapeSat { .. } => {
+            if let (NodeState::TapeSat { saturator }, Some(input)) = (node_state, input(0)) {
+                for (y, &x) in outputs[0].iter_mut().zip(input) {
+                    *y = saturator.process(x);
+                }
+            }
+        }
+        NodeType::Filter { .. } => {
+            if let (NodeState::Filter { biquad }, Some(input)) = (node_state, input(0)) {
+                for (y, &x) in outputs[0].iter_mut().zip(input) {
+                    *y = biquad.process(x);
+                }
+            }
+        }
+        NodeType::Adsr { .. } => {
+            if let NodeState::Adsr { env } = node_state {
+                match input(0) {
+                    Some(input) => {
+                        for (y, &x) in outputs[0].iter_mut().zip(input) {
+                            *y = x * env.advance();
                         }
                     }
-                    NodeType::OutputSink => {
-                        if let Some(&edge_idx) = self.temp_inputs.first() {
-                            let input = &self.edge_buffers[edge_idx][..];
-                            out.copy_from_slice(input);
+                    None => {
+                        for y in outputs[0].iter_mut() {
+                            *y = env.advance();
                         }
                     }
                 }
-                // Store outputs in edge buffers
-                for (i, &(edge_idx, _)) in self.plan.node_outputs[node_id.0].iter().enumerate() {
-                    self.edge_buffers[edge_idx].copy_from_slice(&outputs[i]);
+            }
+        }
+        NodeType::External { def } => {
+            if let NodeState::External {
+                state,
+                legacy_outputs,
+            } = node_state
+            {
+                // Ports past the cap are not processed (see `NodeType::External`).
+                let num_inputs = num_inputs.min(MAX_EXTERNAL_PORTS);
+                let mut inputs: [&[f32]; MAX_EXTERNAL_PORTS] =
+                    [&silence[..frames]; MAX_EXTERNAL_PORTS];
+                for (port, slot) in inputs[..num_inputs].iter_mut().enumerate() {
+                    *slot = input(port)
+                        .unwrap_or_else(|| &silence[..input_rates[port].buffer_len(frames)]);
                 }
-            } else {
-                // Fail-closed: silence outputs
-                for &(edge_idx, _) in &self.plan.node_outputs[node_id.0] {
-                    self.edge_buffers[edge_idx].fill(0.0);
+                let ctx = ProcessCtx {
+                    sample_rate,
+                    block_size: env.block_size,
+                    block_index: env.block_index,
+                    transport: *env.transport,
+                };
+                if def
+                    .process(
+                        state.as_mut(),
+                        &inputs[..num_inputs],
+                        outputs,
+                        legacy_outputs,
+                        &ctx,
+                    )
+                    .is_err()
+                {
+                    // Fail-closed: a failing external node is silent
+                    for output in outputs.iter_mut() {
+                        output.fill(0.0);
+                    }
                 }
             }
         }
-        Ok(())
     }
 }
 
+/// Product of the gains of `nodes` in a fused chain.
+fn chain_gain(nodes: &[Option<NodeType>], chain: &[NodeId]) -> f32 {
+    chain
+        .iter()
+        .map(|node| match nodes[node.0] {
+            Some(NodeType::Gain { gain }) => gain,
+            _ => 1.0,
+        })
+        .product()
+}
+
+/// Render a bypassed node: its first input is copied to its first output
+/// when both run at the same rate; everything else stays silent.
+pub(crate) fn pass_through(input: Option<&[f32]>, outputs: &mut [&mut [f32]]) {
+    if let (Some(input), Some(output)) = (input, outputs.first_mut()) {
+        if input.len() == output.len() {
+            output.copy_from_slice(input);
+        }
+    }
+}
+
+/// Scale `buffer` by `fade` from where it stands, without moving it. A
+/// control-rate buffer takes one gain per `CONTROL_RATE_DIVISOR` frames.
+fn apply_fade(fade: &LinearRamp, buffer: &mut [f32], frames: usize) {
+    let mut fade = fade.clone();
+    let stride = if buffer.len() < frames {
+        CONTROL_RATE_DIVISOR
+    } else {
+        1
+    };
+    for x in buffer.iter_mut() {
+        *x *= fade.advance();
+        fade.advance_by(stride - 1);
+    }
+}
+
+/// Zero every non-finite sample in `buffer`; true if there were any.
+fn scrub_nonfinite(buffer: &mut [f32]) -> bool {
+    let mut found = false;
+    for x in buffer.iter_mut().filter(|x| !x.is_finite()) {
+        *x = 0.0;
+        found = true;
+    }
+    found
+}
+
+/// Crossfade state for a bypassed chain.
+#[derive(Debug)]
+struct SubgraphBypass {
+    input_edge: usize,
+    output_edge: usize,
+    dry: Vec<f32>,
+    /// 0.0 = fully processed, 1.0 = fully dry.
+    mix: f32,
+    target: f32,
+    step: f32,
+}
+
 /// Render offline to a buffer.
 pub fn render_offline(runtime: &mut Runtime, frames: usize) -> Result<Vec<f32>, &'static str> {
+    render_offline_with_msgs(runtime, frames, &[])
+}
+
+/// Render offline, applying scheduled control messages along the way.
+///
+/// Each `(frame, msg)` is applied at the first block boundary at or after
+/// `frame`, counted from the start of this render. Messages sharing a
+/// boundary are applied in slice order, so identical schedules always
+/// produce identical output.
+pub fn render_offline_with_msgs(
+    runtime: &mut Runtime,
+    frames: usize,
+    msgs: &[(u64, ControlMsg)],
+) -> Result<Vec<f32>, &'static str> {
     if runtime.plan.block_size == 0 {
         return Err("Block size must be > 0");
     }
+    // Stable sort keeps slice order among messages at the same frame.
+    let mut schedule: Vec<&(u64, ControlMsg)> = msgs.iter().collect();
+    schedule.sort_by_key(|(frame, _)| *frame);
+    let mut pending = schedule.into_iter().peekable();
+
     let mut output = vec![0.0; frames];
     let block_size = runtime.plan.block_size;
     let mut offset = 0;
     while offset < frames {
+        while let Some((_, msg)) = pending.next_if(|(frame, _)| *frame <= offset as u64) {
+            runtime.apply_control(*msg);
+        }
         let block_len = (frames - offset).min(block_size);
         if block_len == block_size {
             runtime.process_block(&mut output[offset..offset + block_size])?;
@@ -176,6 +2868,82 @@ pub fn render_offline(runtime: &mut Runtime, frames: usize) -> Result<Vec<f32>,
     Ok(output)
 }
 
+/// Sample encoding of a WAV file written by [`render_offline_to_wav`].
+#[cfg(feature = "wav")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WavFormat {
+    /// 16-bit PCM.
+    Int16,
+    /// 24-bit PCM.
+    Int24,
+    /// 32-bit PCM.
+    Int32,
+    /// 32-bit IEEE float, written as rendered (no clamping).
+    Float32,
+}
+
+/// Render `frames` frames offline straight into a mono WAV file at `path`,
+/// at the runtime's sample rate.
+///
+/// Rendering goes block by block, so memory use does not grow with
+/// `frames`. Integer formats clamp to full scale and write NaN as zero (see
+/// [`format`]). Not RT-safe: allocates and does file I/O.
+#[cfg(feature = "wav")]
+pub fn render_offline_to_wav(
+    runtime: &mut Runtime,
+    frames: usize,
+    path: impl AsRef<std::path::Path>,
+    format: WavFormat,
+) -> Result<(), &'static str> {
+    let block_size = runtime.plan.block_size;
+    if block_size == 0 {
+        return Err("Block size must be > 0");
+    }
+    let (bits_per_sample, sample_format) = match format {
+        WavFormat::Int16 => (16, hound::SampleFormat::Int),
+        WavFormat::Int24 => (24, hound::SampleFormat::Int),
+        WavFormat::Int32 => (32, hound::SampleFormat::Int),
+        WavFormat::Float32 => (32, hound::SampleFormat::Float),
+    };
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: runtime.sample_rate().round() as u32,
+        bits_per_sample,
+        sample_format,
+    };
+    let mut writer =
+        hound::WavWriter::create(path, spec).map_err(|_| "failed to create WAV file")?;
+    let mut block = vec![0.0; block_size];
+    let mut ints = vec![0i32; block_size];
+    let mut shorts = vec![0i16; block_size];
+    let mut offset = 0;
+    while offset < frames {
+        runtime.process_block(&mut block)?;
+        let len = (frames - offset).min(block_size);
+        let samples = &block[..len];
+        let written = match format {
+            WavFormat::Int16 => {
+                format::to_i16(samples, &mut shorts[..len])?;
+                shorts[..len]
+                    .iter()
+                    .try_for_each(|&s| writer.write_sample(s))
+            }
+            WavFormat::Int24 | WavFormat::Int32 => {
+                if format == WavFormat::Int24 {
+                    format::to_i24(samples, &mut ints[..len])?;
+                } else {
+                    format::to_i32(samples, &mut ints[..len])?;
+                }
+                ints[..len].iter().try_for_each(|&s| writer.write_sample(s))
+            }
+            WavFormat::Float32 => samples.iter().try_for_each(|&s| writer.write_sample(s)),
+        };
+        written.map_err(|_| "failed to write WAV file")?;
+        offset += len;
+    }
+    writer.finalize().map_err(|_| "failed to write WAV file")
+}
+
 /// Run process_block with panic containment.
 pub fn process_block_safe(runtime: &mut Runtime, out: &mut [f32]) {
     let result =
@@ -231,6 +2999,7 @@ mod tests {
                 to_node: sink,
                 to_port: PortId(0),
                 rate: Rate::Audio,
+                muted: false,
             })
             .unwrap();
         let plan = Plan::compile(&graph, 64).unwrap();
@@ -244,6 +3013,43 @@ mod tests {
         );
     }
 
+    #[test]
+    fn rt_metered_peak_matches_output() {
+        let mut graph = Graph::new();
+        let osc = graph.add_node(NodeType::SineOsc { freq: 1000.0 });
+        let gain = graph.add_node(NodeType::Gain { gain: 0.5 });
+        let sink = graph.add_node(NodeType::OutputSink);
+        for (from, to) in [(osc, gain), (gain, sink)] {
+            graph
+                .add_edge(crate::graph::Edge {
+                    from_node: from,
+                    from_port: PortId(0),
+                    to_node: to,
+                    to_port: PortId(0),
+                    rate: Rate::Audio,
+                    muted: false,
+                })
+                .unwrap();
+        }
+        let plan = Plan::compile(&graph, 96).unwrap();
+        let mut runtime = Runtime::new(plan, &graph, 48000.0);
+        let mut out = vec![0.0; 96];
+        let peak = runtime.process_block_metered(&mut out).unwrap();
+        let max_abs = out.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        assert_eq!(peak, max_abs);
+        // 48 samples per cycle hit the crest exactly.
+        assert!((peak - 0.5).abs() < 1e-4);
+
+        // The sink's mute fade is metered as written, down to silence.
+        runtime.apply_control(ControlMsg::Mute { node: sink });
+        for _ in 0..4 {
+            let peak = runtime.process_block_metered(&mut out).unwrap();
+            let max_abs = out.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+            assert_eq!(peak, max_abs);
+        }
+        assert_eq!(runtime.process_block_metered(&mut out).unwrap(), 0.0);
+    }
+
     #[test]
     fn rt_determinism() {
         let mut graph = Graph::new();
@@ -266,11 +3072,12 @@ mod tests {
         let node2 = graph.add_node(NodeType::OutputSink);
         graph
             .add_edge(crate::graph::Edge {
-                from_node: NodeId(0),
+                from_node: NodeId(0, 0),
                 from_port: PortId(0),
                 to_node: node2,
                 to_port: PortId(0),
                 rate: Rate::Audio,
+                muted: false,
             })
             .unwrap();
         let plan = Plan::compile(&graph, 64).unwrap();
@@ -0,0 +1,120 @@
+//! Output sample-format conversion for fixed-point devices.
+//!
+//! All converters clamp to the target range and round to nearest; NaN maps
+//! to zero. They write into caller-provided buffers and are RT-safe.
+
+#![forbid(unsafe_code)]
+
+/// Full-scale value of signed 24-bit samples.
+const I24_MAX: f32 = 8_388_607.0;
+
+#[inline]
+fn quantize(x: f32, scale: f32, min: f32, max: f32) -> f32 {
+    if x.is_nan() {
+        0.0
+    } else {
+        (x * scale).round().clamp(min, max)
+    }
+}
+
+/// Convert to 16-bit PCM, mapping `[-1.0, 1.0]` to `[-32767, 32767]`.
+pub fn to_i16(input: &[f32], out: &mut [i16]) -> Result<(), &'static str> {
+    if input.len() != out.len() {
+        return Err("input and output buffers must have the same length");
+    }
+    for (o, &x) in out.iter_mut().zip(input) {
+        *o = quantize(x, i16::MAX as f32, -(i16::MAX as f32), i16::MAX as f32) as i16;
+    }
+    Ok(())
+}
+
+/// Convert to 24-bit PCM stored in the low bits of an `i32`, mapping
+/// `[-1.0, 1.0]` to `[-8388607, 8388607]`.
+pub fn to_i24(input: &[f32], out: &mut [i32]) -> Result<(), &'static str> {
+    if input.len() != out.len() {
+        return Err("input and output buffers must have the same length");
+    }
+    for (o, &x) in out.iter_mut().zip(input) {
+        *o = quantize(x, I24_MAX, -I24_MAX, I24_MAX) as i32;
+    }
+    Ok(())
+}
+
+/// Convert to 32-bit PCM, mapping `[-1.0, 1.0]` to
+/// `[-2147483647, 2147483647]`.
+pub fn to_i32(input: &[f32], out: &mut [i32]) -> Result<(), &'static str> {
+    if input.len() != out.len() {
+        return Err("input and output buffers must have the same length");
+    }
+    let max = i32::MAX as f64;
+    for (o, &x) in out.iter_mut().zip(input) {
+        *o = if x.is_nan() {
+            0
+        } else {
+            (x as f64 * max).round().clamp(-max, max) as i32
+        };
+    }
+    Ok(())
+}
+
+/// Convert to Q15 fixed point: `value / 32768`, saturating at
+/// `[-1.0, 1.0 - 2^-15]`.
+pub fn to_q15(input: &[f32], out: &mut [i16]) -> Result<(), &'static str> {
+    if input.len() != out.len() {
+        return Err("input and output buffers must have the same length");
+    }
+    for (o, &x) in out.iter_mut().zip(input) {
+        *o = quantize(x, 32768.0, i16::MIN as f32, i16::MAX as f32) as i16;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_i16_clamps_and_rounds() {
+        let input = [
+            0.0,
+            1.0,
+            -1.0,
+            2.0,
+            -2.0,
+            0.5,
+            1.6 / 32767.0,
+            1.4 / 32767.0,
+            f32::NAN,
+        ];
+        let mut out = [0i16; 9];
+        to_i16(&input, &mut out).unwrap();
+        assert_eq!(out, [0, 32767, -32767, 32767, -32767, 16384, 2, 1, 0]);
+    }
+
+    #[test]
+    fn to_q15_saturates_at_full_scale() {
+        let mut out = [0i16; 3];
+        to_q15(&[1.0, -1.0, 0.25], &mut out).unwrap();
+        assert_eq!(out, [i16::MAX, i16::MIN, 8192]);
+    }
+
+    #[test]
+    fn to_i24_range() {
+        let mut out = [0i32; 3];
+        to_i24(&[1.5, -1.0, 0.0], &mut out).unwrap();
+        assert_eq!(out, [8_388_607, -8_388_607, 0]);
+    }
+
+    #[test]
+    fn to_i32_range() {
+        let mut out = [0i32; 4];
+        to_i32(&[1.5, -1.0, 0.5, f32::NAN], &mut out).unwrap();
+        assert_eq!(out, [i32::MAX, -i32::MAX, 1_073_741_824, 0]);
+    }
+
+    #[test]
+    fn length_mismatch_is_rejected() {
+        let mut out = [0i16; 2];
+        assert!(to_i16(&[0.0], &mut out).is_err());
+    }
+}
@@ -0,0 +1,498 @@
+//! Multi-threaded rendering of plan levels.
+//!
+//! [`Plan::levels`](crate::plan::Plan::levels) groups the schedule into
+//! levels whose nodes do not depend on each other. A [`ParallelRuntime`]
+//! renders each level across a fixed pool of worker threads: nodes move to
+//! a worker by value through a lock-free queue, together with copies of
+//! their inputs, and come back once rendered. The calling thread writes the
+//! results to the edge buffers in level order, so the output never depends
+//! on which worker finished first.
+
+#![forbid(unsafe_code)]
+
+use super::{pass_through, render_node, NodeEnv, Runtime, MAX_EXTERNAL_PORTS};
+use crate::dsp::LinearRamp;
+use crate::graph::{NodeId, NodeType, Rate};
+use crate::states::NodeState;
+use crate::transport::Transport;
+use rtrb::{Consumer, Producer, PushError, RingBuffer};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+/// Empty polls a worker spins through before parking until the next job.
+const WORKER_SPINS: u32 = 1 << 14;
+
+/// A [`Runtime`] that renders each level of its plan across a fixed pool of
+/// worker threads.
+///
+/// Levels of one node, and `OutputSink` nodes, run on the calling thread.
+/// Rendered outputs are written back in the order of
+/// [`Plan::levels`](crate::plan::Plan::levels), so a `ParallelRuntime`
+/// produces exactly what the same [`Runtime`] renders serially. Rendering
+/// allocates nothing and takes no locks; waking a parked worker is the only
+/// system call.
+///
+/// Plans from [`Plan::compile_shared`](crate::plan::Plan::compile_shared)
+/// are refused, since their buffer reuse follows the serial order. A runtime
+/// swapped in through `RuntimeControl::swap_plan` takes the workers over and
+/// runs serially if its plan needs more job slots than the pool has. Per-node
+/// profiling is not recorded while levels run in parallel.
+#[derive(Debug)]
+pub struct ParallelRuntime {
+    runtime: Runtime,
+}
+
+impl ParallelRuntime {
+    /// Start `threads` workers for `runtime`.
+    ///
+    /// Not RT-safe: spawns the threads and allocates job buffers for the
+    /// widest level of the plan.
+    pub fn new(mut runtime: Runtime, threads: usize) -> Result<Self, &'static str> {
+        if threads == 0 {
+            return Err("a parallel runtime needs at least one worker thread");
+        }
+        if runtime.plan.shares_buffers() {
+            return Err("a parallel runtime cannot run a plan with shared buffers");
+        }
+        runtime.workers = Some(WorkerPool::new(threads, &runtime)?);
+        Ok(Self { runtime })
+    }
+
+    /// Render one block; see [`Runtime::process_block`]. RT-safe.
+    pub fn process_block(&mut self, out: &mut [f32]) -> Result<(), &'static str> {
+        self.runtime.process_block(out)
+    }
+
+    /// The wrapped runtime.
+    pub fn runtime(&self) -> &Runtime {
+        &self.runtime
+    }
+
+    /// The wrapped runtime, for controls and the other `process_block`
+    /// variants, which all render in parallel.
+    pub fn runtime_mut(&mut self) -> &mut Runtime {
+        &mut self.runtime
+    }
+
+    /// Stop the workers and return the runtime, which renders serially
+    /// again. Blocks until every worker has exited.
+    pub fn into_inner(mut self) -> Runtime {
+        self.runtime.workers = None;
+        self.runtime
+    }
+}
+
+impl Runtime {
+    /// Whether the worker pool can render this runtime's plan.
+    pub(super) fn runs_in_parallel(&self) -> bool {
+        self.workers.as_ref().is_some_and(|pool| pool.fits(self))
+    }
+
+    /// Render the plan level by level, handing every level wider than one
+    /// node to the workers.
+    pub(super) fn process_levels(
+        &mut self,
+        out: &mut [f32],
+        start: usize,
+    ) -> Result<(), &'static str> {
+        let Some(mut pool) = self.workers.take() else {
+            return Err("no worker pool");
+        };
+        let frames = out.len();
+        let mut result = Ok(());
+        for level in 0..self.plan.levels().len() {
+            let width = self.plan.levels()[level].len();
+            // Hand out every job before any node of the level writes an
+            // edge buffer, so jobs copy the inputs the serial order sees.
+            pool.dispatched.clear();
+            pool.dispatched.resize(width, false);
+            let mut outstanding = 0;
+            for slot in 0..width {
+                let node_id = self.plan.levels()[level][slot];
+                self.capture_bypass_dry(node_id);
+                let offload = width > 1
+                    && self.renders(node_id)
+                    && !matches!(self.nodes[node_id.0], Some(NodeType::OutputSink));
+                // Only pop a job to use it: a dropped job is lost to the pool.
+                let job = if offload { pool.idle.pop() } else { None };
+                if let Some(mut job) = job {
+                    job.load(self, node_id, slot, frames, start);
+                    if pool.dispatch(job) {
+                        outstanding += 1;
+                    }
+                    pool.dispatched[slot] = true;
+                }
+            }
+            // The rest run here while the workers render. None of them
+            // writes an edge buffer another node of the level reads.
+            for slot in 0..width {
+                let node_id = self.plan.levels()[level][slot];
+                if pool.dispatched[slot] {
+                    continue;
+                }
+                if self.renders(node_id) {
+                    self.render_step(node_id, out, start);
+                } else {
+                    self.settle_node(node_id, out, start);
+                }
+            }
+            if let Err(e) = pool.collect(outstanding) {
+                result = Err(e);
+            }
+            // Write results in level order, whatever order they came back in.
+            for slot in 0..width {
+                let node_id = self.plan.levels()[level][slot];
+                if let Some(mut job) = pool.pending[slot].take() {
+                    job.unload(self, out);
+                    pool.idle.push(job);
+                }
+                self.apply_bypass_fades(node_id, frames);
+            }
+        }
+        self.workers = Some(pool);
+        result
+    }
+}
+
+/// Threads rendering jobs, with the jobs' buffers.
+#[derive(Debug)]
+pub(crate) struct WorkerPool {
+    workers: Vec<Worker>,
+    /// Jobs not in flight. Holds `capacity` jobs between levels.
+    idle: Vec<Job>,
+    /// Rendered jobs by slot in the current level.
+    pending: Vec<Option<Job>>,
+    /// Per slot in the current level: the node was handed to a job.
+    dispatched: Vec<bool>,
+    /// Worker the next job goes to.
+    next: usize,
+    shutdown: Arc<AtomicBool>,
+    /// A worker died; render serially from now on.
+    failed: bool,
+    /// Limits the job buffers were sized for.
+    capacity: usize,
+    max_inputs: usize,
+    max_outputs: usize,
+    block_size: usize,
+}
+
+#[derive(Debug)]
+struct Worker {
+    jobs: Producer<Job>,
+    done: Consumer<Job>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl WorkerPool {
+    fn new(threads: usize, runtime: &Runtime) -> Result<Self, &'static str> {
+        let capacity = runtime
+            .plan
+            .levels()
+            .iter()
+            .map(Vec::len)
+            .max()
+            .unwrap_or(0);
+        let max_inputs = runtime.temp_inputs.len();
+        let max_outputs = runtime.temp_output_vecs.len();
+        let block_size = runtime.plan.block_size;
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let mut workers = Vec::with_capacity(threads);
+        for n in 0..threads {
+            // Room for every job of a level, so pushes never fail.
+            let (jobs, jobs_rx) = RingBuffer::new(capacity.max(1));
+            let (done_tx, done) = RingBuffer::new(capacity.max(1));
+            let stop = Arc::clone(&shutdown);
+            let thread = std::thread::Builder::new()
+                .name(format!("auxide-worker-{}", n))
+                .spawn(move || work(jobs_rx, done_tx, &stop))
+                .map_err(|_| "failed to spawn a worker thread")?;
+            workers.push(Worker {
+                jobs,
+                done,
+                thread: Some(thread),
+            });
+        }
+        Ok(Self {
+            workers,
+            idle: (0..capacity)
+                .map(|_| Job::new(max_inputs, max_outputs, block_size))
+                .collect(),
+            pending: (0..capacity).map(|_| None).collect(),
+            dispatched: Vec::with_capacity(capacity),
+            next: 0,
+            shutdown,
+            failed: false,
+            capacity,
+            max_inputs,
+            max_outputs,
+            block_size,
+        })
+    }
+
+    /// Whether the job buffers cover `runtime`'s plan.
+    fn fits(&self, runtime: &Runtime) -> bool {
+        !self.failed
+            && !runtime.plan.shares_buffers()
+            && runtime.plan.block_size <= self.block_size
+            && runtime.temp_inputs.len() <= self.max_inputs
+            && runtime.temp_output_vecs.len() <= self.max_outputs
+            && runtime
+                .plan
+                .levels()
+                .iter()
+                .all(|level| level.len() <= self.capacity)
+    }
+
+    /// Queue `job` on the next worker and wake it. Returns false if the job
+    /// was rendered here instead because the queue was full.
+    fn dispatch(&mut self, job: Job) -> bool {
+        let count = self.workers.len();
+        let worker = &mut self.workers[self.next];
+        self.next = (self.next + 1) % count;
+        match worker.jobs.push(job) {
+            Ok(()) => {
+                if let Some(thread) = &worker.thread {
+                    thread.thread().unpark();
+                }
+                true
+            }
+            Err(PushError::Full(mut job)) => {
+                job.run();
+                let slot = job.slot;
+                self.pending[slot] = Some(job);
+                false
+            }
+        }
+    }
+
+    /// Wait for `outstanding` jobs to come back into `pending`.
+    fn collect(&mut self, mut outstanding: usize) -> Result<(), &'static str> {
+        while outstanding > 0 {
+            let mut received = false;
+            for worker in &mut self.workers {
+                while let Ok(job) = worker.done.pop() {
+                    let slot = job.slot;
+                    self.pending[slot] = Some(job);
+                    outstanding -= 1;
+                    received = true;
+                }
+            }
+            if received {
+                continue;
+            }
+            let died = self
+                .workers
+                .iter()
+                .any(|w| w.thread.as_ref().is_none_or(|t| t.is_finished()));
+            if died {
+                // Its jobs are lost; their nodes stay silent.
+                self.failed = true;
+                return Err("a worker thread stopped");
+            }
+            std::hint::spin_loop();
+        }
+        Ok(())
+    }
+}
+
+impl Drop for WorkerPool {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::Release);
+        for worker in &mut self.workers {
+            if let Some(thread) = worker.thread.take() {
+                thread.thread().unpark();
+                let _ = thread.join();
+            }
+        }
+    }
+}
+
+/// Worker loop: render jobs as they arrive, park when idle.
+fn work(mut jobs: Consumer<Job>, mut done: Producer<Job>, shutdown: &AtomicBool) {
+    let mut idle = 0;
+    loop {
+        if let Ok(mut job) = jobs.pop() {
+            job.run();
+            // Sized for a whole level: never full.
+            let _ = done.push(job);
+            idle = 0;
+        } else if shutdown.load(Ordering::Acquire) {
+            return;
+        } else if idle < WORKER_SPINS {
+            idle += 1;
+            std::hint::spin_loop();
+        } else {
+            std::thread::park();
+        }
+    }
+}
+
+/// One node in flight: the node itself, moved out of the runtime, and
+/// everything it reads and writes while rendering.
+#[derive(Debug)]
+struct Job {
+    /// Position in the level.
+    slot: usize,
+    node: NodeId,
+    node_type: Option<NodeType>,
+    state: Option<NodeState>,
+    ramp: LinearRamp,
+    sample_rate: f32,
+    block_size: usize,
+    frames: usize,
+    start: usize,
+    reads_previous_block: bool,
+    silenced: bool,
+    bypassed: bool,
+    /// The node's mute fade, applied by sinks while it is ramping.
+    fade: LinearRamp,
+    block_index: u64,
+    transport: Transport,
+    input_rates: Vec<Rate>,
+    /// Copies of the input buffers, by port.
+    inputs: Vec<Vec<f32>>,
+    connected: Vec<bool>,
+    outputs: Vec<Vec<f32>>,
+    num_outputs: usize,
+    silence: Vec<f32>,
+}
+
+impl Job {
+    fn new(max_inputs: usize, max_outputs: usize, block_size: usize) -> Self {
+        let buffer = || Vec::with_capacity(block_size);
+        Self {
+            slot: 0,
+            node: NodeId(0, 0),
+            node_type: None,
+            state: None,
+            ramp: LinearRamp::default(),
+            sample_rate: 0.0,
+            block_size,
+            frames: 0,
+            start: 0,
+            reads_previous_block: false,
+            silenced: false,
+            bypassed: false,
+            fade: LinearRamp::default(),
+            block_index: 0,
+            transport: Transport::default(),
+            input_rates: Vec::with_capacity(max_inputs),
+            inputs: (0..max_inputs).map(|_| buffer()).collect(),
+            connected: Vec::with_capacity(max_inputs),
+            outputs: (0..max_outputs).map(|_| buffer()).collect(),
+            num_outputs: 0,
+            silence: vec![0.0; block_size],
+        }
+    }
+
+    /// Move `node_id` out of `runtime` and copy its inputs.
+    fn load(
+        &mut self,
+        runtime: &mut Runtime,
+        node_id: NodeId,
+        slot: usize,
+        frames: usize,
+        start: usize,
+    ) {
+        let plan = &runtime.plan;
+        self.slot = slot;
+        self.node = node_id;
+        self.node_type = runtime.nodes[node_id.0].take();
+        self.state = runtime.states[node_id.0].take();
+        self.ramp = std::mem::take(&mut runtime.ramps[node_id.0]);
+        self.sample_rate = runtime.sample_rate;
+        self.block_size = plan.block_size;
+        self.frames = frames;
+        self.start = start;
+        self.reads_previous_block = plan.reads_previous_block(node_id);
+        self.silenced = runtime.is_silenced(node_id);
+        self.bypassed = runtime.bypassed[node_id.0];
+        self.fade = runtime.mute_fades[node_id.0].clone();
+        self.block_index = runtime.blocks - 1;
+        self.transport = runtime.transport;
+
+        let (num_inputs, num_outputs) = runtime.port_counts[node_id.0];
+        let (input_rates, output_rates) = &runtime.port_rates[node_id.0];
+        // All within capacity: never reallocates.
+        self.input_rates.clear();
+        self.input_rates.extend(input_rates.iter().cloned());
+        self.connected.clear();
+        self.connected.resize(num_inputs, false);
+        for &(edge_idx, port) in &plan.node_inputs[node_id.0] {
+            if port.0 >= num_inputs {
+                continue;
+            }
+            let edge = &plan.edges[edge_idx];
+            let len = edge.rate.buffer_len(frames);
+            let buffer = &mut self.inputs[port.0];
+            buffer.clear();
+            if edge.muted {
+                buffer.resize(len, 0.0);
+            } else {
+                let source = &runtime.edge_buffers[plan.edge_buffer_index(edge_idx)];
+                buffer.extend_from_slice(&source[..len]);
+            }
+            self.connected[port.0] = true;
+        }
+        for (output, rate) in self.outputs[..num_outputs].iter_mut().zip(output_rates) {
+            output.clear();
+            output.resize(rate.buffer_len(frames), 0.0);
+        }
+        self.num_outputs = num_outputs.min(MAX_EXTERNAL_PORTS);
+    }
+
+    fn run(&mut self) {
+        let (Some(node_type), Some(state)) = (&self.node_type, &mut self.state) else {
+            return;
+        };
+        let env = NodeEnv {
+            sample_rate: self.sample_rate,
+            block_size: self.block_size,
+            frames: self.frames,
+            start: self.start,
+            input_rates: &self.input_rates,
+            silence: &self.silence,
+            reads_previous_block: self.reads_previous_block,
+            silenced: self.silenced,
+            fade: Some(&self.fade).filter(|fade| fade.is_ramping()),
+            block_index: self.block_index,
+            transport: &self.transport,
+        };
+        let mut outputs: [&mut [f32]; MAX_EXTERNAL_PORTS] = Default::default();
+        for (slot, output) in outputs.iter_mut().zip(self.outputs.iter_mut()) {
+            *slot = output.as_mut_slice();
+        }
+        let inputs = &self.inputs;
+        let connected = &self.connected;
+        let input = |port: usize| {
+            connected
+                .get(port)
+                .copied()
+                .unwrap_or(false)
+                .then(|| inputs[port].as_slice())
+        };
+        if self.bypassed && self.num_outputs > 0 {
+            pass_through(input(0), &mut outputs[..self.num_outputs]);
+            return;
+        }
+        render_node(
+            node_type,
+            state,
+            &mut self.ramp,
+            &env,
+            &input,
+            &mut outputs[..self.num_outputs],
+            &mut [],
+        );
+    }
+
+    /// Put the node back into `runtime` and store its outputs.
+    fn unload(&mut self, runtime: &mut Runtime, out: &mut [f32]) {
+        let node_id = self.node;
+        runtime.nodes[node_id.0] = self.node_type.take();
+        runtime.states[node_id.0] = self.state.take();
+        runtime.ramps[node_id.0] = std::mem::take(&mut self.ramp);
+        runtime.store_outputs(node_id, &mut self.outputs[..self.num_outputs], out);
+    }
+}
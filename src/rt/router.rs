@@ -0,0 +1,65 @@
+//! Output channel routing for multichannel devices.
+//!
+//! The runtime renders logical output channels (the `OutputSink` signal is
+//! logical channel 0). A [`ChannelRouter`] decides which device channels
+//! each one lands on when writing interleaved device buffers. Routing is
+//! RT-safe: it only reads the route table.
+
+#![forbid(unsafe_code)]
+
+/// Maps logical output channels onto device channels.
+///
+/// A logical channel may feed any number of device channels (mono to many)
+/// in any order. Device channels fed by several logical channels get their
+/// sum; device channels with no route are silent.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChannelRouter {
+    /// `(logical, device)` pairs.
+    routes: Vec<(usize, usize)>,
+}
+
+impl ChannelRouter {
+    /// A router with no routes: every device channel is silent.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Logical channel `i` to device channel `i`, for `channels` channels.
+    pub fn identity(channels: usize) -> Self {
+        Self {
+            routes: (0..channels).map(|c| (c, c)).collect(),
+        }
+    }
+
+    /// Add a route from `logical` to `device`.
+    pub fn route(mut self, logical: usize, device: usize) -> Self {
+        self.routes.push((logical, device));
+        self
+    }
+
+    /// All `(logical, device)` routes, in the order they were added.
+    pub fn routes(&self) -> &[(usize, usize)] {
+        &self.routes
+    }
+
+    /// Write `logical` channels into the interleaved buffer `out`, which
+    /// holds `device_channels` samples per frame. Routes naming a missing
+    /// logical channel or a device channel out of range are skipped.
+    pub fn write_interleaved(&self, logical: &[&[f32]], out: &mut [f32], device_channels: usize) {
+        out.fill(0.0);
+        if device_channels == 0 {
+            return;
+        }
+        for &(from, to) in &self.routes {
+            let Some(signal) = logical.get(from) else {
+                continue;
+            };
+            if to >= device_channels {
+                continue;
+            }
+            for (frame, &x) in out.chunks_exact_mut(device_channels).zip(signal.iter()) {
+                frame[to] += x;
+            }
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! Runtime snapshots for session recall.
+//!
+//! A [`RuntimeSnapshot`] captures everything a [`Runtime`](crate::rt::Runtime)
+//! accumulates while running: live-tweaked parameters, mute/solo/freeze
+//! flags and per-node DSP state. With the `serde` feature it can be
+//! serialized; restoring it into a runtime built from the same graph
+//! continues exactly where the snapshot was taken.
+//!
+//! Subgraph bypass fades are not captured.
+
+#![forbid(unsafe_code)]
+
+use crate::control::LooperMode;
+use crate::dsp::{
+    Adsr, BinauralPanner, Biquad, ColoredNoise, Crossover, DelayLine, EnvelopeFollower,
+    FreqShifter, NoiseGate, Resampler, TapeSaturator, TruePeakLimiter,
+};
+use crate::transport::NoteDivision;
+
+/// Full runtime state at a block boundary.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RuntimeSnapshot {
+    /// Sample rate the runtime was running at.
+    pub sample_rate: f32,
+    /// Block size of the plan.
+    pub block_size: usize,
+    pub muted: Vec<bool>,
+    pub soloed: Vec<bool>,
+    pub frozen: Vec<bool>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub bypassed: Vec<bool>,
+    /// Transport tempo in BPM; `None` in snapshots from before it was saved.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub tempo: Option<f32>,
+    /// Per node: the note value a tempo-synced `Delay` follows (see
+    /// `ControlMsg::SetSyncedTime`). Empty in older snapshots.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub synced_times: Vec<Option<NoteDivision>>,
+    /// Edge buffer contents (frozen nodes replay these).
+    pub edge_buffers: Vec<Vec<f32>>,
+    /// Per-node state, indexed by `NodeId`; `None` for removed nodes.
+    pub nodes: Vec<Option<NodeSnapshot>>,
+}
+
+/// Parameters and DSP state of one node.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NodeSnapshot {
+    SineOsc {
+        freq: f32,
+        phase: f32,
+    },
+    Chirp {
+        phase: f32,
+        elapsed: usize,
+    },
+    DualTone {
+        freq_a: f32,
+        freq_b: f32,
+        phases: [f32; 2],
+    },
+    Wavetable {
+        freq: f32,
+        phase: f32,
+    },
+    BlepOsc {
+        freq: f32,
+        /// Pulse width of a `PulseOsc`; `None` for the other shapes.
+        width: Option<f32>,
+        phase: f32,
+    },
+    ControlToAudio {
+        last: f32,
+    },
+    Gain {
+        gain: f32,
+    },
+    PanBank {
+        pans: Vec<f32>,
+    },
+    StereoWidth {
+        width: f32,
+    },
+    AutoPan {
+        rate_hz: f32,
+        depth: f32,
+        phase: f32,
+    },
+    Rotary {
+        speed_hz: f32,
+        depth: f32,
+        line: DelayLine,
+        phase: f32,
+    },
+    Binaural {
+        azimuth: f32,
+        panner: BinauralPanner,
+    },
+    Looper {
+        buffer: Vec<f32>,
+        len: usize,
+        pos: usize,
+        mode: LooperMode,
+    },
+    Delay {
+        line: DelayLine,
+    },
+    Chorus {
+        line: DelayLine,
+        phase: f32,
+    },
+    Compressor {
+        follower: EnvelopeFollower,
+    },
+    NoiseGate {
+        gate: NoiseGate,
+    },
+    TruePeakLimiter {
+        limiter: Box<TruePeakLimiter>,
+    },
+    Resample {
+        resampler: Box<Resampler>,
+    },
+    Diff {
+        prev: f32,
+    },
+    Noise {
+        noise: ColoredNoise,
+    },
+    Crossover {
+        split: Crossover,
+    },
+    FreqShift {
+        shifter: FreqShifter,
+    },
+    TapeSat {
+        saturator: TapeSaturator,
+    },
+    Filter {
+        cutoff: f32,
+        q: f32,
+        biquad: Biquad,
+    },
+    Adsr {
+        env: Adsr,
+    },
+    /// Bytes from `NodeDef::serialize_state`, or `None` if the node does
+    /// not support snapshots.
+    External {
+        state: Option<Vec<u8>>,
+    },
+    /// Node with no parameters or state worth keeping.
+    Stateless,
+}
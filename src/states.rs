@@ -2,6 +2,11 @@
 
 // IMPORTANT: Do not call assert_invariant or any PPT logging in RT paths to avoid locks/allocs.
 
+use crate::control::LooperMode;
+use crate::dsp::{
+    Adsr, BinauralPanner, Biquad, ColoredNoise, Crossover, DelayLine, EnvelopeFollower,
+    FreqShifter, LinearRamp, NoiseGate, Resampler, TapeSaturator, TruePeakLimiter,
+};
 use std::any::Any;
 
 /// Node states for mutable data.
@@ -12,17 +17,164 @@ pub enum NodeState {
         /// Current phase in radians.
         phase: f32,
     },
-    /// Gain node (stateless).
-    Gain,
+    /// Chirp phase and sweep position.
+    Chirp {
+        /// Current phase in radians.
+        phase: f32,
+        /// Samples elapsed since the sweep started.
+        elapsed: usize,
+    },
+    /// Dual-tone phases.
+    DualTone {
+        /// Phases of the two oscillators in radians.
+        phases: [f32; 2],
+    },
+    /// Wavetable oscillator phase.
+    Wavetable {
+        /// Current phase in cycles, [0, 1).
+        phase: f32,
+    },
+    /// Saw, pulse or triangle oscillator phase.
+    BlepOsc {
+        /// Current phase in cycles, [0, 1).
+        phase: f32,
+    },
+    /// Auto-panner LFO.
+    AutoPan {
+        /// LFO phase in cycles, [0, 1).
+        phase: f32,
+    },
+    /// Rotary speaker horn.
+    Rotary {
+        /// Doppler delay line, sized for the largest excursion.
+        line: DelayLine,
+        /// Rotor angle in revolutions, [0, 1).
+        phase: f32,
+    },
+    /// Binaural panner.
+    Binaural {
+        /// Per-ear delay lines and gains.
+        panner: BinauralPanner,
+    },
+    /// Gated gain (stateless).
+    GatedGain,
+    /// Stereo width (stateless).
+    StereoWidth,
+    /// Gain node. `fused` is the product of the gains folded into it by
+    /// `Plan::compile_fused`, ramping when one changes; 1 otherwise.
+    Gain { fused: LinearRamp },
     /// Mix node (stateless).
     Mix,
-    /// Output sink (stateless).
-    OutputSink,
+    /// Output sink.
+    OutputSink {
+        /// Peak absolute sample of the last frames written to `out`, for
+        /// `Runtime::process_block_metered`.
+        peak: f32,
+    },
+    /// Multi-channel output sink.
+    MultiOutputSink {
+        /// Last block of each channel, for `Runtime::process_block_multi`.
+        channels: Vec<Vec<f32>>,
+    },
     /// Dummy passthrough (stateless).
     Dummy,
+    /// Host input buffer.
+    AudioInput {
+        /// Latest host samples, zero-padded to the block size.
+        buffer: Vec<f32>,
+    },
+    /// Looper with its pre-allocated loop buffer.
+    Looper {
+        /// Loop storage, sized to `max_samples` at init.
+        buffer: Vec<f32>,
+        /// Recorded loop length in samples.
+        len: usize,
+        /// Playback position within the loop.
+        pos: usize,
+        /// Current transport mode.
+        mode: LooperMode,
+    },
+    /// Delay line.
+    Delay {
+        /// Ring buffer sized for the configured delay.
+        line: DelayLine,
+    },
+    /// Chorus with a shared multi-tap delay line.
+    Chorus {
+        /// Ring buffer sized for base delay plus depth.
+        line: DelayLine,
+        /// LFO phase in cycles, [0, 1).
+        phase: f32,
+    },
+    /// Compressor detector state.
+    Compressor {
+        /// Peak follower on the detector (sidechain or main) signal.
+        follower: EnvelopeFollower,
+    },
+    /// Noise gate envelope and hold timer.
+    NoiseGate { gate: NoiseGate },
+    /// True-peak limiter look-ahead and gain.
+    TruePeakLimiter {
+        /// Oversampled detector, look-ahead buffer and gain smoother.
+        limiter: Box<TruePeakLimiter>,
+    },
+    /// Resampler history buffers.
+    Resample {
+        /// Input and virtual-rate history.
+        resampler: Box<Resampler>,
+    },
+    /// First-difference node.
+    Diff {
+        /// Last input sample of the previous block.
+        prev: f32,
+    },
+    /// Noise generator.
+    Noise {
+        /// Seeded PRNG plus colouring filters.
+        noise: ColoredNoise,
+    },
+    /// Waveshaper (stateless).
+    Waveshaper,
+    /// Panner bank (stateless).
+    PanBank,
+    /// Audio-to-control adapter (stateless).
+    AudioToControl,
+    /// Control-to-audio adapter.
+    ControlToAudio {
+        /// Control value the ramp starts from.
+        last: f32,
+    },
+    /// Two-band crossover.
+    Crossover {
+        /// Linkwitz-Riley filter pair.
+        split: Crossover,
+    },
+    /// Resonant low-pass.
+    Filter {
+        /// Biquad coefficients and memory.
+        biquad: Biquad,
+    },
+    /// Envelope generator.
+    Adsr {
+        /// Stage and level.
+        env: Adsr,
+    },
+    /// Tape saturation curve.
+    TapeSat {
+        /// Curve with its hysteresis memory.
+        saturator: TapeSaturator,
+    },
+    /// Frequency shifter.
+    FreqShift {
+        /// Hilbert transformer and carrier.
+        shifter: FreqShifter,
+    },
     /// External node with type-erased state.
     External {
         /// The node's runtime state.
         state: Box<dyn Any + Send>,
+        /// One `block_size` buffer per output port, for definitions still
+        /// rendering through the deprecated `NodeDef::process_block`.
+        legacy_outputs: Vec<Vec<f32>>,
     },
 }
@@ -0,0 +1,276 @@
+//! Test helpers for verifying graphs and nodes, behind the `testutil`
+//! feature.
+//!
+//! # RT allocation checks
+//!
+//! [`assert_no_alloc_in_rt`] needs [`CountingAllocator`] installed as the
+//! global allocator of the test binary:
+//!
+//! ```ignore
+//! use auxide::testutil::CountingAllocator;
+//!
+//! #[global_allocator]
+//! static ALLOC: CountingAllocator = CountingAllocator;
+//! ```
+//!
+//! Allocations are counted per thread, so parallel tests do not interfere.
+//!
+//! # Block-size invariance
+//!
+//! [`assert_block_size_invariant`] renders a graph at several block sizes
+//! and checks the outputs agree. Every built-in node passes, provided graphs
+//! with control-rate edges use block sizes that are multiples of
+//! `CONTROL_RATE_DIVISOR` (control values cover fixed segments of each
+//! block); external nodes pass only if their `NodeDef::process` is per-sample.
+//!
+//! # Golden files
+//!
+//! [`assert_matches_golden`] compares a render with a mono 32-bit float WAV
+//! fixture. Create or refresh fixtures with [`write_golden`], or run the
+//! tests with `AUXIDE_UPDATE_GOLDENS=1` to rewrite every fixture they touch.
+
+use crate::graph::Graph;
+use crate::plan::Plan;
+use crate::rt::{render_offline, Runtime};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::io;
+use std::path::Path;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Global allocator wrapper over [`System`] that counts allocations per thread.
+pub struct CountingAllocator;
+
+// SAFETY: delegates every call to `System`; the counter is a const-initialised
+// thread local, so touching it never allocates or re-enters the allocator.
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|c| c.set(c.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|c| c.set(c.get() + 1));
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+/// Number of allocations made on the current thread so far.
+///
+/// Always zero unless [`CountingAllocator`] is the global allocator.
+pub fn allocation_count() -> usize {
+    ALLOCATIONS.with(|c| c.get())
+}
+
+/// Assert that `process_block` performs no allocations for `graph`.
+///
+/// Compiles `graph` at `block_size`, builds a runtime (allocation allowed),
+/// then runs `blocks` blocks and asserts the allocation count did not move.
+///
+/// # Panics
+/// Panics if the graph fails to compile, if any block errors or allocates,
+/// or if [`CountingAllocator`] is not installed.
+pub fn assert_no_alloc_in_rt(graph: &Graph, block_size: usize, blocks: usize) {
+    let probe_before = allocation_count();
+    drop(std::hint::black_box(Box::new(0u8)));
+    assert!(
+        allocation_count() > probe_before,
+        "CountingAllocator must be installed as the #[global_allocator]"
+    );
+
+    let plan = Plan::compile(graph, block_size).expect("graph must compile");
+    let mut runtime = Runtime::new(plan, graph, 44100.0);
+    let mut out = vec![0.0; block_size];
+
+    let before = allocation_count();
+    for _ in 0..blocks {
+        runtime
+            .process_block(&mut out)
+            .expect("process_block failed");
+    }
+    let allocations = allocation_count() - before;
+    assert_eq!(
+        allocations, 0,
+        "process_block allocated {} times over {} blocks",
+        allocations, blocks
+    );
+}
+
+/// Assert that rendering `build_graph()` offline gives the same samples at
+/// every block size in `block_sizes`.
+///
+/// A fresh graph is built per block size so stateful nodes start clean.
+/// Outputs are compared with the first block size's render, sample by
+/// sample, within `1e-5`.
+///
+/// # Panics
+/// Panics if the graph fails to compile or render, or on the first sample
+/// that differs, naming both block sizes and the frame.
+pub fn assert_block_size_invariant(
+    build_graph: impl Fn() -> Graph,
+    frames: usize,
+    block_sizes: &[usize],
+) {
+    let render = |block_size: usize| {
+        let graph = build_graph();
+        let plan = Plan::compile(&graph, block_size).expect("graph must compile");
+        let mut runtime = Runtime::new(plan, &graph, 44100.0);
+        render_offline(&mut runtime, frames).expect("render failed")
+    };
+    let Some((&reference_size, others)) = block_sizes.split_first() else {
+        return;
+    };
+    let reference = render(reference_size);
+    for &block_size in others {
+        let output = render(block_size);
+        if let Some(frame) = (0..frames).find(|&i| (output[i] - reference[i]).abs() > 1e-5) {
+            panic!(
+                "block size {} diverges from block size {} at frame {}: {} vs {}",
+                block_size, reference_size, frame, output[frame], reference[frame]
+            );
+        }
+    }
+}
+
+/// Environment variable that makes [`assert_matches_golden`] rewrite its
+/// fixture instead of comparing against it.
+pub const UPDATE_GOLDENS_ENV: &str = "AUXIDE_UPDATE_GOLDENS";
+
+/// Render `frames` samples and save them as the golden fixture at `path`.
+pub fn write_golden(
+    runtime: &mut Runtime,
+    frames: usize,
+    path: impl AsRef<Path>,
+) -> io::Result<()> {
+    let output = render_offline(runtime, frames).map_err(io::Error::other)?;
+    write_wav(path, &output, runtime.sample_rate())
+}
+
+/// Render `frames` samples and assert each is within `tolerance` of the
+/// golden fixture at `path`.
+///
+/// With [`UPDATE_GOLDENS_ENV`] set, the fixture is rewritten instead.
+///
+/// # Panics
+/// Panics if rendering fails, the fixture cannot be read, its length
+/// differs, or a sample is off by more than `tolerance`; the message names
+/// the first mismatching frame, both values and the largest error.
+pub fn assert_matches_golden(
+    runtime: &mut Runtime,
+    frames: usize,
+    path: impl AsRef<Path>,
+    tolerance: f32,
+) {
+    let path = path.as_ref();
+    if std::env::var_os(UPDATE_GOLDENS_ENV).is_some() {
+        write_golden(runtime, frames, path).expect("failed to write golden");
+        return;
+    }
+    let output = render_offline(runtime, frames).expect("render failed");
+    let golden =
+        read_wav(path).unwrap_or_else(|e| panic!("cannot read golden {}: {}", path.display(), e));
+    assert_eq!(
+        output.len(),
+        golden.len(),
+        "render has {} frames but golden {} has {}",
+        output.len(),
+        path.display(),
+        golden.len()
+    );
+    // NaN errors count as mismatches.
+    let error = |i: usize| (output[i] - golden[i]).abs();
+    let within = |i: usize| error(i) <= tolerance;
+    if let Some(first) = (0..frames).find(|&i| !within(i)) {
+        let worst = (0..frames).map(error).fold(0.0f32, f32::max);
+        panic!(
+            "render differs from golden {} at frame {}: {} vs {} (error {}, tolerance {}, \
+             largest error {})",
+            path.display(),
+            first,
+            output[first],
+            golden[first],
+            error(first),
+            tolerance,
+            worst
+        );
+    }
+}
+
+/// Write mono samples as a 32-bit float WAV file.
+pub fn write_wav(path: impl AsRef<Path>, samples: &[f32], sample_rate: f32) -> io::Result<()> {
+    let data_len = u32::try_from(samples.len() * 4)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "too many samples"))?;
+    let sample_rate = sample_rate.round() as u32;
+    let mut bytes = Vec::with_capacity(44 + samples.len() * 4);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    bytes.extend_from_slice(&WAV_FORMAT_FLOAT.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes()); // channels
+    bytes.extend_from_slice(&sample_rate.to_le_bytes());
+    bytes.extend_from_slice(&(sample_rate * 4).to_le_bytes()); // byte rate
+    bytes.extend_from_slice(&4u16.to_le_bytes()); // block align
+    bytes.extend_from_slice(&32u16.to_le_bytes()); // bits per sample
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_len.to_le_bytes());
+    for s in samples {
+        bytes.extend_from_slice(&s.to_le_bytes());
+    }
+    std::fs::write(path, bytes)
+}
+
+/// Read a mono WAV file holding 32-bit float or 16-bit PCM samples.
+pub fn read_wav(path: impl AsRef<Path>) -> io::Result<Vec<f32>> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+    let bytes = std::fs::read(path)?;
+    if bytes.len() < 12 || &bytes[..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err(invalid("not a RIFF/WAVE file"));
+    }
+    let u16_at = |at: usize| u16::from_le_bytes([bytes[at], bytes[at + 1]]);
+    let mut format = None;
+    let mut pos = 12;
+    while pos + 8 <= bytes.len() {
+        let id = &bytes[pos..pos + 4];
+        let len = u32::from_le_bytes(bytes[pos + 4..pos + 8].try_into().unwrap()) as usize;
+        let body = pos + 8;
+        let end = body
+            .checked_add(len)
+            .filter(|&end| end <= bytes.len())
+            .ok_or_else(|| invalid("truncated chunk"))?;
+        match id {
+            b"fmt " if len >= 16 => {
+                format = Some((u16_at(body), u16_at(body + 2), u16_at(body + 14)));
+            }
+            b"data" => {
+                let data = &bytes[body..end];
+                return match format {
+                    Some((WAV_FORMAT_FLOAT, 1, 32)) => Ok(data
+                        .chunks_exact(4)
+                        .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+                        .collect()),
+                    Some((WAV_FORMAT_PCM, 1, 16)) => Ok(data
+                        .chunks_exact(2)
+                        .map(|b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0)
+                        .collect()),
+                    Some(_) => Err(invalid("only mono 32-bit float or 16-bit PCM is supported")),
+                    None => Err(invalid("data chunk before fmt chunk")),
+                };
+            }
+            _ => {}
+        }
+        // Chunks are padded to an even length.
+        pos = end + (len & 1);
+    }
+    Err(invalid("no data chunk"))
+}
+
+const WAV_FORMAT_PCM: u16 = 1;
+const WAV_FORMAT_FLOAT: u16 = 3;
@@ -0,0 +1,185 @@
+//! Musical clock for tempo-synced processing.
+//!
+//! The runtime owns one [`Transport`] and advances it by every frame it
+//! renders while playing. `ControlMsg::SetTempo`, `ControlMsg::Start` and
+//! `ControlMsg::Stop` drive it; like any control message they can land
+//! mid-block through `RuntimeControl::send_at`.
+//!
+//! The position is kept both in samples and in beats. A beat is a quarter
+//! note. Beats accumulate at whatever tempo was in force, so a tempo change
+//! never makes the beat position jump.
+//!
+//! [`NoteDivision`] expresses times as note values; the runtime converts
+//! them to samples against the current tempo (see
+//! `ControlMsg::SetSyncedTime`).
+
+#![forbid(unsafe_code)]
+
+/// Tempo, play state and position.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transport {
+    tempo: f32,
+    playing: bool,
+    position: u64,
+    beats: f64,
+}
+
+impl Default for Transport {
+    /// Stopped at the start, at 120 BPM.
+    fn default() -> Self {
+        Self::new(120.0)
+    }
+}
+
+impl Transport {
+    /// A stopped transport at the start, at `tempo` beats per minute.
+    pub fn new(tempo: f32) -> Self {
+        let mut transport = Self {
+            tempo: 120.0,
+            playing: false,
+            position: 0,
+            beats: 0.0,
+        };
+        transport.set_tempo(tempo);
+        transport
+    }
+
+    /// Beats per minute.
+    pub fn tempo(&self) -> f32 {
+        self.tempo
+    }
+
+    /// Change the tempo from here on. Tempos that are not positive and
+    /// finite are ignored.
+    pub fn set_tempo(&mut self, bpm: f32) {
+        if bpm.is_finite() && bpm > 0.0 {
+            self.tempo = bpm;
+        }
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// Start moving from the current position.
+    pub fn start(&mut self) {
+        self.playing = true;
+    }
+
+    /// Stop moving; the position is kept.
+    pub fn stop(&mut self) {
+        self.playing = false;
+    }
+
+    /// Samples played since the start.
+    pub fn position_samples(&self) -> u64 {
+        self.position
+    }
+
+    /// Beats played since the start.
+    pub fn position_beats(&self) -> f64 {
+        self.beats
+    }
+
+    /// Length of one beat at the current tempo.
+    pub fn samples_per_beat(&self, sample_rate: f32) -> f64 {
+        60.0 * sample_rate as f64 / self.tempo as f64
+    }
+
+    /// Move on by `frames` rendered frames if playing. RT-safe.
+    pub fn advance(&mut self, frames: usize, sample_rate: f32) {
+        if self.playing {
+            self.position += frames as u64;
+            self.beats += frames as f64 / self.samples_per_beat(sample_rate);
+        }
+    }
+}
+
+/// A note value such as 1/8 or a dotted 1/4.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NoteDivision {
+    beats: f32,
+}
+
+impl NoteDivision {
+    /// `numerator / denominator` of a whole note: `new(1, 8)` is an eighth
+    /// note, `new(3, 4)` three quarter notes.
+    pub fn new(numerator: u32, denominator: u32) -> Self {
+        Self {
+            beats: 4.0 * numerator as f32 / denominator.max(1) as f32,
+        }
+    }
+
+    /// Half as long again.
+    pub fn dotted(self) -> Self {
+        Self {
+            beats: self.beats * 1.5,
+        }
+    }
+
+    /// Two thirds as long: three fit where two did.
+    pub fn triplet(self) -> Self {
+        Self {
+            beats: self.beats * 2.0 / 3.0,
+        }
+    }
+
+    /// Length in beats (quarter notes).
+    pub fn beats(self) -> f32 {
+        self.beats
+    }
+
+    /// Length in samples at the tempo of `transport`.
+    pub fn samples(self, transport: &Transport, sample_rate: f32) -> f32 {
+        (self.beats as f64 * transport.samples_per_beat(sample_rate)) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_moves_while_playing() {
+        let mut transport = Transport::new(120.0);
+        transport.advance(48000, 48000.0);
+        assert_eq!(transport.position_samples(), 0);
+
+        transport.start();
+        transport.advance(48000, 48000.0);
+        assert_eq!(transport.position_samples(), 48000);
+        assert_eq!(transport.position_beats(), 2.0);
+
+        transport.stop();
+        transport.advance(48000, 48000.0);
+        assert_eq!(transport.position_samples(), 48000);
+    }
+
+    #[test]
+    fn beats_follow_tempo_changes() {
+        let mut transport = Transport::new(60.0);
+        transport.start();
+        transport.advance(44100, 44100.0);
+        transport.set_tempo(180.0);
+        transport.set_tempo(-1.0);
+        transport.set_tempo(f32::NAN);
+        assert_eq!(transport.tempo(), 180.0);
+        transport.advance(44100, 44100.0);
+        assert!((transport.position_beats() - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn note_divisions_measure_in_quarter_notes() {
+        assert_eq!(NoteDivision::new(1, 4).beats(), 1.0);
+        assert_eq!(NoteDivision::new(1, 8).beats(), 0.5);
+        assert_eq!(NoteDivision::new(1, 4).dotted().beats(), 1.5);
+        assert!((NoteDivision::new(1, 4).triplet().beats() - 2.0 / 3.0).abs() < 1e-6);
+        // 120 BPM at 48 kHz: 24000 samples per beat.
+        let transport = Transport::new(120.0);
+        assert_eq!(
+            NoteDivision::new(1, 8).samples(&transport, 48000.0),
+            12000.0
+        );
+    }
+}
@@ -0,0 +1,215 @@
+//! Voice allocation for polyphonic graphs.
+//!
+//! [`VoiceAllocator`] maps notes onto a fixed pool of voices. When every
+//! voice is busy, a [`VoiceStealPolicy`] picks the one to reuse. Decisions
+//! depend only on the allocator's state, with ties going to the lowest
+//! voice index, so the same note stream always yields the same assignment.
+//!
+//! All storage is allocated in [`VoiceAllocator::new`]; note handling is
+//! RT-safe.
+//!
+//! Voices instanced into a graph (see `GraphBuilder::instance_voices`) are
+//! recorded as [`VoiceNodes`], so `Runtime::voice_node` can turn a voice
+//! index from the allocator into the node a control message targets. The
+//! runtime keeps an allocator over those voices and plays
+//! `ControlMsg::NoteOn` / `NoteOff` on them directly.
+
+#![forbid(unsafe_code)]
+
+use crate::graph::NodeId;
+
+/// Role of a node within one instanced voice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LogicalNode {
+    /// The voice's sound source.
+    Oscillator,
+    /// The voice's filter.
+    Filter,
+    /// The envelope shaping the voice's amplitude; its output.
+    AmpEnv,
+}
+
+impl LogicalNode {
+    /// Number of roles, i.e. the length of [`VoiceNodes`].
+    pub const COUNT: usize = 3;
+}
+
+/// Node ids of one instanced voice, indexed by `LogicalNode as usize`.
+pub type VoiceNodes = [NodeId; LogicalNode::COUNT];
+
+/// Which busy voice to reuse when the pool is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VoiceStealPolicy {
+    /// The voice that started earliest.
+    #[default]
+    Oldest,
+    /// The voice with the lowest reported level (see
+    /// [`VoiceAllocator::set_level`]).
+    Quietest,
+    /// The voice playing the lowest note.
+    Lowest,
+}
+
+/// One slot in the voice pool.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Voice {
+    /// Note being played, or `None` when the voice is free.
+    pub note: Option<u8>,
+    /// Allocation stamp; larger means more recent.
+    pub started: u64,
+    /// Last level reported for the voice.
+    pub level: f32,
+}
+
+/// Fixed-size voice pool with a configurable steal policy.
+#[derive(Debug, Clone)]
+pub struct VoiceAllocator {
+    voices: Vec<Voice>,
+    policy: VoiceStealPolicy,
+    clock: u64,
+}
+
+impl VoiceAllocator {
+    /// Pool of `capacity` voices (at least one), stealing the oldest.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            voices: vec![
+                Voice {
+                    note: None,
+                    started: 0,
+                    level: 0.0,
+                };
+                capacity.max(1)
+            ],
+            policy: VoiceStealPolicy::default(),
+            clock: 0,
+        }
+    }
+
+    /// Choose how a voice is picked when the pool is full.
+    pub fn set_policy(&mut self, policy: VoiceStealPolicy) {
+        self.policy = policy;
+    }
+
+    /// Current steal policy.
+    pub fn policy(&self) -> VoiceStealPolicy {
+        self.policy
+    }
+
+    /// All voices, indexed by voice number.
+    pub fn voices(&self) -> &[Voice] {
+        &self.voices
+    }
+
+    /// Report a voice's current level (e.g. its envelope or RMS) for
+    /// [`VoiceStealPolicy::Quietest`].
+    pub fn set_level(&mut self, voice: usize, level: f32) {
+        if let Some(v) = self.voices.get_mut(voice) {
+            v.level = level;
+        }
+    }
+
+    /// Assign `note` to a voice and return its index.
+    ///
+    /// Uses the lowest free voice; if none is free, steals one according
+    /// to the policy.
+    pub fn note_on(&mut self, note: u8) -> usize {
+        let index = match self.voices.iter().position(|v| v.note.is_none()) {
+            Some(free) => free,
+            None => self.steal_candidate(),
+        };
+        self.clock += 1;
+        self.voices[index] = Voice {
+            note: Some(note),
+            started: self.clock,
+            level: 0.0,
+        };
+        index
+    }
+
+    /// Release the most recently started voice playing `note`.
+    pub fn note_off(&mut self, note: u8) -> Option<usize> {
+        let index = self
+            .voices
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| v.note == Some(note))
+            .max_by_key(|(_, v)| v.started)
+            .map(|(i, _)| i)?;
+        self.voices[index].note = None;
+        Some(index)
+    }
+
+    /// Release every voice.
+    pub fn release_all(&mut self) {
+        for voice in &mut self.voices {
+            voice.note = None;
+        }
+    }
+
+    /// Voice the policy would steal next (the first of equals wins).
+    fn steal_candidate(&self) -> usize {
+        let better = |a: &Voice, b: &Voice| match self.policy {
+            VoiceStealPolicy::Oldest => a.started < b.started,
+            VoiceStealPolicy::Quietest => a.level < b.level,
+            VoiceStealPolicy::Lowest => a.note < b.note,
+        };
+        let mut best = 0;
+        for (i, voice) in self.voices.iter().enumerate().skip(1) {
+            if better(voice, &self.voices[best]) {
+                best = i;
+            }
+        }
+        best
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quietest_voice_is_stolen() {
+        let mut alloc = VoiceAllocator::new(3);
+        alloc.set_policy(VoiceStealPolicy::Quietest);
+        for (note, level) in [(60, 0.8), (64, 0.1), (67, 0.5)] {
+            let voice = alloc.note_on(note);
+            alloc.set_level(voice, level);
+        }
+
+        let stolen = alloc.note_on(72);
+        assert_eq!(stolen, 1, "voice 1 (level 0.1) is the quietest");
+        assert_eq!(alloc.voices()[1].note, Some(72));
+        assert_eq!(alloc.voices()[0].note, Some(60));
+        assert_eq!(alloc.voices()[2].note, Some(67));
+    }
+
+    #[test]
+    fn policies_are_deterministic() {
+        let run = |policy| {
+            let mut alloc = VoiceAllocator::new(2);
+            alloc.set_policy(policy);
+            [64, 60, 67, 55, 70]
+                .into_iter()
+                .map(|note| alloc.note_on(note))
+                .collect::<Vec<_>>()
+        };
+        // Oldest: round-robin once full.
+        assert_eq!(run(VoiceStealPolicy::Oldest), vec![0, 1, 0, 1, 0]);
+        // Lowest: always replaces whichever voice holds the lowest note.
+        assert_eq!(run(VoiceStealPolicy::Lowest), vec![0, 1, 1, 0, 0]);
+        // Quietest with equal levels: ties go to the lowest index.
+        assert_eq!(run(VoiceStealPolicy::Quietest), vec![0, 1, 0, 0, 0]);
+        assert_eq!(run(VoiceStealPolicy::Oldest), run(VoiceStealPolicy::Oldest));
+    }
+
+    #[test]
+    fn note_off_frees_voice_for_reuse() {
+        let mut alloc = VoiceAllocator::new(2);
+        alloc.note_on(60);
+        alloc.note_on(62);
+        assert_eq!(alloc.note_off(60), Some(0));
+        assert_eq!(alloc.note_off(60), None);
+        assert_eq!(alloc.note_on(65), 0);
+    }
+}
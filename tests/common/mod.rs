@@ -0,0 +1,28 @@
+//! Helpers shared by the integration tests. Each test crate uses its own
+//! subset.
+#![allow(dead_code)]
+
+use auxide::graph::{Edge, NodeId, PortId, Rate};
+
+/// An audio edge from output 0 of `from` to input 0 of `to`.
+pub fn edge(from: NodeId, to: NodeId) -> Edge {
+    port_edge(from, 0, to, 0)
+}
+
+/// An audio edge from output 0 of `from` to input `to_port` of `to`.
+pub fn edge_to(from: NodeId, to: NodeId, to_port: usize) -> Edge {
+    port_edge(from, 0, to, to_port)
+}
+
+/// An audio edge from output `from_port` of `from` to input `to_port` of
+/// `to`.
+pub fn port_edge(from: NodeId, from_port: usize, to: NodeId, to_port: usize) -> Edge {
+    Edge {
+        from_node: from,
+        from_port: PortId(from_port),
+        to_node: to,
+        to_port: PortId(to_port),
+        rate: Rate::Audio,
+        muted: false,
+    }
+}
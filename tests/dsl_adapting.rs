@@ -0,0 +1,112 @@
+use auxide::dsl::GraphBuilder;
+use auxide::graph::{NodeType, Port, PortId, Rate, CONTROL_RATE_DIVISOR};
+use auxide::node::{NodeDef, ProcessCtx};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+
+const BLOCK: usize = 64;
+
+static CONTROL_IN: [Port; 1] = [Port {
+    id: PortId(0),
+    rate: Rate::Control,
+}];
+
+static AUDIO_OUT: [Port; 1] = [Port {
+    id: PortId(0),
+    rate: Rate::Audio,
+}];
+
+/// Outputs its control input as audio, each value held for its segment, so
+/// the control values can be observed.
+struct ControlProbe;
+
+impl NodeDef for ControlProbe {
+    type State = ();
+
+    fn input_ports(&self) -> &'static [Port] {
+        &CONTROL_IN
+    }
+
+    fn output_ports(&self) -> &'static [Port] {
+        &AUDIO_OUT
+    }
+
+    fn required_inputs(&self) -> usize {
+        1
+    }
+
+    fn init_state(&self, _sample_rate: f32, _block_size: usize) -> Self::State {}
+
+    fn process(
+        &self,
+        _state: &mut Self::State,
+        inputs: &[&[f32]],
+        outputs: &mut [&mut [f32]],
+        _ctx: &ProcessCtx,
+    ) -> Result<(), &'static str> {
+        for (out, &value) in outputs[0].chunks_mut(CONTROL_RATE_DIVISOR).zip(inputs[0]) {
+            out.fill(value);
+        }
+        Ok(())
+    }
+}
+
+fn render(graph: &auxide::graph::Graph, frames: usize) -> Vec<f32> {
+    let plan = Plan::compile(graph, BLOCK).unwrap();
+    let mut runtime = Runtime::new(plan, graph, 44100.0);
+    render_offline(&mut runtime, frames).unwrap()
+}
+
+#[test]
+fn connect_adapting_inserts_audio_to_control_adapter() {
+    let mut builder = GraphBuilder::new();
+    let lfo = builder.node(NodeType::SineOsc { freq: 20.0 });
+    let probe = builder.node(NodeType::external(ControlProbe));
+    let sink = builder.node(NodeType::OutputSink);
+
+    // A plain connect refuses the audio -> control edge.
+    assert!(builder
+        .connect(lfo, PortId(0), probe, PortId(0), Rate::Audio)
+        .is_err());
+
+    let adapter = builder
+        .connect_adapting(lfo, PortId(0), probe, PortId(0))
+        .unwrap()
+        .expect("rates differ, so an adapter is inserted");
+    // Matching rates connect directly.
+    assert!(builder
+        .connect_adapting(probe, PortId(0), sink, PortId(0))
+        .unwrap()
+        .is_none());
+    let graph = builder.build().unwrap();
+
+    assert_eq!(graph.nodes.len(), 4);
+    let adapter_data = graph.nodes[adapter.0 .0].as_ref().unwrap();
+    assert!(matches!(adapter_data.node_type, NodeType::AudioToControl));
+    assert_eq!(
+        graph.edges_from_port(lfo.0, PortId(0))[0].to_node,
+        adapter.0
+    );
+    assert_eq!(
+        graph.edges_into_port(probe.0, PortId(0))[0].rate,
+        Rate::Control
+    );
+
+    // Each control value is the mean of the LFO's audio over its segment.
+    let frames = BLOCK * 40;
+    let control = render(&graph, frames);
+    let mut reference = GraphBuilder::new();
+    let lfo = reference.node(NodeType::SineOsc { freq: 20.0 });
+    let sink = reference.node(NodeType::OutputSink);
+    reference
+        .connect(lfo, PortId(0), sink, PortId(0), Rate::Audio)
+        .unwrap();
+    let audio = render(&reference.build().unwrap(), frames);
+
+    let segment = CONTROL_RATE_DIVISOR;
+    for (c, a) in control.chunks(segment).zip(audio.chunks(segment)) {
+        let mean = a.iter().sum::<f32>() / segment as f32;
+        assert!(c.iter().all(|&v| (v - mean).abs() < 1e-6));
+    }
+    assert!(control.iter().any(|&v| v > 0.5) && control.iter().any(|&v| v < -0.5));
+}
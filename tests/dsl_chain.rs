@@ -0,0 +1,41 @@
+use auxide::dsl::GraphBuilder;
+use auxide::graph::{NodeType, Rate};
+
+#[test]
+fn chain_and_then_connect_in_series() {
+    let mut builder = GraphBuilder::new();
+    let osc = builder.node(NodeType::SineOsc { freq: 440.0 });
+    let gain = builder.node(NodeType::Gain { gain: 0.5 });
+    let sink = builder.node(NodeType::OutputSink);
+    builder.chain(&[osc, gain, sink]).unwrap();
+    let chained = builder.build().unwrap();
+
+    let mut builder = GraphBuilder::new();
+    let osc = builder.node(NodeType::SineOsc { freq: 440.0 });
+    let sink = builder
+        .from(osc)
+        .then_new(NodeType::Gain { gain: 0.5 })
+        .unwrap()
+        .then_new(NodeType::OutputSink)
+        .unwrap()
+        .end();
+    let fluent = builder.build().unwrap();
+
+    assert_eq!(chained.edges, fluent.edges);
+    assert_eq!(fluent.edges.len(), 2);
+    assert_eq!(fluent.edges[1].to_node, sink.0);
+}
+
+#[test]
+fn rate_follows_the_source_port() {
+    let mut builder = GraphBuilder::new();
+    let osc = builder.node(NodeType::SineOsc { freq: 440.0 });
+    let down = builder.node(NodeType::AudioToControl);
+    let gain = builder.node(NodeType::Gain { gain: 1.0 });
+    builder.chain(&[osc, down, gain]).unwrap();
+    let graph = builder.build().unwrap();
+    assert_eq!(graph.edges[1].rate, Rate::Control);
+    // Control into the gain's audio input is upsampled.
+    assert_eq!(graph.edges.len(), 3);
+    assert!(graph.validate().is_ok());
+}
@@ -0,0 +1,45 @@
+use auxide::dsl::{DslError, GraphBuilder};
+use auxide::graph::NodeType;
+use auxide::patch::PatchError;
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+
+#[test]
+fn parsed_patch_renders_and_keeps_labels() {
+    let builder = GraphBuilder::parse("osc: sine(440); g: gain(0.5); osc -> g -> out;").unwrap();
+    let gain = builder.get_node_by_name("g").unwrap();
+    let graph = builder.build().unwrap();
+    assert!(matches!(
+        graph.nodes[gain.0].as_ref().unwrap().node_type,
+        NodeType::Gain { gain } if gain == 0.5
+    ));
+    assert_eq!(graph.edges.len(), 2);
+
+    let plan = Plan::compile(&graph, 64).unwrap();
+    let out = render_offline(&mut Runtime::new(plan, &graph, 48000.0), 256).unwrap();
+    assert!(out.iter().any(|&x| x != 0.0));
+    assert!(out.iter().all(|&x| x.abs() <= 0.5 + 1e-6));
+}
+
+#[test]
+fn errors_point_at_the_offending_element() {
+    let err = GraphBuilder::parse("osc: sine(440)\nosc -> reverb(2) -> out").unwrap_err();
+    assert_eq!(
+        err,
+        DslError::Parse {
+            line: 2,
+            column: 8,
+            error: PatchError::UnknownNode("reverb".to_string()),
+        }
+    );
+
+    let err = GraphBuilder::parse("g: gain(0.5);  g -> sine(x)").unwrap_err();
+    assert_eq!(
+        err,
+        DslError::Parse {
+            line: 1,
+            column: 21,
+            error: PatchError::BadArgs("sine(x)".to_string()),
+        }
+    );
+}
@@ -0,0 +1,50 @@
+use auxide::control::ControlMsg;
+use auxide::dsl::GraphBuilder;
+use auxide::graph::{NodeType, PortId, Rate};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+
+const SAMPLE_RATE: f32 = 48000.0;
+// 10 ms windows.
+const WINDOW: usize = 480;
+
+fn rms(signal: &[f32]) -> f32 {
+    (signal.iter().map(|s| s * s).sum::<f32>() / signal.len() as f32).sqrt()
+}
+
+#[test]
+fn subtractive_voice_plays_and_decays() {
+    let mut builder = GraphBuilder::new();
+    let voice = builder.subtractive_voice(220.0);
+    let sink = builder.node(NodeType::OutputSink);
+    builder
+        .connect(voice.amp, PortId(0), sink, PortId(0), Rate::Audio)
+        .unwrap();
+    let graph = builder.build().unwrap();
+    let plan = Plan::compile(&graph, WINDOW).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, SAMPLE_RATE);
+
+    // Closed gate: silence.
+    let idle = render_offline(&mut runtime, WINDOW * 5).unwrap();
+    assert!(idle.iter().all(|&s| s == 0.0));
+
+    // Note on: attack and decay settle on the sustain level.
+    runtime.apply_control(ControlMsg::TriggerGate {
+        node: voice.amp.0,
+        on: true,
+    });
+    let held = render_offline(&mut runtime, WINDOW * 30).unwrap();
+    let sustained = rms(&held[WINDOW * 25..]);
+    assert!(sustained > 0.1, "sustain rms {}", sustained);
+
+    // Note off: the 200 ms release fades out window by window.
+    runtime.apply_control(ControlMsg::TriggerGate {
+        node: voice.amp.0,
+        on: false,
+    });
+    let released = render_offline(&mut runtime, WINDOW * 30).unwrap();
+    let levels: Vec<f32> = released.chunks(WINDOW).map(rms).collect();
+    assert!(levels[0] < sustained);
+    assert!(levels[..15].windows(2).all(|w| w[1] < w[0]), "{:?}", levels);
+    assert!(levels[20..].iter().all(|&l| l == 0.0));
+}
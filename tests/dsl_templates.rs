@@ -0,0 +1,67 @@
+use auxide::dsl::{DslError, GraphBuilder};
+use auxide::graph::{NodeType, PortId, Rate};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+
+/// gain(0.5) -> filter, exposing the gain's input and the filter's output.
+fn define_voice(builder: &mut GraphBuilder) {
+    let mut voice = GraphBuilder::new();
+    let gain = voice.node(NodeType::Gain { gain: 0.5 });
+    let filter = voice.node(NodeType::Filter {
+        cutoff: 2000.0,
+        q: 0.707,
+    });
+    voice.chain(&[gain, filter]).unwrap();
+    builder
+        .define_template("voice", voice, &[(gain, PortId(0))], &[(filter, PortId(0))])
+        .unwrap();
+}
+
+#[test]
+fn instances_are_independent_copies() {
+    let mut builder = GraphBuilder::new();
+    define_voice(&mut builder);
+    let osc = builder.node(NodeType::SineOsc { freq: 440.0 });
+    let mix = builder.node(NodeType::Mix);
+    let sink = builder.node(NodeType::OutputSink);
+    let a = builder.instantiate("voice").unwrap();
+    let b = builder.instantiate("voice").unwrap();
+    assert_ne!(a.inputs[0].0 .0, b.inputs[0].0 .0);
+    assert_eq!(a.nodes.len(), 2);
+
+    for (port, voice) in [&a, &b].into_iter().enumerate() {
+        let (input, in_port) = voice.inputs[0];
+        let (output, out_port) = voice.outputs[0];
+        builder
+            .connect(osc, PortId(0), input, in_port, Rate::Audio)
+            .unwrap();
+        builder
+            .connect(output, out_port, mix, PortId(port), Rate::Audio)
+            .unwrap();
+    }
+    builder.chain(&[mix, sink]).unwrap();
+    let graph = builder.build().unwrap();
+    // 3 nodes, 2 per instance; 1 edge inside each, 5 outside.
+    assert_eq!(graph.nodes.len(), 7);
+    assert_eq!(graph.edges.len(), 7);
+
+    let plan = Plan::compile(&graph, 64).unwrap();
+    let out = render_offline(&mut Runtime::new(plan, &graph, 48000.0), 512).unwrap();
+    assert!(out.iter().any(|&x| x != 0.0));
+}
+
+#[test]
+fn unknown_templates_and_ports_fail() {
+    let mut builder = GraphBuilder::new();
+    assert_eq!(
+        builder.instantiate("voice").unwrap_err(),
+        DslError::UnknownTemplate("voice".to_string())
+    );
+
+    let mut template = GraphBuilder::new();
+    let gain = template.node(NodeType::Gain { gain: 1.0 });
+    assert_eq!(
+        builder.define_template("bad", template, &[(gain, PortId(3))], &[]),
+        Err(DslError::UnboundPort)
+    );
+}
@@ -0,0 +1,74 @@
+use auxide::control::ControlMsg;
+use auxide::dsl::GraphBuilder;
+use auxide::graph::{NodeType, PortId, Rate};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+use auxide::voice::LogicalNode;
+
+const SAMPLE_RATE: f32 = 48000.0;
+
+fn three_voices() -> (Runtime, Vec<auxide::dsl::VoiceHandles>) {
+    let mut builder = GraphBuilder::new();
+    let (voices, out) = builder.subtractive_voices(3, 220.0);
+    let sink = builder.node(NodeType::OutputSink);
+    builder
+        .connect(out, PortId(0), sink, PortId(0), Rate::Audio)
+        .unwrap();
+    let graph = builder.build().unwrap();
+    let plan = Plan::compile(&graph, 256).unwrap();
+    (Runtime::new(plan, &graph, SAMPLE_RATE), voices)
+}
+
+#[test]
+fn voice_node_maps_voice_and_role_to_node() {
+    let (runtime, voices) = three_voices();
+    for (index, voice) in voices.iter().enumerate() {
+        assert_eq!(
+            runtime.voice_node(index, LogicalNode::Oscillator),
+            Some(voice.osc.0)
+        );
+        assert_eq!(
+            runtime.voice_node(index, LogicalNode::Filter),
+            Some(voice.filter.0)
+        );
+        assert_eq!(
+            runtime.voice_node(index, LogicalNode::AmpEnv),
+            Some(voice.amp.0)
+        );
+    }
+    assert_eq!(runtime.voice_node(3, LogicalNode::Oscillator), None);
+
+    // Instancing is deterministic: rebuilding gives the same ids.
+    let (again, _) = three_voices();
+    for voice in 0..3 {
+        assert_eq!(
+            again.voice_node(voice, LogicalNode::Oscillator),
+            runtime.voice_node(voice, LogicalNode::Oscillator)
+        );
+    }
+}
+
+#[test]
+fn set_frequency_reaches_voice_one_only() {
+    let (mut runtime, _) = three_voices();
+    let osc = runtime.voice_node(1, LogicalNode::Oscillator).unwrap();
+    let amp = runtime.voice_node(1, LogicalNode::AmpEnv).unwrap();
+    runtime.apply_control(ControlMsg::SetFrequency {
+        node: osc,
+        hz: 600.0,
+    });
+    runtime.apply_control(ControlMsg::TriggerGate {
+        node: amp,
+        on: true,
+    });
+
+    // Only voice 1 is gated, so its pitch is the output's pitch.
+    let out = render_offline(&mut runtime, SAMPLE_RATE as usize).unwrap();
+    let settled = &out[SAMPLE_RATE as usize / 2..];
+    let crossings = settled
+        .windows(2)
+        .filter(|w| w[0] < 0.0 && w[1] >= 0.0)
+        .count();
+    // Half a second at 600 Hz.
+    assert!((295..=305).contains(&crossings), "{} crossings", crossings);
+}
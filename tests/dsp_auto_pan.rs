@@ -0,0 +1,87 @@
+mod common;
+
+use auxide::control::ControlMsg;
+use auxide::graph::{Graph, NodeId, NodeType};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+use common::{edge, port_edge};
+
+const SAMPLE_RATE: f32 = 44100.0;
+// One period of the 441 Hz carrier per window.
+const WINDOW: usize = 100;
+
+/// (left, right) outputs of an auto-panned sine.
+fn render(rate_hz: f32, depth: f32, msgs: &[ControlMsg], frames: usize) -> (Vec<f32>, Vec<f32>) {
+    let channel = |out_port: usize| {
+        let mut graph = Graph::new();
+        let osc = graph.add_node(NodeType::SineOsc { freq: 441.0 });
+        let pan = graph.add_node(NodeType::AutoPan { rate_hz, depth });
+        let sink = graph.add_node(NodeType::OutputSink);
+        graph.add_edge(edge(osc, pan)).unwrap();
+        graph.add_edge(port_edge(pan, out_port, sink, 0)).unwrap();
+        let plan = Plan::compile(&graph, WINDOW).unwrap();
+        let mut runtime = Runtime::new(plan, &graph, SAMPLE_RATE);
+        for &msg in msgs {
+            runtime.apply_control(msg);
+        }
+        render_offline(&mut runtime, frames).unwrap()
+    };
+    (channel(0), channel(1))
+}
+
+/// Per-window RMS envelope with its mean removed.
+fn envelope(signal: &[f32]) -> Vec<f32> {
+    let env: Vec<f32> = signal
+        .chunks(WINDOW)
+        .map(|w| (w.iter().map(|s| s * s).sum::<f32>() / w.len() as f32).sqrt())
+        .collect();
+    let mean = env.iter().sum::<f32>() / env.len() as f32;
+    env.iter().map(|e| e - mean).collect()
+}
+
+fn upward_crossings(env: &[f32]) -> usize {
+    env.windows(2).filter(|w| w[0] < 0.0 && w[1] >= 0.0).count()
+}
+
+#[test]
+fn auto_pan_swings_in_anti_phase() {
+    // Two seconds at 2 Hz: four full LFO cycles.
+    let (left, right) = render(2.0, 1.0, &[], 2 * SAMPLE_RATE as usize);
+    let (env_l, env_r) = (envelope(&left), envelope(&right));
+
+    let dot: f32 = env_l.iter().zip(&env_r).map(|(l, r)| l * r).sum();
+    let norm_l = env_l.iter().map(|l| l * l).sum::<f32>().sqrt();
+    let norm_r = env_r.iter().map(|r| r * r).sum::<f32>().sqrt();
+    let correlation = dot / (norm_l * norm_r);
+    assert!(correlation < -0.9, "correlation {}", correlation);
+
+    assert_eq!(upward_crossings(&env_l), 4);
+    assert_eq!(upward_crossings(&env_r), 4);
+}
+
+#[test]
+fn auto_pan_zero_depth_is_centered() {
+    let (left, right) = render(2.0, 0.0, &[], SAMPLE_RATE as usize);
+    assert_eq!(left, right);
+    assert!(left.iter().any(|&s| s != 0.0));
+}
+
+#[test]
+fn auto_pan_set_param() {
+    let node = NodeId(1, 0);
+    let msgs = [
+        ControlMsg::SetParam {
+            node,
+            param_idx: 0,
+            value: 4.0,
+        },
+        ControlMsg::SetParam {
+            node,
+            param_idx: 1,
+            value: 1.0,
+        },
+    ];
+    let (left, right) = render(0.5, 0.0, &msgs, SAMPLE_RATE as usize);
+    assert_eq!(upward_crossings(&envelope(&left)), 4);
+    assert_ne!(left, right);
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use auxide::control::ControlMsg;
+use auxide::dsp::{BinauralPanner, BINAURAL_MAX_ILD_DB};
+use auxide::graph::{Graph, NodeId, NodeType};
+use auxide::plan::Plan;
+use auxide::rt::Runtime;
+use common::{edge, port_edge};
+
+const SAMPLE_RATE: f32 = 44100.0;
+const BLOCK: usize = 128;
+
+/// One channel of the binaural response to a unit impulse.
+fn impulse_response(azimuth: f32, msgs: &[ControlMsg], out_port: usize) -> Vec<f32> {
+    let mut graph = Graph::new();
+    let input = graph.add_node(NodeType::AudioInput {
+        label: "in".to_string(),
+        channel: 0,
+    });
+    let binaural = graph.add_node(NodeType::Binaural { azimuth });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(input, binaural)).unwrap();
+    graph
+        .add_edge(port_edge(binaural, out_port, sink, 0))
+        .unwrap();
+    let plan = Plan::compile(&graph, BLOCK).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, SAMPLE_RATE);
+    for &msg in msgs {
+        runtime.apply_control(msg);
+    }
+    runtime.set_input(input, &[1.0]).unwrap();
+    let mut out = vec![0.0; BLOCK];
+    runtime.process_block(&mut out).unwrap();
+    out
+}
+
+/// (arrival time in samples, energy) of an impulse response.
+fn arrival_and_energy(response: &[f32]) -> (f32, f32) {
+    let energy: f32 = response.iter().map(|s| s * s).sum();
+    let centroid = response
+        .iter()
+        .enumerate()
+        .map(|(n, s)| n as f32 * s * s)
+        .sum::<f32>()
+        / energy;
+    (centroid, energy)
+}
+
+#[test]
+fn source_at_90_degrees_reaches_right_ear_first_and_louder() {
+    let (left_t, left_e) = arrival_and_energy(&impulse_response(90.0, &[], 0));
+    let (right_t, right_e) = arrival_and_energy(&impulse_response(90.0, &[], 1));
+
+    // Woodworth: r / c * (pi/2 + 1) is about 0.656 ms, ~28.9 samples.
+    let expected_itd = 0.0875 / 343.0 * (std::f32::consts::FRAC_PI_2 + 1.0) * SAMPLE_RATE;
+    assert!((expected_itd - BinauralPanner::max_itd(SAMPLE_RATE)).abs() < 1e-3);
+    assert_eq!(right_t, 0.0);
+    assert!(
+        (left_t - right_t - expected_itd).abs() < 0.5,
+        "itd {} samples, expected {}",
+        left_t - right_t,
+        expected_itd
+    );
+
+    // The fractional delay spreads the far-ear impulse over two samples,
+    // which only lowers its energy and peak: both bound the ILD from below.
+    let ild_db = 10.0 * (right_e / left_e).log10();
+    assert!(ild_db >= BINAURAL_MAX_ILD_DB, "ild {} dB", ild_db);
+    let left_peak = impulse_response(90.0, &[], 0)
+        .iter()
+        .fold(0.0f32, |m, s| m.max(s.abs()));
+    assert!(left_peak <= 10f32.powf(-BINAURAL_MAX_ILD_DB / 20.0) + 1e-6);
+}
+
+#[test]
+fn front_is_centered_and_left_mirrors_right() {
+    let left = impulse_response(0.0, &[], 0);
+    let right = impulse_response(0.0, &[], 1);
+    assert_eq!(left, right);
+    assert_eq!(left[0], 1.0);
+
+    assert_eq!(
+        impulse_response(-90.0, &[], 0),
+        impulse_response(90.0, &[], 1)
+    );
+    assert_eq!(
+        impulse_response(-90.0, &[], 1),
+        impulse_response(90.0, &[], 0)
+    );
+}
+
+#[test]
+fn set_param_moves_the_source() {
+    let moved = [ControlMsg::SetParam {
+        node: NodeId(1, 0),
+        param_idx: 0,
+        value: 90.0,
+    }];
+    assert_eq!(
+        impulse_response(0.0, &moved, 0),
+        impulse_response(90.0, &[], 0)
+    );
+}
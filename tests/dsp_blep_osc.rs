@@ -0,0 +1,81 @@
+mod common;
+
+use auxide::control::ControlMsg;
+use auxide::graph::{Graph, NodeId, NodeType};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+use common::edge;
+
+const SAMPLE_RATE: f32 = 48000.0;
+
+fn runtime(node_type: NodeType) -> (Runtime, NodeId) {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(node_type);
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, sink)).unwrap();
+    let plan = Plan::compile(&graph, 64).unwrap();
+    (Runtime::new(plan, &graph, SAMPLE_RATE), osc)
+}
+
+fn render(node_type: NodeType, frames: usize) -> Vec<f32> {
+    render_offline(&mut runtime(node_type).0, frames).unwrap()
+}
+
+fn mean(signal: &[f32]) -> f32 {
+    signal.iter().sum::<f32>() / signal.len() as f32
+}
+
+#[test]
+fn oscillators_hold_their_period_and_range() {
+    // 480 Hz: exactly 100 samples per cycle, so one second is 480 cycles.
+    for node_type in [
+        NodeType::SawOsc { freq: 480.0 },
+        NodeType::PulseOsc {
+            freq: 480.0,
+            width: 0.5,
+        },
+        NodeType::TriOsc { freq: 480.0 },
+    ] {
+        let name = node_type.type_name();
+        let out = render(node_type, SAMPLE_RATE as usize);
+        assert!(out.iter().all(|x| x.abs() <= 1.0), "{} out of range", name);
+        assert!(mean(&out).abs() < 0.01, "{} has DC", name);
+        let rising = out.windows(2).filter(|w| w[0] < 0.0 && w[1] >= 0.0).count();
+        assert!((479..=481).contains(&rising), "{}: {} cycles", name, rising);
+    }
+}
+
+#[test]
+fn pulse_width_sets_the_duty_cycle() {
+    let out = render(
+        NodeType::PulseOsc {
+            freq: 480.0,
+            width: 0.25,
+        },
+        4800,
+    );
+    // High a quarter of the time: mean of 0.25 * 1 + 0.75 * -1.
+    assert!((mean(&out) + 0.5).abs() < 0.02, "mean {}", mean(&out));
+}
+
+#[test]
+fn set_frequency_retunes_every_shape() {
+    for node_type in [
+        NodeType::SawOsc { freq: 480.0 },
+        NodeType::PulseOsc {
+            freq: 480.0,
+            width: 0.5,
+        },
+        NodeType::TriOsc { freq: 480.0 },
+    ] {
+        let name = node_type.type_name();
+        let (mut runtime, osc) = runtime(node_type);
+        runtime.apply_control(ControlMsg::SetFrequency {
+            node: osc,
+            hz: 960.0,
+        });
+        let out = render_offline(&mut runtime, 4800).unwrap();
+        let rising = out.windows(2).filter(|w| w[0] < 0.0 && w[1] >= 0.0).count();
+        assert!((95..=97).contains(&rising), "{}: {} cycles", name, rising);
+    }
+}
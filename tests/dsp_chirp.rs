@@ -0,0 +1,66 @@
+mod common;
+
+use auxide::graph::{Graph, NodeType};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+use common::edge;
+
+const SAMPLE_RATE: f32 = 48000.0;
+const START_HZ: f32 = 100.0;
+const END_HZ: f32 = 1600.0;
+const DURATION: usize = 480_000;
+
+fn render(log: bool) -> Vec<f32> {
+    let mut graph = Graph::new();
+    let chirp = graph.add_node(NodeType::Chirp {
+        start_hz: START_HZ,
+        end_hz: END_HZ,
+        duration_samples: DURATION,
+        log,
+    });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(chirp, sink)).unwrap();
+    let plan = Plan::compile(&graph, 256).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, SAMPLE_RATE);
+    render_offline(&mut runtime, DURATION + 1024).unwrap()
+}
+
+/// (position, frequency) estimated from successive upward zero crossings.
+fn instantaneous_freq(signal: &[f32]) -> Vec<(usize, f32)> {
+    let ups: Vec<usize> = (1..signal.len())
+        .filter(|&n| signal[n - 1] < 0.0 && signal[n] >= 0.0)
+        .collect();
+    ups.windows(2)
+        .map(|w| ((w[0] + w[1]) / 2, SAMPLE_RATE / (w[1] - w[0]) as f32))
+        .collect()
+}
+
+#[test]
+fn chirp_sweeps_configured_range() {
+    for log in [false, true] {
+        let out = render(log);
+        let freqs = instantaneous_freq(&out);
+        let (_, first) = freqs[0];
+        let (_, last) = *freqs.last().unwrap();
+        assert!((first / START_HZ - 1.0).abs() < 0.05, "start {} Hz", first);
+        assert!((last / END_HZ - 1.0).abs() < 0.05, "end {} Hz", last);
+        // Silent after the sweep.
+        assert!(out[DURATION..].iter().all(|&s| s == 0.0));
+    }
+}
+
+#[test]
+fn log_chirp_lingers_in_low_frequencies() {
+    // Time taken to pass the geometric centre of the range (400 Hz).
+    let centre = (START_HZ * END_HZ).sqrt();
+    let time_to_centre = |log: bool| {
+        let freqs = instantaneous_freq(&render(log));
+        let (pos, _) = freqs.iter().find(|&&(_, f)| f >= centre).unwrap();
+        *pos as f32 / DURATION as f32
+    };
+    let linear = time_to_centre(false);
+    let log = time_to_centre(true);
+    // Linear: (400 - 100) / 1500 = 20% of the sweep; log: exactly half.
+    assert!((linear - 0.2).abs() < 0.02, "linear {}", linear);
+    assert!((log - 0.5).abs() < 0.02, "log {}", log);
+}
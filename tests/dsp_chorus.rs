@@ -0,0 +1,94 @@
+mod common;
+
+use auxide::graph::{Graph, NodeId, NodeType};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+use common::edge;
+
+fn connect(graph: &mut Graph, from: NodeId, to: NodeId) {
+    graph.add_edge(edge(from, to)).unwrap();
+}
+
+fn render(chorus: Option<NodeType>, frames: usize) -> Vec<f32> {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 1000.0 });
+    let sink = graph.add_node(NodeType::OutputSink);
+    match chorus {
+        Some(node_type) => {
+            let chorus = graph.add_node(node_type);
+            connect(&mut graph, osc, chorus);
+            connect(&mut graph, chorus, sink);
+        }
+        None => connect(&mut graph, osc, sink),
+    }
+    let plan = Plan::compile(&graph, 256).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, 44100.0);
+    render_offline(&mut runtime, frames).unwrap()
+}
+
+fn block_rms(signal: &[f32], block: usize) -> Vec<f32> {
+    signal
+        .chunks(block)
+        .map(|c| (c.iter().map(|s| s * s).sum::<f32>() / c.len() as f32).sqrt())
+        .collect()
+}
+
+#[test]
+fn chorus_mix_zero_is_dry() {
+    let dry = render(None, 4096);
+    let wet = render(
+        Some(NodeType::Chorus {
+            rate_hz: 1.0,
+            depth_ms: 3.0,
+            mix: 0.0,
+            voices: 2,
+        }),
+        4096,
+    );
+    assert_eq!(dry, wet);
+}
+
+#[test]
+fn chorus_modulation_varies_comb_response() {
+    let frames = 44100;
+    let dry = render(None, frames);
+    let modulated = render(
+        Some(NodeType::Chorus {
+            rate_hz: 2.0,
+            depth_ms: 3.0,
+            mix: 0.5,
+            voices: 1,
+        }),
+        frames,
+    );
+    let static_comb = render(
+        Some(NodeType::Chorus {
+            rate_hz: 2.0,
+            depth_ms: 0.0,
+            mix: 0.5,
+            voices: 1,
+        }),
+        frames,
+    );
+
+    // Skip the first 20 ms while the delay line fills.
+    let settle = 1024;
+    let spread = |rms: &[f32]| {
+        let max = rms.iter().cloned().fold(f32::MIN, f32::max);
+        let min = rms.iter().cloned().fold(f32::MAX, f32::min);
+        max - min
+    };
+    let modulated_rms = block_rms(&modulated[settle..], 512);
+    let static_rms = block_rms(&static_comb[settle..], 512);
+    assert!(
+        spread(&modulated_rms) > 0.2,
+        "modulated delay should sweep the comb"
+    );
+    assert!(spread(&static_rms) < 0.02, "unmodulated comb is static");
+
+    // The dry component is preserved: removing the wet half leaves a delayed
+    // copy of the input, never more than half scale.
+    for (m, d) in modulated[settle..].iter().zip(&dry[settle..]) {
+        assert!((m - 0.5 * d).abs() <= 0.5 + 1e-4);
+    }
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use auxide::graph::{Graph, NodeType};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+use common::{edge, port_edge};
+
+const SAMPLE_RATE: f32 = 44100.0;
+const CROSSOVER_HZ: f32 = 1000.0;
+
+/// Which crossover output reaches the sink.
+enum Tap {
+    Dry,
+    Low,
+    High,
+    Sum,
+}
+
+fn render(freq: f32, tap: Tap, frames: usize) -> Vec<f32> {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq });
+    let sink = graph.add_node(NodeType::OutputSink);
+    let xover = graph.add_node(NodeType::Crossover { freq: CROSSOVER_HZ });
+    graph.add_edge(edge(osc, xover)).unwrap();
+    match tap {
+        Tap::Dry => graph.add_edge(edge(osc, sink)).unwrap(),
+        Tap::Low => graph.add_edge(edge(xover, sink)).unwrap(),
+        Tap::High => graph.add_edge(port_edge(xover, 1, sink, 0)).unwrap(),
+        Tap::Sum => {
+            let mix = graph.add_node(NodeType::Mix);
+            graph.add_edge(edge(xover, mix)).unwrap();
+            graph.add_edge(port_edge(xover, 1, mix, 1)).unwrap();
+            graph.add_edge(edge(mix, sink)).unwrap();
+        }
+    }
+    let plan = Plan::compile(&graph, 128).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, SAMPLE_RATE);
+    render_offline(&mut runtime, frames).unwrap()
+}
+
+/// RMS after the filters have settled.
+fn settled_rms(signal: &[f32]) -> f32 {
+    let tail = &signal[signal.len() / 2..];
+    (tail.iter().map(|s| s * s).sum::<f32>() / tail.len() as f32).sqrt()
+}
+
+#[test]
+fn crossover_bands_sum_to_input_level() {
+    for freq in [60.0, 400.0, CROSSOVER_HZ, 3000.0, 10000.0] {
+        let dry = settled_rms(&render(freq, Tap::Dry, 8192));
+        let sum = settled_rms(&render(freq, Tap::Sum, 8192));
+        assert!(
+            (sum / dry - 1.0).abs() < 0.02,
+            "{} Hz: bands sum to rms {} vs input {}",
+            freq,
+            sum,
+            dry
+        );
+    }
+}
+
+#[test]
+fn crossover_routes_low_sine_to_low_band() {
+    let low = settled_rms(&render(100.0, Tap::Low, 8192));
+    let high = settled_rms(&render(100.0, Tap::High, 8192));
+    assert!(low > 0.69, "low band should pass 100 Hz (rms {})", low);
+    assert!(high < low * 0.01, "high band leaks {} vs {}", high, low);
+
+    // At the crossover both bands sit 6 dB down.
+    let low = settled_rms(&render(CROSSOVER_HZ, Tap::Low, 8192));
+    let high = settled_rms(&render(CROSSOVER_HZ, Tap::High, 8192));
+    assert!((low - high).abs() < 0.01);
+    assert!((low - std::f32::consts::FRAC_1_SQRT_2 * 0.5).abs() < 0.01);
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use auxide::graph::{Graph, NodeId, NodeType};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+use common::edge;
+
+fn connect(graph: &mut Graph, from: NodeId, to: NodeId) {
+    graph.add_edge(edge(from, to)).unwrap();
+}
+
+fn render_sine(delay: Option<f32>, frames: usize) -> Vec<f32> {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let sink = graph.add_node(NodeType::OutputSink);
+    match delay {
+        Some(delay_samples) => {
+            let delay = graph.add_node(NodeType::Delay { delay_samples });
+            connect(&mut graph, osc, delay);
+            connect(&mut graph, delay, sink);
+        }
+        None => connect(&mut graph, osc, sink),
+    }
+    let plan = Plan::compile(&graph, 64).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, 44100.0);
+    render_offline(&mut runtime, frames).unwrap()
+}
+
+#[test]
+fn delay_shifts_across_blocks() {
+    let dry = render_sine(None, 256);
+    let wet = render_sine(Some(100.0), 256);
+    assert!(wet[..100].iter().all(|&s| s == 0.0));
+    for n in 100..256 {
+        assert!((wet[n] - dry[n - 100]).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn fractional_delay_interpolates_between_samples() {
+    let dry = render_sine(None, 256);
+    let wet = render_sine(Some(10.5), 256);
+    for n in 12..256 {
+        let expected = 0.5 * (dry[n - 10] + dry[n - 11]);
+        assert!((wet[n] - expected).abs() < 1e-5, "sample {}", n);
+    }
+}
+
+#[test]
+fn out_of_range_delays_render_clamped() {
+    let dry = render_sine(None, 256);
+    assert_eq!(render_sine(Some(f32::NAN), 256), dry);
+    // Capped at `DelayLine::MAX_DELAY`, far past the end of the render.
+    for delay in [f32::INFINITY, 1e12] {
+        assert!(render_sine(Some(delay), 256).iter().all(|&s| s == 0.0));
+    }
+}
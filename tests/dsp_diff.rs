@@ -0,0 +1,75 @@
+mod common;
+
+use auxide::graph::{Graph, NodeId, NodeType, Port, PortId, Rate};
+use auxide::node::{NodeDef, ProcessCtx};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+use common::edge;
+
+static AUDIO_OUT: [Port; 1] = [Port {
+    id: PortId(0),
+    rate: Rate::Audio,
+}];
+
+/// Outputs 0.0 before sample `at`, 1.0 from then on.
+struct Step {
+    at: usize,
+}
+
+impl NodeDef for Step {
+    type State = usize;
+
+    fn input_ports(&self) -> &'static [Port] {
+        &[]
+    }
+
+    fn output_ports(&self) -> &'static [Port] {
+        &AUDIO_OUT
+    }
+
+    fn required_inputs(&self) -> usize {
+        0
+    }
+
+    fn init_state(&self, _sample_rate: f32, _block_size: usize) -> Self::State {
+        0
+    }
+
+    fn process(
+        &self,
+        t: &mut Self::State,
+        _inputs: &[&[f32]],
+        outputs: &mut [&mut [f32]],
+        _ctx: &ProcessCtx,
+    ) -> Result<(), &'static str> {
+        for y in outputs[0].iter_mut() {
+            *y = if *t >= self.at { 1.0 } else { 0.0 };
+            *t += 1;
+        }
+        Ok(())
+    }
+}
+
+fn connect(graph: &mut Graph, from: NodeId, to: NodeId) {
+    graph.add_edge(edge(from, to)).unwrap();
+}
+
+#[test]
+fn diff_of_step_is_impulse() {
+    for at in [10, 64, 100] {
+        let mut graph = Graph::new();
+        let step = graph.add_node(NodeType::external(Step { at }));
+        let diff = graph.add_node(NodeType::Diff);
+        let sink = graph.add_node(NodeType::OutputSink);
+        connect(&mut graph, step, diff);
+        connect(&mut graph, diff, sink);
+
+        let plan = Plan::compile(&graph, 64).unwrap();
+        let mut runtime = Runtime::new(plan, &graph, 44100.0);
+        let out = render_offline(&mut runtime, 256).unwrap();
+        for (n, &y) in out.iter().enumerate() {
+            let expected = if n == at { 1.0 } else { 0.0 };
+            assert_eq!(y, expected, "step at {}: sample {}", at, n);
+        }
+    }
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use auxide::control::ControlMsg;
+use auxide::graph::{Graph, NodeType};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+use common::edge;
+
+const SAMPLE_RATE: f32 = 8000.0;
+const FRAMES: usize = 4000;
+
+fn render(freq_a: f32, freq_b: f32, msgs: &[ControlMsg]) -> Vec<f32> {
+    let mut graph = Graph::new();
+    let tone = graph.add_node(NodeType::DualTone { freq_a, freq_b });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(tone, sink)).unwrap();
+    let plan = Plan::compile(&graph, 80).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, SAMPLE_RATE);
+    for &msg in msgs {
+        runtime.apply_control(msg);
+    }
+    render_offline(&mut runtime, FRAMES).unwrap()
+}
+
+/// Normalised DFT magnitude at `freq` Hz (a full-scale sine gives 1.0).
+fn magnitude(signal: &[f32], freq: f32) -> f32 {
+    let w = 2.0 * std::f32::consts::PI * freq / SAMPLE_RATE;
+    let (mut re, mut im) = (0.0f32, 0.0f32);
+    for (i, &x) in signal.iter().enumerate() {
+        re += x * (w * i as f32).cos();
+        im += x * (w * i as f32).sin();
+    }
+    2.0 * (re * re + im * im).sqrt() / signal.len() as f32
+}
+
+/// The two strongest 2 Hz-spaced bins, in ascending frequency.
+fn peaks(signal: &[f32]) -> [f32; 2] {
+    let mut bins: Vec<(f32, f32)> = (100..2000)
+        .step_by(2)
+        .map(|f| (f as f32, magnitude(signal, f as f32)))
+        .collect();
+    bins.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let mut top = [bins[0].0, bins[1].0];
+    top.sort_by(f32::total_cmp);
+    top
+}
+
+#[test]
+fn dual_tone_has_both_frequencies() {
+    // DTMF digit 5.
+    let out = render(770.0, 1336.0, &[]);
+    assert_eq!(peaks(&out), [770.0, 1336.0]);
+    assert!((magnitude(&out, 770.0) - 0.5).abs() < 0.01);
+    assert!((magnitude(&out, 1336.0) - 0.5).abs() < 0.01);
+    assert!(out.iter().all(|s| s.abs() <= 1.0));
+}
+
+#[test]
+fn dual_tone_set_param() {
+    let node = auxide::graph::NodeId(0, 0);
+    let msgs = [
+        ControlMsg::SetParam {
+            node,
+            param_idx: 0,
+            value: 697.0,
+        },
+        ControlMsg::SetParam {
+            node,
+            param_idx: 1,
+            value: 1209.0,
+        },
+    ];
+    // Bins are 2 Hz apart, so odd frequencies land between two of them.
+    let [low, high] = peaks(&render(770.0, 1336.0, &msgs));
+    assert!((low - 697.0).abs() <= 1.0, "{}", low);
+    assert!((high - 1209.0).abs() <= 1.0, "{}", high);
+}
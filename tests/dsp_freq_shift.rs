@@ -0,0 +1,60 @@
+mod common;
+
+use auxide::graph::{Graph, NodeType};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+use common::edge;
+
+const SAMPLE_RATE: f32 = 48000.0;
+const SETTLE: usize = 4800;
+const FRAMES: usize = 9600;
+
+fn render(shift_hz: f32) -> Vec<f32> {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let shift = graph.add_node(NodeType::FreqShift { shift_hz });
+    let sink = graph.add_node(NodeType::OutputSink);
+    for (from, to) in [(osc, shift), (shift, sink)] {
+        graph.add_edge(edge(from, to)).unwrap();
+    }
+    let plan = Plan::compile(&graph, 64).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, SAMPLE_RATE);
+    render_offline(&mut runtime, SETTLE + FRAMES).unwrap()[SETTLE..].to_vec()
+}
+
+/// Magnitude of the Hann-windowed DFT of `signal` at `freq` Hz.
+fn magnitude(signal: &[f32], freq: f32) -> f32 {
+    let n = signal.len() as f32;
+    let w = 2.0 * std::f32::consts::PI * freq / SAMPLE_RATE;
+    let (mut re, mut im) = (0.0f32, 0.0f32);
+    for (i, &x) in signal.iter().enumerate() {
+        let hann = 0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / n).cos();
+        re += x * hann * (w * i as f32).cos();
+        im += x * hann * (w * i as f32).sin();
+    }
+    (re * re + im * im).sqrt()
+}
+
+fn peak_freq(signal: &[f32]) -> f32 {
+    (100..=1000)
+        .map(|f| f as f32)
+        .max_by(|&a, &b| magnitude(signal, a).total_cmp(&magnitude(signal, b)))
+        .unwrap()
+}
+
+#[test]
+fn freq_shift_moves_sine_up() {
+    let out = render(100.0);
+    let peak = peak_freq(&out);
+    assert!((peak - 540.0).abs() <= 5.0, "peak at {} Hz", peak);
+    // Single sideband: the image and the original are strongly suppressed.
+    let wanted = magnitude(&out, 540.0);
+    assert!(magnitude(&out, 340.0) < wanted * 0.05);
+    assert!(magnitude(&out, 440.0) < wanted * 0.05);
+}
+
+#[test]
+fn freq_shift_moves_sine_down() {
+    let peak = peak_freq(&render(-100.0));
+    assert!((peak - 340.0).abs() <= 5.0, "peak at {} Hz", peak);
+}
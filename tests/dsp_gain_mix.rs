@@ -1,6 +1,9 @@
-use auxide::graph::{Graph, NodeType, PortId, Rate};
+mod common;
+
+use auxide::graph::{Graph, NodeType};
 use auxide::plan::Plan;
 use auxide::rt::{render_offline, Runtime};
+use common::{edge, edge_to};
 
 #[test]
 fn dsp_gain_mix_algebra() {
@@ -9,24 +12,8 @@ fn dsp_gain_mix_algebra() {
     let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
     let gain = graph.add_node(NodeType::Gain { gain: 0.0 });
     let sink = graph.add_node(NodeType::OutputSink);
-    graph
-        .add_edge(auxide::graph::Edge {
-            from_node: osc,
-            from_port: PortId(0),
-            to_node: gain,
-            to_port: PortId(0),
-            rate: Rate::Audio,
-        })
-        .unwrap();
-    graph
-        .add_edge(auxide::graph::Edge {
-            from_node: gain,
-            from_port: PortId(0),
-            to_node: sink,
-            to_port: PortId(0),
-            rate: Rate::Audio,
-        })
-        .unwrap();
+    graph.add_edge(edge(osc, gain)).unwrap();
+    graph.add_edge(edge(gain, sink)).unwrap();
 
     let plan = Plan::compile(&graph, 64).unwrap();
     let mut runtime = Runtime::new(plan, &graph, 44100.0);
@@ -41,24 +28,8 @@ fn dsp_gain_mix_algebra() {
     let osc2 = graph2.add_node(NodeType::SineOsc { freq: 440.0 });
     let gain2 = graph2.add_node(NodeType::Gain { gain: 1.0 });
     let sink2 = graph2.add_node(NodeType::OutputSink);
-    graph2
-        .add_edge(auxide::graph::Edge {
-            from_node: osc2,
-            from_port: PortId(0),
-            to_node: gain2,
-            to_port: PortId(0),
-            rate: Rate::Audio,
-        })
-        .unwrap();
-    graph2
-        .add_edge(auxide::graph::Edge {
-            from_node: gain2,
-            from_port: PortId(0),
-            to_node: sink2,
-            to_port: PortId(0),
-            rate: Rate::Audio,
-        })
-        .unwrap();
+    graph2.add_edge(edge(osc2, gain2)).unwrap();
+    graph2.add_edge(edge(gain2, sink2)).unwrap();
 
     let plan2 = Plan::compile(&graph2, 64).unwrap();
     let mut runtime2 = Runtime::new(plan2, &graph2, 44100.0);
@@ -68,15 +39,7 @@ fn dsp_gain_mix_algebra() {
     let mut graph3 = Graph::new();
     let osc3 = graph3.add_node(NodeType::SineOsc { freq: 440.0 });
     let sink3 = graph3.add_node(NodeType::OutputSink);
-    graph3
-        .add_edge(auxide::graph::Edge {
-            from_node: osc3,
-            from_port: PortId(0),
-            to_node: sink3,
-            to_port: PortId(0),
-            rate: Rate::Audio,
-        })
-        .unwrap();
+    graph3.add_edge(edge(osc3, sink3)).unwrap();
 
     let plan3 = Plan::compile(&graph3, 64).unwrap();
     let mut runtime3 = Runtime::new(plan3, &graph3, 44100.0);
@@ -92,33 +55,9 @@ fn dsp_gain_mix_algebra() {
     let osc4b = graph4.add_node(NodeType::SineOsc { freq: 440.0 });
     let mix = graph4.add_node(NodeType::Mix);
     let sink4 = graph4.add_node(NodeType::OutputSink);
-    graph4
-        .add_edge(auxide::graph::Edge {
-            from_node: osc4a,
-            from_port: PortId(0),
-            to_node: mix,
-            to_port: PortId(0),
-            rate: Rate::Audio,
-        })
-        .unwrap();
-    graph4
-        .add_edge(auxide::graph::Edge {
-            from_node: osc4b,
-            from_port: PortId(0),
-            to_node: mix,
-            to_port: PortId(1),
-            rate: Rate::Audio,
-        })
-        .unwrap();
-    graph4
-        .add_edge(auxide::graph::Edge {
-            from_node: mix,
-            from_port: PortId(0),
-            to_node: sink4,
-            to_port: PortId(0),
-            rate: Rate::Audio,
-        })
-        .unwrap();
+    graph4.add_edge(edge(osc4a, mix)).unwrap();
+    graph4.add_edge(edge_to(osc4b, mix, 1)).unwrap();
+    graph4.add_edge(edge(mix, sink4)).unwrap();
 
     let plan4 = Plan::compile(&graph4, 64).unwrap();
     let mut runtime4 = Runtime::new(plan4, &graph4, 44100.0);
@@ -0,0 +1,110 @@
+mod common;
+
+use auxide::graph::{Edge, Graph, NodeType, Port, PortId, Rate, CONTROL_RATE_DIVISOR};
+use auxide::node::{NodeDef, ProcessCtx};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+use common::{edge, edge_to};
+
+const HALF_PERIOD: u64 = 3 * CONTROL_RATE_DIVISOR as u64;
+
+static CONTROL_OUT: [Port; 1] = [Port {
+    id: PortId(0),
+    rate: Rate::Control,
+}];
+
+/// Control-rate square wave: 1.0 for `HALF_PERIOD` audio samples, then 0.0.
+struct ControlSquare;
+
+impl NodeDef for ControlSquare {
+    type State = u64;
+
+    fn input_ports(&self) -> &'static [Port] {
+        &[]
+    }
+
+    fn output_ports(&self) -> &'static [Port] {
+        &CONTROL_OUT
+    }
+
+    fn required_inputs(&self) -> usize {
+        0
+    }
+
+    fn init_state(&self, _sample_rate: f32, _block_size: usize) -> Self::State {
+        0
+    }
+
+    fn process(
+        &self,
+        elapsed: &mut Self::State,
+        _inputs: &[&[f32]],
+        outputs: &mut [&mut [f32]],
+        _ctx: &ProcessCtx,
+    ) -> Result<(), &'static str> {
+        for y in outputs[0].iter_mut() {
+            *y = if (*elapsed / HALF_PERIOD).is_multiple_of(2) {
+                1.0
+            } else {
+                0.0
+            };
+            *elapsed += CONTROL_RATE_DIVISOR as u64;
+        }
+        Ok(())
+    }
+}
+
+fn render(audio: NodeType, frames: usize) -> Vec<f32> {
+    let mut graph = Graph::new();
+    let audio = graph.add_node(audio);
+    let control = graph.add_node(NodeType::external(ControlSquare));
+    let gate = graph.add_node(NodeType::GatedGain);
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(audio, gate)).unwrap();
+    graph
+        .add_edge(Edge {
+            rate: Rate::Control,
+            ..edge_to(control, gate, 1)
+        })
+        .unwrap();
+    graph.add_edge(edge(gate, sink)).unwrap();
+    // Block size deliberately not a multiple of the square's period.
+    let plan = Plan::compile(&graph, 64).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, 44100.0);
+    render_offline(&mut runtime, frames).unwrap()
+}
+
+#[test]
+fn square_control_gates_audio_per_segment() {
+    let frames = 1000;
+    let dry = {
+        let mut graph = Graph::new();
+        let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+        let sink = graph.add_node(NodeType::OutputSink);
+        graph.add_edge(edge(osc, sink)).unwrap();
+        let plan = Plan::compile(&graph, 64).unwrap();
+        render_offline(&mut Runtime::new(plan, &graph, 44100.0), frames).unwrap()
+    };
+    let gated = render(NodeType::SineOsc { freq: 440.0 }, frames);
+
+    for (n, (&g, &d)) in gated.iter().zip(&dry).enumerate() {
+        if (n as u64 / HALF_PERIOD).is_multiple_of(2) {
+            assert_eq!(g, d, "frame {} should pass", n);
+        } else {
+            assert_eq!(g, 0.0, "frame {} should be silent", n);
+        }
+    }
+    assert!(gated.iter().any(|&s| s.abs() > 0.5));
+}
+
+#[test]
+fn gated_gain_requires_both_inputs() {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let gate = graph.add_node(NodeType::GatedGain);
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, gate)).unwrap();
+    graph.add_edge(edge(gate, sink)).unwrap();
+    assert!(Plan::compile(&graph, 64).is_err());
+    assert!(graph.add_edge(edge_to(osc, gate, 1)).is_err());
+}
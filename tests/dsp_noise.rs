@@ -0,0 +1,57 @@
+mod common;
+
+use auxide::graph::{Graph, NodeType};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+use common::edge;
+
+fn render_noise(seed: u64, color: f32, frames: usize) -> Vec<f32> {
+    let mut graph = Graph::new();
+    let noise = graph.add_node(NodeType::Noise { seed, color });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(noise, sink)).unwrap();
+    let plan = Plan::compile(&graph, 128).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, 44100.0);
+    render_offline(&mut runtime, frames).unwrap()
+}
+
+/// Energy below ~200 Hz divided by energy above it, via a one-pole split.
+fn low_high_ratio(signal: &[f32]) -> f32 {
+    let coef = (-2.0 * std::f32::consts::PI * 200.0 / 44100.0).exp();
+    let mut low = 0.0;
+    let (mut e_low, mut e_high) = (0.0f64, 0.0f64);
+    for &x in signal {
+        low = x + (low - x) * coef;
+        let high = x - low;
+        e_low += (low * low) as f64;
+        e_high += (high * high) as f64;
+    }
+    (e_low / e_high) as f32
+}
+
+#[test]
+fn noise_is_deterministic_per_seed() {
+    assert_eq!(render_noise(7, 0.0, 1000), render_noise(7, 0.0, 1000));
+    assert_ne!(render_noise(7, 0.0, 1000), render_noise(8, 0.0, 1000));
+    assert!(render_noise(7, 0.0, 1000)
+        .iter()
+        .all(|s| (-1.0..1.0).contains(s)));
+}
+
+#[test]
+fn noise_slope_steepens_with_color() {
+    let ratios: Vec<f32> = [0.0, 0.5, 1.0, 1.5, 2.0]
+        .iter()
+        .map(|&c| low_high_ratio(&render_noise(42, c, 44100)))
+        .collect();
+    for pair in ratios.windows(2) {
+        assert!(
+            pair[1] > pair[0],
+            "low/high energy should rise with color: {:?}",
+            ratios
+        );
+    }
+    // White noise has little energy below 200 Hz; brown is dominated by it.
+    assert!(ratios[0] < 0.1);
+    assert!(ratios[4] > 1.0);
+}
@@ -0,0 +1,112 @@
+mod common;
+
+use auxide::graph::{Graph, NodeType, Port, PortId, Rate};
+use auxide::node::{NodeDef, ProcessCtx};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+use common::edge;
+
+const SAMPLE_RATE: f32 = 44100.0;
+/// Loud until this sample, then a -60 dB noise floor until `LOUD_AGAIN`.
+const QUIET_FROM: usize = 22050;
+const LOUD_AGAIN: usize = 44100;
+const HOLD_MS: f32 = 50.0;
+
+static AUDIO_OUT: [Port; 1] = [Port {
+    id: PortId(0),
+    rate: Rate::Audio,
+}];
+
+/// 220 Hz sine at -6 dB, dropping to -60 dB between `QUIET_FROM` and `LOUD_AGAIN`.
+struct Phrase;
+
+impl NodeDef for Phrase {
+    type State = usize;
+
+    fn input_ports(&self) -> &'static [Port] {
+        &[]
+    }
+
+    fn output_ports(&self) -> &'static [Port] {
+        &AUDIO_OUT
+    }
+
+    fn required_inputs(&self) -> usize {
+        0
+    }
+
+    fn init_state(&self, _sample_rate: f32, _block_size: usize) -> Self::State {
+        0
+    }
+
+    fn process(
+        &self,
+        t: &mut Self::State,
+        _inputs: &[&[f32]],
+        outputs: &mut [&mut [f32]],
+        ctx: &ProcessCtx,
+    ) -> Result<(), &'static str> {
+        for y in outputs[0].iter_mut() {
+            let level = if (QUIET_FROM..LOUD_AGAIN).contains(t) {
+                0.001
+            } else {
+                0.5
+            };
+            *y = level * (2.0 * std::f32::consts::PI * 220.0 * *t as f32 / ctx.sample_rate).sin();
+            *t += 1;
+        }
+        Ok(())
+    }
+}
+
+fn render(gated: bool) -> Vec<f32> {
+    let mut graph = Graph::new();
+    let src = graph.add_node(NodeType::external(Phrase));
+    let sink = graph.add_node(NodeType::OutputSink);
+    if gated {
+        let gate = graph.add_node(NodeType::NoiseGate {
+            threshold_db: -40.0,
+            attack_ms: 1.0,
+            hold_ms: HOLD_MS,
+            release_ms: 10.0,
+        });
+        graph.add_edge(edge(src, gate)).unwrap();
+        graph.add_edge(edge(gate, sink)).unwrap();
+    } else {
+        graph.add_edge(edge(src, sink)).unwrap();
+    }
+    let plan = Plan::compile(&graph, 64).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, SAMPLE_RATE);
+    render_offline(&mut runtime, LOUD_AGAIN + 22050).unwrap()
+}
+
+fn rms(signal: &[f32]) -> f32 {
+    (signal.iter().map(|s| s * s).sum::<f32>() / signal.len() as f32).sqrt()
+}
+
+#[test]
+fn noise_gate_closes_after_hold_and_reopens() {
+    let dry = render(false);
+    let wet = render(true);
+    let ms = |ms: f32| (ms * SAMPLE_RATE / 1000.0) as usize;
+
+    // Open while loud.
+    let loud = 4410..QUIET_FROM;
+    assert!((rms(&wet[loud.clone()]) / rms(&dry[loud]) - 1.0).abs() < 0.01);
+
+    // Still open during the hold time: the noise floor passes unchanged.
+    let held = QUIET_FROM + ms(10.0)..QUIET_FROM + ms(HOLD_MS - 10.0);
+    assert!((rms(&wet[held.clone()]) / rms(&dry[held]) - 1.0).abs() < 0.01);
+
+    // Closed once hold and release have elapsed.
+    let closed = QUIET_FROM + ms(HOLD_MS + 100.0)..LOUD_AGAIN;
+    let floor = rms(&dry[closed.clone()]);
+    assert!(
+        rms(&wet[closed]) < floor * 1e-3,
+        "gate should silence the noise floor"
+    );
+
+    // Re-opens when the signal returns.
+    let reopened = LOUD_AGAIN + ms(10.0)..wet.len();
+    assert!((rms(&wet[reopened.clone()]) / rms(&dry[reopened]) - 1.0).abs() < 0.01);
+}
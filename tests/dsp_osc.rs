@@ -1,21 +1,16 @@
-use auxide::graph::{Graph, NodeType, PortId};
+mod common;
+
+use auxide::graph::{Graph, NodeType};
 use auxide::plan::Plan;
 use auxide::rt::{render_offline, Runtime};
+use common::edge;
 
 #[test]
 fn dsp_osc_correctness() {
     let mut graph = Graph::new();
     let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
     let sink = graph.add_node(NodeType::OutputSink);
-    graph
-        .add_edge(auxide::graph::Edge {
-            from_node: osc,
-            from_port: PortId(0),
-            to_node: sink,
-            to_port: PortId(0),
-            rate: auxide::graph::Rate::Audio,
-        })
-        .unwrap();
+    graph.add_edge(edge(osc, sink)).unwrap();
 
     let plan = Plan::compile(&graph, 64).unwrap();
     let mut runtime = Runtime::new(plan, &graph, 44100.0);
@@ -44,7 +39,7 @@ fn dsp_osc_correctness() {
     }
     // For 440 Hz at 44100, period 100.22, in 64 samples ~0.64 periods, ~1-2 crossings
     assert!(
-        zero_crossings >= 1 && zero_crossings <= 3,
+        (1..=3).contains(&zero_crossings),
         "Zero crossings: {}",
         zero_crossings
     );
@@ -0,0 +1,67 @@
+mod common;
+
+use auxide::control::ControlMsg;
+use auxide::graph::{Graph, NodeId, NodeType};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+use common::{edge_to, port_edge};
+
+const PANS: [f32; 3] = [-1.0, 0.0, 1.0];
+
+fn rms(signal: &[f32]) -> f32 {
+    (signal.iter().map(|s| s * s).sum::<f32>() / signal.len() as f32).sqrt()
+}
+
+/// RMS of the (left, right) outputs with a sine on each of `inputs`.
+fn render(inputs: &[usize], msgs: &[ControlMsg]) -> (f32, f32) {
+    let channel = |out_port: usize| {
+        let mut graph = Graph::new();
+        let bank = graph.add_node(NodeType::PanBank {
+            pans: PANS.to_vec(),
+        });
+        let sink = graph.add_node(NodeType::OutputSink);
+        for &input in inputs {
+            let osc = graph.add_node(NodeType::SineOsc { freq: 441.0 });
+            graph.add_edge(edge_to(osc, bank, input)).unwrap();
+        }
+        graph.add_edge(port_edge(bank, out_port, sink, 0)).unwrap();
+        let plan = Plan::compile(&graph, 100).unwrap();
+        let mut runtime = Runtime::new(plan, &graph, 44100.0);
+        for &msg in msgs {
+            runtime.apply_control(msg);
+        }
+        rms(&render_offline(&mut runtime, 4400).unwrap())
+    };
+    (channel(0), channel(1))
+}
+
+#[test]
+fn pan_bank_places_each_input() {
+    let full = std::f32::consts::FRAC_1_SQRT_2;
+    let half_power = full * full;
+
+    let (l, r) = render(&[0], &[]);
+    assert!((l - full).abs() < 1e-3 && r < 1e-6, "left: {} {}", l, r);
+
+    let (l, r) = render(&[1], &[]);
+    assert!((l - half_power).abs() < 1e-3 && (r - half_power).abs() < 1e-3);
+
+    let (l, r) = render(&[2], &[]);
+    assert!(l < 1e-6 && (r - full).abs() < 1e-3, "right: {} {}", l, r);
+
+    // All three in phase: each side gets one hard-panned copy plus the centre.
+    let (l, r) = render(&[0, 1, 2], &[]);
+    let expected = full * (1.0 + full);
+    assert!((l - expected).abs() < 1e-3 && (r - expected).abs() < 1e-3);
+}
+
+#[test]
+fn pan_bank_repans_via_set_param() {
+    let move_left_input_right = ControlMsg::SetParam {
+        node: NodeId(0, 0),
+        param_idx: 0,
+        value: 1.0,
+    };
+    let (l, r) = render(&[0], &[move_left_input_right]);
+    assert!(l < 1e-6 && (r - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-3);
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use auxide::dsp::Resampler;
+use auxide::graph::{Graph, NodeType};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+use common::edge;
+
+fn render(ratio: Option<f32>, frames: usize) -> Vec<f32> {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 1000.0 });
+    let sink = graph.add_node(NodeType::OutputSink);
+    match ratio {
+        Some(ratio) => {
+            let resample = graph.add_node(NodeType::Resample { ratio });
+            graph.add_edge(edge(osc, resample)).unwrap();
+            graph.add_edge(edge(resample, sink)).unwrap();
+        }
+        None => graph.add_edge(edge(osc, sink)).unwrap(),
+    }
+    let plan = Plan::compile(&graph, 128).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, 48000.0);
+    render_offline(&mut runtime, frames).unwrap()
+}
+
+#[test]
+fn resample_round_trip_preserves_sine() {
+    let frames = 8192;
+    let dry = render(None, frames);
+    for ratio in [2.0, 0.5, 1.5, 3.0] {
+        let wet = render(Some(ratio), frames);
+        let latency = Resampler::latency_for(ratio);
+        assert!(wet[..latency].iter().all(|&s| s == 0.0));
+        // Skip the kernel warm-up after the latency.
+        let settle = latency + 256;
+        let max_err = wet[settle..]
+            .iter()
+            .zip(&dry[settle - latency..])
+            .map(|(w, d)| (w - d).abs())
+            .fold(0.0f32, f32::max);
+        assert!(max_err < 1e-2, "ratio {}: error {}", ratio, max_err);
+    }
+}
+
+#[test]
+fn non_finite_ratios_act_as_one() {
+    let frames = 1024;
+    let unity = render(Some(1.0), frames);
+    for ratio in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+        assert_eq!(Resampler::latency_for(ratio), Resampler::latency_for(1.0));
+        assert_eq!(render(Some(ratio), frames), unity, "ratio {}", ratio);
+    }
+}
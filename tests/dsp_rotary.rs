@@ -0,0 +1,84 @@
+mod common;
+
+use auxide::graph::{Graph, NodeType};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+use common::{edge, port_edge};
+
+const SAMPLE_RATE: f32 = 44100.0;
+// One period of the 441 Hz carrier per window.
+const WINDOW: usize = 100;
+
+/// Per-window RMS of the (left, right) outputs of a rotary-speaker sine.
+fn envelopes(speed_hz: f32, depth: f32, frames: usize) -> (Vec<f32>, Vec<f32>) {
+    let channel = |out_port: usize| {
+        let mut graph = Graph::new();
+        let osc = graph.add_node(NodeType::SineOsc { freq: 441.0 });
+        let rotary = graph.add_node(NodeType::Rotary { speed_hz, depth });
+        let sink = graph.add_node(NodeType::OutputSink);
+        graph.add_edge(edge(osc, rotary)).unwrap();
+        graph
+            .add_edge(port_edge(rotary, out_port, sink, 0))
+            .unwrap();
+        let plan = Plan::compile(&graph, WINDOW).unwrap();
+        let mut runtime = Runtime::new(plan, &graph, SAMPLE_RATE);
+        render_offline(&mut runtime, frames)
+            .unwrap()
+            .chunks(WINDOW)
+            // Skip the first window while the delay line fills.
+            .skip(1)
+            .map(|w| (w.iter().map(|s| s * s).sum::<f32>() / w.len() as f32).sqrt())
+            .collect::<Vec<f32>>()
+    };
+    (channel(0), channel(1))
+}
+
+/// Number of upward crossings of the mean.
+fn cycles(signal: &[f32]) -> usize {
+    let mean = signal.iter().sum::<f32>() / signal.len() as f32;
+    signal
+        .windows(2)
+        .filter(|w| w[0] < mean && w[1] >= mean)
+        .count()
+}
+
+#[test]
+fn rotary_modulates_amplitude_and_pan_at_speed() {
+    // Two seconds at 5 Hz: ten rotations.
+    let (left, right) = envelopes(5.0, 1.0, 2 * SAMPLE_RATE as usize);
+    let level: Vec<f32> = left.iter().zip(&right).map(|(l, r)| l.hypot(*r)).collect();
+    let pan: Vec<f32> = left
+        .iter()
+        .zip(&right)
+        .map(|(l, r)| (r - l) / (r + l))
+        .collect();
+
+    let level_cycles = cycles(&level);
+    let pan_cycles = cycles(&pan);
+    assert!((9..=11).contains(&level_cycles), "level {}", level_cycles);
+    assert!((9..=11).contains(&pan_cycles), "pan {}", pan_cycles);
+
+    let swing = |v: &[f32]| {
+        let (min, max) = v
+            .iter()
+            .fold((f32::MAX, f32::MIN), |(lo, hi), &x| (lo.min(x), hi.max(x)));
+        max - min
+    };
+    assert!(swing(&level) > 0.3, "level swing {}", swing(&level));
+    assert!(swing(&pan) > 1.0, "pan swing {}", swing(&pan));
+}
+
+#[test]
+fn rotary_at_speed_zero_is_static() {
+    let (left, right) = envelopes(0.0, 1.0, SAMPLE_RATE as usize / 2);
+    for env in [&left, &right] {
+        let first = env[0];
+        assert!(first > 0.4);
+        assert!(
+            env.iter().all(|&e| (e - first).abs() < 1e-3),
+            "envelope moves: {:?}",
+            &env[..8]
+        );
+    }
+    assert!((left[0] - right[0]).abs() < 1e-3, "horn at rest is centred");
+}
@@ -0,0 +1,134 @@
+mod common;
+
+use auxide::graph::{Graph, NodeType, Port, PortId, Rate};
+use auxide::node::{NodeDef, ProcessCtx};
+use auxide::plan::{Plan, PlanError};
+use auxide::rt::{render_offline, Runtime};
+use common::{edge, edge_to};
+
+const KICK_PERIOD: usize = 11025; // four hits per second at 44.1 kHz
+const KICK_LENGTH: usize = 2000;
+
+static AUDIO_OUT: [Port; 1] = [Port {
+    id: PortId(0),
+    rate: Rate::Audio,
+}];
+
+/// Decaying 60 Hz bursts every `KICK_PERIOD` samples.
+struct Kick;
+
+impl NodeDef for Kick {
+    type State = usize;
+
+    fn input_ports(&self) -> &'static [Port] {
+        &[]
+    }
+
+    fn output_ports(&self) -> &'static [Port] {
+        &AUDIO_OUT
+    }
+
+    fn required_inputs(&self) -> usize {
+        0
+    }
+
+    fn init_state(&self, _sample_rate: f32, _block_size: usize) -> Self::State {
+        0
+    }
+
+    fn process(
+        &self,
+        t: &mut Self::State,
+        _inputs: &[&[f32]],
+        outputs: &mut [&mut [f32]],
+        ctx: &ProcessCtx,
+    ) -> Result<(), &'static str> {
+        for y in outputs[0].iter_mut() {
+            let k = *t % KICK_PERIOD;
+            *y = if k < KICK_LENGTH {
+                let decay = 1.0 - k as f32 / KICK_LENGTH as f32;
+                decay * (2.0 * std::f32::consts::PI * 60.0 * k as f32 / ctx.sample_rate).sin()
+            } else {
+                0.0
+            };
+            *t += 1;
+        }
+        Ok(())
+    }
+}
+
+fn window_rms(signal: &[f32], start: usize, len: usize) -> f32 {
+    let w = &signal[start..start + len];
+    (w.iter().map(|s| s * s).sum::<f32>() / len as f32).sqrt()
+}
+
+#[test]
+fn sidechain_kick_ducks_pad() {
+    let mut graph = Graph::new();
+    let pad = graph.add_node(NodeType::SineOsc { freq: 220.0 });
+    let kick = graph.add_node(NodeType::external(Kick));
+    let comp = graph.add_node(NodeType::Compressor {
+        threshold_db: -30.0,
+        ratio: 8.0,
+        attack_ms: 1.0,
+        release_ms: 50.0,
+    });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(pad, comp)).unwrap();
+    graph.add_edge(edge_to(kick, comp, 1)).unwrap();
+    graph.add_edge(edge(comp, sink)).unwrap();
+
+    let plan = Plan::compile(&graph, 64).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, 44100.0);
+    let out = render_offline(&mut runtime, KICK_PERIOD * 4).unwrap();
+
+    for hit in 1..4 {
+        let onset = hit * KICK_PERIOD;
+        // Pad is untouched in the quiet gap before each kick...
+        let before = window_rms(&out, onset - 2000, 1000);
+        // ...and ducked while the kick sounds.
+        let during = window_rms(&out, onset + 200, 1000);
+        assert!(
+            (before - std::f32::consts::FRAC_1_SQRT_2).abs() < 0.05,
+            "pad should be at full level between kicks (rms {})",
+            before
+        );
+        assert!(
+            during < before * 0.5,
+            "kick {} should duck the pad: {} vs {}",
+            hit,
+            during,
+            before
+        );
+    }
+}
+
+#[test]
+fn sidechain_is_not_a_required_input() {
+    // Only the sidechain is connected: the main input is still missing.
+    let mut graph = Graph::new();
+    let kick = graph.add_node(NodeType::external(Kick));
+    let comp = graph.add_node(NodeType::Compressor {
+        threshold_db: -20.0,
+        ratio: 4.0,
+        attack_ms: 1.0,
+        release_ms: 50.0,
+    });
+    graph.add_edge(edge_to(kick, comp, 1)).unwrap();
+    assert_eq!(
+        Plan::compile(&graph, 64).unwrap_err(),
+        PlanError::RequiredInputMissing { node: comp }
+    );
+
+    // Main connected, sidechain left open: compiles and self-detects.
+    let mut graph = Graph::new();
+    let pad = graph.add_node(NodeType::SineOsc { freq: 220.0 });
+    let comp = graph.add_node(NodeType::Compressor {
+        threshold_db: -20.0,
+        ratio: 4.0,
+        attack_ms: 1.0,
+        release_ms: 50.0,
+    });
+    graph.add_edge(edge(pad, comp)).unwrap();
+    assert!(Plan::compile(&graph, 64).is_ok());
+}
@@ -1,6 +1,9 @@
-use auxide::graph::{Graph, NodeType, PortId, Rate};
+mod common;
+
+use auxide::graph::{Graph, NodeType};
 use auxide::plan::Plan;
 use auxide::rt::{render_offline, Runtime};
+use common::edge;
 
 #[test]
 fn dsp_silence_propagation() {
@@ -9,24 +12,8 @@ fn dsp_silence_propagation() {
     let dummy = graph.add_node(NodeType::Dummy);
     let gain = graph.add_node(NodeType::Gain { gain: 2.0 });
     let sink = graph.add_node(NodeType::OutputSink);
-    graph
-        .add_edge(auxide::graph::Edge {
-            from_node: dummy,
-            from_port: PortId(0),
-            to_node: gain,
-            to_port: PortId(0),
-            rate: Rate::Audio,
-        })
-        .unwrap();
-    graph
-        .add_edge(auxide::graph::Edge {
-            from_node: gain,
-            from_port: PortId(0),
-            to_node: sink,
-            to_port: PortId(0),
-            rate: Rate::Audio,
-        })
-        .unwrap();
+    graph.add_edge(edge(dummy, gain)).unwrap();
+    graph.add_edge(edge(gain, sink)).unwrap();
 
     let plan = Plan::compile(&graph, 64).unwrap();
     let mut runtime = Runtime::new(plan, &graph, 44100.0);
@@ -0,0 +1,68 @@
+mod common;
+
+use auxide::control::ControlMsg;
+use auxide::graph::{Graph, NodeId, NodeType};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+use common::{edge, edge_to, port_edge};
+
+const FRAMES: usize = 4096;
+
+/// Output port `out_port` of a width node fed by two different sines, or the
+/// unprocessed input on that side when `width` is `None`.
+fn render(width: Option<f32>, msgs: &[ControlMsg], out_port: usize) -> Vec<f32> {
+    let mut graph = Graph::new();
+    let left = graph.add_node(NodeType::SineOsc { freq: 441.0 });
+    let right = graph.add_node(NodeType::SineOsc { freq: 661.5 });
+    let sink = graph.add_node(NodeType::OutputSink);
+    match width {
+        Some(width) => {
+            let node = graph.add_node(NodeType::StereoWidth { width });
+            graph.add_edge(edge(left, node)).unwrap();
+            graph.add_edge(edge_to(right, node, 1)).unwrap();
+            graph.add_edge(port_edge(node, out_port, sink, 0)).unwrap();
+        }
+        None => {
+            let source = [left, right][out_port];
+            graph.add_edge(edge(source, sink)).unwrap();
+        }
+    }
+    let plan = Plan::compile(&graph, 64).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, 44100.0);
+    for &msg in msgs {
+        runtime.apply_control(msg);
+    }
+    render_offline(&mut runtime, FRAMES).unwrap()
+}
+
+fn max_diff(a: &[f32], b: &[f32]) -> f32 {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| (x - y).abs())
+        .fold(0.0, f32::max)
+}
+
+#[test]
+fn zero_width_collapses_to_mono() {
+    let (l, r) = (render(Some(0.0), &[], 0), render(Some(0.0), &[], 1));
+    assert_eq!(l, r);
+    assert!(l.iter().any(|&s| s != 0.0));
+}
+
+#[test]
+fn unit_width_leaves_image_unchanged() {
+    for port in 0..2 {
+        let diff = max_diff(&render(Some(1.0), &[], port), &render(None, &[], port));
+        assert!(diff < 1e-6, "port {}: {}", port, diff);
+    }
+}
+
+#[test]
+fn width_set_param() {
+    let msg = ControlMsg::SetParam {
+        node: NodeId(3, 0),
+        param_idx: 0,
+        value: 0.0,
+    };
+    assert_eq!(render(Some(1.5), &[msg], 0), render(Some(1.5), &[msg], 1));
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use auxide::graph::{Graph, NodeType};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+use common::edge;
+
+const SAMPLE_RATE: f32 = 48000.0;
+const FREQ: f32 = 1000.0;
+// Exactly 100 cycles of the test tone.
+const FRAMES: usize = 4800;
+
+/// A sine of `amplitude` through `TapeSat { drive: 4, bias: 0.2 }`.
+fn render(amplitude: f32) -> Vec<f32> {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: FREQ });
+    let level = graph.add_node(NodeType::Gain { gain: amplitude });
+    let tape = graph.add_node(NodeType::TapeSat {
+        drive: 4.0,
+        bias: 0.2,
+    });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, level)).unwrap();
+    graph.add_edge(edge(level, tape)).unwrap();
+    graph.add_edge(edge(tape, sink)).unwrap();
+    let plan = Plan::compile(&graph, 64).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, SAMPLE_RATE);
+    render_offline(&mut runtime, FRAMES).unwrap()
+}
+
+/// DFT magnitude of the `k`th harmonic (a sine of amplitude A gives A).
+fn harmonic(signal: &[f32], k: usize) -> f32 {
+    let w = 2.0 * std::f32::consts::PI * FREQ * k as f32 / SAMPLE_RATE;
+    let (mut re, mut im) = (0.0f32, 0.0f32);
+    for (i, &x) in signal.iter().enumerate() {
+        re += x * (w * i as f32).cos();
+        im += x * (w * i as f32).sin();
+    }
+    2.0 * (re * re + im * im).sqrt() / signal.len() as f32
+}
+
+#[test]
+fn tape_sat_is_linear_when_quiet() {
+    let out = render(0.01);
+    let fundamental = harmonic(&out, 1);
+    assert!(
+        (fundamental - 0.01).abs() < 1e-4,
+        "gain {}",
+        fundamental / 0.01
+    );
+    for k in 2..=3 {
+        assert!(harmonic(&out, k) < fundamental * 0.01, "harmonic {}", k);
+    }
+}
+
+#[test]
+fn tape_sat_compresses_and_adds_harmonics_when_loud() {
+    let out = render(1.0);
+    let fundamental = harmonic(&out, 1);
+    let peak = out.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+    assert!(peak < 0.5, "peak {}", peak);
+    // Bias makes the curve asymmetric (even), clipping adds odd harmonics.
+    assert!(harmonic(&out, 2) > fundamental * 0.03);
+    assert!(harmonic(&out, 3) > fundamental * 0.03);
+}
+
+#[test]
+fn tape_sat_is_deterministic() {
+    assert_eq!(render(0.8), render(0.8));
+}
@@ -0,0 +1,122 @@
+mod common;
+
+use auxide::graph::{Graph, NodeId, NodeType, Port, PortId, Rate};
+use auxide::node::{NodeDef, ProcessCtx};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+use common::edge;
+
+const CEILING_DB: f32 = -1.0;
+
+static AUDIO_OUT: [Port; 1] = [Port {
+    id: PortId(0),
+    rate: Rate::Audio,
+}];
+
+/// Full-scale sine at fs/4 with a 45 degree phase offset: every sample lands
+/// at +-0.707 while the waveform peaks at 1.0 halfway between samples.
+struct InterSampleOvers;
+
+impl NodeDef for InterSampleOvers {
+    type State = usize;
+
+    fn input_ports(&self) -> &'static [Port] {
+        &[]
+    }
+
+    fn output_ports(&self) -> &'static [Port] {
+        &AUDIO_OUT
+    }
+
+    fn required_inputs(&self) -> usize {
+        0
+    }
+
+    fn init_state(&self, _sample_rate: f32, _block_size: usize) -> Self::State {
+        0
+    }
+
+    fn process(
+        &self,
+        t: &mut Self::State,
+        _inputs: &[&[f32]],
+        outputs: &mut [&mut [f32]],
+        _ctx: &ProcessCtx,
+    ) -> Result<(), &'static str> {
+        for y in outputs[0].iter_mut() {
+            let phase = std::f32::consts::FRAC_PI_2 * *t as f32 + std::f32::consts::FRAC_PI_4;
+            *y = phase.sin();
+            *t += 1;
+        }
+        Ok(())
+    }
+}
+
+fn render(limited: bool) -> Vec<f32> {
+    let mut graph = Graph::new();
+    let src = graph.add_node(NodeType::external(InterSampleOvers));
+    let sink = graph.add_node(NodeType::OutputSink);
+    let edge = |from: NodeId, to: NodeId| edge(from, to);
+    if limited {
+        let limiter = graph.add_node(NodeType::TruePeakLimiter {
+            ceiling_db: CEILING_DB,
+            release_ms: 50.0,
+        });
+        graph.add_edge(edge(src, limiter)).unwrap();
+        graph.add_edge(edge(limiter, sink)).unwrap();
+    } else {
+        graph.add_edge(edge(src, sink)).unwrap();
+    }
+    let plan = Plan::compile(&graph, 64).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, 48000.0);
+    render_offline(&mut runtime, 8192).unwrap()
+}
+
+fn sample_peak(signal: &[f32]) -> f32 {
+    signal.iter().fold(0.0f32, |m, s| m.max(s.abs()))
+}
+
+/// Peak of the signal reconstructed at 4x with a long windowed-sinc
+/// interpolator, independent of the limiter's own detector.
+fn true_peak(signal: &[f32]) -> f32 {
+    const HALF: usize = 32;
+    let mut peak = 0.0f32;
+    for m in HALF..signal.len() - HALF {
+        for k in 0..4 {
+            let frac = k as f64 / 4.0;
+            let mut y = 0.0f64;
+            let first = m + 1 - HALF;
+            for (j, &s) in signal[first..=m + HALF].iter().enumerate() {
+                let t = (m - first) as f64 - j as f64 + frac;
+                let x = std::f64::consts::PI * t;
+                let sinc = if x.abs() < 1e-9 { 1.0 } else { x.sin() / x };
+                let window = 0.5 + 0.5 * (x / HALF as f64).cos();
+                y += s as f64 * sinc * window;
+            }
+            peak = peak.max(y.abs() as f32);
+        }
+    }
+    peak
+}
+
+#[test]
+fn true_peak_limiter_catches_inter_sample_overs() {
+    let ceiling = 10f32.powf(CEILING_DB / 20.0);
+    let dry = render(false);
+
+    // A sample-peak limiter sees nothing to do...
+    assert!(sample_peak(&dry) < ceiling);
+    // ...yet the reconstructed waveform goes over.
+    assert!(true_peak(&dry) > 0.99);
+
+    let wet = render(true);
+    let peak = true_peak(&wet);
+    assert!(
+        peak <= ceiling * 1.01,
+        "4x true peak {} exceeds ceiling {}",
+        peak,
+        ceiling
+    );
+    // Limiting, not muting.
+    assert!(peak > ceiling * 0.95);
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use auxide::graph::{Graph, NodeId, NodeType};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+use common::edge;
+
+fn connect(graph: &mut Graph, from: NodeId, to: NodeId) {
+    graph.add_edge(edge(from, to)).unwrap();
+}
+
+fn render(table: Option<Vec<f32>>) -> Vec<f32> {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let sink = graph.add_node(NodeType::OutputSink);
+    match table {
+        Some(table) => {
+            let shaper = graph.add_node(NodeType::Waveshaper { table });
+            connect(&mut graph, osc, shaper);
+            connect(&mut graph, shaper, sink);
+        }
+        None => connect(&mut graph, osc, sink),
+    }
+    let plan = Plan::compile(&graph, 64).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, 44100.0);
+    render_offline(&mut runtime, 512).unwrap()
+}
+
+#[test]
+fn waveshaper_identity_table_passes_through() {
+    let dry = render(None);
+    let shaped = render(Some(vec![-1.0, 1.0]));
+    for (s, d) in shaped.iter().zip(&dry) {
+        assert!((s - d).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn waveshaper_hard_clip_table() {
+    let dry = render(None);
+    // Linear in [-0.5, 0.5], flat at +-0.5 beyond.
+    let shaped = render(Some(vec![-0.5, -0.5, 0.0, 0.5, 0.5]));
+    for (s, d) in shaped.iter().zip(&dry) {
+        let expected = d.clamp(-0.5, 0.5);
+        assert!((s - expected).abs() < 1e-6, "{} -> {}", d, s);
+    }
+    assert!(shaped.contains(&0.5));
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use auxide::dsp::Interpolation;
+use auxide::graph::{Graph, NodeType};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+use common::edge;
+
+const SAMPLE_RATE: f32 = 48000.0;
+const TABLE_LEN: usize = 64;
+// 1.333... table entries per sample, so reads land between entries.
+const FREQ: f32 = 1000.0;
+// Exactly 100 cycles of the fundamental.
+const FRAMES: usize = 4800;
+
+fn render(interpolation: Interpolation) -> Vec<f32> {
+    let table: Vec<f32> = (0..TABLE_LEN)
+        .map(|i| (2.0 * std::f32::consts::PI * i as f32 / TABLE_LEN as f32).sin())
+        .collect();
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::Wavetable {
+        table,
+        freq: FREQ,
+        interpolation,
+    });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, sink)).unwrap();
+    let plan = Plan::compile(&graph, 64).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, SAMPLE_RATE);
+    render_offline(&mut runtime, FRAMES).unwrap()
+}
+
+/// Energy outside the fundamental relative to the fundamental's energy.
+fn distortion(signal: &[f32]) -> f64 {
+    let w = 2.0 * std::f64::consts::PI * FREQ as f64 / SAMPLE_RATE as f64;
+    let n = signal.len() as f64;
+    let (mut a, mut b) = (0.0, 0.0);
+    for (i, &y) in signal.iter().enumerate() {
+        a += y as f64 * (w * i as f64).cos();
+        b += y as f64 * (w * i as f64).sin();
+    }
+    let (a, b) = (2.0 * a / n, 2.0 * b / n);
+    let residual: f64 = signal
+        .iter()
+        .enumerate()
+        .map(|(i, &y)| {
+            let r = y as f64 - a * (w * i as f64).cos() - b * (w * i as f64).sin();
+            r * r
+        })
+        .sum();
+    let fundamental = (a * a + b * b) / 2.0 * n;
+    residual / fundamental
+}
+
+#[test]
+fn cubic_has_lower_distortion_than_linear() {
+    let none = distortion(&render(Interpolation::None));
+    let linear = distortion(&render(Interpolation::Linear));
+    let cubic = distortion(&render(Interpolation::Cubic));
+    assert!(linear < none, "linear {linear} vs none {none}");
+    assert!(cubic < linear / 10.0, "cubic {cubic} vs linear {linear}");
+}
+
+#[test]
+fn wavetable_plays_at_freq() {
+    let out = render(Interpolation::Cubic);
+    let ups = (1..out.len())
+        .filter(|&n| out[n - 1] < 0.0 && out[n] >= 0.0)
+        .count();
+    assert!((99..=100).contains(&ups), "{ups} cycles");
+    assert!(out.iter().all(|s| s.abs() <= 1.01));
+}
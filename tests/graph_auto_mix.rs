@@ -0,0 +1,60 @@
+mod common;
+
+use auxide::graph::{Graph, GraphError, NodeId, NodeType};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+use common::edge;
+
+fn render(graph: &Graph) -> Vec<f32> {
+    let plan = Plan::compile(graph, 64).unwrap();
+    render_offline(&mut Runtime::new(plan, graph, 48000.0), 512).unwrap()
+}
+
+#[test]
+fn second_writer_is_mixed_in() {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let gains: Vec<NodeId> = [1.0, 0.5, 0.25]
+        .into_iter()
+        .map(|gain| graph.add_node(NodeType::Gain { gain }))
+        .collect();
+    let sink = graph.add_node(NodeType::OutputSink);
+    for &gain in &gains {
+        graph.add_edge(edge(osc, gain)).unwrap();
+    }
+
+    assert_eq!(graph.add_edge_auto_mix(edge(gains[0], sink)), Ok(None));
+    assert_eq!(
+        graph.add_edge(edge(gains[1], sink)),
+        Err(GraphError::PortAlreadyConnected)
+    );
+    let first = graph.add_edge_auto_mix(edge(gains[1], sink)).unwrap();
+    let second = graph.add_edge_auto_mix(edge(gains[2], sink)).unwrap();
+    assert!(first.is_some() && second.is_some() && first != second);
+    assert!(graph.validate().is_ok());
+
+    let mut dry = Graph::new();
+    let osc = dry.add_node(NodeType::SineOsc { freq: 440.0 });
+    let gain = dry.add_node(NodeType::Gain { gain: 1.75 });
+    let sink = dry.add_node(NodeType::OutputSink);
+    dry.add_edge(edge(osc, gain)).unwrap();
+    dry.add_edge(edge(gain, sink)).unwrap();
+    for (&y, &x) in render(&graph).iter().zip(&render(&dry)) {
+        assert!((y - x).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn cycles_are_still_rejected() {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let a = graph.add_node(NodeType::Gain { gain: 1.0 });
+    let b = graph.add_node(NodeType::Gain { gain: 1.0 });
+    graph.add_edge(edge(osc, a)).unwrap();
+    graph.add_edge(edge(a, b)).unwrap();
+    assert_eq!(
+        graph.add_edge_auto_mix(edge(b, a)),
+        Err(GraphError::CycleDetected)
+    );
+    assert_eq!(graph.edges.len(), 2);
+}
@@ -0,0 +1,37 @@
+mod common;
+
+use auxide::graph::{Graph, GraphError, NodeId, NodeType};
+use common::edge;
+
+const CHAIN: usize = 100_000;
+
+/// A chain of `CHAIN` gains. Edges are pushed directly: each `add_edge`
+/// scans every existing edge, so building this through it is quadratic.
+fn long_chain() -> (Graph, NodeId, NodeId) {
+    let mut graph = Graph::new();
+    let nodes: Vec<NodeId> = (0..CHAIN)
+        .map(|_| graph.add_node(NodeType::Gain { gain: 1.0 }))
+        .collect();
+    graph
+        .edges
+        .extend(nodes.windows(2).map(|w| edge(w[0], w[1])));
+    (graph, nodes[0], nodes[CHAIN - 1])
+}
+
+#[test]
+fn cycle_through_long_chain_is_detected_without_overflow() {
+    let (mut graph, first, last) = long_chain();
+    // Run on a small stack: recursion one frame per node would overflow it.
+    let result = std::thread::Builder::new()
+        .stack_size(64 * 1024)
+        .spawn(move || {
+            let closing = graph.add_edge(edge(last, first));
+            let sink = graph.add_node(NodeType::OutputSink);
+            let open = graph.add_edge(edge(last, sink));
+            (closing, open)
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+    assert_eq!(result, (Err(GraphError::CycleDetected), Ok(())));
+}
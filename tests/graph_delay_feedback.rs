@@ -0,0 +1,90 @@
+mod common;
+
+use auxide::graph::{Graph, GraphError, NodeType};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+use common::{edge, edge_to};
+
+/// osc -> mix -> out, with mix -> delay -> gain(0.5) -> mix closing a loop.
+fn comb(delay_samples: f32) -> Graph {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let mix = graph.add_node(NodeType::Mix);
+    let delay = graph.add_node(NodeType::Delay { delay_samples });
+    let gain = graph.add_node(NodeType::Gain { gain: 0.5 });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, mix)).unwrap();
+    graph.add_edge(edge(mix, delay)).unwrap();
+    graph.add_edge(edge(delay, gain)).unwrap();
+    graph.add_edge(edge_to(gain, mix, 1)).unwrap();
+    graph.add_edge(edge(mix, sink)).unwrap();
+    graph
+}
+
+fn render(graph: &Graph, frames: usize) -> Vec<f32> {
+    let plan = Plan::compile(graph, 64).unwrap();
+    let mut runtime = Runtime::new(plan, graph, 44100.0);
+    render_offline(&mut runtime, frames).unwrap()
+}
+
+fn dry(frames: usize) -> Vec<f32> {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, sink)).unwrap();
+    render(&graph, frames)
+}
+
+fn expected_comb(loop_delay: usize, frames: usize) -> Vec<f32> {
+    let x = dry(frames);
+    let mut y = vec![0.0f32; frames];
+    for n in 0..frames {
+        y[n] = x[n]
+            + if n >= loop_delay {
+                0.5 * y[n - loop_delay]
+            } else {
+                0.0
+            };
+    }
+    y
+}
+
+#[test]
+fn cycle_without_delay_is_rejected() {
+    let mut graph = Graph::new();
+    let a = graph.add_node(NodeType::Gain { gain: 0.5 });
+    let b = graph.add_node(NodeType::Gain { gain: 0.5 });
+    graph.add_edge(edge(a, b)).unwrap();
+    assert_eq!(graph.add_edge(edge(b, a)), Err(GraphError::CycleDetected));
+}
+
+#[test]
+fn loop_through_delay_renders_exact_comb() {
+    let graph = comb(100.0);
+    let wet = render(&graph, 512);
+    let expected = expected_comb(100, 512);
+    for n in 0..512 {
+        assert!((wet[n] - expected[n]).abs() < 1e-5, "sample {}", n);
+    }
+}
+
+#[test]
+fn short_loop_delay_rounds_up_to_one_block() {
+    let graph = comb(10.0);
+    let wet = render(&graph, 512);
+    let expected = expected_comb(64, 512);
+    for n in 0..512 {
+        assert!((wet[n] - expected[n]).abs() < 1e-5, "sample {}", n);
+    }
+}
+
+#[test]
+fn delay_may_feed_itself() {
+    let mut graph = Graph::new();
+    let delay = graph.add_node(NodeType::Delay {
+        delay_samples: 128.0,
+    });
+    graph.add_edge(edge(delay, delay)).unwrap();
+    let plan = Plan::compile(&graph, 64).unwrap();
+    assert!(plan.reads_previous_block(delay));
+}
@@ -0,0 +1,54 @@
+mod common;
+
+use auxide::graph::{Graph, GraphError, NodeType, PortId};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+use common::{edge, edge_to};
+
+fn render(graph: &Graph) -> Vec<f32> {
+    let plan = Plan::compile(graph, 64).unwrap();
+    let mut runtime = Runtime::new(plan, graph, 44100.0);
+    render_offline(&mut runtime, 512).unwrap()
+}
+
+#[test]
+fn muted_edge_silences_one_destination() {
+    // osc fans out to two gains summed by a mix.
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let quiet = graph.add_node(NodeType::Gain { gain: 0.25 });
+    let loud = graph.add_node(NodeType::Gain { gain: 0.5 });
+    let mix = graph.add_node(NodeType::Mix);
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, quiet)).unwrap();
+    graph.add_edge(edge(osc, loud)).unwrap();
+    graph.add_edge(edge(quiet, mix)).unwrap();
+    graph.add_edge(edge_to(loud, mix, 1)).unwrap();
+    graph.add_edge(edge(mix, sink)).unwrap();
+
+    let mut dry = Graph::new();
+    let dry_osc = dry.add_node(NodeType::SineOsc { freq: 440.0 });
+    let dry_sink = dry.add_node(NodeType::OutputSink);
+    dry.add_edge(edge(dry_osc, dry_sink)).unwrap();
+    let sine = render(&dry);
+    let scaled = |gain: f32| sine.iter().map(|s| s * gain).collect::<Vec<f32>>();
+
+    graph
+        .set_edge_muted(osc, PortId(0), quiet, PortId(0), true)
+        .unwrap();
+    assert_eq!(render(&graph), scaled(0.5));
+    assert!(Plan::compile(&graph, 64).unwrap().is_static_silence(quiet));
+
+    graph
+        .set_edge_muted(osc, PortId(0), quiet, PortId(0), false)
+        .unwrap();
+    graph
+        .set_edge_muted(osc, PortId(0), loud, PortId(0), true)
+        .unwrap();
+    assert_eq!(render(&graph), scaled(0.25));
+
+    assert_eq!(
+        graph.set_edge_muted(osc, PortId(0), sink, PortId(0), true),
+        Err(GraphError::InvalidPort)
+    );
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use auxide::graph::{Graph, GraphError, NodeType, Port, PortId, Rate};
+use auxide::node::{NodeDef, ProcessCtx};
+use auxide::plan::Plan;
+use auxide::rt::Runtime;
+use common::{edge, edge_to};
+
+const BLOCK: usize = 16;
+const COEFF: f32 = 0.5;
+
+static TWO_AUDIO: [Port; 2] = [
+    Port {
+        id: PortId(0),
+        rate: Rate::Audio,
+    },
+    Port {
+        id: PortId(1),
+        rate: Rate::Audio,
+    },
+];
+
+/// `out = in0 + COEFF * in1`: a one-pole when `in1` is fed back from `out`.
+struct OnePole;
+
+impl NodeDef for OnePole {
+    type State = ();
+
+    fn input_ports(&self) -> &'static [Port] {
+        &TWO_AUDIO
+    }
+
+    fn output_ports(&self) -> &'static [Port] {
+        &TWO_AUDIO[..1]
+    }
+
+    fn required_inputs(&self) -> usize {
+        2
+    }
+
+    fn init_state(&self, _sample_rate: f32, _block_size: usize) -> Self::State {}
+
+    fn process(
+        &self,
+        _state: &mut Self::State,
+        inputs: &[&[f32]],
+        outputs: &mut [&mut [f32]],
+        _ctx: &ProcessCtx,
+    ) -> Result<(), &'static str> {
+        for ((y, &x), &fb) in outputs[0].iter_mut().zip(inputs[0]).zip(inputs[1]) {
+            *y = x + COEFF * fb;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn self_edge_needs_the_block_delay() {
+    let mut graph = Graph::new();
+    let pole = graph.add_node(NodeType::external(OnePole));
+    let other = graph.add_node(NodeType::Mix);
+
+    assert_eq!(
+        graph.add_edge(edge_to(pole, pole, 1)),
+        Err(GraphError::CycleDetected)
+    );
+    assert_eq!(
+        graph.add_feedback_edge(edge_to(other, pole, 1)),
+        Err(GraphError::InvalidNode)
+    );
+    graph.add_feedback_edge(edge_to(pole, pole, 1)).unwrap();
+    assert_eq!(
+        graph.add_feedback_edge(edge_to(pole, pole, 1)),
+        Err(GraphError::PortAlreadyConnected)
+    );
+    assert_eq!(
+        graph.add_edge(edge_to(other, pole, 1)),
+        Err(GraphError::PortAlreadyConnected)
+    );
+}
+
+#[test]
+fn one_pole_feedback_decays_exponentially() {
+    let mut graph = Graph::new();
+    let input = graph.add_node(NodeType::AudioInput {
+        label: "in".to_string(),
+        channel: 0,
+    });
+    let pole = graph.add_node(NodeType::external(OnePole));
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(input, pole)).unwrap();
+    graph.add_feedback_edge(edge_to(pole, pole, 1)).unwrap();
+    graph.add_edge(edge(pole, sink)).unwrap();
+
+    let plan = Plan::compile(&graph, BLOCK).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, 44100.0);
+    runtime.set_input(input, &[1.0]).unwrap();
+
+    // The impulse recirculates once per block, scaled by COEFF each time.
+    let mut out = vec![0.0; BLOCK];
+    for block in 0..20 {
+        runtime.process_block(&mut out).unwrap();
+        if block == 0 {
+            runtime.set_input(input, &[]).unwrap();
+        }
+        let expected = COEFF.powi(block);
+        assert!(
+            (out[0] - expected).abs() < 1e-6,
+            "block {}: {} vs {}",
+            block,
+            out[0],
+            expected
+        );
+        assert!(out[1..].iter().all(|&s| s == 0.0));
+    }
+}
@@ -1,6 +1,9 @@
-use auxide::graph::{Edge, Graph, GraphError, NodeId, NodeType, PortId, Rate};
+mod common;
+
+use auxide::graph::{Graph, GraphError, NodeId, NodeType};
 use auxide::invariant_ppt::{clear_invariant_log, contract_test, GRAPH_REJECTS_INVALID};
 use auxide::plan::Plan;
+use common::{edge, port_edge};
 
 #[test]
 fn no_cycles_unless_delay() {
@@ -8,22 +11,10 @@ fn no_cycles_unless_delay() {
     let node1 = graph.add_node(NodeType::Dummy);
     let node2 = graph.add_node(NodeType::Mix);
     // Add edge 1 -> 2
-    let edge1 = Edge {
-        from_node: node1,
-        from_port: PortId(0),
-        to_node: node2,
-        to_port: PortId(0),
-        rate: Rate::Audio,
-    };
+    let edge1 = edge(node1, node2);
     graph.add_edge(edge1).unwrap();
     // Try to add 2 -> 1, creating cycle
-    let edge2 = Edge {
-        from_node: node2,
-        from_port: PortId(0),
-        to_node: node1,
-        to_port: PortId(0),
-        rate: Rate::Audio,
-    };
+    let edge2 = edge(node2, node1);
     assert_eq!(graph.add_edge(edge2), Err(GraphError::CycleDetected));
     // Note: No Delay node yet, so cycles are always forbidden
 }
@@ -38,15 +29,7 @@ fn input_ports_connected_or_optional() {
 
     // Add the required edge
     let osc_node = graph.add_node(NodeType::SineOsc { freq: 440.0 });
-    graph
-        .add_edge(Edge {
-            from_node: osc_node,
-            from_port: PortId(0),
-            to_node: gain_node,
-            to_port: PortId(0),
-            rate: Rate::Audio,
-        })
-        .unwrap();
+    graph.add_edge(edge(osc_node, gain_node)).unwrap();
     // Now should succeed
     assert!(auxide::plan::Plan::compile(&graph, 64).is_ok());
 
@@ -67,24 +50,8 @@ fn output_ports_fan_out_via_mix() {
     let node1 = graph.add_node(NodeType::SineOsc { freq: 440.0 });
     let node2 = graph.add_node(NodeType::Gain { gain: 1.0 });
     let node3 = graph.add_node(NodeType::Gain { gain: 1.0 });
-    graph
-        .add_edge(Edge {
-            from_node: node1,
-            from_port: PortId(0),
-            to_node: node2,
-            to_port: PortId(0),
-            rate: Rate::Audio,
-        })
-        .unwrap();
-    graph
-        .add_edge(Edge {
-            from_node: node1,
-            from_port: PortId(0),
-            to_node: node3,
-            to_port: PortId(0),
-            rate: Rate::Audio,
-        })
-        .unwrap();
+    graph.add_edge(edge(node1, node2)).unwrap();
+    graph.add_edge(edge(node1, node3)).unwrap();
     // Should succeed, as fan-out is allowed.
     assert!(auxide::plan::Plan::compile(&graph, 64).is_ok());
 }
@@ -94,11 +61,11 @@ fn node_ids_stable_monotonic() {
     let mut graph = Graph::new();
     let node1 = graph.add_node(NodeType::Dummy);
     let node2 = graph.add_node(NodeType::Dummy);
-    assert_eq!(node1, NodeId(0));
-    assert_eq!(node2, NodeId(1));
+    assert_eq!(node1, NodeId(0, 0));
+    assert_eq!(node2, NodeId(1, 0));
     // Monotonic: next is 2
     let node3 = graph.add_node(NodeType::Dummy);
-    assert_eq!(node3, NodeId(2));
+    assert_eq!(node3, NodeId(2, 0));
 }
 
 #[test]
@@ -106,15 +73,7 @@ fn remove_node_invalidates_edges() {
     let mut graph = Graph::new();
     let node1 = graph.add_node(NodeType::Dummy);
     let node2 = graph.add_node(NodeType::Dummy);
-    graph
-        .add_edge(Edge {
-            from_node: node1,
-            from_port: PortId(0),
-            to_node: node2,
-            to_port: PortId(0),
-            rate: Rate::Audio,
-        })
-        .unwrap();
+    graph.add_edge(edge(node1, node2)).unwrap();
     // Remove node1
     graph.remove_node(node1).unwrap();
     // Edges to/from node1 should be removed
@@ -132,37 +91,13 @@ fn remove_middle_node_preserves_survivors() {
     let node1 = graph.add_node(NodeType::Dummy);
     let node2 = graph.add_node(NodeType::Dummy);
     // Add edge 0 -> 1
-    graph
-        .add_edge(Edge {
-            from_node: node0,
-            from_port: PortId(0),
-            to_node: node1,
-            to_port: PortId(0),
-            rate: Rate::Audio,
-        })
-        .unwrap();
+    graph.add_edge(edge(node0, node1)).unwrap();
     // Add edge 1 -> 2
-    graph
-        .add_edge(Edge {
-            from_node: node1,
-            from_port: PortId(0),
-            to_node: node2,
-            to_port: PortId(0),
-            rate: Rate::Audio,
-        })
-        .unwrap();
+    graph.add_edge(edge(node1, node2)).unwrap();
     // Remove middle node1
     graph.remove_node(node1).unwrap();
     // Now add edge between survivors 0 -> 2
-    graph
-        .add_edge(Edge {
-            from_node: node0,
-            from_port: PortId(0),
-            to_node: node2,
-            to_port: PortId(0),
-            rate: Rate::Audio,
-        })
-        .unwrap();
+    graph.add_edge(edge(node0, node2)).unwrap();
     // Compile plan without panic or misrouting
     let plan = Plan::compile(&graph, 64).unwrap();
     assert_eq!(plan.edges.len(), 1);
@@ -180,15 +115,7 @@ fn remove_node_stress_recompile() {
     }
     // Add some edges
     for i in 0..9 {
-        graph
-            .add_edge(Edge {
-                from_node: nodes[i],
-                from_port: PortId(0),
-                to_node: nodes[i + 1],
-                to_port: PortId(0),
-                rate: Rate::Audio,
-            })
-            .unwrap();
+        graph.add_edge(edge(nodes[i], nodes[i + 1])).unwrap();
     }
     // Compile initial plan
     let mut plan = Plan::compile(&graph, 64).unwrap();
@@ -205,18 +132,11 @@ fn remove_node_stress_recompile() {
 
     // Add new nodes and edges
     let new_node = graph.add_node(NodeType::Dummy);
-    graph
-        .add_edge(Edge {
-            from_node: nodes[1], // Assuming still exists
-            from_port: PortId(0),
-            to_node: new_node,
-            to_port: PortId(0),
-            rate: Rate::Audio,
-        })
-        .unwrap();
+    // Assuming nodes[1] still exists
+    graph.add_edge(edge(nodes[1], new_node)).unwrap();
     // Recompile again
     plan = Plan::compile(&graph, 64).unwrap();
-    assert!(plan.edges.len() > 0);
+    assert!(!plan.edges.is_empty());
 }
 
 #[test]
@@ -228,7 +148,10 @@ fn remove_node_bounds_check() {
     assert!(graph.remove_node(osc).is_ok());
 
     // Invalid removal
-    assert_eq!(graph.remove_node(NodeId(999)), Err(GraphError::InvalidNode));
+    assert_eq!(
+        graph.remove_node(NodeId(999, 0)),
+        Err(GraphError::InvalidNode)
+    );
 }
 
 #[test]
@@ -249,25 +172,11 @@ fn edge_direction_validation() {
     let mix = graph.add_node(NodeType::Mix);
 
     // Valid: output to input
-    assert!(graph
-        .add_edge(Edge {
-            from_node: osc,
-            from_port: PortId(0), // output
-            to_node: gain,
-            to_port: PortId(0), // input
-            rate: Rate::Audio,
-        })
-        .is_ok());
+    assert!(graph.add_edge(edge(osc, gain)).is_ok());
 
     // Invalid: from non-existent output port
     assert_eq!(
-        graph.add_edge(Edge {
-            from_node: mix,
-            from_port: PortId(2), // not an output port
-            to_node: gain,
-            to_port: PortId(0), // input
-            rate: Rate::Audio,
-        }),
+        graph.add_edge(port_edge(mix, 2, gain, 0)),
         Err(GraphError::InvalidPort)
     );
 
@@ -285,13 +194,7 @@ fn invalid_node_bounds_check() {
 
     // Try to add edge with invalid node ID
     assert_eq!(
-        graph.add_edge(Edge {
-            from_node: NodeId(999), // out of bounds
-            from_port: PortId(0),
-            to_node: osc,
-            to_port: PortId(0),
-            rate: Rate::Audio,
-        }),
+        graph.add_edge(edge(NodeId(999, 0), osc)),
         Err(GraphError::InvalidNode)
     );
 }
@@ -0,0 +1,88 @@
+mod common;
+
+use auxide::graph::{Graph, NodeType, Port, PortId, Rate};
+use auxide::node::{NodeDef, ProcessCtx};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+use common::{edge, edge_to};
+
+static AUDIO_OUT: [Port; 1] = [Port {
+    id: PortId(0),
+    rate: Rate::Audio,
+}];
+
+/// Unit impulse at sample 0.
+struct Impulse;
+
+impl NodeDef for Impulse {
+    type State = bool;
+
+    fn input_ports(&self) -> &'static [Port] {
+        &[]
+    }
+
+    fn output_ports(&self) -> &'static [Port] {
+        &AUDIO_OUT
+    }
+
+    fn required_inputs(&self) -> usize {
+        0
+    }
+
+    fn init_state(&self, _sample_rate: f32, _block_size: usize) -> Self::State {
+        false
+    }
+
+    fn process(
+        &self,
+        fired: &mut Self::State,
+        _inputs: &[&[f32]],
+        outputs: &mut [&mut [f32]],
+        _ctx: &ProcessCtx,
+    ) -> Result<(), &'static str> {
+        if !*fired {
+            outputs[0][0] = 1.0;
+            *fired = true;
+        }
+        Ok(())
+    }
+}
+
+fn impulse_arrivals(graph: &Graph) -> Vec<(usize, f32)> {
+    let plan = Plan::compile(graph, 64).unwrap();
+    let mut runtime = Runtime::new(plan, graph, 48000.0);
+    render_offline(&mut runtime, 256)
+        .unwrap()
+        .into_iter()
+        .enumerate()
+        .filter(|&(_, y)| y != 0.0)
+        .collect()
+}
+
+#[test]
+fn compensation_aligns_impulse_at_mix() {
+    // impulse -> delay(50) -> mix[0]
+    // impulse ------------->  mix[1] -> sink
+    let mut graph = Graph::new();
+    let src = graph.add_node(NodeType::external(Impulse));
+    let delay = graph.add_node(NodeType::Delay {
+        delay_samples: 50.0,
+    });
+    let mix = graph.add_node(NodeType::Mix);
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(src, delay)).unwrap();
+    graph.add_edge(edge(delay, mix)).unwrap();
+    graph.add_edge(edge_to(src, mix, 1)).unwrap();
+    graph.add_edge(edge(mix, sink)).unwrap();
+
+    assert_eq!(impulse_arrivals(&graph), vec![(0, 1.0), (50, 1.0)]);
+
+    let inserted = graph.compensate_latency();
+    assert_eq!(inserted.len(), 1);
+    let plan = Plan::compile(&graph, 64).unwrap();
+    assert!(plan.latency_imbalances().is_empty());
+    assert_eq!(impulse_arrivals(&graph), vec![(50, 2.0)]);
+
+    // Already aligned: nothing more to do.
+    assert!(graph.compensate_latency().is_empty());
+}
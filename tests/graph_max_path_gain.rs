@@ -0,0 +1,55 @@
+mod common;
+
+use auxide::graph::{Graph, GraphError, NodeType, PortId};
+use common::{edge, edge_to};
+
+#[test]
+fn two_unity_sources_into_mix() {
+    let mut graph = Graph::new();
+    let a = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let b = graph.add_node(NodeType::SineOsc { freq: 660.0 });
+    let mix = graph.add_node(NodeType::Mix);
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(a, mix)).unwrap();
+    graph.add_edge(edge_to(b, mix, 1)).unwrap();
+    graph.add_edge(edge(mix, sink)).unwrap();
+    assert_eq!(graph.max_path_gain(), Ok(2.0));
+
+    graph
+        .set_edge_muted(b, PortId(0), mix, PortId(1), true)
+        .unwrap();
+    assert_eq!(graph.max_path_gain(), Ok(1.0));
+}
+
+#[test]
+fn gains_multiply_along_paths() {
+    // osc -> gain(0.5) -> gain(3) -> delay -> sink
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let half = graph.add_node(NodeType::Gain { gain: 0.5 });
+    let triple = graph.add_node(NodeType::Gain { gain: -3.0 });
+    let delay = graph.add_node(NodeType::Delay { delay_samples: 4.0 });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, half)).unwrap();
+    graph.add_edge(edge(half, triple)).unwrap();
+    graph.add_edge(edge(triple, delay)).unwrap();
+    graph.add_edge(edge(delay, sink)).unwrap();
+    assert_eq!(graph.max_path_gain(), Ok(1.5));
+
+    assert_eq!(Graph::new().max_path_gain(), Ok(0.0));
+}
+
+#[test]
+fn cycle_without_delay_is_an_error() {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let mix = graph.add_node(NodeType::Mix);
+    let gain = graph.add_node(NodeType::Gain { gain: 0.5 });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, mix)).unwrap();
+    graph.add_edge(edge(mix, gain)).unwrap();
+    graph.add_edge(edge(gain, sink)).unwrap();
+    // `add_edge` refuses the loop, so close it by hand.
+    graph.edges.push(edge_to(gain, mix, 1));
+    assert_eq!(graph.max_path_gain(), Err(GraphError::CycleDetected));
+}
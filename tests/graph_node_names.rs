@@ -0,0 +1,52 @@
+use auxide::control::{new_runtime_control, ControlMsg};
+use auxide::dsl::GraphBuilder;
+use auxide::graph::{Graph, NodeType, PortId, Rate};
+
+#[test]
+fn builder_names_survive_into_the_graph() {
+    let mut builder = GraphBuilder::new();
+    let osc = builder.node_named("osc", NodeType::SineOsc { freq: 440.0 });
+    let sink = builder.node_named("out", NodeType::OutputSink);
+    builder
+        .connect(osc, PortId(0), sink, PortId(0), Rate::Audio)
+        .unwrap();
+    let graph = builder.build().unwrap();
+    assert_eq!(graph.node_by_name("osc"), Some(osc.0));
+    assert_eq!(graph.node_name(sink.0), Some("out"));
+    assert_eq!(graph.node_by_name("missing"), None);
+}
+
+#[test]
+fn names_are_unique() {
+    let mut graph = Graph::new();
+    let a = graph.add_node(NodeType::Gain { gain: 1.0 });
+    let b = graph.add_node(NodeType::Gain { gain: 1.0 });
+    graph.set_node_name(a, "amp").unwrap();
+    graph.set_node_name(b, "amp").unwrap();
+    assert_eq!(graph.node_by_name("amp"), Some(b));
+    assert_eq!(graph.node_name(a), None);
+
+    graph.remove_node(b).unwrap();
+    assert_eq!(graph.node_by_name("amp"), None);
+    assert!(graph.set_node_name(b, "amp").is_err());
+}
+
+#[test]
+fn control_resolves_names_on_the_main_thread() {
+    let mut graph = Graph::new();
+    let amp = graph.add_node(NodeType::Gain { gain: 1.0 });
+    graph.set_node_name(amp, "amp").unwrap();
+    let (mut control, mut control_rx, _) = new_runtime_control();
+    control.set_node_names(&graph);
+
+    assert_eq!(control.node_by_name("amp"), Some(amp));
+    control
+        .send_named("amp", |node| ControlMsg::Mute { node })
+        .unwrap();
+    assert!(matches!(control_rx.pop(), Ok((None, ControlMsg::Mute { node })) if node == amp));
+    assert_eq!(
+        control.send_named("nope", |node| ControlMsg::Mute { node }),
+        Err("unknown node name")
+    );
+    assert!(control_rx.is_empty());
+}
@@ -0,0 +1,125 @@
+mod common;
+
+use auxide::control::ControlMsg;
+use auxide::graph::{Graph, GraphError, NodeId, NodeType, Port, PortId, Rate};
+use auxide::node::{NodeDef, ProcessCtx};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+use common::edge;
+
+const SAMPLE_RATE: f32 = 48000.0;
+
+fn render(graph: &Graph, frames: usize) -> Vec<f32> {
+    let plan = Plan::compile(graph, 64).unwrap();
+    let mut runtime = Runtime::new(plan, graph, SAMPLE_RATE);
+    render_offline(&mut runtime, frames).unwrap()
+}
+
+#[test]
+fn freq_param_retunes_sine() {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, sink)).unwrap();
+
+    assert_eq!(graph.get_node_param(osc, "freq"), None);
+    graph.set_node_param(osc, "freq", 500.0).unwrap();
+    graph.set_node_param(osc, "freq", 1000.0).unwrap();
+    assert_eq!(graph.get_node_param(osc, "freq"), Some(1000.0));
+    assert_eq!(
+        graph.set_node_param(NodeId(9, 0), "freq", 1.0),
+        Err(GraphError::InvalidNode)
+    );
+
+    // One second of output: upward zero crossings count cycles.
+    let out = render(&graph, SAMPLE_RATE as usize);
+    let cycles = (1..out.len())
+        .filter(|&n| out[n - 1] < 0.0 && out[n] >= 0.0)
+        .count();
+    assert!((999..=1000).contains(&cycles), "{} cycles", cycles);
+}
+
+static AUDIO_OUT: [Port; 1] = [Port {
+    id: PortId(0),
+    rate: Rate::Audio,
+}];
+
+/// Emits a constant level settable through the "level" parameter.
+struct Level;
+
+impl NodeDef for Level {
+    type State = f32;
+
+    fn input_ports(&self) -> &'static [Port] {
+        &[]
+    }
+
+    fn output_ports(&self) -> &'static [Port] {
+        &AUDIO_OUT
+    }
+
+    fn required_inputs(&self) -> usize {
+        0
+    }
+
+    fn init_state(&self, _sample_rate: f32, _block_size: usize) -> f32 {
+        0.0
+    }
+
+    fn set_param(&self, state: &mut f32, name: &str, value: f32) {
+        if name == "level" {
+            *state = value;
+        }
+    }
+
+    fn set_param_index(&self, state: &mut f32, idx: u8, value: f32) {
+        if idx == 0 {
+            *state = value;
+        }
+    }
+
+    fn process(
+        &self,
+        state: &mut f32,
+        _inputs: &[&[f32]],
+        outputs: &mut [&mut [f32]],
+        _ctx: &ProcessCtx,
+    ) -> Result<(), &'static str> {
+        outputs[0].fill(*state);
+        Ok(())
+    }
+}
+
+#[test]
+fn unknown_params_reach_external_nodes() {
+    let mut graph = Graph::new();
+    let level = graph.add_node(NodeType::external(Level));
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(level, sink)).unwrap();
+    graph.set_node_param(level, "level", 0.25).unwrap();
+    graph.set_node_param(level, "ignored", 9.0).unwrap();
+
+    assert!(render(&graph, 128).iter().all(|&s| s == 0.25));
+}
+
+#[test]
+fn set_param_messages_reach_external_nodes() {
+    let mut graph = Graph::new();
+    let level = graph.add_node(NodeType::external(Level));
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(level, sink)).unwrap();
+    let plan = Plan::compile(&graph, 64).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, SAMPLE_RATE);
+
+    for (param_idx, value) in [(0, 0.5), (3, 9.0)] {
+        runtime.apply_control(ControlMsg::SetParam {
+            node: level,
+            param_idx,
+            value,
+        });
+    }
+    assert!(render_offline(&mut runtime, 128)
+        .unwrap()
+        .iter()
+        .all(|&s| s == 0.5));
+}
@@ -0,0 +1,67 @@
+use auxide::graph::{Graph, NodeType, Port};
+use auxide::node::{NodeDef, ProcessCtx};
+
+struct Silent;
+
+impl NodeDef for Silent {
+    type State = ();
+
+    fn input_ports(&self) -> &'static [Port] {
+        &[]
+    }
+
+    fn output_ports(&self) -> &'static [Port] {
+        &[]
+    }
+
+    fn required_inputs(&self) -> usize {
+        0
+    }
+
+    fn init_state(&self, _sample_rate: f32, _block_size: usize) -> Self::State {}
+
+    fn process(
+        &self,
+        _state: &mut Self::State,
+        _inputs: &[&[f32]],
+        _outputs: &mut [&mut [f32]],
+        _ctx: &ProcessCtx,
+    ) -> Result<(), &'static str> {
+        Ok(())
+    }
+}
+
+#[test]
+fn builtin_nodes_compare_by_parameters() {
+    assert_eq!(
+        NodeType::SineOsc { freq: 440.0 },
+        NodeType::SineOsc { freq: 440.0 }
+    );
+    assert_ne!(
+        NodeType::SineOsc { freq: 440.0 },
+        NodeType::SineOsc { freq: 441.0 }
+    );
+    assert_ne!(NodeType::Gain { gain: 1.0 }, NodeType::Mix);
+}
+
+#[test]
+fn external_nodes_compare_by_definition() {
+    let a = NodeType::external(Silent);
+    assert_eq!(a, a.clone());
+    assert_ne!(a, NodeType::external(Silent));
+}
+
+#[test]
+fn subgraphs_compare_by_contents() {
+    let subgraph = |freq| {
+        let mut inner = Graph::new();
+        inner.add_node(NodeType::SineOsc { freq });
+        NodeType::Subgraph {
+            graph: Box::new(inner),
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+        }
+    };
+    assert_eq!(subgraph(440.0), subgraph(440.0));
+    assert_ne!(subgraph(440.0), subgraph(220.0));
+}
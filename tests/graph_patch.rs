@@ -0,0 +1,61 @@
+use auxide::graph::{Graph, NodeType, PortId};
+use auxide::patch::PatchError;
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+
+#[test]
+fn linear_patch_round_trips() {
+    let text = "osc(440) -> gain(0.5) -> sink";
+    let graph = Graph::from_patch_str(text).unwrap();
+    assert_eq!(graph.nodes.len(), 3);
+    assert_eq!(graph.edges.len(), 2);
+
+    let plan = Plan::compile(&graph, 64).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, 44100.0);
+    let out = render_offline(&mut runtime, 256).unwrap();
+    let peak = out.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+    assert!((peak - 0.5).abs() < 1e-3, "peak {}", peak);
+
+    assert_eq!(graph.to_patch_str().unwrap(), text);
+}
+
+#[test]
+fn labelled_fan_in_round_trips() {
+    let graph =
+        Graph::from_patch_str("m: mix -> sink\nosc(440) -> m; noise(7, 1) -> delay(2.5) -> m")
+            .unwrap();
+    let mix_inputs: Vec<PortId> = graph
+        .edges
+        .iter()
+        .filter(|e| e.to_node.0 == 0)
+        .map(|e| e.to_port)
+        .collect();
+    assert_eq!(mix_inputs, vec![PortId(0), PortId(1)]);
+    assert!(Plan::compile(&graph, 64).is_ok());
+
+    // The printed form is canonical: it reparses to itself.
+    let printed = graph.to_patch_str().unwrap();
+    let reparsed = Graph::from_patch_str(&printed).unwrap();
+    assert_eq!(reparsed.to_patch_str().unwrap(), printed);
+    assert_eq!(reparsed.edges.len(), graph.edges.len());
+}
+
+#[test]
+fn patch_errors() {
+    assert_eq!(
+        Graph::from_patch_str("osc(440) -> reverb -> sink").unwrap_err(),
+        PatchError::UnknownNode("reverb".to_string())
+    );
+    assert_eq!(
+        Graph::from_patch_str("osc(fast) -> sink").unwrap_err(),
+        PatchError::BadArgs("osc(fast)".to_string())
+    );
+    assert_eq!(
+        Graph::from_patch_str("osc(1) -> s: sink; osc(2) -> s").unwrap_err(),
+        PatchError::NoFreeInput("s".to_string())
+    );
+
+    let mut graph = Graph::new();
+    graph.add_node(NodeType::Waveshaper { table: vec![] });
+    assert_eq!(graph.to_patch_str().unwrap_err(), PatchError::Unsupported);
+}
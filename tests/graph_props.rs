@@ -1,5 +1,8 @@
-use auxide::graph::{Edge, Graph, NodeType, PortId, Rate};
+mod common;
+
+use auxide::graph::{Graph, NodeType};
 use auxide::plan::Plan;
+use common::port_edge;
 use proptest::prelude::*;
 
 fn node_type_strategy() -> impl Strategy<Value = NodeType> {
@@ -40,15 +43,8 @@ fn graph_strategy() -> impl Strategy<Value = Graph> {
                 .map(|nt| graph.add_node(nt))
                 .collect();
             for (from_idx, to_idx, from_port, to_port) in edge_specs {
-                let from_node = node_ids[from_idx];
-                let to_node = node_ids[to_idx];
-                let edge = Edge {
-                    from_node,
-                    from_port: PortId(from_port),
-                    to_node,
-                    to_port: PortId(to_port),
-                    rate: Rate::Audio, // Simplify
-                };
+                // Audio only, to keep it simple.
+                let edge = port_edge(node_ids[from_idx], from_port, node_ids[to_idx], to_port);
                 let _ = graph.add_edge(edge); // Ignore errors for now
             }
             graph
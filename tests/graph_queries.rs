@@ -0,0 +1,38 @@
+mod common;
+
+use auxide::graph::{Graph, NodeType};
+use common::{edge, edge_to};
+
+#[test]
+fn neighbours_and_edges_of_a_node() {
+    // osc feeds both inputs of the mix, directly and through a gain.
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let gain = graph.add_node(NodeType::Gain { gain: 0.5 });
+    let mix = graph.add_node(NodeType::Mix);
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, mix)).unwrap();
+    graph.add_edge(edge(osc, gain)).unwrap();
+    graph.add_edge(edge_to(gain, mix, 1)).unwrap();
+    graph.add_edge(edge(mix, sink)).unwrap();
+
+    assert_eq!(graph.inputs_of(mix).count(), 2);
+    assert_eq!(graph.outputs_of(osc).count(), 2);
+    assert_eq!(graph.predecessors(mix).collect::<Vec<_>>(), vec![osc, gain]);
+    assert_eq!(graph.successors(osc).collect::<Vec<_>>(), vec![gain, mix]);
+    assert_eq!(graph.successors(sink).count(), 0);
+    assert_eq!(
+        graph.edges_between(gain, mix).collect::<Vec<_>>(),
+        vec![&edge_to(gain, mix, 1)]
+    );
+    assert_eq!(graph.edges_between(mix, gain).count(), 0);
+}
+
+#[test]
+fn feedback_edges_count() {
+    let mut graph = Graph::new();
+    let mix = graph.add_node(NodeType::Mix);
+    graph.add_feedback_edge(edge_to(mix, mix, 1)).unwrap();
+    assert_eq!(graph.predecessors(mix).collect::<Vec<_>>(), vec![mix]);
+    assert_eq!(graph.edges_between(mix, mix).count(), 1);
+}
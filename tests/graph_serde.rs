@@ -0,0 +1,98 @@
+#![cfg(feature = "serde")]
+
+mod common;
+
+use auxide::graph::{Graph, NodeType, Port, PortId, Rate};
+use auxide::node::{NodeDef, NodeRegistry, ProcessCtx};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+use common::{edge, edge_to};
+
+static AUDIO_OUT: [Port; 1] = [Port {
+    id: PortId(0),
+    rate: Rate::Audio,
+}];
+
+/// Constant 0.25 DC offset.
+struct Offset;
+
+impl NodeDef for Offset {
+    type State = ();
+
+    fn type_name(&self) -> &'static str {
+        "offset"
+    }
+
+    fn input_ports(&self) -> &'static [Port] {
+        &[]
+    }
+
+    fn output_ports(&self) -> &'static [Port] {
+        &AUDIO_OUT
+    }
+
+    fn required_inputs(&self) -> usize {
+        0
+    }
+
+    fn init_state(&self, _sample_rate: f32, _block_size: usize) -> Self::State {}
+
+    fn process(
+        &self,
+        _state: &mut Self::State,
+        _inputs: &[&[f32]],
+        outputs: &mut [&mut [f32]],
+        _ctx: &ProcessCtx,
+    ) -> Result<(), &'static str> {
+        outputs[0].fill(0.25);
+        Ok(())
+    }
+}
+
+fn patch() -> Graph {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::Wavetable {
+        table: vec![0.0, 1.0, 0.0, -1.0],
+        freq: 220.0,
+        interpolation: Default::default(),
+    });
+    let offset = graph.add_node(NodeType::external(Offset));
+    let mix = graph.add_node(NodeType::Mix);
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, mix)).unwrap();
+    graph.add_edge(edge_to(offset, mix, 1)).unwrap();
+    graph.add_edge(edge(mix, sink)).unwrap();
+    graph.set_node_name(mix, "mix").unwrap();
+    graph
+}
+
+fn render(graph: &Graph) -> Vec<f32> {
+    let plan = Plan::compile(graph, 64).unwrap();
+    let mut runtime = Runtime::new(plan, graph, 48000.0);
+    render_offline(&mut runtime, 256).unwrap()
+}
+
+#[test]
+fn graph_round_trips_through_json() {
+    let graph = patch();
+    let mut json = Vec::new();
+    graph.to_writer(&mut json).unwrap();
+    assert!(String::from_utf8_lossy(&json).contains("\"offset\""));
+
+    let mut registry = NodeRegistry::new();
+    registry.register(Offset);
+    let loaded = Graph::from_reader(json.as_slice(), &registry).unwrap();
+    assert_eq!(loaded.edges, graph.edges);
+    assert_eq!(loaded.node_by_name("mix"), graph.node_by_name("mix"));
+    assert_eq!(render(&loaded), render(&graph));
+}
+
+#[test]
+fn unregistered_external_node_fails_to_load() {
+    let mut json = Vec::new();
+    patch().to_writer(&mut json).unwrap();
+    let err = Graph::from_reader(json.as_slice(), &NodeRegistry::new()).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("unregistered external node `offset`"));
+}
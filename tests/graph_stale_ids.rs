@@ -0,0 +1,95 @@
+mod common;
+
+use auxide::control::ControlMsg;
+use auxide::graph::{Graph, GraphError, NodeId, NodeType, PortId};
+use auxide::plan::{Plan, PlanError};
+use auxide::rt::Runtime;
+use common::edge;
+
+#[test]
+fn reused_slots_get_a_new_generation() {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let stale = graph.add_node(NodeType::Gain { gain: 1.0 });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, stale)).unwrap();
+    graph.add_edge(edge(stale, sink)).unwrap();
+    graph.remove_node(stale).unwrap();
+
+    let fresh = graph.add_node(NodeType::Gain { gain: 1.0 });
+    assert_eq!(fresh, NodeId(stale.0, stale.1 + 1));
+    assert!(graph.contains(fresh) && !graph.contains(stale));
+    assert_eq!(
+        graph.add_edge(edge(osc, stale)),
+        Err(GraphError::InvalidNode)
+    );
+    assert_eq!(
+        graph.set_node_param(stale, "gain", 0.5),
+        Err(GraphError::InvalidNode)
+    );
+    assert_eq!(graph.remove_node(stale), Err(GraphError::InvalidNode));
+
+    // `Graph::edges` is public, so a stale edge can still be pushed.
+    graph.add_edge(edge(osc, fresh)).unwrap();
+    graph.add_edge(edge(fresh, sink)).unwrap();
+    graph.edges.push(edge(stale, fresh));
+    assert_eq!(
+        Plan::compile(&graph, 64).unwrap_err(),
+        PlanError::InvalidNode { node: stale }
+    );
+    graph.edges.pop();
+
+    let plan = Plan::compile(&graph, 64).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, 48000.0);
+    for msg in [
+        ControlMsg::SetGain {
+            node: stale,
+            gain: 0.0,
+        },
+        ControlMsg::Mute { node: stale },
+        ControlMsg::Solo { node: stale },
+        ControlMsg::Mute {
+            node: NodeId(99, 0),
+        },
+    ] {
+        runtime.apply_control(msg);
+    }
+    let mut out = vec![0.0; 64];
+    runtime.process_block(&mut out).unwrap();
+    assert!(out.iter().any(|&x| x != 0.0));
+}
+
+#[test]
+fn subgraph_contents_fill_free_slots() {
+    let mut inner = Graph::new();
+    let gain = inner.add_node(NodeType::Gain { gain: 0.5 });
+    let mut middle = Graph::new();
+    let nested = middle
+        .add_subgraph(inner, &[(gain, PortId(0))], &[(gain, PortId(0))])
+        .unwrap();
+
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let removed = graph.add_node(NodeType::Dummy);
+    let composite = graph
+        .add_subgraph(middle, &[(nested, PortId(0))], &[(nested, PortId(0))])
+        .unwrap();
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, composite)).unwrap();
+    graph.add_edge(edge(composite, sink)).unwrap();
+    graph.remove_node(removed).unwrap();
+
+    // The nested subgraph lands in the freed slot, before the composite,
+    // and is still expanded.
+    let flat = graph.flatten();
+    assert!(flat
+        .nodes
+        .iter()
+        .flatten()
+        .all(|n| !matches!(n.node_type, NodeType::Subgraph { .. })));
+    let plan = Plan::compile(&graph, 64).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, 48000.0);
+    let mut out = vec![0.0; 64];
+    runtime.process_block(&mut out).unwrap();
+    assert!(out.iter().any(|&x| x != 0.0));
+}
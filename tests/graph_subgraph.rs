@@ -0,0 +1,75 @@
+mod common;
+
+use auxide::graph::{Graph, GraphError, NodeId, NodeType, PortId};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+use common::edge;
+
+/// Two gains of 0.5 in series, exposed as one input and one output.
+fn quarter() -> (Graph, (NodeId, PortId), (NodeId, PortId)) {
+    let mut graph = Graph::new();
+    let a = graph.add_node(NodeType::Gain { gain: 0.5 });
+    let b = graph.add_node(NodeType::Gain { gain: 0.5 });
+    graph.add_edge(edge(a, b)).unwrap();
+    (graph, (a, PortId(0)), (b, PortId(0)))
+}
+
+/// osc -> `node` -> sink, rendered.
+fn render_through(mut graph: Graph, node: NodeId) -> Vec<f32> {
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, node)).unwrap();
+    graph.add_edge(edge(node, sink)).unwrap();
+    let plan = Plan::compile(&graph, 64).unwrap();
+    render_offline(&mut Runtime::new(plan, &graph, 48000.0), 512).unwrap()
+}
+
+#[test]
+fn composite_node_renders_like_its_contents() {
+    let mut expected = Graph::new();
+    let gain = expected.add_node(NodeType::Gain { gain: 0.25 });
+    let expected = render_through(expected, gain);
+
+    let (sub, input, output) = quarter();
+    let mut graph = Graph::new();
+    let composite = graph.add_subgraph(sub, &[input], &[output]).unwrap();
+    assert_eq!(graph.nodes.len(), 1);
+    assert_eq!(render_through(graph, composite), expected);
+}
+
+#[test]
+fn composites_nest() {
+    let mut expected = Graph::new();
+    let gain = expected.add_node(NodeType::Gain { gain: 0.0625 });
+    let expected = render_through(expected, gain);
+
+    let (sub, input, output) = quarter();
+    let mut middle = Graph::new();
+    let first = middle
+        .add_subgraph(sub.clone(), &[input], &[output])
+        .unwrap();
+    let second = middle.add_subgraph(sub, &[input], &[output]).unwrap();
+    middle.add_edge(edge(first, second)).unwrap();
+
+    let mut graph = Graph::new();
+    let composite = graph
+        .add_subgraph(middle, &[(first, PortId(0))], &[(second, PortId(0))])
+        .unwrap();
+    let flat = graph.flatten();
+    assert!(flat.nodes[composite.0].is_none());
+    assert_eq!(flat.nodes.iter().flatten().count(), 4);
+    assert_eq!(flat.edges.len(), 3);
+    for (&y, &x) in render_through(graph, composite).iter().zip(&expected) {
+        assert!((y - x).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn exposed_ports_must_exist() {
+    let (sub, input, _) = quarter();
+    let mut graph = Graph::new();
+    assert_eq!(
+        graph.add_subgraph(sub, &[input], &[(NodeId(9, 0), PortId(0))]),
+        Err(GraphError::InvalidNode)
+    );
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use auxide::dsp::DelayLine;
+use auxide::graph::{Edge, Graph, NodeType, PortId, Rate, ValidationError};
+use auxide::plan::Plan;
+use common::{edge, edge_to};
+
+#[test]
+fn valid_graph_reports_nothing() {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let delay = graph.add_node(NodeType::Delay {
+        delay_samples: 100.0,
+    });
+    let mix = graph.add_node(NodeType::Mix);
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, mix)).unwrap();
+    graph.add_edge(edge(mix, delay)).unwrap();
+    // A loop through a delay is fine.
+    graph.add_edge(edge_to(delay, mix, 1)).unwrap();
+    graph.add_edge(edge(delay, sink)).unwrap();
+    assert!(graph.validate().is_ok());
+    assert!(Plan::compile(&graph, 64).is_ok());
+}
+
+#[test]
+fn every_problem_is_reported_at_once() {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let lonely = graph.add_node(NodeType::Gain { gain: 1.0 });
+    let a = graph.add_node(NodeType::Gain { gain: 0.5 });
+    let b = graph.add_node(NodeType::Gain { gain: 0.5 });
+    let sink = graph.add_node(NodeType::OutputSink);
+    // `Graph::edges` is public, so broken graphs can be built directly.
+    graph.edges.push(Edge {
+        rate: Rate::Control,
+        ..edge(osc, sink)
+    });
+    graph.edges.push(edge(osc, a));
+    graph.edges.push(edge(a, b));
+    graph.edges.push(edge(b, a));
+
+    assert_eq!(
+        graph.validate().errors,
+        vec![
+            ValidationError::RateMismatch {
+                node: osc,
+                port: PortId(0)
+            },
+            ValidationError::RateMismatch {
+                node: sink,
+                port: PortId(0)
+            },
+            ValidationError::RequiredInputMissing { node: lonely },
+            ValidationError::MultipleWritersToInput {
+                node: a,
+                port: PortId(0)
+            },
+            ValidationError::Cycle { nodes: vec![a, b] },
+        ]
+    );
+    assert!(Plan::compile(&graph, 64).is_err());
+}
+
+#[test]
+fn delays_that_get_clamped_are_reported() {
+    let mut graph = Graph::new();
+    let delays: Vec<_> = [
+        f32::INFINITY,
+        f32::NAN,
+        1e12,
+        -1.0,
+        DelayLine::MAX_DELAY,
+        0.0,
+    ]
+    .into_iter()
+    .map(|delay_samples| graph.add_node(NodeType::Delay { delay_samples }))
+    .collect();
+    let reported: Vec<_> = graph
+        .validate()
+        .errors
+        .into_iter()
+        .filter_map(|e| match e {
+            ValidationError::DelayOutOfRange { node } => Some(node),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(reported, delays[..4]);
+}
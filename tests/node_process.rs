@@ -0,0 +1,254 @@
+mod common;
+
+use auxide::control::{new_runtime_control, ControlMsg};
+use auxide::graph::{Graph, NodeType, Port, PortId, Rate};
+use auxide::node::{NodeDef, ProcessCtx};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime, MAX_EXTERNAL_PORTS};
+use common::edge;
+
+static AUDIO_IN: [Port; 1] = [Port {
+    id: PortId(0),
+    rate: Rate::Audio,
+}];
+
+static AUDIO_OUT: [Port; 1] = [Port {
+    id: PortId(0),
+    rate: Rate::Audio,
+}];
+
+/// Halves its input through the slice-based `process`.
+struct Half;
+
+impl NodeDef for Half {
+    type State = ();
+
+    fn input_ports(&self) -> &'static [Port] {
+        &AUDIO_IN
+    }
+
+    fn output_ports(&self) -> &'static [Port] {
+        &AUDIO_OUT
+    }
+
+    fn required_inputs(&self) -> usize {
+        1
+    }
+
+    fn init_state(&self, _sample_rate: f32, _block_size: usize) {}
+
+    fn process(
+        &self,
+        _state: &mut (),
+        inputs: &[&[f32]],
+        outputs: &mut [&mut [f32]],
+        _ctx: &ProcessCtx,
+    ) -> Result<(), &'static str> {
+        for (y, &x) in outputs[0].iter_mut().zip(inputs[0]) {
+            *y = 0.5 * x;
+        }
+        Ok(())
+    }
+}
+
+/// The same node written against the deprecated `Vec` outputs.
+struct LegacyHalf;
+
+impl NodeDef for LegacyHalf {
+    type State = ();
+
+    fn input_ports(&self) -> &'static [Port] {
+        &AUDIO_IN
+    }
+
+    fn output_ports(&self) -> &'static [Port] {
+        &AUDIO_OUT
+    }
+
+    fn required_inputs(&self) -> usize {
+        1
+    }
+
+    fn init_state(&self, _sample_rate: f32, _block_size: usize) {}
+
+    fn process_block(
+        &self,
+        _state: &mut (),
+        inputs: &[&[f32]],
+        outputs: &mut [Vec<f32>],
+        _sample_rate: f32,
+    ) -> Result<(), &'static str> {
+        for (y, &x) in outputs[0].iter_mut().zip(inputs[0]) {
+            *y = 0.5 * x;
+        }
+        Ok(())
+    }
+}
+
+/// Emits the block index, then the transport position in samples.
+struct Clock;
+
+impl NodeDef for Clock {
+    type State = ();
+
+    fn input_ports(&self) -> &'static [Port] {
+        &[]
+    }
+
+    fn output_ports(&self) -> &'static [Port] {
+        &AUDIO_OUT
+    }
+
+    fn required_inputs(&self) -> usize {
+        0
+    }
+
+    fn init_state(&self, _sample_rate: f32, _block_size: usize) {}
+
+    fn process(
+        &self,
+        _state: &mut (),
+        _inputs: &[&[f32]],
+        outputs: &mut [&mut [f32]],
+        ctx: &ProcessCtx,
+    ) -> Result<(), &'static str> {
+        assert_eq!(ctx.block_size, 64);
+        assert_eq!(ctx.sample_rate, 48000.0);
+        outputs[0][0] = ctx.block_index as f32;
+        if let Some(y) = outputs[0].get_mut(1) {
+            *y = ctx.transport.position_samples() as f32;
+        }
+        Ok(())
+    }
+}
+
+/// Implements neither method.
+struct Empty;
+
+impl NodeDef for Empty {
+    type State = ();
+
+    fn input_ports(&self) -> &'static [Port] {
+        &AUDIO_IN
+    }
+
+    fn output_ports(&self) -> &'static [Port] {
+        &AUDIO_OUT
+    }
+
+    fn required_inputs(&self) -> usize {
+        1
+    }
+
+    fn init_state(&self, _sample_rate: f32, _block_size: usize) {}
+}
+
+/// One audio port past `MAX_EXTERNAL_PORTS`, each way.
+static WIDE_PORTS: [Port; MAX_EXTERNAL_PORTS + 1] = {
+    let mut ports = [const {
+        Port {
+            id: PortId(0),
+            rate: Rate::Audio,
+        }
+    }; MAX_EXTERNAL_PORTS + 1];
+    let mut i = 0;
+    while i < ports.len() {
+        ports[i].id = PortId(i);
+        i += 1;
+    }
+    ports
+};
+
+/// Halves input 0 into output 0 and sees only the capped ports.
+struct Wide;
+
+impl NodeDef for Wide {
+    type State = ();
+
+    fn input_ports(&self) -> &'static [Port] {
+        &WIDE_PORTS
+    }
+
+    fn output_ports(&self) -> &'static [Port] {
+        &WIDE_PORTS
+    }
+
+    fn required_inputs(&self) -> usize {
+        1
+    }
+
+    fn init_state(&self, _sample_rate: f32, _block_size: usize) {}
+
+    fn process(
+        &self,
+        _state: &mut (),
+        inputs: &[&[f32]],
+        outputs: &mut [&mut [f32]],
+        _ctx: &ProcessCtx,
+    ) -> Result<(), &'static str> {
+        assert_eq!(inputs.len(), MAX_EXTERNAL_PORTS);
+        assert_eq!(outputs.len(), MAX_EXTERNAL_PORTS);
+        for (y, &x) in outputs[0].iter_mut().zip(inputs[0]) {
+            *y = 0.5 * x;
+        }
+        Ok(())
+    }
+}
+
+fn render(node: NodeType) -> Vec<f32> {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let node = graph.add_node(node);
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, node)).unwrap();
+    graph.add_edge(edge(node, sink)).unwrap();
+    let plan = Plan::compile(&graph, 64).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, 48000.0);
+    render_offline(&mut runtime, 256).unwrap()
+}
+
+#[test]
+fn slice_and_legacy_outputs_render_alike() {
+    let slices = render(NodeType::external(Half));
+    assert!(slices.iter().any(|&x| x != 0.0));
+    assert_eq!(render(NodeType::external(LegacyHalf)), slices);
+}
+
+#[test]
+fn ports_past_the_cap_are_left_out() {
+    assert_eq!(
+        render(NodeType::external(Wide)),
+        render(NodeType::external(Half))
+    );
+}
+
+#[test]
+fn node_without_process_is_silent() {
+    assert!(render(NodeType::external(Empty)).iter().all(|&x| x == 0.0));
+}
+
+#[test]
+fn context_carries_block_index_and_transport() {
+    let mut graph = Graph::new();
+    let clock = graph.add_node(NodeType::external(Clock));
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(clock, sink)).unwrap();
+    let plan = Plan::compile(&graph, 64).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, 48000.0);
+    let (mut control, mut control_rx, _) = new_runtime_control();
+    let mut out = vec![0.0; 64];
+
+    runtime.process_block(&mut out).unwrap();
+    assert_eq!(out[..2], [0.0, 0.0]);
+    runtime.apply_control(ControlMsg::Start);
+    runtime.process_block(&mut out).unwrap();
+    assert_eq!(out[..2], [1.0, 0.0]);
+
+    // Split at sample 10: both pieces belong to block 2.
+    control.send_at(10, ControlMsg::Stop).unwrap();
+    runtime
+        .process_block_with_channels(&mut out, &mut control_rx)
+        .unwrap();
+    assert_eq!(out[..2], [2.0, 64.0]);
+    assert_eq!(out[10..12], [2.0, 74.0]);
+}
@@ -1,21 +1,16 @@
-use auxide::graph::{Graph, NodeType, PortId, Rate};
+mod common;
+
+use auxide::graph::{Graph, NodeType};
 use auxide::plan::Plan;
 use auxide::rt::{render_offline, Runtime};
+use common::edge;
 
 #[test]
 fn offline_render_determinism() {
     let mut graph = Graph::new();
     let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
     let sink = graph.add_node(NodeType::OutputSink);
-    graph
-        .add_edge(auxide::graph::Edge {
-            from_node: osc,
-            from_port: PortId(0),
-            to_node: sink,
-            to_port: PortId(0),
-            rate: Rate::Audio,
-        })
-        .unwrap();
+    graph.add_edge(edge(osc, sink)).unwrap();
 
     let plan = Plan::compile(&graph, 64).unwrap();
     let mut runtime1 = Runtime::new(plan.clone(), &graph, 44100.0);
@@ -32,15 +27,7 @@ fn offline_render_partial_block() {
     let mut graph = Graph::new();
     let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
     let sink = graph.add_node(NodeType::OutputSink);
-    graph
-        .add_edge(auxide::graph::Edge {
-            from_node: osc,
-            from_port: PortId(0),
-            to_node: sink,
-            to_port: PortId(0),
-            rate: Rate::Audio,
-        })
-        .unwrap();
+    graph.add_edge(edge(osc, sink)).unwrap();
 
     let plan = Plan::compile(&graph, 64).unwrap();
     let mut runtime = Runtime::new(plan, &graph, 44100.0);
@@ -59,3 +46,58 @@ fn offline_render_partial_block() {
         "Should produce non-zero output"
     );
 }
+
+#[test]
+fn offline_render_with_msgs_is_deterministic() {
+    use auxide::control::ControlMsg;
+    use auxide::rt::render_offline_with_msgs;
+
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let gain = graph.add_node(NodeType::Gain { gain: 1.0 });
+    let sink = graph.add_node(NodeType::OutputSink);
+    for (from, to) in [(osc, gain), (gain, sink)] {
+        graph.add_edge(edge(from, to)).unwrap();
+    }
+    // Deliberately unsorted; frame 100 lands on the boundary at 128.
+    let msgs = [
+        (
+            300,
+            ControlMsg::SetFrequency {
+                node: osc,
+                hz: 880.0,
+            },
+        ),
+        (
+            100,
+            ControlMsg::SetGain {
+                node: gain,
+                gain: 0.0,
+            },
+        ),
+        (
+            200,
+            ControlMsg::SetGain {
+                node: gain,
+                gain: 0.5,
+            },
+        ),
+    ];
+
+    let plan = Plan::compile(&graph, 64).unwrap();
+    let mut runtime1 = Runtime::new(plan.clone(), &graph, 44100.0);
+    let mut runtime2 = Runtime::new(plan, &graph, 44100.0);
+    let output1 = render_offline_with_msgs(&mut runtime1, 512, &msgs).unwrap();
+    let output2 = render_offline_with_msgs(&mut runtime2, 512, &msgs).unwrap();
+    assert_eq!(
+        output1, output2,
+        "identical schedules must render identically"
+    );
+
+    assert!(output1[64..128].iter().any(|&x| x != 0.0));
+    assert!(
+        output1[128..256].iter().all(|&x| x == 0.0),
+        "gain 0 applies from the block boundary at 128"
+    );
+    assert!(output1[256..].iter().any(|&x| x != 0.0));
+}
@@ -0,0 +1,69 @@
+#![cfg(feature = "wav")]
+
+mod common;
+
+use auxide::graph::{Graph, NodeType};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, render_offline_to_wav, Runtime, WavFormat};
+use common::edge;
+
+const FRAMES: usize = 1000;
+
+fn runtime() -> Runtime {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, sink)).unwrap();
+    // 1000 frames is not a whole number of blocks.
+    let plan = Plan::compile(&graph, 64).unwrap();
+    Runtime::new(plan, &graph, 44100.0)
+}
+
+fn wav_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("auxide-{}-{}.wav", name, std::process::id()))
+}
+
+#[test]
+fn every_format_round_trips() {
+    let expected = render_offline(&mut runtime(), FRAMES).unwrap();
+    for (format, bits, full_scale) in [
+        (WavFormat::Int16, 16, i16::MAX as f64),
+        (WavFormat::Int24, 24, 8_388_607.0),
+        (WavFormat::Int32, 32, i32::MAX as f64),
+    ] {
+        let path = wav_path(&format!("{:?}", format));
+        render_offline_to_wav(&mut runtime(), FRAMES, &path, format).unwrap();
+        let mut reader = hound::WavReader::open(&path).unwrap();
+        let spec = reader.spec();
+        assert_eq!(spec.channels, 1);
+        assert_eq!(spec.sample_rate, 44100);
+        assert_eq!(spec.bits_per_sample, bits);
+        assert_eq!(spec.sample_format, hound::SampleFormat::Int);
+        let samples: Vec<i32> = reader.samples::<i32>().map(Result::unwrap).collect();
+        assert_eq!(samples.len(), FRAMES);
+        for (&s, &x) in samples.iter().zip(&expected) {
+            assert!((s as f64 / full_scale - x as f64).abs() <= 1.0 / full_scale);
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+}
+
+#[test]
+fn float_format_is_exact() {
+    let expected = render_offline(&mut runtime(), FRAMES).unwrap();
+    let path = wav_path("float");
+    render_offline_to_wav(&mut runtime(), FRAMES, &path, WavFormat::Float32).unwrap();
+    let mut reader = hound::WavReader::open(&path).unwrap();
+    assert_eq!(reader.spec().sample_format, hound::SampleFormat::Float);
+    let samples: Vec<f32> = reader.samples::<f32>().map(Result::unwrap).collect();
+    assert_eq!(samples, expected);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn unwritable_path_is_an_error() {
+    let path = std::env::temp_dir()
+        .join("auxide-missing-dir")
+        .join("out.wav");
+    assert!(render_offline_to_wav(&mut runtime(), FRAMES, path, WavFormat::Int16).is_err());
+}
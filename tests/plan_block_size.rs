@@ -0,0 +1,81 @@
+mod common;
+
+use auxide::graph::{Graph, NodeType, Port, PortId, Rate};
+use auxide::node::{NodeDef, ProcessCtx};
+use auxide::plan::{Plan, PlanError};
+use common::edge;
+
+static AUDIO_OUT: [Port; 1] = [Port {
+    id: PortId(0),
+    rate: Rate::Audio,
+}];
+
+/// Stand-in for an FFT node that works on 128-sample frames.
+struct Framed;
+
+impl NodeDef for Framed {
+    type State = ();
+
+    fn input_ports(&self) -> &'static [Port] {
+        &[]
+    }
+
+    fn output_ports(&self) -> &'static [Port] {
+        &AUDIO_OUT
+    }
+
+    fn required_inputs(&self) -> usize {
+        0
+    }
+
+    fn min_block_size(&self) -> usize {
+        128
+    }
+
+    fn init_state(&self, _sample_rate: f32, _block_size: usize) -> Self::State {}
+
+    fn process(
+        &self,
+        _state: &mut Self::State,
+        _inputs: &[&[f32]],
+        _outputs: &mut [&mut [f32]],
+        _ctx: &ProcessCtx,
+    ) -> Result<(), &'static str> {
+        Ok(())
+    }
+}
+
+#[test]
+fn min_block_size_is_enforced_by_compile() {
+    let mut graph = Graph::new();
+    assert_eq!(graph.min_block_size(), 1);
+
+    let framed = graph.add_node(NodeType::external(Framed));
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(framed, sink)).unwrap();
+    assert_eq!(graph.min_block_size(), 128);
+
+    assert_eq!(
+        Plan::compile(&graph, 64).unwrap_err(),
+        PlanError::BlockSizeTooSmall { required: 128 }
+    );
+    assert!(Plan::compile(&graph, 128).is_ok());
+    assert!(Plan::compile(&graph, 256).is_ok());
+}
+
+#[test]
+fn validate_block_sizes_reports_each_size() {
+    let mut graph = Graph::new();
+    let framed = graph.add_node(NodeType::external(Framed));
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(framed, sink)).unwrap();
+
+    assert_eq!(
+        graph.validate_block_sizes(&[64, 256]),
+        vec![
+            (64, Err(PlanError::BlockSizeTooSmall { required: 128 })),
+            (256, Ok(())),
+        ]
+    );
+    assert!(graph.validate_block_sizes(&[]).is_empty());
+}
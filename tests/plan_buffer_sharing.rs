@@ -0,0 +1,91 @@
+mod common;
+
+use auxide::control::ControlMsg;
+use auxide::graph::{Graph, NodeId, NodeType};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+use common::{edge, edge_to};
+
+const BLOCK: usize = 64;
+
+/// osc -> gain -> gain -> gain -> out, plus the osc mixed back in at the end.
+fn chain() -> (Graph, NodeId) {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let mut prev = osc;
+    for gain in [0.5, 0.8, 1.5] {
+        let node = graph.add_node(NodeType::Gain { gain });
+        graph.add_edge(edge(prev, node)).unwrap();
+        prev = node;
+    }
+    let mix = graph.add_node(NodeType::Mix);
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(prev, mix)).unwrap();
+    graph.add_edge(edge_to(osc, mix, 1)).unwrap();
+    graph.add_edge(edge(mix, sink)).unwrap();
+    (graph, osc)
+}
+
+fn render(plan: Plan, graph: &Graph) -> Vec<f32> {
+    let mut runtime = Runtime::new(plan, graph, 48000.0);
+    render_offline(&mut runtime, 4 * BLOCK).unwrap()
+}
+
+#[test]
+fn shared_plan_uses_fewer_buffers_and_renders_the_same() {
+    let (graph, _) = chain();
+    let plain = Plan::compile(&graph, BLOCK).unwrap();
+    let shared = Plan::compile_shared(&graph, BLOCK).unwrap();
+    assert!(!plain.shares_buffers());
+    assert!(shared.shares_buffers());
+    assert_eq!(plain.buffer_count(), graph.edges.len());
+    // At most the osc's two outgoing edges and one link of the chain are
+    // live at once.
+    assert_eq!(shared.buffer_count(), 3);
+    assert_eq!(render(shared, &graph), render(plain, &graph));
+}
+
+#[test]
+fn feedback_edges_keep_their_own_buffer() {
+    // A comb: mix -> delay -> gain -> mix closes a loop through the delay.
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let mix = graph.add_node(NodeType::Mix);
+    let delay = graph.add_node(NodeType::Delay {
+        delay_samples: 100.0,
+    });
+    let gain = graph.add_node(NodeType::Gain { gain: 0.5 });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, mix)).unwrap();
+    graph.add_edge(edge(mix, delay)).unwrap();
+    graph.add_edge(edge(delay, gain)).unwrap();
+    graph.add_edge(edge_to(gain, mix, 1)).unwrap();
+    graph.add_edge(edge(mix, sink)).unwrap();
+
+    let shared = Plan::compile_shared(&graph, BLOCK).unwrap();
+    let feedback: Vec<usize> = (0..shared.edges.len())
+        .filter(|&idx| shared.edges[idx].feedback)
+        .collect();
+    assert!(!feedback.is_empty());
+    for &fb in &feedback {
+        for idx in (0..shared.edges.len()).filter(|&idx| idx != fb) {
+            assert_ne!(shared.edge_buffer_index(idx), shared.edge_buffer_index(fb));
+        }
+    }
+    let plain = Plan::compile(&graph, BLOCK).unwrap();
+    assert_eq!(render(shared, &graph), render(plain, &graph));
+}
+
+#[test]
+fn freeze_has_no_effect_under_a_shared_plan() {
+    let (graph, osc) = chain();
+    let plan = Plan::compile_shared(&graph, BLOCK).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, 48000.0);
+    runtime.apply_control(ControlMsg::FreezeNode {
+        node: osc,
+        frozen: true,
+    });
+    let frozen = render_offline(&mut runtime, 4 * BLOCK).unwrap();
+    let plain = Plan::compile_shared(&graph, BLOCK).unwrap();
+    assert_eq!(frozen, render(plain, &graph));
+}
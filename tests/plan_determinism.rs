@@ -1,20 +1,15 @@
-use auxide::graph::{Edge, Graph, NodeType, PortId, Rate};
+mod common;
+
+use auxide::graph::{Graph, NodeType};
 use auxide::plan::Plan;
+use common::edge;
 
 #[test]
 fn plan_deterministic_compilation() {
     let mut graph = Graph::new();
     let node1 = graph.add_node(NodeType::Dummy);
     let node2 = graph.add_node(NodeType::Mix);
-    graph
-        .add_edge(Edge {
-            from_node: node1,
-            from_port: PortId(0),
-            to_node: node2,
-            to_port: PortId(0),
-            rate: Rate::Audio,
-        })
-        .unwrap();
+    graph.add_edge(edge(node1, node2)).unwrap();
 
     let plan1 = Plan::compile(&graph, 64).unwrap();
     let plan2 = Plan::compile(&graph, 64).unwrap();
@@ -28,15 +23,7 @@ fn plan_rejects_zero_block_size() {
     let mut graph = Graph::new();
     let node1 = graph.add_node(NodeType::Dummy);
     let node2 = graph.add_node(NodeType::OutputSink);
-    graph
-        .add_edge(Edge {
-            from_node: node1,
-            from_port: PortId(0),
-            to_node: node2,
-            to_port: PortId(0),
-            rate: Rate::Audio,
-        })
-        .unwrap();
+    graph.add_edge(edge(node1, node2)).unwrap();
 
     assert!(Plan::compile(&graph, 0).is_err());
 }
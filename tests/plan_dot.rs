@@ -0,0 +1,75 @@
+mod common;
+
+use auxide::graph::{Edge, Graph, NodeType};
+use auxide::plan::Plan;
+use common::{edge, edge_to};
+
+/// Sink added first so ids and execution steps differ.
+fn patch() -> Graph {
+    let mut graph = Graph::new();
+    let sink = graph.add_node(NodeType::OutputSink);
+    let mix = graph.add_node(NodeType::Mix);
+    let osc = graph.add_node(NodeType::SineOsc { freq: 220.0 });
+    let lfo = graph.add_node(NodeType::SineOsc { freq: 2.0 });
+    graph.add_edge(edge(osc, mix)).unwrap();
+    graph
+        .add_edge(Edge {
+            muted: true,
+            ..edge_to(lfo, mix, 1)
+        })
+        .unwrap();
+    graph.add_edge(edge(mix, sink)).unwrap();
+    graph
+}
+
+#[test]
+fn graph_dot_lists_nodes_and_edges() {
+    let dot = patch().to_dot();
+    assert!(dot.starts_with("digraph auxide {"));
+    assert!(dot.trim_end().ends_with('}'));
+    assert!(dot.contains("n0 [label=\"#0 OutputSink\"];"));
+    assert!(dot.contains("n2 [label=\"#2 SineOsc\"];"));
+    assert!(dot.contains("n2 -> n1 [label=\"audio\", taillabel=\"0\", headlabel=\"0\"];"));
+    assert!(
+        dot.contains("n3 -> n1 [label=\"audio\", taillabel=\"0\", headlabel=\"1\", style=dashed];")
+    );
+}
+
+#[test]
+fn plan_dot_labels_execution_steps() {
+    let graph = patch();
+    let plan = Plan::compile(&graph, 64).unwrap();
+    let dot = plan.to_dot(&graph);
+    for (step, node) in plan.order.iter().enumerate() {
+        let name = plan.order_names(&graph)[step];
+        let label = format!(
+            "n{} [label=\"#{} {}\\nstep {}\"];",
+            node.0, node.0, name, step
+        );
+        assert!(dot.contains(&label), "missing {} in\n{}", label, dot);
+    }
+    assert_eq!(dot.matches(" -> ").count(), plan.edges.len());
+}
+
+#[test]
+fn plan_dot_marks_delay_loops_as_feedback() {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let mix = graph.add_node(NodeType::Mix);
+    let delay = graph.add_node(NodeType::Delay {
+        delay_samples: 100.0,
+    });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, mix)).unwrap();
+    graph.add_edge(edge(mix, delay)).unwrap();
+    graph.add_edge(edge_to(delay, mix, 1)).unwrap();
+    graph.add_edge(edge(mix, sink)).unwrap();
+    let plan = Plan::compile(&graph, 64).unwrap();
+
+    // The edge into the delay is cut to schedule the loop.
+    let dot = plan.to_dot(&graph);
+    let feedback = "n1 -> n2 [label=\"audio\", taillabel=\"0\", headlabel=\"0\", \
+                    style=dotted, constraint=false];";
+    assert!(dot.contains(feedback), "{}", dot);
+    assert!(!graph.to_dot().contains("dotted"));
+}
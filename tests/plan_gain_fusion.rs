@@ -0,0 +1,75 @@
+mod common;
+
+use auxide::control::ControlMsg;
+use auxide::graph::{Graph, NodeId, NodeType};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+use common::edge;
+
+/// osc -> gain(0.8) -> gain(0.6) -> gain(0.4) -> sink.
+fn gain_chain() -> (Graph, NodeId, Vec<NodeId>, NodeId) {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let gains: Vec<NodeId> = [0.8, 0.6, 0.4]
+        .into_iter()
+        .map(|gain| graph.add_node(NodeType::Gain { gain }))
+        .collect();
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, gains[0])).unwrap();
+    graph.add_edge(edge(gains[0], gains[1])).unwrap();
+    graph.add_edge(edge(gains[1], gains[2])).unwrap();
+    graph.add_edge(edge(gains[2], sink)).unwrap();
+    (graph, osc, gains, sink)
+}
+
+fn assert_close(a: &[f32], b: &[f32]) {
+    assert!(a.iter().any(|&x| x != 0.0));
+    for (&x, &y) in a.iter().zip(b) {
+        assert!((x - y).abs() < 1e-6, "{} vs {}", x, y);
+    }
+}
+
+#[test]
+fn gain_chain_runs_as_one_node() {
+    let (graph, osc, gains, sink) = gain_chain();
+    let plan = Plan::compile_fused(&graph, 64).unwrap();
+    assert_eq!(plan.fused_gains(), &[(gains[0], vec![gains[1], gains[2]])]);
+    assert_eq!(plan.order, vec![osc, gains[0], sink]);
+
+    let fused = render_offline(&mut Runtime::new(plan, &graph, 48000.0), 512).unwrap();
+    let plan = Plan::compile(&graph, 64).unwrap();
+    assert!(plan.fused_gains().is_empty());
+    let plain = render_offline(&mut Runtime::new(plan, &graph, 48000.0), 512).unwrap();
+    assert_close(&fused, &plain);
+}
+
+#[test]
+fn set_gain_reaches_fused_nodes() {
+    let (graph, _, gains, _) = gain_chain();
+    let mut fused = Runtime::new(Plan::compile_fused(&graph, 64).unwrap(), &graph, 48000.0);
+    let mut plain = Runtime::new(Plan::compile(&graph, 64).unwrap(), &graph, 48000.0);
+    for runtime in [&mut fused, &mut plain] {
+        runtime.set_param_smoothing(1.0);
+        runtime.apply_control(ControlMsg::SetGain {
+            node: gains[2],
+            gain: 0.1,
+        });
+        runtime.apply_control(ControlMsg::SetGain {
+            node: gains[0],
+            gain: 0.5,
+        });
+    }
+    assert_close(
+        &render_offline(&mut fused, 512).unwrap(),
+        &render_offline(&mut plain, 512).unwrap(),
+    );
+}
+
+#[test]
+fn fan_out_stops_a_chain() {
+    let (mut graph, _, gains, _) = gain_chain();
+    let tap = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(gains[1], tap)).unwrap();
+    let plan = Plan::compile_fused(&graph, 64).unwrap();
+    assert_eq!(plan.fused_gains(), &[(gains[0], vec![gains[1]])]);
+}
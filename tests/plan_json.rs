@@ -0,0 +1,40 @@
+#![cfg(feature = "serde")]
+
+mod common;
+
+use auxide::graph::{Graph, NodeType};
+use auxide::plan::Plan;
+use common::{edge, edge_to};
+
+#[test]
+fn plan_json_exports_schedule() {
+    let mut graph = Graph::new();
+    let sink = graph.add_node(NodeType::OutputSink);
+    let mix = graph.add_node(NodeType::Mix);
+    let a = graph.add_node(NodeType::SineOsc { freq: 220.0 });
+    let b = graph.add_node(NodeType::SineOsc { freq: 330.0 });
+    graph.add_edge(edge(a, mix)).unwrap();
+    graph.add_edge(edge_to(b, mix, 1)).unwrap();
+    graph.add_edge(edge(mix, sink)).unwrap();
+    let plan = Plan::compile(&graph, 128).unwrap();
+
+    let json: serde_json::Value = serde_json::from_str(&plan.to_json()).unwrap();
+    let order: Vec<usize> = json["order"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|n| n.as_u64().unwrap() as usize)
+        .collect();
+    assert_eq!(order, plan.order.iter().map(|n| n.0).collect::<Vec<_>>());
+    assert_eq!(order.last(), Some(&sink.0));
+
+    assert_eq!(json["block_size"], 128);
+    let edges = json["edges"].as_array().unwrap();
+    assert_eq!(edges.len(), 3);
+    assert_eq!(edges[2]["from_node"], mix.0);
+    assert_eq!(edges[2]["to_node"], sink.0);
+    assert_eq!(edges[2]["rate"], "audio");
+    assert_eq!(edges[2]["muted"], false);
+    assert_eq!(edges[2]["feedback"], false);
+    assert_eq!(edges[2]["buffer"], plan.edge_buffer_index(2));
+}
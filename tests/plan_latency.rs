@@ -0,0 +1,85 @@
+mod common;
+
+use auxide::graph::{Graph, NodeId, NodeType};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+use common::{edge, edge_to};
+
+/// osc -> delay(`delay`) -> mix[0], osc -> mix[1], mix -> sink.
+fn split_and_merge(delay: f32) -> (Graph, NodeId) {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let delay = graph.add_node(NodeType::Delay {
+        delay_samples: delay,
+    });
+    let mix = graph.add_node(NodeType::Mix);
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, delay)).unwrap();
+    graph.add_edge(edge(delay, mix)).unwrap();
+    graph.add_edge(edge_to(osc, mix, 1)).unwrap();
+    graph.add_edge(edge(mix, sink)).unwrap();
+    (graph, mix)
+}
+
+#[test]
+fn mix_of_delayed_and_dry_branch_is_imbalanced() {
+    let (graph, mix) = split_and_merge(100.0);
+    let plan = Plan::compile(&graph, 64).unwrap();
+    assert_eq!(plan.latency_imbalances(), vec![(mix, 100)]);
+    assert_eq!(plan.output_latencies()[mix.0], 100);
+}
+
+#[test]
+fn aligned_branches_report_nothing() {
+    let (graph, _) = split_and_merge(0.0);
+    let plan = Plan::compile(&graph, 64).unwrap();
+    assert!(plan.latency_imbalances().is_empty());
+}
+
+#[test]
+fn total_latency_is_the_longest_path_to_the_output() {
+    let (mut graph, mix) = split_and_merge(100.0);
+    assert_eq!(Plan::compile(&graph, 64).unwrap().total_latency(), 100);
+    assert_eq!(
+        Plan::compile_subgraph(&graph, 64, &[mix])
+            .unwrap()
+            .total_latency(),
+        100
+    );
+
+    // Compensation aligns the dry branch; the output is still 100 late.
+    graph.compensate_latency();
+    let plan = Plan::compile(&graph, 64).unwrap();
+    assert!(plan.latency_imbalances().is_empty());
+    assert_eq!(plan.total_latency(), 100);
+}
+
+#[test]
+fn compensated_plan_aligns_without_changing_the_graph() {
+    let (graph, _) = split_and_merge(50.0);
+    let edges = graph.edges.len();
+    let plan = Plan::compile_compensated(&graph, 64).unwrap();
+    assert_eq!(graph.edges.len(), edges);
+    assert!(plan.latency_imbalances().is_empty());
+    assert_eq!(plan.total_latency(), 50);
+    let [(delay, samples)] = plan.compensation_delays() else {
+        panic!("expected one compensating delay");
+    };
+    assert_eq!(*samples, 50.0);
+    assert_eq!(plan.order_names(&graph).len(), 5);
+    assert!(plan.order.contains(delay));
+
+    // Renders the same as compensating the graph itself.
+    let mut compensated = graph.clone();
+    compensated.compensate_latency();
+    let mut reference = Runtime::new(
+        Plan::compile(&compensated, 64).unwrap(),
+        &compensated,
+        48000.0,
+    );
+    let mut runtime = Runtime::new(plan, &graph, 48000.0);
+    assert_eq!(
+        render_offline(&mut runtime, 1024).unwrap(),
+        render_offline(&mut reference, 1024).unwrap()
+    );
+}
@@ -0,0 +1,41 @@
+mod common;
+
+use auxide::graph::{Graph, NodeType};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+use common::edge;
+
+#[test]
+fn nodes_feeding_no_sink_are_dropped() {
+    let mut graph = Graph::new();
+    let noise = graph.add_node(NodeType::Noise {
+        seed: 1,
+        color: 0.0,
+    });
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let dead = graph.add_node(NodeType::Gain { gain: 0.5 });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, sink)).unwrap();
+    // The noise branch leads nowhere.
+    graph.add_edge(edge(noise, dead)).unwrap();
+
+    let full = Plan::compile(&graph, 64).unwrap();
+    assert_eq!(full.order.len(), 4);
+    assert!(full.pruned_nodes().is_empty());
+
+    let pruned = Plan::compile_pruned(&graph, 64).unwrap();
+    assert_eq!(pruned.order, vec![osc, sink]);
+    assert_eq!(pruned.pruned_nodes(), &[noise, dead]);
+
+    let render = |plan| render_offline(&mut Runtime::new(plan, &graph, 48000.0), 512).unwrap();
+    assert_eq!(render(pruned), render(full));
+}
+
+#[test]
+fn graph_without_sinks_prunes_everything() {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let plan = Plan::compile_pruned(&graph, 64).unwrap();
+    assert!(plan.order.is_empty());
+    assert_eq!(plan.pruned_nodes(), &[osc]);
+}
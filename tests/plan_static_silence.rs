@@ -0,0 +1,68 @@
+mod common;
+
+use auxide::graph::{Graph, NodeType};
+use auxide::plan::{Plan, PlanError};
+use auxide::rt::Runtime;
+use auxide::snapshot::NodeSnapshot;
+use common::{edge, edge_to};
+
+#[test]
+fn unfed_gain_is_static_silence() {
+    // gain (no input) -> delay -> mix[0]; osc -> mix[1]; mix -> sink
+    let mut graph = Graph::new();
+    let gain = graph.add_node(NodeType::Gain { gain: 2.0 });
+    let delay = graph.add_node(NodeType::Delay {
+        delay_samples: 10.0,
+    });
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let mix = graph.add_node(NodeType::Mix);
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(gain, delay)).unwrap();
+    graph.add_edge(edge(delay, mix)).unwrap();
+    graph.add_edge(edge_to(osc, mix, 1)).unwrap();
+    graph.add_edge(edge(mix, sink)).unwrap();
+
+    assert_eq!(
+        Plan::compile(&graph, 64).unwrap_err(),
+        PlanError::RequiredInputMissing { node: gain }
+    );
+    let plan = Plan::compile_lenient(&graph, 64).unwrap();
+    // Silence propagates through the delay but stops at the live mix.
+    assert_eq!(plan.static_silence_nodes(), vec![gain, delay]);
+
+    let mut runtime = Runtime::new(plan, &graph, 48000.0);
+    let mut out = vec![0.0; 64];
+    runtime.process_block(&mut out).unwrap();
+    assert!(out.iter().any(|&s| s != 0.0));
+}
+
+#[test]
+fn static_silence_nodes_are_skipped() {
+    // A compressor with only its sidechain connected can never output
+    // anything, so its detector must not run.
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let comp = graph.add_node(NodeType::Compressor {
+        threshold_db: -20.0,
+        ratio: 4.0,
+        attack_ms: 1.0,
+        release_ms: 50.0,
+    });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge_to(osc, comp, 1)).unwrap();
+    graph.add_edge(edge(comp, sink)).unwrap();
+
+    let plan = Plan::compile_lenient(&graph, 64).unwrap();
+    assert_eq!(plan.static_silence_nodes(), vec![comp, sink]);
+
+    let mut runtime = Runtime::new(plan, &graph, 48000.0);
+    let mut out = vec![1.0; 64];
+    for _ in 0..4 {
+        runtime.process_block(&mut out).unwrap();
+    }
+    assert!(out.iter().all(|&s| s == 0.0));
+    match &runtime.snapshot().nodes[comp.0] {
+        Some(NodeSnapshot::Compressor { follower }) => assert_eq!(follower.value(), 0.0),
+        other => panic!("unexpected snapshot {:?}", other),
+    }
+}
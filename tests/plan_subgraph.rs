@@ -0,0 +1,65 @@
+mod common;
+
+use auxide::graph::{Graph, NodeId, NodeType};
+use auxide::plan::{Plan, PlanError};
+use auxide::rt::Runtime;
+use common::{edge, edge_to};
+
+const BLOCK: usize = 64;
+
+#[test]
+fn subgraph_ends_at_mid_chain_gain() {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let gain = graph.add_node(NodeType::Gain { gain: 0.5 });
+    let boost = graph.add_node(NodeType::Gain { gain: 4.0 });
+    let sink = graph.add_node(NodeType::OutputSink);
+    // A source that does not feed the gain stays out too.
+    let other = graph.add_node(NodeType::Noise {
+        seed: 1,
+        color: 0.0,
+    });
+    let mix = graph.add_node(NodeType::Mix);
+    graph.add_edge(edge(osc, gain)).unwrap();
+    graph.add_edge(edge(gain, boost)).unwrap();
+    graph.add_edge(edge(boost, mix)).unwrap();
+    graph.add_edge(edge_to(other, mix, 1)).unwrap();
+    graph.add_edge(edge(mix, sink)).unwrap();
+
+    let plan = Plan::compile_subgraph(&graph, BLOCK, &[gain]).unwrap();
+    assert_eq!(plan.order, vec![osc, gain]);
+    assert_eq!(plan.preview_outputs(), &[gain]);
+
+    let mut preview = Runtime::new(plan, &graph, 44100.0);
+    let mut out = vec![1.0; BLOCK];
+    preview.process_block(&mut out).unwrap();
+
+    let mut reference_graph = Graph::new();
+    let osc = reference_graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let gain = reference_graph.add_node(NodeType::Gain { gain: 0.5 });
+    let sink = reference_graph.add_node(NodeType::OutputSink);
+    reference_graph.add_edge(edge(osc, gain)).unwrap();
+    reference_graph.add_edge(edge(gain, sink)).unwrap();
+    let plan = Plan::compile(&reference_graph, BLOCK).unwrap();
+    let mut reference = Runtime::new(plan, &reference_graph, 44100.0);
+    let mut expected = vec![0.0; BLOCK];
+    reference.process_block(&mut expected).unwrap();
+
+    assert_eq!(out, expected);
+    assert!(out.iter().any(|&s| s != 0.0));
+}
+
+#[test]
+fn subgraph_rejects_unknown_outputs() {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    graph.remove_node(osc).unwrap();
+    assert_eq!(
+        Plan::compile_subgraph(&graph, BLOCK, &[osc]).err(),
+        Some(PlanError::InvalidNode { node: osc })
+    );
+    assert_eq!(
+        Plan::compile_subgraph(&graph, BLOCK, &[NodeId(7, 0)]).err(),
+        Some(PlanError::InvalidNode { node: NodeId(7, 0) })
+    );
+}
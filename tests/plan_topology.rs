@@ -1,5 +1,8 @@
-use auxide::graph::{Edge, Graph, NodeType, PortId, Rate};
+mod common;
+
+use auxide::graph::{Graph, NodeType};
 use auxide::plan::Plan;
+use common::{edge, edge_to};
 
 #[test]
 fn plan_topology_preservation() {
@@ -7,24 +10,8 @@ fn plan_topology_preservation() {
     let node1 = graph.add_node(NodeType::Dummy);
     let node2 = graph.add_node(NodeType::Dummy);
     let node3 = graph.add_node(NodeType::Mix);
-    graph
-        .add_edge(Edge {
-            from_node: node1,
-            from_port: PortId(0),
-            to_node: node3,
-            to_port: PortId(0),
-            rate: Rate::Audio,
-        })
-        .unwrap();
-    graph
-        .add_edge(Edge {
-            from_node: node2,
-            from_port: PortId(0),
-            to_node: node3,
-            to_port: PortId(1),
-            rate: Rate::Audio,
-        })
-        .unwrap();
+    graph.add_edge(edge(node1, node3)).unwrap();
+    graph.add_edge(edge_to(node2, node3, 1)).unwrap();
 
     let plan = Plan::compile(&graph, 64).unwrap();
     // Every graph edge corresponds to exactly one plan edge
@@ -47,3 +34,22 @@ fn plan_topology_preservation() {
         seen.insert(key);
     }
 }
+
+#[test]
+fn plan_order_names() {
+    // Added in reverse so the order comes from the edges, not the ids.
+    let mut graph = Graph::new();
+    let sink = graph.add_node(NodeType::OutputSink);
+    let gain = graph.add_node(NodeType::Gain { gain: 0.5 });
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    for (from, to) in [(osc, gain), (gain, sink)] {
+        graph.add_edge(edge(from, to)).unwrap();
+    }
+
+    let plan = Plan::compile(&graph, 64).unwrap();
+    assert_eq!(
+        plan.order_names(&graph),
+        vec!["SineOsc", "Gain", "OutputSink"]
+    );
+    assert_eq!(plan.order_names(&Graph::new()), vec!["?"; 3]);
+}
@@ -0,0 +1,36 @@
+use auxide::control::ControlMsg;
+use auxide::dsl::GraphBuilder;
+use auxide::graph::{NodeType, PortId, Rate};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+
+const SAMPLE_RATE: f32 = 48000.0;
+
+#[test]
+fn all_notes_off_releases_every_envelope() {
+    let mut builder = GraphBuilder::new();
+    let (voices, out) = builder.subtractive_voices(3, 220.0);
+    let sink = builder.node(NodeType::OutputSink);
+    builder
+        .connect(out, PortId(0), sink, PortId(0), Rate::Audio)
+        .unwrap();
+    let graph = builder.build().unwrap();
+    let plan = Plan::compile(&graph, 480).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, SAMPLE_RATE);
+
+    for voice in &voices {
+        runtime.apply_control(ControlMsg::TriggerGate {
+            node: voice.amp.0,
+            on: true,
+        });
+    }
+    let held = render_offline(&mut runtime, 4800).unwrap();
+    assert!(held[2400..].iter().any(|&s| s.abs() > 0.1));
+
+    // One message closes all three gates; after the 200 ms release the
+    // output is silent.
+    runtime.apply_control(ControlMsg::AllNotesOff);
+    let released = render_offline(&mut runtime, 14400).unwrap();
+    assert!(released[..480].iter().any(|&s| s != 0.0));
+    assert!(released[9600..].iter().all(|&s| s == 0.0));
+}
@@ -0,0 +1,118 @@
+mod common;
+
+use auxide::control::ControlMsg;
+use auxide::graph::{Graph, NodeId, NodeType};
+use auxide::plan::Plan;
+use auxide::rt::parallel::ParallelRuntime;
+use auxide::rt::Runtime;
+use common::{edge, edge_to};
+
+const BLOCK: usize = 64;
+
+/// osc -> gain(0.5) -> sink. Returns the graph, the oscillator, the gain
+/// and the sink.
+fn gain_graph() -> (Graph, NodeId, NodeId, NodeId) {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let gain = graph.add_node(NodeType::Gain { gain: 0.5 });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, gain)).unwrap();
+    graph.add_edge(edge(gain, sink)).unwrap();
+    (graph, osc, gain, sink)
+}
+
+fn render(runtime: &mut Runtime, blocks: usize) -> Vec<f32> {
+    let mut out = vec![0.0; BLOCK];
+    let mut rendered = Vec::new();
+    for _ in 0..blocks {
+        runtime.process_block(&mut out).unwrap();
+        rendered.extend_from_slice(&out);
+    }
+    rendered
+}
+
+#[test]
+fn bypassed_node_passes_its_input_through() {
+    let (graph, _, gain, _) = gain_graph();
+    let mut runtime = Runtime::new(Plan::compile(&graph, BLOCK).unwrap(), &graph, 48000.0);
+    let mut reference = Runtime::new(Plan::compile(&graph, BLOCK).unwrap(), &graph, 48000.0);
+
+    runtime.apply_control(ControlMsg::Bypass {
+        node: gain,
+        enabled: true,
+    });
+    let bypassed = render(&mut runtime, 4);
+    let processed = render(&mut reference, 4);
+    assert!(processed.iter().any(|&x| x != 0.0));
+    for (b, p) in bypassed.iter().zip(&processed) {
+        assert_eq!(*b, p * 2.0, "bypass must skip the gain");
+    }
+
+    runtime.apply_control(ControlMsg::Bypass {
+        node: gain,
+        enabled: false,
+    });
+    assert_eq!(render(&mut runtime, 4), render(&mut reference, 4));
+}
+
+#[test]
+fn bypassed_source_is_silent() {
+    let (graph, osc, _, _) = gain_graph();
+    let mut runtime = Runtime::new(Plan::compile(&graph, BLOCK).unwrap(), &graph, 48000.0);
+    runtime.apply_control(ControlMsg::Bypass {
+        node: osc,
+        enabled: true,
+    });
+    assert!(render(&mut runtime, 2).iter().all(|&x| x == 0.0));
+}
+
+#[test]
+fn bypass_ignores_sinks() {
+    let (graph, _, _, sink) = gain_graph();
+    let mut runtime = Runtime::new(Plan::compile(&graph, BLOCK).unwrap(), &graph, 48000.0);
+    let mut reference = Runtime::new(Plan::compile(&graph, BLOCK).unwrap(), &graph, 48000.0);
+    runtime.apply_control(ControlMsg::Bypass {
+        node: sink,
+        enabled: true,
+    });
+    assert_eq!(render(&mut runtime, 2), render(&mut reference, 2));
+}
+
+#[test]
+fn parallel_bypass_matches_serial() {
+    let mut graph = Graph::new();
+    let mix = graph.add_node(NodeType::Mix);
+    let sink = graph.add_node(NodeType::OutputSink);
+    let mut filters = Vec::new();
+    for (port, freq) in [220.0, 330.0].into_iter().enumerate() {
+        let osc = graph.add_node(NodeType::SawOsc { freq });
+        let filter = graph.add_node(NodeType::Filter {
+            cutoff: 800.0,
+            q: 0.7,
+        });
+        graph.add_edge(edge(osc, filter)).unwrap();
+        graph.add_edge(edge_to(filter, mix, port)).unwrap();
+        filters.push(filter);
+    }
+    graph.add_edge(edge(mix, sink)).unwrap();
+
+    let mut serial = Runtime::new(Plan::compile(&graph, BLOCK).unwrap(), &graph, 48000.0);
+    let runtime = Runtime::new(Plan::compile(&graph, BLOCK).unwrap(), &graph, 48000.0);
+    let mut parallel = ParallelRuntime::new(runtime, 2).unwrap();
+
+    let mut expected = vec![0.0; BLOCK];
+    let mut actual = vec![0.0; BLOCK];
+    for block in 0..20 {
+        if block == 5 || block == 12 {
+            for runtime in [&mut serial, parallel.runtime_mut()] {
+                runtime.apply_control(ControlMsg::Bypass {
+                    node: filters[0],
+                    enabled: block == 5,
+                });
+            }
+        }
+        serial.process_block(&mut expected).unwrap();
+        parallel.process_block(&mut actual).unwrap();
+        assert_eq!(actual, expected, "block {}", block);
+    }
+}
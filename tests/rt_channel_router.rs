@@ -0,0 +1,63 @@
+mod common;
+
+use auxide::graph::{Graph, NodeType};
+use auxide::plan::Plan;
+use auxide::rt::router::ChannelRouter;
+use auxide::rt::Runtime;
+use common::edge;
+
+const BLOCK: usize = 64;
+
+fn sine_runtime() -> Runtime {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, sink)).unwrap();
+    let plan = Plan::compile(&graph, BLOCK).unwrap();
+    Runtime::new(plan, &graph, 44100.0)
+}
+
+fn channel(interleaved: &[f32], channels: usize, index: usize) -> Vec<f32> {
+    interleaved
+        .chunks_exact(channels)
+        .map(|frame| frame[index])
+        .collect()
+}
+
+#[test]
+fn mono_sink_routes_to_device_channels_0_and_2() {
+    let mut reference = sine_runtime();
+    let mut mono = vec![0.0; BLOCK];
+
+    let mut runtime = sine_runtime();
+    runtime.set_channel_router(ChannelRouter::new().route(0, 0).route(0, 2));
+    let mut out = vec![1.0; BLOCK * 3];
+    for _ in 0..3 {
+        reference.process_block(&mut mono).unwrap();
+        runtime.process_block_interleaved(&mut out, 3).unwrap();
+        assert_eq!(channel(&out, 3, 0), mono);
+        assert_eq!(channel(&out, 3, 2), mono);
+        assert!(channel(&out, 3, 1).iter().all(|&s| s == 0.0));
+    }
+    assert!(mono.iter().any(|&s| s != 0.0));
+}
+
+#[test]
+fn default_router_feeds_channel_0_and_checks_length() {
+    let mut runtime = sine_runtime();
+    let mut out = vec![0.0; BLOCK * 2];
+    runtime.process_block_interleaved(&mut out, 2).unwrap();
+    assert!(channel(&out, 2, 0).iter().any(|&s| s != 0.0));
+    assert!(channel(&out, 2, 1).iter().all(|&s| s == 0.0));
+
+    assert!(runtime.process_block_interleaved(&mut out, 3).is_err());
+    assert!(runtime.process_block_interleaved(&mut out, 0).is_err());
+}
+
+#[test]
+fn out_of_range_routes_are_skipped() {
+    let router = ChannelRouter::identity(2).route(0, 5).route(3, 0);
+    let mut out = vec![9.0; 4];
+    router.write_interleaved(&[&[1.0, 2.0]], &mut out, 2);
+    assert_eq!(out, vec![1.0, 0.0, 2.0, 0.0]);
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use auxide::graph::{Edge, Graph, NodeType, Port, PortId, Rate, CONTROL_RATE_DIVISOR};
+use auxide::node::{NodeDef, ProcessCtx};
+use auxide::plan::{Plan, PlanError};
+use auxide::rt::{render_offline, Runtime};
+use common::edge;
+
+const BLOCK: usize = 64;
+
+static CONTROL_PORT: [Port; 1] = [Port {
+    id: PortId(0),
+    rate: Rate::Control,
+}];
+
+/// Control-rate ramp: 0, 1, 2, ... one step per control value.
+struct ControlCounter;
+
+impl NodeDef for ControlCounter {
+    type State = f32;
+
+    fn input_ports(&self) -> &'static [Port] {
+        &[]
+    }
+
+    fn output_ports(&self) -> &'static [Port] {
+        &CONTROL_PORT
+    }
+
+    fn required_inputs(&self) -> usize {
+        0
+    }
+
+    fn init_state(&self, _sample_rate: f32, _block_size: usize) -> Self::State {
+        0.0
+    }
+
+    fn process(
+        &self,
+        count: &mut Self::State,
+        _inputs: &[&[f32]],
+        outputs: &mut [&mut [f32]],
+        _ctx: &ProcessCtx,
+    ) -> Result<(), &'static str> {
+        // Fails the node (to silence) unless the buffer is k-rate.
+        if outputs[0].len() != BLOCK / CONTROL_RATE_DIVISOR {
+            return Err("control buffer is not k-rate");
+        }
+        for y in outputs[0].iter_mut() {
+            *count += 1.0;
+            *y = *count;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn control_edge_into_audio_input_is_upsampled() {
+    let mut graph = Graph::new();
+    let counter = graph.add_node(NodeType::external(ControlCounter));
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph
+        .add_edge(Edge {
+            rate: Rate::Control,
+            ..edge(counter, sink)
+        })
+        .unwrap();
+
+    // An upsampler now sits between the two.
+    assert_eq!(graph.nodes.len(), 3);
+    let adapter = graph.edges_into_port(sink, PortId(0))[0].from_node;
+    assert!(matches!(
+        graph.nodes[adapter.0].as_ref().unwrap().node_type,
+        NodeType::ControlToAudio
+    ));
+
+    let plan = Plan::compile(&graph, BLOCK).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, 48000.0);
+    let out = render_offline(&mut runtime, 2 * BLOCK).unwrap();
+    // Linear from each control value to the next, landing on the value at
+    // the end of its segment.
+    for (n, &y) in out.iter().enumerate() {
+        let expected = (n + 1) as f32 / CONTROL_RATE_DIVISOR as f32;
+        assert!((y - expected).abs() < 1e-5, "sample {}: {}", n, y);
+    }
+}
+
+#[test]
+fn plan_rejects_edges_with_the_wrong_rate() {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let sink = graph.add_node(NodeType::OutputSink);
+    // Bypass `add_edge` validation.
+    graph.edges.push(Edge {
+        rate: Rate::Control,
+        ..edge(osc, sink)
+    });
+    assert_eq!(
+        Plan::compile(&graph, BLOCK).err(),
+        Some(PlanError::RateMismatch {
+            node: osc,
+            port: PortId(0),
+        })
+    );
+}
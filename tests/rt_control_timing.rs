@@ -0,0 +1,62 @@
+mod common;
+
+use auxide::control::{new_control_queue, ControlMsg};
+use auxide::graph::{Graph, NodeType};
+use auxide::plan::Plan;
+use auxide::rt::{ControlTiming, Runtime};
+use common::edge;
+
+const BLOCK: usize = 64;
+
+fn peak(block: &[f32]) -> f32 {
+    block.iter().fold(0.0f32, |m, s| m.max(s.abs()))
+}
+
+/// Peaks of blocks 0..4 with a gain of 0.1 queued just before block 2.
+fn peaks(timing: ControlTiming) -> Vec<f32> {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 1000.0 });
+    let gain = graph.add_node(NodeType::Gain { gain: 1.0 });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, gain)).unwrap();
+    graph.add_edge(edge(gain, sink)).unwrap();
+    let plan = Plan::compile(&graph, BLOCK).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, 44100.0);
+    runtime.set_control_timing(timing);
+
+    let (mut tx, mut rx) = new_control_queue();
+    let mut out = vec![0.0; BLOCK];
+    (0..4)
+        .map(|block| {
+            if block == 2 {
+                tx.push((
+                    None,
+                    ControlMsg::SetGain {
+                        node: gain,
+                        gain: 0.1,
+                    },
+                ))
+                .unwrap();
+            }
+            runtime
+                .process_block_with_channels(&mut out, &mut rx)
+                .unwrap();
+            peak(&out)
+        })
+        .collect()
+}
+
+#[test]
+fn this_block_applies_immediately() {
+    let p = peaks(ControlTiming::ThisBlock);
+    assert!(p[1] > 0.9);
+    assert!(p[2] < 0.11);
+}
+
+#[test]
+fn next_block_defers_by_one_block() {
+    let p = peaks(ControlTiming::NextBlock);
+    assert!(p[1] > 0.9);
+    assert!(p[2] > 0.9, "gain change must not reach block N");
+    assert!(p[3] < 0.11, "gain change applies from block N+1");
+}
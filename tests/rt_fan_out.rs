@@ -0,0 +1,65 @@
+mod common;
+
+use auxide::control::ControlMsg;
+use auxide::graph::{Graph, NodeId, NodeType};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+use common::{edge, edge_to};
+
+const FRAMES: usize = 1000;
+
+/// A sine fanned out to two gains, summed. Returns the graph and the
+/// sine.
+fn fan_out() -> (Graph, NodeId) {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let mix = graph.add_node(NodeType::Mix);
+    for (port, gain) in [1.0, 0.5].into_iter().enumerate() {
+        let gain = graph.add_node(NodeType::Gain { gain });
+        graph.add_edge(edge(osc, gain)).unwrap();
+        graph.add_edge(edge_to(gain, mix, port)).unwrap();
+    }
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(mix, sink)).unwrap();
+    (graph, osc)
+}
+
+fn sine() -> Vec<f32> {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, sink)).unwrap();
+    let plan = Plan::compile(&graph, 64).unwrap();
+    render_offline(&mut Runtime::new(plan, &graph, 48000.0), FRAMES).unwrap()
+}
+
+#[test]
+fn every_edge_of_a_port_carries_its_output() {
+    let expected = sine();
+    let (graph, _) = fan_out();
+    for plan in [
+        Plan::compile(&graph, 64).unwrap(),
+        Plan::compile_shared(&graph, 64).unwrap(),
+    ] {
+        let out = render_offline(&mut Runtime::new(plan, &graph, 48000.0), FRAMES).unwrap();
+        for (&y, &x) in out.iter().zip(&expected) {
+            assert!((y - 1.5 * x).abs() < 1e-6);
+        }
+    }
+}
+
+#[test]
+fn muting_a_fanned_out_node_silences_every_edge() {
+    let (graph, osc) = fan_out();
+    let plan = Plan::compile(&graph, 64).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, 48000.0);
+    assert!(render_offline(&mut runtime, 256)
+        .unwrap()
+        .iter()
+        .any(|&x| x != 0.0));
+    runtime.apply_control(ControlMsg::Mute { node: osc });
+    assert!(render_offline(&mut runtime, 256)
+        .unwrap()
+        .iter()
+        .all(|&x| x == 0.0));
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use auxide::control::{new_runtime_control, ControlMsg, RtMessage};
+use auxide::graph::{Graph, NodeId, NodeType};
+use auxide::invariant_rt::INV_CONTROL_MSG_PROCESSED;
+use auxide::plan::Plan;
+use auxide::rt::Runtime;
+use common::edge;
+
+const BLOCK: usize = 64;
+
+fn runtime() -> (Runtime, NodeId) {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 1000.0 });
+    let gain = graph.add_node(NodeType::Gain { gain: 1.0 });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, gain)).unwrap();
+    graph.add_edge(edge(gain, sink)).unwrap();
+    let plan = Plan::compile(&graph, BLOCK).unwrap();
+    (Runtime::new(plan, &graph, 44100.0), gain)
+}
+
+#[test]
+fn control_and_meter_messages_interleave_in_order() {
+    let (mut runtime, gain) = runtime();
+    let (mut control, mut control_rx, message_tx) = new_runtime_control();
+    runtime.set_message_queue(message_tx);
+    runtime.set_metering(true);
+
+    let mut out = vec![0.0; BLOCK];
+    control
+        .send(ControlMsg::SetGain {
+            node: gain,
+            gain: 0.5,
+        })
+        .unwrap();
+    control
+        .send(ControlMsg::SetParam {
+            node: gain,
+            param_idx: 3,
+            value: 0.25,
+        })
+        .unwrap();
+    runtime
+        .process_block_with_channels(&mut out, &mut control_rx)
+        .unwrap();
+    runtime
+        .process_block_with_channels(&mut out, &mut control_rx)
+        .unwrap();
+
+    let messages = control.drain_messages();
+    assert_eq!(messages.len(), 5, "{:?}", messages);
+    assert_eq!(messages[0], RtMessage::Invariant(INV_CONTROL_MSG_PROCESSED));
+    assert_eq!(
+        messages[1],
+        RtMessage::ParamResponse {
+            node: gain,
+            param_idx: 3,
+            value: 0.25,
+        }
+    );
+    assert_eq!(messages[2], RtMessage::Invariant(INV_CONTROL_MSG_PROCESSED));
+    for msg in &messages[3..] {
+        let RtMessage::Meter { peak } = *msg else {
+            panic!("expected a meter reading, got {:?}", msg);
+        };
+        assert!(peak > 0.3 && peak <= 0.5, "peak {}", peak);
+    }
+    assert!(control.drain_messages().is_empty());
+}
+
+#[test]
+fn metering_is_opt_in() {
+    let (mut runtime, gain) = runtime();
+    let (mut control, mut control_rx, message_tx) = new_runtime_control();
+    runtime.set_message_queue(message_tx);
+
+    let mut out = vec![0.0; BLOCK];
+    control
+        .send(ControlMsg::SetGain {
+            node: gain,
+            gain: 0.5,
+        })
+        .unwrap();
+    runtime
+        .process_block_with_channels(&mut out, &mut control_rx)
+        .unwrap();
+    assert_eq!(
+        control.drain_messages(),
+        vec![RtMessage::Invariant(INV_CONTROL_MSG_PROCESSED)]
+    );
+}
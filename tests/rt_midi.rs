@@ -0,0 +1,180 @@
+mod common;
+
+use auxide::control::{new_runtime_control, ControlMsg, QueuedControl};
+use auxide::graph::{Graph, NodeId, NodeType};
+use auxide::midi::{CcTarget, MidiEvent, MidiMap};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+use common::edge;
+use rtrb::Consumer;
+
+const SAMPLE_RATE: f32 = 48000.0;
+
+/// sine -> ADSR -> sink. Returns the runtime, the oscillator and the ADSR.
+fn mono_synth() -> (Runtime, NodeId, NodeId) {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 100.0 });
+    let env = graph.add_node(NodeType::Adsr {
+        attack: 1.0,
+        decay: 0.0,
+        sustain: 1.0,
+        release: 1.0,
+    });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, env)).unwrap();
+    graph.add_edge(edge(env, sink)).unwrap();
+    let plan = Plan::compile(&graph, 256).unwrap();
+    (Runtime::new(plan, &graph, SAMPLE_RATE), osc, env)
+}
+
+fn drain(control_rx: &mut Consumer<QueuedControl>) -> Vec<ControlMsg> {
+    std::iter::from_fn(|| control_rx.pop().ok())
+        .map(|(offset, msg)| {
+            assert_eq!(offset, None);
+            msg
+        })
+        .collect()
+}
+
+fn crossings(signal: &[f32]) -> usize {
+    signal
+        .windows(2)
+        .filter(|w| w[0] < 0.0 && w[1] >= 0.0)
+        .count()
+}
+
+#[test]
+fn notes_go_to_voices_by_default() {
+    let (mut control, mut control_rx, _) = new_runtime_control();
+    control
+        .send_midi(MidiEvent::NoteOn {
+            note: 60,
+            velocity: 127,
+        })
+        .unwrap();
+    control
+        .send_midi(MidiEvent::NoteOn {
+            note: 60,
+            velocity: 0,
+        })
+        .unwrap();
+    let sent = drain(&mut control_rx);
+    assert_eq!(sent.len(), 2);
+    assert!(matches!(
+        sent[0],
+        ControlMsg::NoteOn { note: 60, velocity } if velocity == 1.0
+    ));
+    assert!(matches!(sent[1], ControlMsg::NoteOff { note: 60 }));
+    assert_eq!(
+        control.send_midi(MidiEvent::PitchBend { value: 100 }),
+        Err("unmapped MIDI event")
+    );
+}
+
+#[test]
+fn controllers_follow_the_cc_table() {
+    let (_, osc, env) = mono_synth();
+    let (mut control, mut control_rx, _) = new_runtime_control();
+    let mut map = MidiMap::new();
+    map.map_cc(74, CcTarget::Frequency { node: osc }, 100.0, 1370.0);
+    map.map_cc(64, CcTarget::Gate { node: env }, 0.0, 1.0);
+    control.set_midi_map(map);
+
+    for value in [127, 0] {
+        control
+            .send_midi(MidiEvent::ControlChange {
+                controller: 74,
+                value,
+            })
+            .unwrap();
+    }
+    control
+        .send_midi(MidiEvent::ControlChange {
+            controller: 64,
+            value: 64,
+        })
+        .unwrap();
+    let sent = drain(&mut control_rx);
+    assert!(
+        matches!(sent[0], ControlMsg::SetFrequency { node, hz } if node == osc && hz == 1370.0)
+    );
+    assert!(matches!(sent[1], ControlMsg::SetFrequency { node, hz } if node == osc && hz == 100.0));
+    assert!(matches!(sent[2], ControlMsg::TriggerGate { node, on: true } if node == env));
+
+    assert_eq!(
+        control.send_midi(MidiEvent::ControlChange {
+            controller: 1,
+            value: 10,
+        }),
+        Err("unmapped MIDI event")
+    );
+    control.midi_map_mut().unmap_cc(74);
+    assert!(control
+        .send_midi(MidiEvent::ControlChange {
+            controller: 74,
+            value: 10,
+        })
+        .is_err());
+    // All Notes Off is understood without a mapping.
+    control
+        .send_midi(MidiEvent::ControlChange {
+            controller: 123,
+            value: 0,
+        })
+        .unwrap();
+    assert!(matches!(
+        drain(&mut control_rx)[..],
+        [ControlMsg::AllNotesOff]
+    ));
+}
+
+#[test]
+fn mapped_notes_play_and_bend_one_oscillator() {
+    let (mut runtime, osc, env) = mono_synth();
+    let (mut control, mut control_rx, _) = new_runtime_control();
+    control.midi_map_mut().map_notes(osc, env);
+    control.midi_map_mut().set_bend_range(12.0);
+
+    control
+        .send_midi(MidiEvent::NoteOn {
+            note: 69,
+            velocity: 100,
+        })
+        .unwrap();
+    for msg in drain(&mut control_rx) {
+        runtime.apply_control(msg);
+    }
+    let out = render_offline(&mut runtime, SAMPLE_RATE as usize).unwrap();
+    let n = crossings(&out);
+    assert!((438..=442).contains(&n), "{} crossings", n);
+
+    // A full bend up is an octave with a 12 semitone range.
+    control
+        .send_midi(MidiEvent::PitchBend { value: 8191 })
+        .unwrap();
+    for msg in drain(&mut control_rx) {
+        runtime.apply_control(msg);
+    }
+    let out = render_offline(&mut runtime, SAMPLE_RATE as usize).unwrap();
+    let n = crossings(&out);
+    assert!((878..=882).contains(&n), "{} crossings", n);
+
+    // Releasing a note that is no longer held leaves the gate open.
+    control
+        .send_midi(MidiEvent::NoteOn {
+            note: 72,
+            velocity: 100,
+        })
+        .unwrap();
+    control.send_midi(MidiEvent::NoteOff { note: 69 }).unwrap();
+    let sent = drain(&mut control_rx);
+    assert_eq!(sent.len(), 2);
+    control.send_midi(MidiEvent::NoteOff { note: 72 }).unwrap();
+    let sent = drain(&mut control_rx);
+    assert!(matches!(sent[..], [ControlMsg::TriggerGate { node, on: false }] if node == env));
+    for msg in sent {
+        runtime.apply_control(msg);
+    }
+    let out = render_offline(&mut runtime, 4800).unwrap();
+    assert!(out[2400..].iter().all(|&x| x.abs() < 1e-4));
+}
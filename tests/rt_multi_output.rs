@@ -0,0 +1,71 @@
+mod common;
+
+use auxide::graph::{Graph, NodeType};
+use auxide::plan::{Plan, PlanError};
+use auxide::rt::Runtime;
+use common::{edge, edge_to};
+
+const BLOCK: usize = 64;
+
+#[test]
+fn stereo_sink_fills_one_buffer_per_channel() {
+    // Left is the oscillator, right the same at half level.
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let half = graph.add_node(NodeType::Gain { gain: 0.5 });
+    let sink = graph.add_node(NodeType::MultiOutputSink { channels: 2 });
+    graph.add_edge(edge(osc, half)).unwrap();
+    graph.add_edge(edge(osc, sink)).unwrap();
+    graph.add_edge(edge_to(half, sink, 1)).unwrap();
+    let plan = Plan::compile(&graph, BLOCK).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, 48000.0);
+
+    let mut left = vec![0.0; BLOCK];
+    let mut right = vec![0.0; BLOCK];
+    runtime
+        .process_block_multi(&mut [&mut left, &mut right])
+        .unwrap();
+    assert!(left.iter().any(|&s| s != 0.0));
+    for (l, r) in left.iter().zip(&right) {
+        assert_eq!(*r, 0.5 * l);
+    }
+
+    let mut extra = vec![0.0; BLOCK];
+    assert!(runtime
+        .process_block_multi(&mut [&mut left, &mut right, &mut extra])
+        .is_err());
+    assert!(runtime
+        .process_block_multi(&mut [&mut left, &mut right[..BLOCK - 1]])
+        .is_err());
+}
+
+#[test]
+fn every_sink_channel_must_be_connected() {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let sink = graph.add_node(NodeType::MultiOutputSink { channels: 2 });
+    graph.add_edge(edge(osc, sink)).unwrap();
+    assert!(graph.add_edge(edge_to(osc, sink, 2)).is_err());
+    assert_eq!(
+        Plan::compile(&graph, BLOCK).err(),
+        Some(PlanError::RequiredInputMissing { node: sink })
+    );
+}
+
+#[test]
+fn mono_graph_is_copied_to_every_channel() {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, sink)).unwrap();
+    let plan = Plan::compile(&graph, BLOCK).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, 48000.0);
+
+    let mut left = vec![0.0; BLOCK];
+    let mut right = vec![0.0; BLOCK];
+    runtime
+        .process_block_multi(&mut [&mut left, &mut right])
+        .unwrap();
+    assert!(left.iter().any(|&s| s != 0.0));
+    assert_eq!(left, right);
+}
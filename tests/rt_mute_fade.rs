@@ -0,0 +1,114 @@
+mod common;
+
+use auxide::control::ControlMsg;
+use auxide::graph::{Edge, Graph, NodeId, NodeType, PortId};
+use auxide::plan::Plan;
+use auxide::rt::Runtime;
+use common::edge;
+
+const BLOCK: usize = 64;
+const FADE: usize = 32;
+
+/// sine -> gain -> sink. Returns the runtime, the gain and the sink.
+fn runtime() -> (Runtime, NodeId, NodeId) {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let gain = graph.add_node(NodeType::Gain { gain: 1.0 });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, gain)).unwrap();
+    graph.add_edge(edge(gain, sink)).unwrap();
+    let plan = Plan::compile(&graph, BLOCK).unwrap();
+    (Runtime::new(plan, &graph, 48000.0), gain, sink)
+}
+
+/// Render a block with `runtime` and an untouched reference; the gain
+/// applied to each sample.
+fn gains(runtime: &mut Runtime, reference: &mut Runtime) -> Vec<f32> {
+    let mut out = vec![0.0; BLOCK];
+    let mut expected = vec![0.0; BLOCK];
+    runtime.process_block(&mut out).unwrap();
+    reference.process_block(&mut expected).unwrap();
+    out.iter()
+        .zip(&expected)
+        .map(|(&y, &x)| if x.abs() > 1e-3 { y / x } else { f32::NAN })
+        .collect()
+}
+
+fn assert_ramp(gains: &[f32], gain_at: impl Fn(usize) -> f32) {
+    for (n, &g) in gains.iter().enumerate() {
+        if !g.is_nan() {
+            assert!((g - gain_at(n)).abs() < 1e-4, "sample {}: {}", n, g);
+        }
+    }
+}
+
+#[test]
+fn mute_and_unmute_fade_over_the_set_length() {
+    // A node with outputs, then a sink.
+    for mute_sink in [false, true] {
+        let (mut runtime, gain, sink) = runtime();
+        let (mut reference, _, _) = self::runtime();
+        let node = if mute_sink { sink } else { gain };
+        runtime.set_mute_fade(FADE);
+
+        runtime.apply_control(ControlMsg::Mute { node });
+        let fade_out = gains(&mut runtime, &mut reference);
+        assert_ramp(&fade_out, |n| 1.0 - ((n + 1) as f32 / FADE as f32).min(1.0));
+        assert_ramp(&gains(&mut runtime, &mut reference), |_| 0.0);
+
+        runtime.apply_control(ControlMsg::Unmute { node });
+        let fade_in = gains(&mut runtime, &mut reference);
+        assert_ramp(&fade_in, |n| ((n + 1) as f32 / FADE as f32).min(1.0));
+    }
+}
+
+#[test]
+fn unmute_mid_fade_turns_around() {
+    let (mut runtime, gain, _) = runtime();
+    let (mut reference, _, _) = self::runtime();
+    runtime.set_mute_fade(4 * BLOCK);
+    runtime.apply_control(ControlMsg::Mute { node: gain });
+    gains(&mut runtime, &mut reference);
+    // A quarter of the way down: back up over the full fade length.
+    runtime.apply_control(ControlMsg::Unmute { node: gain });
+    let fade = (4 * BLOCK) as f32;
+    assert_ramp(&gains(&mut runtime, &mut reference), |n| {
+        0.75 + 0.25 * (n + 1) as f32 / fade
+    });
+}
+
+#[test]
+fn solo_fades_the_other_nodes() {
+    let mut graph = Graph::new();
+    let a = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let b = graph.add_node(NodeType::SineOsc { freq: 660.0 });
+    let mix = graph.add_node(NodeType::Mix);
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(a, mix)).unwrap();
+    graph
+        .add_edge(Edge {
+            to_port: PortId(1),
+            ..edge(b, mix)
+        })
+        .unwrap();
+    graph.add_edge(edge(mix, sink)).unwrap();
+    let plan = Plan::compile(&graph, BLOCK).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, 48000.0);
+    runtime.set_mute_fade(FADE);
+    let mut out = vec![0.0; BLOCK];
+    runtime.process_block(&mut out).unwrap();
+
+    runtime.apply_control(ControlMsg::Solo { node: a });
+    runtime.process_block(&mut out).unwrap();
+    // Still fading: `b` is partly in the mix.
+    let solo_plan = Plan::compile(&graph, BLOCK).unwrap();
+    let mut alone = Runtime::new(solo_plan, &graph, 48000.0);
+    alone.apply_control(ControlMsg::Solo { node: a });
+    let mut expected = vec![0.0; BLOCK];
+    alone.process_block(&mut expected).unwrap();
+    alone.process_block(&mut expected).unwrap();
+    assert_ne!(out[..FADE - 1], expected[..FADE - 1]);
+    for (&y, &x) in out[FADE..].iter().zip(&expected[FADE..]) {
+        assert!((y - x).abs() < 1e-6);
+    }
+}
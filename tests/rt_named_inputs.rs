@@ -0,0 +1,115 @@
+mod common;
+
+use auxide::graph::{Graph, NodeType};
+use auxide::plan::Plan;
+use auxide::rt::Runtime;
+use common::{edge, edge_to};
+
+const BLOCK: usize = 32;
+
+#[test]
+fn named_inputs_reach_their_nodes() {
+    // out = mic - line, so swapped buffers would flip the sign.
+    let mut graph = Graph::new();
+    let mic = graph.add_node(NodeType::AudioInput {
+        label: "mic".to_string(),
+        channel: 0,
+    });
+    let line = graph.add_node(NodeType::AudioInput {
+        label: "line".to_string(),
+        channel: 1,
+    });
+    let invert = graph.add_node(NodeType::Gain { gain: -1.0 });
+    let mix = graph.add_node(NodeType::Mix);
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(mic, mix)).unwrap();
+    graph.add_edge(edge(line, invert)).unwrap();
+    graph.add_edge(edge_to(invert, mix, 1)).unwrap();
+    graph.add_edge(edge(mix, sink)).unwrap();
+    let plan = Plan::compile(&graph, BLOCK).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, 48000.0);
+
+    let ramp: Vec<f32> = (0..BLOCK).map(|i| i as f32 / BLOCK as f32).collect();
+    runtime.set_input_named("mic", &ramp).unwrap();
+    runtime.set_input_named("line", &[0.25; BLOCK]).unwrap();
+    let mut out = vec![0.0; BLOCK];
+    runtime.process_block(&mut out).unwrap();
+    for (y, x) in out.iter().zip(&ramp) {
+        assert!((y - (x - 0.25)).abs() < 1e-6);
+    }
+
+    // A short buffer is zero-padded; the other input keeps its last data.
+    runtime.set_input_named("mic", &[1.0; 4]).unwrap();
+    runtime.process_block(&mut out).unwrap();
+    assert_eq!(out[..4], [0.75; 4]);
+    assert_eq!(out[4..], [-0.25; BLOCK - 4]);
+
+    assert!(runtime.set_input_named("aux", &ramp).is_err());
+    assert!(runtime.set_input_named("mic", &[0.0; BLOCK + 1]).is_err());
+    assert!(runtime.set_input(mix, &ramp).is_err());
+}
+
+#[test]
+fn process_block_io_feeds_inputs_by_channel() {
+    // Effect use: out = 0.5 * host channel 1; channel 0 is ignored.
+    let mut graph = Graph::new();
+    let input = graph.add_node(NodeType::AudioInput {
+        label: "guitar".to_string(),
+        channel: 1,
+    });
+    let gain = graph.add_node(NodeType::Gain { gain: 0.5 });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(input, gain)).unwrap();
+    graph.add_edge(edge(gain, sink)).unwrap();
+    let plan = Plan::compile(&graph, BLOCK).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, 48000.0);
+
+    let ramp: Vec<f32> = (0..BLOCK).map(|i| i as f32).collect();
+    let mut out = vec![0.0; BLOCK];
+    runtime
+        .process_block_io(&[&[9.0; BLOCK], &ramp], &mut out)
+        .unwrap();
+    for (y, x) in out.iter().zip(&ramp) {
+        assert_eq!(*y, 0.5 * x);
+    }
+
+    // A host with fewer channels leaves the input silent.
+    runtime.process_block_io(&[&ramp], &mut out).unwrap();
+    assert!(out.iter().all(|&s| s == 0.0));
+
+    assert!(runtime
+        .process_block_io(&[&ramp, &[0.0; BLOCK + 1]], &mut out)
+        .is_err());
+}
+
+#[test]
+fn process_block_io_rejects_long_inputs_before_copying() {
+    let mut graph = Graph::new();
+    let mix = graph.add_node(NodeType::Mix);
+    for channel in 0..2 {
+        let input = graph.add_node(NodeType::AudioInput {
+            label: format!("in {}", channel),
+            channel,
+        });
+        graph.add_edge(edge_to(input, mix, channel)).unwrap();
+    }
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(mix, sink)).unwrap();
+    let plan = Plan::compile(&graph, BLOCK).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, 48000.0);
+
+    let ramp: Vec<f32> = (0..BLOCK).map(|i| i as f32).collect();
+    let mut expected = vec![0.0; BLOCK];
+    runtime
+        .process_block_io(&[&ramp, &ramp], &mut expected)
+        .unwrap();
+
+    // Channel 0 is fine but channel 1 is too long: channel 0 must keep
+    // its previous block.
+    let mut out = vec![0.0; BLOCK];
+    assert!(runtime
+        .process_block_io(&[&[9.0; BLOCK], &[0.0; BLOCK + 1]], &mut out)
+        .is_err());
+    runtime.process_block(&mut out).unwrap();
+    assert_eq!(out, expected);
+}
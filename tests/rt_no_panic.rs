@@ -1,6 +1,9 @@
+mod common;
+
 use auxide::graph::{Graph, NodeType};
 use auxide::plan::Plan;
 use auxide::rt::Runtime;
+use common::edge;
 use proptest::prelude::*;
 
 proptest! {
@@ -17,13 +20,7 @@ proptest! {
                 let mut g = Graph::new();
                 let n1 = g.add_node(NodeType::SineOsc { freq: 440.0 });
                 let n2 = g.add_node(NodeType::OutputSink);
-                g.add_edge(auxide::graph::Edge {
-                    from_node: n1,
-                    from_port: auxide::graph::PortId(0),
-                    to_node: n2,
-                    to_port: auxide::graph::PortId(0),
-                    rate: auxide::graph::Rate::Audio,
-                }).unwrap();
+                g.add_edge(edge(n1, n2)).unwrap();
                 g
             },
         ];
@@ -0,0 +1,117 @@
+#![cfg(feature = "profiling")]
+
+mod common;
+
+use auxide::control::{new_runtime_control, RtMessage};
+use auxide::graph::{Graph, NodeId, NodeType, Port, PortId, Rate};
+use auxide::node::{NodeDef, ProcessCtx};
+use auxide::plan::Plan;
+use auxide::rt::Runtime;
+use common::{edge, edge_to};
+use std::time::{Duration, Instant};
+
+const BLOCK: usize = 64;
+const SPIN: Duration = Duration::from_millis(2);
+
+static AUDIO_OUT: [Port; 1] = [Port {
+    id: PortId(0),
+    rate: Rate::Audio,
+}];
+
+/// Busy-waits for `SPIN` every block.
+struct Slow;
+
+impl NodeDef for Slow {
+    type State = ();
+
+    fn input_ports(&self) -> &'static [Port] {
+        &[]
+    }
+
+    fn output_ports(&self) -> &'static [Port] {
+        &AUDIO_OUT
+    }
+
+    fn required_inputs(&self) -> usize {
+        0
+    }
+
+    fn init_state(&self, _sample_rate: f32, _block_size: usize) -> Self::State {}
+
+    fn process(
+        &self,
+        _state: &mut Self::State,
+        _inputs: &[&[f32]],
+        _outputs: &mut [&mut [f32]],
+        _ctx: &ProcessCtx,
+    ) -> Result<(), &'static str> {
+        let start = Instant::now();
+        while start.elapsed() < SPIN {
+            std::hint::spin_loop();
+        }
+        Ok(())
+    }
+}
+
+/// Slow node and a sine mixed into the sink.
+fn runtime() -> (Runtime, NodeId) {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let slow = graph.add_node(NodeType::external(Slow));
+    let mix = graph.add_node(NodeType::Mix);
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, mix)).unwrap();
+    graph.add_edge(edge_to(slow, mix, 1)).unwrap();
+    graph.add_edge(edge(mix, sink)).unwrap();
+    let plan = Plan::compile(&graph, BLOCK).unwrap();
+    (Runtime::new(plan, &graph, 48000.0), slow)
+}
+
+#[test]
+fn block_profile_names_the_slowest_node() {
+    let (mut runtime, slow) = runtime();
+    let (mut control, mut control_rx, message_tx) = new_runtime_control();
+    runtime.set_message_queue(message_tx);
+    runtime.set_node_profiling(true);
+
+    let mut out = vec![0.0; BLOCK];
+    for _ in 0..2 {
+        runtime
+            .process_block_with_channels(&mut out, &mut control_rx)
+            .unwrap();
+    }
+    let profiles: Vec<_> = control
+        .drain_messages()
+        .into_iter()
+        .filter_map(|msg| match msg {
+            RtMessage::BlockProfile {
+                total,
+                slowest,
+                slowest_time,
+            } => Some((total, slowest, slowest_time)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(profiles.len(), 2);
+    for (total, slowest, slowest_time) in profiles {
+        assert_eq!(slowest, slow);
+        assert!(slowest_time >= SPIN);
+        assert!(total >= slowest_time);
+    }
+    let times = runtime.node_times();
+    assert!(times[slow.0] >= SPIN);
+    assert!(times.iter().all(|&t| t <= times[slow.0]));
+}
+
+#[test]
+fn profiling_is_off_by_default() {
+    let (mut runtime, _) = runtime();
+    let (mut control, mut control_rx, message_tx) = new_runtime_control();
+    runtime.set_message_queue(message_tx);
+    let mut out = vec![0.0; BLOCK];
+    runtime
+        .process_block_with_channels(&mut out, &mut control_rx)
+        .unwrap();
+    assert!(control.drain_messages().is_empty());
+    assert!(runtime.node_times().iter().all(|t| t.is_zero()));
+}
@@ -0,0 +1,105 @@
+#![cfg(all(feature = "profiling", feature = "invariant-queue"))]
+
+mod common;
+
+use auxide::graph::{Graph, NodeId, NodeType, Port, PortId, Rate};
+use auxide::invariant_rt::{drain_invariant_signals, new_invariant_queue, INV_RT_OVERLOAD};
+use auxide::node::{NodeDef, ProcessCtx};
+use auxide::plan::Plan;
+use auxide::rt::Runtime;
+use common::edge;
+use std::time::{Duration, Instant};
+
+static AUDIO_OUT: [Port; 1] = [Port {
+    id: PortId(0),
+    rate: Rate::Audio,
+}];
+
+/// Busy-waits for `spin` every block.
+struct Slow {
+    spin: Duration,
+}
+
+impl NodeDef for Slow {
+    type State = ();
+
+    fn input_ports(&self) -> &'static [Port] {
+        &[]
+    }
+
+    fn output_ports(&self) -> &'static [Port] {
+        &AUDIO_OUT
+    }
+
+    fn required_inputs(&self) -> usize {
+        0
+    }
+
+    fn init_state(&self, _sample_rate: f32, _block_size: usize) -> Self::State {}
+
+    fn process(
+        &self,
+        _state: &mut Self::State,
+        _inputs: &[&[f32]],
+        _outputs: &mut [&mut [f32]],
+        _ctx: &ProcessCtx,
+    ) -> Result<(), &'static str> {
+        let start = Instant::now();
+        while start.elapsed() < self.spin {
+            std::hint::spin_loop();
+        }
+        Ok(())
+    }
+}
+
+fn runtime_with(node_type: NodeType) -> Runtime {
+    let mut graph = Graph::new();
+    let src = graph.add_node(node_type);
+    let sink = graph.add_node(NodeType::OutputSink);
+    connect(&mut graph, src, sink);
+    let plan = Plan::compile(&graph, 64).unwrap();
+    Runtime::new(plan, &graph, 44100.0)
+}
+
+fn connect(graph: &mut Graph, from: NodeId, to: NodeId) {
+    graph.add_edge(edge(from, to)).unwrap();
+}
+
+#[test]
+fn overload_signaled_when_budget_exceeded() {
+    let (tx, mut rx) = new_invariant_queue();
+    let mut runtime = runtime_with(NodeType::external(Slow {
+        spin: Duration::from_millis(3),
+    }));
+    runtime.set_invariant_queue(tx);
+    runtime.set_block_budget(Some(Duration::from_millis(1)));
+
+    let mut out = vec![0.0; 64];
+    for _ in 0..3 {
+        runtime.process_block(&mut out).unwrap();
+    }
+    let signals = drain_invariant_signals(&mut rx);
+    assert_eq!(
+        signals.iter().filter(|&&id| id == INV_RT_OVERLOAD).count(),
+        3
+    );
+}
+
+#[test]
+fn no_overload_within_budget_or_when_disabled() {
+    let (tx, mut rx) = new_invariant_queue();
+    let mut runtime = runtime_with(NodeType::SineOsc { freq: 440.0 });
+    runtime.set_invariant_queue(tx);
+    runtime.set_block_budget(Some(Duration::from_secs(1)));
+    let mut out = vec![0.0; 64];
+    runtime.process_block(&mut out).unwrap();
+    assert!(!drain_invariant_signals(&mut rx).contains(&INV_RT_OVERLOAD));
+
+    let (tx, mut rx) = new_invariant_queue();
+    let mut slow = runtime_with(NodeType::external(Slow {
+        spin: Duration::from_millis(2),
+    }));
+    slow.set_invariant_queue(tx);
+    slow.process_block(&mut out).unwrap();
+    assert!(drain_invariant_signals(&mut rx).is_empty());
+}
@@ -0,0 +1,145 @@
+mod common;
+
+use auxide::control::ControlMsg;
+use auxide::graph::{Graph, NodeId, NodeType};
+use auxide::plan::Plan;
+use auxide::rt::parallel::ParallelRuntime;
+use auxide::rt::Runtime;
+use common::{edge, edge_to};
+
+const BLOCK: usize = 64;
+
+/// Four filtered sources mixed pairwise into a comb (mix -> delay -> gain
+/// -> mix) and out. Returns the graph and the gain nodes.
+fn wide_graph() -> (Graph, Vec<NodeId>) {
+    let mut graph = Graph::new();
+    let sources = [
+        NodeType::SineOsc { freq: 220.0 },
+        NodeType::SawOsc { freq: 330.0 },
+        NodeType::TriOsc { freq: 440.0 },
+        NodeType::Noise {
+            seed: 7,
+            color: Default::default(),
+        },
+    ];
+    let mut gains = Vec::new();
+    for source in sources {
+        let osc = graph.add_node(source);
+        let filter = graph.add_node(NodeType::Filter {
+            cutoff: 2000.0,
+            q: 0.7,
+        });
+        let gain = graph.add_node(NodeType::Gain { gain: 0.25 });
+        graph.add_edge(edge(osc, filter)).unwrap();
+        graph.add_edge(edge(filter, gain)).unwrap();
+        gains.push(gain);
+    }
+    let left = graph.add_node(NodeType::Mix);
+    let right = graph.add_node(NodeType::Mix);
+    let all = graph.add_node(NodeType::Mix);
+    graph.add_edge(edge(gains[0], left)).unwrap();
+    graph.add_edge(edge_to(gains[1], left, 1)).unwrap();
+    graph.add_edge(edge(gains[2], right)).unwrap();
+    graph.add_edge(edge_to(gains[3], right, 1)).unwrap();
+    graph.add_edge(edge(left, all)).unwrap();
+    graph.add_edge(edge_to(right, all, 1)).unwrap();
+
+    let comb = graph.add_node(NodeType::Mix);
+    let delay = graph.add_node(NodeType::Delay {
+        delay_samples: 100.0,
+    });
+    let feedback = graph.add_node(NodeType::Gain { gain: 0.5 });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(all, comb)).unwrap();
+    graph.add_edge(edge(comb, delay)).unwrap();
+    graph.add_edge(edge(delay, feedback)).unwrap();
+    graph.add_edge(edge_to(feedback, comb, 1)).unwrap();
+    graph.add_edge(edge(comb, sink)).unwrap();
+    (graph, gains)
+}
+
+fn level_of(plan: &Plan, node: NodeId) -> usize {
+    plan.levels()
+        .iter()
+        .position(|level| level.contains(&node))
+        .unwrap()
+}
+
+#[test]
+fn levels_group_independent_nodes() {
+    let mut graph = Graph::new();
+    let a = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let b = graph.add_node(NodeType::SineOsc { freq: 660.0 });
+    let mix = graph.add_node(NodeType::Mix);
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(a, mix)).unwrap();
+    graph.add_edge(edge_to(b, mix, 1)).unwrap();
+    graph.add_edge(edge(mix, sink)).unwrap();
+    let plan = Plan::compile(&graph, BLOCK).unwrap();
+    assert_eq!(plan.levels(), &[vec![a, b], vec![mix], vec![sink]]);
+}
+
+#[test]
+fn levels_respect_every_edge() {
+    let (graph, _) = wide_graph();
+    let plan = Plan::compile(&graph, BLOCK).unwrap();
+    let scheduled: usize = plan.levels().iter().map(Vec::len).sum();
+    assert_eq!(scheduled, plan.order.len());
+    // The four sources run side by side, with the delay: it only hears
+    // the previous block.
+    assert_eq!(plan.levels()[0].len(), 5);
+    for edge in &plan.edges {
+        let (from, to) = (
+            level_of(&plan, edge.from_node),
+            level_of(&plan, edge.to_node),
+        );
+        if edge.feedback {
+            // The reader still hears the previous block.
+            assert!(to <= from, "{:?}", edge);
+        } else {
+            assert!(from < to, "{:?}", edge);
+        }
+    }
+}
+
+#[test]
+fn parallel_runtime_matches_serial_runtime() {
+    let (graph, gains) = wide_graph();
+    let mut serial = Runtime::new(Plan::compile(&graph, BLOCK).unwrap(), &graph, 48000.0);
+    let runtime = Runtime::new(Plan::compile(&graph, BLOCK).unwrap(), &graph, 48000.0);
+    let mut parallel = ParallelRuntime::new(runtime, 3).unwrap();
+
+    let mut expected = vec![0.0; BLOCK];
+    let mut actual = vec![0.0; BLOCK];
+    for block in 0..200 {
+        if block == 50 {
+            for runtime in [&mut serial, parallel.runtime_mut()] {
+                runtime.apply_control(ControlMsg::SetGain {
+                    node: gains[1],
+                    gain: 0.0,
+                });
+            }
+        }
+        serial.process_block(&mut expected).unwrap();
+        parallel.process_block(&mut actual).unwrap();
+        assert_eq!(actual, expected, "block {}", block);
+    }
+    assert!(expected.iter().any(|&x| x != 0.0));
+
+    // Back to serial rendering, carrying on from the same state.
+    let mut runtime = parallel.into_inner();
+    for _ in 0..10 {
+        serial.process_block(&mut expected).unwrap();
+        runtime.process_block(&mut actual).unwrap();
+        assert_eq!(actual, expected);
+    }
+}
+
+#[test]
+fn parallel_runtime_refuses_shared_plans_and_empty_pools() {
+    let (graph, _) = wide_graph();
+    let shared = Plan::compile_shared(&graph, BLOCK).unwrap();
+    assert!(ParallelRuntime::new(Runtime::new(shared, &graph, 48000.0), 2).is_err());
+    let plan = Plan::compile(&graph, BLOCK).unwrap();
+    assert!(ParallelRuntime::new(Runtime::new(plan, &graph, 48000.0), 0).is_err());
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use auxide::control::ControlMsg;
+use auxide::graph::{Graph, NodeId, NodeType};
+use auxide::plan::Plan;
+use auxide::rt::Runtime;
+use common::edge;
+
+const BLOCK: usize = 64;
+const SAMPLE_RATE: f32 = 48000.0;
+
+/// osc -> gain -> out; returns the runtime and the gain node.
+fn runtime() -> (Runtime, NodeId, NodeId) {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 1000.0 });
+    let gain = graph.add_node(NodeType::Gain { gain: 1.0 });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, gain)).unwrap();
+    graph.add_edge(edge(gain, sink)).unwrap();
+    let plan = Plan::compile(&graph, BLOCK).unwrap();
+    (Runtime::new(plan, &graph, SAMPLE_RATE), osc, gain)
+}
+
+fn block(runtime: &mut Runtime) -> Vec<f32> {
+    let mut out = vec![0.0; BLOCK];
+    runtime.process_block(&mut out).unwrap();
+    out
+}
+
+#[test]
+fn gain_change_ramps_over_the_smoothing_time() {
+    let (mut dry, _, _) = runtime();
+    let reference = block(&mut dry);
+
+    // 1 ms at 48 kHz: 48 samples from 1.0 down to 0.0.
+    let (mut runtime, _, gain) = runtime();
+    runtime.set_param_smoothing(1.0);
+    runtime.apply_control(ControlMsg::SetGain {
+        node: gain,
+        gain: 0.0,
+    });
+    let out = block(&mut runtime);
+    for n in 0..BLOCK {
+        let level = (1.0 - (n + 1) as f32 / 48.0).max(0.0);
+        assert!((out[n] - level * reference[n]).abs() < 1e-5, "sample {}", n);
+    }
+}
+
+#[test]
+fn retarget_mid_ramp_starts_from_the_current_value() {
+    let (mut dry, _, _) = runtime();
+    block(&mut dry);
+    let reference = block(&mut dry);
+
+    // 10 ms = 480 samples; after one block the gain has fallen to
+    // 1 - 64/480 when it is sent back up.
+    let (mut runtime, _, gain) = runtime();
+    runtime.set_param_smoothing(10.0);
+    runtime.apply_control(ControlMsg::SetGain {
+        node: gain,
+        gain: 0.0,
+    });
+    block(&mut runtime);
+    runtime.apply_control(ControlMsg::SetGain {
+        node: gain,
+        gain: 1.0,
+    });
+    let out = block(&mut runtime);
+    let reached = 1.0 - BLOCK as f32 / 480.0;
+    for n in 0..BLOCK {
+        let level = reached + (1.0 - reached) * (n + 1) as f32 / 480.0;
+        assert!((out[n] - level * reference[n]).abs() < 1e-4, "sample {}", n);
+    }
+}
+
+#[test]
+fn frequency_change_glides_without_a_phase_jump() {
+    let (mut runtime, osc, _) = runtime();
+    runtime.set_param_smoothing(5.0);
+    block(&mut runtime);
+    runtime.apply_control(ControlMsg::SetFrequency {
+        node: osc,
+        hz: 3000.0,
+    });
+    let mut out = Vec::new();
+    for _ in 0..8 {
+        out.extend(block(&mut runtime));
+    }
+    // The largest sample-to-sample step grows smoothly: never more than
+    // a 3 kHz sine allows.
+    let limit = 2.0 * std::f32::consts::PI * 3000.0 / SAMPLE_RATE;
+    assert!(out.windows(2).all(|w| (w[1] - w[0]).abs() <= limit + 1e-4));
+    // Early on the pitch is still close to 1 kHz.
+    let step = 2.0 * std::f32::consts::PI * 1000.0 / SAMPLE_RATE;
+    assert!((out[1] - out[0]).abs() <= 1.1 * step);
+}
+
+#[test]
+fn smoothing_is_off_by_default() {
+    let (mut runtime, _, gain) = runtime();
+    runtime.apply_control(ControlMsg::SetGain {
+        node: gain,
+        gain: 0.0,
+    });
+    assert!(block(&mut runtime).iter().all(|&s| s == 0.0));
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use auxide::graph::{Graph, NodeType};
+use auxide::plan::Plan;
+use auxide::rt::{BlockSizePolicy, Runtime};
+use common::edge;
+
+fn sine_runtime() -> Runtime {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, sink)).unwrap();
+    let plan = Plan::compile(&graph, 64).unwrap();
+    Runtime::new(plan, &graph, 44100.0)
+}
+
+#[test]
+fn short_buffer_rejected_by_default() {
+    let mut runtime = sine_runtime();
+    let mut out = vec![0.0; 32];
+    assert!(runtime.process_block(&mut out).is_err());
+}
+
+#[test]
+fn short_buffer_padded_when_enabled() {
+    let mut reference = sine_runtime();
+    let mut full = vec![0.0; 64];
+
+    let mut runtime = sine_runtime();
+    runtime.set_block_size_policy(BlockSizePolicy::PadShort);
+    let mut short = vec![0.0; 32];
+
+    for _ in 0..3 {
+        reference.process_block(&mut full).unwrap();
+        runtime.process_block(&mut short).unwrap();
+        assert_eq!(short[..], full[..32]);
+    }
+
+    // Full-size buffers still work under the lenient policy.
+    reference.process_block(&mut full).unwrap();
+    let mut block = vec![0.0; 64];
+    runtime.process_block(&mut block).unwrap();
+    assert_eq!(block, full);
+
+    // Oversized buffers are still an error.
+    let mut long = vec![0.0; 128];
+    assert!(runtime.process_block(&mut long).is_err());
+}
@@ -0,0 +1,108 @@
+mod common;
+
+use auxide::control::{new_runtime_control, ControlMsg};
+use auxide::graph::{Graph, NodeId, NodeType};
+use auxide::plan::Plan;
+use auxide::rt::Runtime;
+use common::edge;
+
+const BLOCK: usize = 64;
+const SAMPLE_RATE: f32 = 48000.0;
+
+/// osc -> gain -> out.
+fn graph(gain: f32) -> Graph {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let amp = graph.add_node(NodeType::Gain { gain });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, amp)).unwrap();
+    graph.add_edge(edge(amp, sink)).unwrap();
+    graph
+}
+
+fn dry(blocks: usize) -> Vec<Vec<f32>> {
+    let g = graph(1.0);
+    let mut runtime = Runtime::new(Plan::compile(&g, BLOCK).unwrap(), &g, SAMPLE_RATE);
+    (0..blocks)
+        .map(|_| {
+            let mut out = vec![0.0; BLOCK];
+            runtime.process_block(&mut out).unwrap();
+            out
+        })
+        .collect()
+}
+
+#[test]
+fn swap_crossfades_and_keeps_oscillator_phase() {
+    let dry = dry(4);
+    let g = graph(1.0);
+    let mut runtime = Runtime::new(Plan::compile(&g, BLOCK).unwrap(), &g, SAMPLE_RATE);
+    let (mut control, mut control_rx, _) = new_runtime_control();
+    control.enable_swaps(&mut runtime);
+
+    let mut out = vec![0.0; BLOCK];
+    runtime
+        .process_block_with_channels(&mut out, &mut control_rx)
+        .unwrap();
+    assert_eq!(out, dry[0]);
+
+    let quiet = graph(0.25);
+    control
+        .swap_plan(Plan::compile(&quiet, BLOCK).unwrap(), &quiet)
+        .unwrap();
+
+    // Fade block: the old graph's block fading into the new graph, whose
+    // oscillator continues from the old one's state.
+    runtime
+        .process_block_with_channels(&mut out, &mut control_rx)
+        .unwrap();
+    for n in 0..BLOCK {
+        let t = (n + 1) as f32 / BLOCK as f32;
+        let expected = dry[1][n] * (1.0 - t) + 0.25 * dry[2][n] * t;
+        assert!((out[n] - expected).abs() < 1e-5, "sample {}", n);
+    }
+
+    // After the fade only the new graph plays, without a phase reset.
+    runtime
+        .process_block_with_channels(&mut out, &mut control_rx)
+        .unwrap();
+    for n in 0..BLOCK {
+        assert!((out[n] - 0.25 * dry[3][n]).abs() < 1e-5, "sample {}", n);
+    }
+
+    // Control messages reach the new runtime.
+    control
+        .send(ControlMsg::SetGain {
+            node: NodeId(1, 0),
+            gain: 0.0,
+        })
+        .unwrap();
+    runtime
+        .process_block_with_channels(&mut out, &mut control_rx)
+        .unwrap();
+    assert!(out.iter().all(|&s| s == 0.0));
+    control.drain_messages();
+}
+
+#[test]
+fn swap_requires_enable_and_matching_block_size() {
+    let g = graph(1.0);
+    let (mut control, mut control_rx, _) = new_runtime_control();
+    assert!(control
+        .swap_plan(Plan::compile(&g, BLOCK).unwrap(), &g)
+        .is_err());
+
+    let mut runtime = Runtime::new(Plan::compile(&g, BLOCK).unwrap(), &g, SAMPLE_RATE);
+    control.enable_swaps(&mut runtime);
+    let muted = graph(0.0);
+    control
+        .swap_plan(Plan::compile(&muted, 2 * BLOCK).unwrap(), &muted)
+        .unwrap();
+    let mut out = vec![0.0; BLOCK];
+    runtime
+        .process_block_with_channels(&mut out, &mut control_rx)
+        .unwrap();
+    // Refused: the original graph keeps playing.
+    assert!(out.iter().any(|&s| s != 0.0));
+    assert_eq!(runtime.plan.block_size, BLOCK);
+}
@@ -0,0 +1,111 @@
+mod common;
+
+use auxide::control::{new_runtime_control, RtMessage};
+use auxide::graph::{Graph, NodeType, Port, PortId, Rate};
+use auxide::invariant_rt::INV_NONFINITE_DETECTED;
+use auxide::node::{NodeDef, ProcessCtx};
+use auxide::plan::Plan;
+use auxide::rt::Runtime;
+use common::{edge, edge_to};
+
+const BLOCK: usize = 64;
+
+static AUDIO_OUT: [Port; 1] = [Port {
+    id: PortId(0),
+    rate: Rate::Audio,
+}];
+
+/// Emits NaN and infinity every other block.
+struct Broken;
+
+impl NodeDef for Broken {
+    type State = u64;
+
+    fn input_ports(&self) -> &'static [Port] {
+        &[]
+    }
+
+    fn output_ports(&self) -> &'static [Port] {
+        &AUDIO_OUT
+    }
+
+    fn required_inputs(&self) -> usize {
+        0
+    }
+
+    fn init_state(&self, _sample_rate: f32, _block_size: usize) -> Self::State {
+        0
+    }
+
+    fn process(
+        &self,
+        blocks: &mut Self::State,
+        _inputs: &[&[f32]],
+        outputs: &mut [&mut [f32]],
+        _ctx: &ProcessCtx,
+    ) -> Result<(), &'static str> {
+        let out = &mut outputs[0];
+        out.fill(0.0);
+        if *blocks % 2 == 0 {
+            out[3] = f32::NAN;
+            out[7] = f32::INFINITY;
+        }
+        *blocks += 1;
+        Ok(())
+    }
+}
+
+/// A sine mixed with the broken node, through a gain.
+fn runtime() -> Runtime {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let broken = graph.add_node(NodeType::external(Broken));
+    let mix = graph.add_node(NodeType::Mix);
+    let gain = graph.add_node(NodeType::Gain { gain: 0.5 });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, mix)).unwrap();
+    graph.add_edge(edge_to(broken, mix, 1)).unwrap();
+    graph.add_edge(edge(mix, gain)).unwrap();
+    graph.add_edge(edge(gain, sink)).unwrap();
+    let plan = Plan::compile(&graph, BLOCK).unwrap();
+    Runtime::new(plan, &graph, 44100.0)
+}
+
+#[test]
+fn non_finite_samples_propagate_by_default() {
+    let mut runtime = runtime();
+    let mut out = vec![0.0; BLOCK];
+    runtime.process_block(&mut out).unwrap();
+    assert!(out[3].is_nan());
+    assert!(out[7].is_infinite());
+}
+
+#[test]
+fn sanitize_silences_non_finite_samples_and_reports_them() {
+    let mut runtime = runtime();
+    let mut reference = self::runtime();
+    let (mut control, _control_rx, message_tx) = new_runtime_control();
+    runtime.set_message_queue(message_tx);
+    runtime.set_sanitize(true);
+
+    let mut out = vec![0.0; BLOCK];
+    let mut expected = vec![0.0; BLOCK];
+    for _ in 0..4 {
+        runtime.process_block(&mut out).unwrap();
+        reference.process_block(&mut expected).unwrap();
+        assert!(out.iter().all(|x| x.is_finite()));
+        // Everything else is the untouched sine.
+        for (n, (&x, &e)) in out.iter().zip(&expected).enumerate() {
+            if n != 3 && n != 7 {
+                assert_eq!(x, e);
+            }
+        }
+    }
+    // One signal for each of the two broken blocks.
+    let signals = control
+        .drain_messages()
+        .into_iter()
+        .filter(|m| matches!(m, RtMessage::Invariant(INV_NONFINITE_DETECTED)))
+        .count();
+    assert_eq!(signals, 2);
+}
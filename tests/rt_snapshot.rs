@@ -0,0 +1,279 @@
+#![cfg(feature = "serde")]
+
+mod common;
+
+use auxide::control::ControlMsg;
+use auxide::dsp::Interpolation;
+use auxide::graph::{Graph, NodeType, Port, PortId, Rate};
+use auxide::node::{NodeDef, ProcessCtx};
+use auxide::plan::Plan;
+use auxide::rt::Runtime;
+use auxide::snapshot::RuntimeSnapshot;
+use auxide::transport::NoteDivision;
+use common::{edge, edge_to};
+
+const BLOCK: usize = 64;
+
+static AUDIO_OUT: [Port; 1] = [Port {
+    id: PortId(0),
+    rate: Rate::Audio,
+}];
+
+/// Sawtooth ramp whose position is its serializable state.
+struct Ramp;
+
+impl NodeDef for Ramp {
+    type State = u32;
+
+    fn input_ports(&self) -> &'static [Port] {
+        &[]
+    }
+
+    fn output_ports(&self) -> &'static [Port] {
+        &AUDIO_OUT
+    }
+
+    fn required_inputs(&self) -> usize {
+        0
+    }
+
+    fn init_state(&self, _sample_rate: f32, _block_size: usize) -> Self::State {
+        0
+    }
+
+    fn serialize_state(&self, state: &Self::State) -> Option<Vec<u8>> {
+        Some(state.to_le_bytes().to_vec())
+    }
+
+    fn deserialize_state(&self, state: &mut Self::State, bytes: &[u8]) -> Result<(), &'static str> {
+        *state = u32::from_le_bytes(bytes.try_into().map_err(|_| "bad ramp state")?);
+        Ok(())
+    }
+
+    fn process(
+        &self,
+        t: &mut Self::State,
+        _inputs: &[&[f32]],
+        outputs: &mut [&mut [f32]],
+        _ctx: &ProcessCtx,
+    ) -> Result<(), &'static str> {
+        for y in outputs[0].iter_mut() {
+            *y = (*t % 100) as f32 / 100.0 - 0.5;
+            *t += 1;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn snapshot_roundtrip_continues_identically() {
+    // osc -> delay -> gain -\
+    //                        mix -> sink
+    // ramp -> diff ---------/
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let delay = graph.add_node(NodeType::Delay {
+        delay_samples: 100.5,
+    });
+    let gain = graph.add_node(NodeType::Gain { gain: 1.0 });
+    let ramp = graph.add_node(NodeType::external(Ramp));
+    let diff = graph.add_node(NodeType::Diff);
+    let mix = graph.add_node(NodeType::Mix);
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, delay)).unwrap();
+    graph.add_edge(edge(delay, gain)).unwrap();
+    graph.add_edge(edge(gain, mix)).unwrap();
+    graph.add_edge(edge(ramp, diff)).unwrap();
+    graph.add_edge(edge_to(diff, mix, 1)).unwrap();
+    graph.add_edge(edge(mix, sink)).unwrap();
+
+    let plan = Plan::compile(&graph, BLOCK).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, 48000.0);
+    let mut out = vec![0.0; BLOCK];
+    for block in 0..10 {
+        if block == 3 {
+            runtime.apply_control(ControlMsg::SetFrequency {
+                node: osc,
+                hz: 523.0,
+            });
+            runtime.apply_control(ControlMsg::SetGain {
+                node: gain,
+                gain: 0.25,
+            });
+        }
+        runtime.process_block(&mut out).unwrap();
+    }
+
+    let json = serde_json::to_string(&runtime.snapshot()).unwrap();
+    let snapshot: RuntimeSnapshot = serde_json::from_str(&json).unwrap();
+    let plan = Plan::compile(&graph, BLOCK).unwrap();
+    let mut restored = Runtime::from_snapshot(plan, &graph, &snapshot).unwrap();
+
+    let mut expected = vec![0.0; BLOCK];
+    for _ in 0..5 {
+        runtime.process_block(&mut expected).unwrap();
+        restored.process_block(&mut out).unwrap();
+        assert_eq!(out, expected);
+    }
+}
+
+#[test]
+fn snapshot_from_other_graph_is_rejected() {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, sink)).unwrap();
+    let runtime = Runtime::new(Plan::compile(&graph, BLOCK).unwrap(), &graph, 48000.0);
+    let snapshot = runtime.snapshot();
+
+    let mut other = Graph::new();
+    let noise = other.add_node(NodeType::Noise {
+        seed: 1,
+        color: 0.0,
+    });
+    let sink = other.add_node(NodeType::OutputSink);
+    other.add_edge(edge(noise, sink)).unwrap();
+    let plan = Plan::compile(&other, BLOCK).unwrap();
+    assert!(Runtime::from_snapshot(plan, &other, &snapshot).is_err());
+}
+
+/// Snapshot a one-node chain of `node_type` through JSON, let `corrupt`
+/// edit that node's saved state, and try to restore it.
+fn restore_corrupted(node_type: NodeType, corrupt: impl Fn(&mut serde_json::Value)) -> bool {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let node = graph.add_node(node_type);
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, node)).unwrap();
+    graph.add_edge(edge(node, sink)).unwrap();
+    let runtime = Runtime::new(Plan::compile(&graph, BLOCK).unwrap(), &graph, 48000.0);
+    let mut json = serde_json::to_value(runtime.snapshot()).unwrap();
+    corrupt(&mut json["nodes"][node.0]);
+    let snapshot: RuntimeSnapshot = serde_json::from_value(json).unwrap();
+    let plan = Plan::compile(&graph, BLOCK).unwrap();
+    Runtime::from_snapshot(plan, &graph, &snapshot).is_ok()
+}
+
+#[test]
+fn corrupted_node_state_is_rejected() {
+    let delay = || NodeType::Delay {
+        delay_samples: 100.0,
+    };
+    let looper = || NodeType::Looper { max_samples: 64 };
+    assert!(restore_corrupted(delay(), |_| {}));
+    assert!(restore_corrupted(looper(), |_| {}));
+
+    assert!(!restore_corrupted(delay(), |n| {
+        n["Delay"]["line"]["buffer"] = serde_json::json!([]);
+    }));
+    assert!(!restore_corrupted(delay(), |n| {
+        n["Delay"]["line"]["buffer"] = serde_json::json!(vec![0.0; 1000]);
+    }));
+    assert!(!restore_corrupted(delay(), |n| {
+        n["Delay"]["line"]["write"] = serde_json::json!(102);
+    }));
+    assert!(!restore_corrupted(
+        NodeType::Chorus {
+            rate_hz: 1.0,
+            depth_ms: 2.0,
+            mix: 0.5,
+            voices: 2,
+        },
+        |n| n["Chorus"]["line"]["write"] = serde_json::json!(usize::MAX),
+    ));
+    assert!(!restore_corrupted(looper(), |n| {
+        n["Looper"]["len"] = serde_json::json!(65);
+    }));
+    assert!(!restore_corrupted(looper(), |n| {
+        n["Looper"]["len"] = serde_json::json!(8);
+        n["Looper"]["pos"] = serde_json::json!(8);
+    }));
+}
+
+/// Retune `osc_type` with `SetFrequency`, snapshot and restore, and check
+/// the restored runtime keeps playing at the new pitch.
+fn assert_retune_survives_restore(osc_type: NodeType) {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(osc_type);
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, sink)).unwrap();
+    let plan = Plan::compile(&graph, BLOCK).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, 48000.0);
+    let mut out = vec![0.0; BLOCK];
+    runtime.process_block(&mut out).unwrap();
+    runtime.apply_control(ControlMsg::SetFrequency {
+        node: osc,
+        hz: 1234.0,
+    });
+    runtime.process_block(&mut out).unwrap();
+
+    let json = serde_json::to_string(&runtime.snapshot()).unwrap();
+    let snapshot: RuntimeSnapshot = serde_json::from_str(&json).unwrap();
+    let plan = Plan::compile(&graph, BLOCK).unwrap();
+    let mut restored = Runtime::from_snapshot(plan, &graph, &snapshot).unwrap();
+    let mut expected = vec![0.0; BLOCK];
+    for _ in 0..3 {
+        runtime.process_block(&mut expected).unwrap();
+        restored.process_block(&mut out).unwrap();
+        assert_eq!(out, expected);
+    }
+}
+
+#[test]
+fn retuned_wavetable_keeps_its_frequency() {
+    assert_retune_survives_restore(NodeType::Wavetable {
+        table: (0..64).map(|i| (i as f32 / 32.0) - 1.0).collect(),
+        freq: 220.0,
+        interpolation: Interpolation::Linear,
+    });
+}
+
+#[test]
+fn retuned_blep_oscillators_keep_their_frequency() {
+    assert_retune_survives_restore(NodeType::SawOsc { freq: 220.0 });
+    assert_retune_survives_restore(NodeType::TriOsc { freq: 220.0 });
+    assert_retune_survives_restore(NodeType::PulseOsc {
+        freq: 220.0,
+        width: 0.3,
+    });
+}
+
+#[test]
+fn synced_delay_keeps_following_the_tempo_after_restore() {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let delay = graph.add_node(NodeType::Delay {
+        delay_samples: 48000.0,
+    });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, delay)).unwrap();
+    graph.add_edge(edge(delay, sink)).unwrap();
+    let plan = Plan::compile(&graph, BLOCK).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, 48000.0);
+    runtime.apply_control(ControlMsg::SetTempo { bpm: 100.0 });
+    runtime.apply_control(ControlMsg::SetSyncedTime {
+        node: delay,
+        division: NoteDivision::new(1, 8),
+    });
+    let mut out = vec![0.0; BLOCK];
+    runtime.process_block(&mut out).unwrap();
+
+    let json = serde_json::to_string(&runtime.snapshot()).unwrap();
+    let snapshot: RuntimeSnapshot = serde_json::from_str(&json).unwrap();
+    let plan = Plan::compile(&graph, BLOCK).unwrap();
+    let mut restored = Runtime::from_snapshot(plan, &graph, &snapshot).unwrap();
+    assert_eq!(restored.transport().tempo(), 100.0);
+
+    let mut expected = vec![0.0; BLOCK];
+    for block in 0..400 {
+        if block == 100 {
+            for runtime in [&mut runtime, &mut restored] {
+                runtime.apply_control(ControlMsg::SetTempo { bpm: 150.0 });
+            }
+        }
+        runtime.process_block(&mut expected).unwrap();
+        restored.process_block(&mut out).unwrap();
+        assert_eq!(out, expected, "block {}", block);
+    }
+    assert!(expected.iter().any(|&x| x != 0.0));
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use auxide::graph::{Graph, NodeId, NodeType};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+use common::{edge, edge_to};
+
+fn connect(graph: &mut Graph, from: NodeId, to: NodeId) {
+    graph.add_edge(edge(from, to)).unwrap();
+}
+
+#[test]
+fn subgraph_bypass_crossfades_to_dry() {
+    // osc -> gain(0.5) -> gain(0.5) -> sink; the chain attenuates by 0.25.
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 100.0 });
+    let g1 = graph.add_node(NodeType::Gain { gain: 0.5 });
+    let g2 = graph.add_node(NodeType::Gain { gain: 0.5 });
+    let sink = graph.add_node(NodeType::OutputSink);
+    connect(&mut graph, osc, g1);
+    connect(&mut graph, g1, g2);
+    connect(&mut graph, g2, sink);
+
+    let mut dry_graph = Graph::new();
+    let dry_osc = dry_graph.add_node(NodeType::SineOsc { freq: 100.0 });
+    let dry_sink = dry_graph.add_node(NodeType::OutputSink);
+    connect(&mut dry_graph, dry_osc, dry_sink);
+    let dry_plan = Plan::compile(&dry_graph, 64).unwrap();
+    let mut dry_runtime = Runtime::new(dry_plan, &dry_graph, 44100.0);
+    let dry = render_offline(&mut dry_runtime, 64 * 8).unwrap();
+
+    let plan = Plan::compile(&graph, 64).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, 44100.0);
+    let before = render_offline(&mut runtime, 64).unwrap();
+    for (o, d) in before.iter().zip(&dry[..64]) {
+        assert!((o - d * 0.25).abs() < 1e-6);
+    }
+
+    runtime.set_subgraph_bypass(&[g1, g2], true, 4).unwrap();
+    let faded = render_offline(&mut runtime, 64 * 7).unwrap();
+
+    // Effective gain ramps monotonically from 0.25 up to 1.0 over 4 blocks.
+    let mut last_gain = 0.25;
+    for (i, (o, d)) in faded.iter().zip(&dry[64..]).enumerate() {
+        if d.abs() < 0.05 {
+            continue;
+        }
+        let gain = o / d;
+        assert!(gain >= last_gain - 1e-4, "gain dipped at sample {}", i);
+        assert!(gain <= 1.0 + 1e-4);
+        last_gain = gain;
+    }
+    for (o, d) in faded[64 * 4..].iter().zip(&dry[64 * 5..]) {
+        assert!((o - d).abs() < 1e-6, "fully bypassed output should be dry");
+    }
+}
+
+#[test]
+fn subgraph_bypass_rejects_ambiguous_chain() {
+    let mut graph = Graph::new();
+    let a = graph.add_node(NodeType::SineOsc { freq: 100.0 });
+    let b = graph.add_node(NodeType::SineOsc { freq: 200.0 });
+    let mix = graph.add_node(NodeType::Mix);
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(a, mix)).unwrap();
+    graph.add_edge(edge_to(b, mix, 1)).unwrap();
+    connect(&mut graph, mix, sink);
+    let plan = Plan::compile(&graph, 64).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, 44100.0);
+    assert!(runtime.set_subgraph_bypass(&[mix], true, 1).is_err());
+}
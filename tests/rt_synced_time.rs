@@ -0,0 +1,65 @@
+mod common;
+
+use auxide::control::ControlMsg;
+use auxide::graph::{Graph, NodeId, NodeType};
+use auxide::plan::Plan;
+use auxide::rt::Runtime;
+use auxide::transport::NoteDivision;
+use common::edge;
+
+const SAMPLE_RATE: f32 = 48000.0;
+
+/// sine -> delay (room for one second) -> sink.
+fn runtime() -> (Runtime, NodeId) {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let delay = graph.add_node(NodeType::Delay {
+        delay_samples: SAMPLE_RATE,
+    });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, delay)).unwrap();
+    graph.add_edge(edge(delay, sink)).unwrap();
+    let plan = Plan::compile(&graph, 64).unwrap();
+    (Runtime::new(plan, &graph, SAMPLE_RATE), delay)
+}
+
+/// Index of the first non-zero sample: the delay time.
+fn onset(runtime: &mut Runtime) -> usize {
+    let mut out = vec![0.0; 2 * SAMPLE_RATE as usize];
+    for block in out.chunks_mut(64) {
+        runtime.process_block(block).unwrap();
+    }
+    out.iter().position(|&x| x != 0.0).unwrap()
+}
+
+#[test]
+fn synced_time_follows_the_tempo() {
+    let (mut runtime, delay) = runtime();
+    // 120 BPM: a dotted eighth is 0.75 beats, 18000 samples.
+    runtime.apply_control(ControlMsg::SetSyncedTime {
+        node: delay,
+        division: NoteDivision::new(1, 8).dotted(),
+    });
+    // The sine starts at sin(0) = 0; its first non-zero sample is one later.
+    assert_eq!(onset(&mut runtime), 18001);
+
+    let (mut runtime, delay) = self::runtime();
+    runtime.apply_control(ControlMsg::SetSyncedTime {
+        node: delay,
+        division: NoteDivision::new(1, 8).dotted(),
+    });
+    runtime.apply_control(ControlMsg::SetTempo { bpm: 150.0 });
+    assert_eq!(onset(&mut runtime), 14401);
+}
+
+#[test]
+fn synced_time_is_capped_by_the_delay_buffer() {
+    let (mut runtime, delay) = runtime();
+    runtime.apply_control(ControlMsg::SetTempo { bpm: 60.0 });
+    // A whole note at 60 BPM is four seconds; the buffer holds one.
+    runtime.apply_control(ControlMsg::SetSyncedTime {
+        node: delay,
+        division: NoteDivision::new(1, 1),
+    });
+    assert_eq!(onset(&mut runtime), SAMPLE_RATE as usize + 1);
+}
@@ -0,0 +1,211 @@
+mod common;
+
+use auxide::control::{new_runtime_control, ControlMsg};
+use auxide::graph::{Graph, NodeId, NodeType};
+use auxide::plan::Plan;
+use auxide::rt::{BlockSizePolicy, Runtime};
+use common::{edge, edge_to};
+
+const BLOCK: usize = 64;
+
+fn runtime() -> (Runtime, NodeId) {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 1000.0 });
+    let gain = graph.add_node(NodeType::Gain { gain: 1.0 });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, gain)).unwrap();
+    graph.add_edge(edge(gain, sink)).unwrap();
+    let plan = Plan::compile(&graph, BLOCK).unwrap();
+    (Runtime::new(plan, &graph, 44100.0), gain)
+}
+
+/// Two blocks at unity gain, for reference.
+fn dry() -> Vec<f32> {
+    let (mut runtime, _) = runtime();
+    let mut out = vec![0.0; 2 * BLOCK];
+    for block in out.chunks_mut(BLOCK) {
+        runtime.process_block(block).unwrap();
+    }
+    out
+}
+
+fn render_with(offset: u32) -> Vec<f32> {
+    let (mut runtime, gain) = runtime();
+    let (mut control, mut control_rx, _) = new_runtime_control();
+    control
+        .send_at(
+            offset,
+            ControlMsg::SetGain {
+                node: gain,
+                gain: 0.25,
+            },
+        )
+        .unwrap();
+    let mut out = vec![0.0; 2 * BLOCK];
+    for block in out.chunks_mut(BLOCK) {
+        runtime
+            .process_block_with_channels(block, &mut control_rx)
+            .unwrap();
+    }
+    out
+}
+
+#[test]
+fn timed_message_lands_on_its_sample() {
+    let dry = dry();
+    let wet = render_with(10);
+    assert_eq!(wet[..10], dry[..10]);
+    for n in 10..2 * BLOCK {
+        assert!((wet[n] - 0.25 * dry[n]).abs() < 1e-6, "sample {}", n);
+    }
+}
+
+#[test]
+fn offset_past_the_block_carries_over() {
+    let dry = dry();
+    let at = BLOCK + 5;
+    let wet = render_with(at as u32);
+    assert_eq!(wet[..at], dry[..at]);
+    for n in at..2 * BLOCK {
+        assert!((wet[n] - 0.25 * dry[n]).abs() < 1e-6, "sample {}", n);
+    }
+}
+
+#[test]
+fn untimed_messages_still_apply_at_block_start() {
+    let dry = dry();
+    let (mut runtime, gain) = runtime();
+    let (mut control, mut control_rx, _) = new_runtime_control();
+    control
+        .send(ControlMsg::SetGain {
+            node: gain,
+            gain: 0.5,
+        })
+        .unwrap();
+    let mut out = vec![0.0; BLOCK];
+    runtime
+        .process_block_with_channels(&mut out, &mut control_rx)
+        .unwrap();
+    for n in 0..BLOCK {
+        assert!((out[n] - 0.5 * dry[n]).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn short_buffer_follows_the_block_size_policy_with_events_pending() {
+    let dry = dry();
+    let (mut runtime, gain) = runtime();
+    let (mut control, mut control_rx, _) = new_runtime_control();
+    control
+        .send_at(
+            10,
+            ControlMsg::SetGain {
+                node: gain,
+                gain: 0.25,
+            },
+        )
+        .unwrap();
+    let mut out = vec![0.0; BLOCK / 2];
+    assert_eq!(
+        runtime.process_block_with_channels(&mut out, &mut control_rx),
+        Err("output buffer must be exactly block_size long")
+    );
+
+    // Padded, the event still lands on its sample of the full block.
+    runtime.set_block_size_policy(BlockSizePolicy::PadShort);
+    runtime
+        .process_block_with_channels(&mut out, &mut control_rx)
+        .unwrap();
+    assert_eq!(out[..10], dry[..10]);
+    for n in 10..BLOCK / 2 {
+        assert!((out[n] - 0.25 * dry[n]).abs() < 1e-6, "sample {}", n);
+    }
+}
+
+/// noise -> mix -> gain(1) -> sink, with mix -> delay(100) -> gain(0.5)
+/// feeding back into mix. Returns the runtime and the unity gain.
+fn feedback_runtime() -> (Runtime, NodeId) {
+    let mut graph = Graph::new();
+    let noise = graph.add_node(NodeType::Noise {
+        seed: 3,
+        color: 0.0,
+    });
+    let mix = graph.add_node(NodeType::Mix);
+    let delay = graph.add_node(NodeType::Delay {
+        delay_samples: 100.0,
+    });
+    let damp = graph.add_node(NodeType::Gain { gain: 0.5 });
+    let unity = graph.add_node(NodeType::Gain { gain: 1.0 });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(noise, mix)).unwrap();
+    graph.add_edge(edge(mix, delay)).unwrap();
+    graph.add_edge(edge(delay, damp)).unwrap();
+    graph.add_edge(edge_to(damp, mix, 1)).unwrap();
+    graph.add_edge(edge(mix, unity)).unwrap();
+    graph.add_edge(edge(unity, sink)).unwrap();
+    let plan = Plan::compile(&graph, BLOCK).unwrap();
+    (Runtime::new(plan, &graph, 44100.0), unity)
+}
+
+#[test]
+fn timed_no_op_leaves_a_delay_loop_unchanged() {
+    let render = |timed: bool| {
+        let (mut runtime, unity) = feedback_runtime();
+        let (mut control, mut control_rx, _) = new_runtime_control();
+        if timed {
+            control
+                .send_at(
+                    10,
+                    ControlMsg::SetGain {
+                        node: unity,
+                        gain: 1.0,
+                    },
+                )
+                .unwrap();
+        }
+        let mut out = vec![0.0; 8 * BLOCK];
+        for block in out.chunks_mut(BLOCK) {
+            runtime
+                .process_block_with_channels(block, &mut control_rx)
+                .unwrap();
+        }
+        out
+    };
+    let dry = render(false);
+    assert!(dry.iter().any(|&x| x != 0.0));
+    assert_eq!(render(true), dry);
+}
+
+#[test]
+fn far_off_message_does_not_hold_back_later_ones() {
+    let dry = dry();
+    let (mut runtime, gain) = runtime();
+    let (mut control, mut control_rx, _) = new_runtime_control();
+    control
+        .send_at(
+            100_000,
+            ControlMsg::SetGain {
+                node: gain,
+                gain: 0.0,
+            },
+        )
+        .unwrap();
+    let mut out = vec![0.0; BLOCK];
+    runtime
+        .process_block_with_channels(&mut out, &mut control_rx)
+        .unwrap();
+    assert_eq!(out, dry[..BLOCK]);
+
+    control
+        .send(ControlMsg::SetGain {
+            node: gain,
+            gain: 0.5,
+        })
+        .unwrap();
+    runtime
+        .process_block_with_channels(&mut out, &mut control_rx)
+        .unwrap();
+    for n in 0..BLOCK {
+        assert!((out[n] - 0.5 * dry[BLOCK + n]).abs() < 1e-6, "sample {}", n);
+    }
+}
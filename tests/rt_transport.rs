@@ -0,0 +1,56 @@
+mod common;
+
+use auxide::control::{new_runtime_control, ControlMsg};
+use auxide::graph::{Graph, NodeType};
+use auxide::plan::Plan;
+use auxide::rt::Runtime;
+use common::edge;
+
+const BLOCK: usize = 64;
+const SAMPLE_RATE: f32 = 48000.0;
+
+fn runtime() -> Runtime {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, sink)).unwrap();
+    let plan = Plan::compile(&graph, BLOCK).unwrap();
+    Runtime::new(plan, &graph, SAMPLE_RATE)
+}
+
+#[test]
+fn transport_follows_start_stop_and_tempo() {
+    let mut runtime = runtime();
+    let mut out = vec![0.0; BLOCK];
+    assert!(!runtime.transport().is_playing());
+    assert_eq!(runtime.transport().tempo(), 120.0);
+    runtime.process_block(&mut out).unwrap();
+    assert_eq!(runtime.transport().position_samples(), 0);
+
+    runtime.apply_control(ControlMsg::SetTempo { bpm: 90.0 });
+    runtime.apply_control(ControlMsg::Start);
+    for _ in 0..500 {
+        runtime.process_block(&mut out).unwrap();
+    }
+    let transport = runtime.transport();
+    assert!(transport.is_playing());
+    assert_eq!(transport.position_samples(), 500 * BLOCK as u64);
+    // 90 BPM: 32000 samples per beat.
+    assert!((transport.position_beats() - 1.0).abs() < 1e-9);
+
+    runtime.apply_control(ControlMsg::Stop);
+    runtime.process_block(&mut out).unwrap();
+    assert_eq!(runtime.transport().position_samples(), 500 * BLOCK as u64);
+}
+
+#[test]
+fn timed_start_counts_from_its_sample() {
+    let mut runtime = runtime();
+    let (mut control, mut control_rx, _) = new_runtime_control();
+    control.send_at(10, ControlMsg::Start).unwrap();
+    let mut out = vec![0.0; BLOCK];
+    runtime
+        .process_block_with_channels(&mut out, &mut control_rx)
+        .unwrap();
+    assert_eq!(runtime.transport().position_samples(), (BLOCK - 10) as u64);
+}
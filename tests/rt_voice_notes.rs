@@ -0,0 +1,156 @@
+use auxide::control::ControlMsg;
+use auxide::dsl::{GraphBuilder, VoiceHandles};
+use auxide::graph::{NodeType, PortId, Rate};
+use auxide::plan::Plan;
+use auxide::rt::{render_offline, Runtime};
+use auxide::snapshot::NodeSnapshot;
+use auxide::voice::LogicalNode;
+
+const SAMPLE_RATE: f32 = 48000.0;
+
+/// `count` sine voices (sine -> open low-pass -> fast ADSR) into the output.
+fn sine_voices(count: usize) -> Runtime {
+    let mut builder = GraphBuilder::new();
+    let (_, out) = builder.instance_voices(count, |b| {
+        let osc = b.node(NodeType::SineOsc { freq: 100.0 });
+        let filter = b.node(NodeType::Filter {
+            cutoff: 20000.0,
+            q: std::f32::consts::FRAC_1_SQRT_2,
+        });
+        let amp = b.node(NodeType::Adsr {
+            attack: 1.0,
+            decay: 0.0,
+            sustain: 1.0,
+            release: 10.0,
+        });
+        b.connect(osc, PortId(0), filter, PortId(0), Rate::Audio)
+            .unwrap();
+        b.connect(filter, PortId(0), amp, PortId(0), Rate::Audio)
+            .unwrap();
+        VoiceHandles { osc, filter, amp }
+    });
+    let sink = builder.node(NodeType::OutputSink);
+    builder
+        .connect(out, PortId(0), sink, PortId(0), Rate::Audio)
+        .unwrap();
+    let graph = builder.build().unwrap();
+    let plan = Plan::compile(&graph, 256).unwrap();
+    Runtime::new(plan, &graph, SAMPLE_RATE)
+}
+
+fn peak(signal: &[f32]) -> f32 {
+    signal.iter().fold(0.0f32, |p, x| p.max(x.abs()))
+}
+
+#[test]
+fn note_on_tunes_a_voice_to_the_note() {
+    let mut runtime = sine_voices(4);
+    runtime.apply_control(ControlMsg::NoteOn {
+        note: 69,
+        velocity: 1.0,
+    });
+    let out = render_offline(&mut runtime, SAMPLE_RATE as usize).unwrap();
+    let crossings = out.windows(2).filter(|w| w[0] < 0.0 && w[1] >= 0.0).count();
+    assert!((438..=442).contains(&crossings), "{} crossings", crossings);
+    assert_eq!(runtime.voice_allocator().voices()[0].note, Some(69));
+}
+
+#[test]
+fn full_pool_steals_the_oldest_voice() {
+    let mut runtime = sine_voices(2);
+    for note in [60, 64, 67] {
+        runtime.apply_control(ControlMsg::NoteOn {
+            note,
+            velocity: 1.0,
+        });
+    }
+    let notes: Vec<_> = runtime
+        .voice_allocator()
+        .voices()
+        .iter()
+        .map(|v| v.note)
+        .collect();
+    assert_eq!(notes, vec![Some(67), Some(64)]);
+
+    runtime.apply_control(ControlMsg::NoteOff { note: 64 });
+    assert_eq!(runtime.voice_allocator().voices()[1].note, None);
+    runtime.apply_control(ControlMsg::AllNotesOff);
+    assert!(runtime
+        .voice_allocator()
+        .voices()
+        .iter()
+        .all(|v| v.note.is_none()));
+}
+
+#[test]
+fn note_off_releases_the_voice() {
+    let mut runtime = sine_voices(2);
+    runtime.apply_control(ControlMsg::NoteOn {
+        note: 60,
+        velocity: 1.0,
+    });
+    let held = render_offline(&mut runtime, 4800).unwrap();
+    assert!(peak(&held) > 0.9);
+    runtime.apply_control(ControlMsg::NoteOff { note: 60 });
+    let released = render_offline(&mut runtime, 4800).unwrap();
+    // 10 ms release: silent well before the end.
+    assert_eq!(peak(&released[2400..]), 0.0);
+}
+
+#[test]
+fn velocity_scales_the_voice() {
+    let render = |velocity| {
+        let mut runtime = sine_voices(1);
+        runtime.apply_control(ControlMsg::NoteOn { note: 60, velocity });
+        peak(&render_offline(&mut runtime, 4800).unwrap())
+    };
+    let ratio = render(0.5) / render(1.0);
+    assert!((ratio - 0.5).abs() < 0.01, "ratio {}", ratio);
+}
+
+#[test]
+fn filter_tracks_the_note() {
+    let mut builder = GraphBuilder::new();
+    let (voices, out) = builder.subtractive_voices(1, 220.0);
+    let sink = builder.node(NodeType::OutputSink);
+    builder
+        .connect(out, PortId(0), sink, PortId(0), Rate::Audio)
+        .unwrap();
+    let graph = builder.build().unwrap();
+    let plan = Plan::compile(&graph, 256).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, SAMPLE_RATE);
+    runtime.apply_control(ControlMsg::NoteOn {
+        note: 69,
+        velocity: 1.0,
+    });
+    // Built at 220 Hz with the cutoff at 880 Hz; A4 keeps the ratio.
+    let filter = voices[0].node(LogicalNode::Filter);
+    match &runtime.snapshot().nodes[filter.0] {
+        Some(NodeSnapshot::Filter { cutoff, .. }) => {
+            assert!((cutoff - 1760.0).abs() < 0.5, "cutoff {}", cutoff)
+        }
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn graphs_without_voices_ignore_notes() {
+    let mut builder = GraphBuilder::new();
+    let osc = builder.node(NodeType::SineOsc { freq: 440.0 });
+    let sink = builder.node(NodeType::OutputSink);
+    builder
+        .connect(osc, PortId(0), sink, PortId(0), Rate::Audio)
+        .unwrap();
+    let graph = builder.build().unwrap();
+    let plan = Plan::compile(&graph, 64).unwrap();
+    let mut runtime = Runtime::new(plan, &graph, SAMPLE_RATE);
+    runtime.apply_control(ControlMsg::NoteOn {
+        note: 60,
+        velocity: 1.0,
+    });
+    runtime.apply_control(ControlMsg::NoteOff { note: 60 });
+    let out = render_offline(&mut runtime, 4800).unwrap();
+    // Still the untouched 440 Hz sine.
+    let crossings = out.windows(2).filter(|w| w[0] < 0.0 && w[1] >= 0.0).count();
+    assert!((43..=44).contains(&crossings), "{} crossings", crossings);
+}
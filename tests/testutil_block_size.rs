@@ -0,0 +1,103 @@
+mod common;
+
+use auxide::graph::{Edge, Graph, NodeType, Rate};
+use auxide::testutil::assert_block_size_invariant;
+use common::edge;
+
+/// Noise through `nodes` in series (all audio rate) into a sink.
+fn chain(nodes: &[NodeType]) -> Graph {
+    let mut graph = Graph::new();
+    let mut prev = graph.add_node(NodeType::Noise {
+        seed: 7,
+        color: 0.0,
+    });
+    for node in nodes {
+        let next = graph.add_node(node.clone());
+        graph.add_edge(edge(prev, next)).unwrap();
+        prev = next;
+    }
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(prev, sink)).unwrap();
+    graph
+}
+
+#[test]
+fn stateful_filter_chain_is_block_size_invariant() {
+    assert_block_size_invariant(
+        || {
+            chain(&[
+                NodeType::Filter {
+                    cutoff: 800.0,
+                    q: 4.0,
+                },
+                NodeType::Delay {
+                    delay_samples: 37.5,
+                },
+                NodeType::Compressor {
+                    threshold_db: -18.0,
+                    ratio: 4.0,
+                    attack_ms: 2.0,
+                    release_ms: 40.0,
+                },
+            ])
+        },
+        5000,
+        &[32, 64, 128],
+    );
+}
+
+#[test]
+#[should_panic(expected = "diverges")]
+fn misaligned_control_segments_are_caught() {
+    assert_block_size_invariant(
+        || {
+            let mut graph = Graph::new();
+            let noise = graph.add_node(NodeType::Noise {
+                seed: 7,
+                color: 0.0,
+            });
+            let to_control = graph.add_node(NodeType::AudioToControl);
+            let to_audio = graph.add_node(NodeType::ControlToAudio);
+            let sink = graph.add_node(NodeType::OutputSink);
+            graph.add_edge(edge(noise, to_control)).unwrap();
+            graph
+                .add_edge(Edge {
+                    rate: Rate::Control,
+                    ..edge(to_control, to_audio)
+                })
+                .unwrap();
+            graph.add_edge(edge(to_audio, sink)).unwrap();
+            graph
+        },
+        1000,
+        // 40 is not a multiple of the control-rate divisor, so control
+        // segments fall differently.
+        &[32, 40],
+    );
+}
+
+#[test]
+fn control_path_is_invariant_across_aligned_block_sizes() {
+    assert_block_size_invariant(
+        || {
+            let mut graph = Graph::new();
+            let noise = graph.add_node(NodeType::Noise {
+                seed: 7,
+                color: 0.0,
+            });
+            let to_control = graph.add_node(NodeType::AudioToControl);
+            let sink = graph.add_node(NodeType::OutputSink);
+            graph.add_edge(edge(noise, to_control)).unwrap();
+            // Upsampled by an automatically inserted `ControlToAudio`.
+            graph
+                .add_edge(Edge {
+                    rate: Rate::Control,
+                    ..edge(to_control, sink)
+                })
+                .unwrap();
+            graph
+        },
+        1000,
+        &[32, 64, 128],
+    );
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use auxide::graph::{Graph, NodeType};
+use auxide::plan::Plan;
+use auxide::rt::Runtime;
+use auxide::testutil::{assert_matches_golden, read_wav, write_golden};
+use common::edge;
+use std::path::PathBuf;
+
+fn sine_runtime(freq: f32) -> Runtime {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq });
+    let sink = graph.add_node(NodeType::OutputSink);
+    graph.add_edge(edge(osc, sink)).unwrap();
+    let plan = Plan::compile(&graph, 64).unwrap();
+    Runtime::new(plan, &graph, 44100.0)
+}
+
+fn golden_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("auxide-{}-{}.wav", name, std::process::id()))
+}
+
+#[test]
+fn generated_golden_sine_matches() {
+    let path = golden_path("sine");
+    write_golden(&mut sine_runtime(440.0), 1000, &path).unwrap();
+    let golden = read_wav(&path).unwrap();
+    assert_eq!(golden.len(), 1000);
+    assert!(golden.iter().any(|&s| s > 0.99));
+
+    assert_matches_golden(&mut sine_runtime(440.0), 1000, &path, 0.0);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+#[should_panic(expected = "at frame 1")]
+fn mismatch_names_the_first_differing_frame() {
+    let path = golden_path("detuned");
+    write_golden(&mut sine_runtime(440.0), 1000, &path).unwrap();
+    // Both start at phase zero, so frame 0 agrees.
+    let mut detuned = sine_runtime(450.0);
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        assert_matches_golden(&mut detuned, 1000, &path, 1e-4)
+    }));
+    std::fs::remove_file(&path).unwrap();
+    std::panic::resume_unwind(result.unwrap_err());
+}
@@ -0,0 +1,111 @@
+mod common;
+
+use auxide::graph::{Graph, NodeType, Port, PortId, Rate};
+use auxide::node::{NodeDef, ProcessCtx};
+use auxide::testutil::{assert_no_alloc_in_rt, CountingAllocator};
+use common::edge;
+
+#[global_allocator]
+static ALLOC: CountingAllocator = CountingAllocator;
+
+static AUDIO_PORT: [Port; 1] = [Port {
+    id: PortId(0),
+    rate: Rate::Audio,
+}];
+
+/// External gain that keeps a running sample count as state.
+struct CountingGain;
+
+impl NodeDef for CountingGain {
+    type State = u64;
+
+    fn input_ports(&self) -> &'static [Port] {
+        &AUDIO_PORT
+    }
+
+    fn output_ports(&self) -> &'static [Port] {
+        &AUDIO_PORT
+    }
+
+    fn required_inputs(&self) -> usize {
+        1
+    }
+
+    fn init_state(&self, _sample_rate: f32, _block_size: usize) -> Self::State {
+        0
+    }
+
+    fn process(
+        &self,
+        state: &mut Self::State,
+        inputs: &[&[f32]],
+        outputs: &mut [&mut [f32]],
+        _ctx: &ProcessCtx,
+    ) -> Result<(), &'static str> {
+        for (o, &i) in outputs[0].iter_mut().zip(inputs[0]) {
+            *o = i * 0.5;
+        }
+        *state += inputs[0].len() as u64;
+        Ok(())
+    }
+}
+
+/// External node that (incorrectly) allocates every block.
+struct AllocatingNode;
+
+impl NodeDef for AllocatingNode {
+    type State = Vec<Vec<f32>>;
+
+    fn input_ports(&self) -> &'static [Port] {
+        &[]
+    }
+
+    fn output_ports(&self) -> &'static [Port] {
+        &AUDIO_PORT
+    }
+
+    fn required_inputs(&self) -> usize {
+        0
+    }
+
+    fn init_state(&self, _sample_rate: f32, _block_size: usize) -> Self::State {
+        Vec::new()
+    }
+
+    fn process(
+        &self,
+        state: &mut Self::State,
+        _inputs: &[&[f32]],
+        outputs: &mut [&mut [f32]],
+        _ctx: &ProcessCtx,
+    ) -> Result<(), &'static str> {
+        state.push(outputs[0].to_vec());
+        Ok(())
+    }
+}
+
+fn chain(graph: &mut Graph, nodes: &[auxide::graph::NodeId]) {
+    for pair in nodes.windows(2) {
+        graph.add_edge(edge(pair[0], pair[1])).unwrap();
+    }
+}
+
+#[test]
+fn external_node_graph_is_alloc_free() {
+    let mut graph = Graph::new();
+    let osc = graph.add_node(NodeType::SineOsc { freq: 440.0 });
+    let ext = graph.add_node(NodeType::external(CountingGain));
+    let sink = graph.add_node(NodeType::OutputSink);
+    chain(&mut graph, &[osc, ext, sink]);
+    assert_no_alloc_in_rt(&graph, 64, 1_000);
+}
+
+#[test]
+#[should_panic(expected = "process_block allocated")]
+fn allocating_external_node_is_caught() {
+    let mut graph = Graph::new();
+    let ext = graph.add_node(NodeType::external(AllocatingNode));
+    let sink = graph.add_node(NodeType::OutputSink);
+    chain(&mut graph, &[ext, sink]);
+    assert_no_alloc_in_rt(&graph, 64, 4);
+}